@@ -0,0 +1,638 @@
+//! NetOpt CLI - 命令行TCP连接管理工具
+//!
+//! 为无图形界面的服务器提供监控、配置和优化功能
+
+use netopt_core::platform::{create_monitor, create_config_manager, create_optimizer, has_admin_privileges};
+use netopt_core::backup;
+use netopt_core::leak::LEAK_MIN_SAMPLES;
+use netopt_core::{calculate_health_report, AppConfig, LeakReport, LeakVerdict, NetOptError, OptimizationEngine, OptimizationSummary, ProcessTcpStats, StatsHistory, StatsOptions, SystemTcpStats, TcpState, TcpSystemConfig};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+用法: netopt-cli [--json] <命令>
+
+命令:
+  stats [--udp] [--port-range 起始-结束]
+                                         系统TCP连接统计（--udp 同时计入 UDP 端口占用，
+                                         --port-range 按指定范围而非系统配置计算端口使用率）
+  udp                                    列出 UDP 套接字
+  listening                              列出监听端口及其所属进程
+  top [N]                                连接数最多的 N 个进程（默认 10）
+  problematic [--threshold N]            TIME_WAIT/CLOSE_WAIT 过多的进程（默认 100）
+  config show                            显示当前TCP系统配置
+  config apply --preset <名称> [--dry-run]
+                                         应用预设配置: high-performance | conservative
+  config backups                         列出自动备份（最新在前）
+  config restore [--file <路径>]         从备份恢复配置（默认最新备份）
+  optimize --pid <PID> [--dry-run]       按策略优化指定进程
+  simulate                               预览对所有进程启用自动优化的效果（不关闭连接）
+  watch [--interval 秒]                  类似 top(1) 持续刷新（默认 2 秒，Ctrl-C 退出）
+  leaks [--interval 秒] [--samples N] [--min-growth N]
+                                         连续采样 N 次（默认每 30 秒、共 10 次），报告 CLOSE_WAIT
+                                         只增不减且增长不少于 min-growth（默认 20）的进程
+
+选项:
+  --json                                 以 JSON 格式输出";
+
+/// 命令行子命令
+enum Command {
+    Stats { udp: bool, port_range: Option<(u32, u32)> },
+    Udp,
+    Listening,
+    Top(usize),
+    Problematic(usize),
+    ConfigShow,
+    ConfigApply { config: TcpSystemConfig, dry_run: bool },
+    ConfigBackups,
+    ConfigRestore(Option<PathBuf>),
+    Optimize { pid: u32, dry_run: bool },
+    Simulate,
+    Watch(u64),
+    Leaks { interval: u64, samples: usize, min_growth: usize },
+}
+
+/// CLI 错误
+enum CliError {
+    /// 参数错误
+    Usage(String),
+    /// 核心库错误
+    Core(NetOptError),
+}
+
+impl From<NetOptError> for CliError {
+    fn from(e: NetOptError) -> Self {
+        CliError::Core(e)
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let json = take_flag(&mut args, "--json");
+
+    let result = parse_command(&args).and_then(|cmd| run(cmd, json));
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(msg)) => {
+            eprintln!("错误: {}\n\n{}", msg, USAGE);
+            ExitCode::from(2)
+        }
+        Err(CliError::Core(NetOptError::PermissionDenied)) => {
+            eprintln!("错误: {}，请使用管理员(Windows)或 sudo(macOS/Linux) 重新运行", NetOptError::PermissionDenied);
+            ExitCode::from(3)
+        }
+        Err(CliError::Core(e)) => {
+            eprintln!("错误: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 移除并返回布尔开关是否存在
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|a| a != flag);
+    args.len() != before
+}
+
+/// 读取 `--name value` 形式的选项值
+fn option_value<'a>(args: &'a [String], name: &str) -> Result<Option<&'a str>, CliError> {
+    match args.iter().position(|a| a == name) {
+        Some(i) => args.get(i + 1)
+            .map(|v| Some(v.as_str()))
+            .ok_or_else(|| CliError::Usage(format!("{} 需要一个参数", name))),
+        None => Ok(None),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, what: &str) -> Result<T, CliError> {
+    value.parse().map_err(|_| CliError::Usage(format!("{} 不是有效的数字: {}", what, value)))
+}
+
+/// 解析 "起始-结束" 形式的端口范围
+fn parse_port_range(value: &str) -> Result<(u32, u32), CliError> {
+    let (start, end) = value.split_once('-')
+        .ok_or_else(|| CliError::Usage(format!("--port-range 格式应为 起始-结束: {}", value)))?;
+    let start: u32 = parse_number(start.trim(), "--port-range")?;
+    let end: u32 = parse_number(end.trim(), "--port-range")?;
+    if start == 0 || start >= end || end > 65535 {
+        return Err(CliError::Usage(format!("--port-range 无效: {}", value)));
+    }
+    Ok((start, end))
+}
+
+fn parse_command(args: &[String]) -> Result<Command, CliError> {
+    let Some(cmd) = args.first() else {
+        return Err(CliError::Usage("缺少命令".into()));
+    };
+    let rest = &args[1..];
+
+    match cmd.as_str() {
+        "stats" => Ok(Command::Stats {
+            udp: rest.iter().any(|a| a == "--udp"),
+            port_range: option_value(rest, "--port-range")?.map(parse_port_range).transpose()?,
+        }),
+        "udp" => Ok(Command::Udp),
+        "listening" => Ok(Command::Listening),
+        "top" => {
+            let limit = match rest.first() {
+                Some(n) => parse_number(n, "N")?,
+                None => 10,
+            };
+            Ok(Command::Top(limit))
+        }
+        "problematic" => {
+            let threshold = match option_value(rest, "--threshold")? {
+                Some(n) => parse_number(n, "--threshold")?,
+                None => 100,
+            };
+            Ok(Command::Problematic(threshold))
+        }
+        "config" => match rest.first().map(String::as_str) {
+            Some("show") => Ok(Command::ConfigShow),
+            Some("apply") => {
+                let preset = match option_value(rest, "--preset")? {
+                    Some("high-performance") => TcpSystemConfig::high_performance(),
+                    Some("conservative") => TcpSystemConfig::conservative(),
+                    Some(other) => return Err(CliError::Usage(format!("未知预设: {}", other))),
+                    None => return Err(CliError::Usage("config apply 需要 --preset".into())),
+                };
+                let dry_run = rest.iter().any(|a| a == "--dry-run");
+                Ok(Command::ConfigApply { config: preset, dry_run })
+            }
+            Some("backups") => Ok(Command::ConfigBackups),
+            Some("restore") => Ok(Command::ConfigRestore(option_value(rest, "--file")?.map(PathBuf::from))),
+            _ => Err(CliError::Usage("config 需要子命令 show / apply / backups / restore".into())),
+        },
+        "optimize" => {
+            let pid = match option_value(rest, "--pid")? {
+                Some(p) => parse_number(p, "--pid")?,
+                None => return Err(CliError::Usage("optimize 需要 --pid".into())),
+            };
+            let dry_run = rest.iter().any(|a| a == "--dry-run");
+            Ok(Command::Optimize { pid, dry_run })
+        }
+        "simulate" => Ok(Command::Simulate),
+        "watch" => {
+            let interval = match option_value(rest, "--interval")? {
+                Some(n) => parse_number(n, "--interval")?,
+                None => 2,
+            };
+            if interval == 0 {
+                return Err(CliError::Usage("--interval 必须大于 0".into()));
+            }
+            Ok(Command::Watch(interval))
+        }
+        "leaks" => {
+            let interval = match option_value(rest, "--interval")? {
+                Some(n) => parse_number(n, "--interval")?,
+                None => 30,
+            };
+            let samples = match option_value(rest, "--samples")? {
+                Some(n) => parse_number(n, "--samples")?,
+                None => 10,
+            };
+            let min_growth = match option_value(rest, "--min-growth")? {
+                Some(n) => parse_number(n, "--min-growth")?,
+                None => 20,
+            };
+            if interval == 0 {
+                return Err(CliError::Usage("--interval 必须大于 0".into()));
+            }
+            if samples < LEAK_MIN_SAMPLES {
+                return Err(CliError::Usage(format!("--samples 至少为 {}", LEAK_MIN_SAMPLES)));
+            }
+            Ok(Command::Leaks { interval, samples, min_growth })
+        }
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+        other => Err(CliError::Usage(format!("未知命令: {}", other))),
+    }
+}
+
+fn run(cmd: Command, json: bool) -> Result<(), CliError> {
+    match cmd {
+        Command::Stats { udp, port_range } => {
+            let monitor = create_monitor();
+            let options = StatsOptions { port_range, ..Default::default() };
+            let stats = if udp {
+                monitor.get_system_stats_with_udp(&options)?
+            } else {
+                monitor.get_system_stats_with(&options)?
+            };
+            if json {
+                print_json(&stats)
+            } else {
+                print_system_stats(&stats);
+                Ok(())
+            }
+        }
+        Command::Udp => {
+            let mut sockets = create_monitor().get_udp_sockets()?;
+            if json {
+                return print_json(&sockets);
+            }
+            sockets.sort_by_key(|s| (s.local_port, s.pid));
+            println!("{:<8} {:<24} {:<40} {:>6}", "PID", "PROCESS", "LOCAL", "PORT");
+            for s in &sockets {
+                println!("{:<8} {:<24} {:<40} {:>6}", s.pid, truncate(&s.process_name, 24), s.local_addr, s.local_port);
+            }
+            Ok(())
+        }
+        Command::Listening => {
+            let ports = create_monitor().get_listening_ports()?;
+            if json {
+                return print_json(&ports);
+            }
+            println!("{:>6} {:<40} {:<8} {:<24}", "PORT", "LOCAL", "PID", "PROCESS");
+            for p in &ports {
+                println!("{:>6} {:<40} {:<8} {:<24}", p.port, p.local_addr, p.pid, truncate(&p.process_name, 24));
+            }
+            Ok(())
+        }
+        Command::Top(limit) => {
+            let procs = create_monitor().get_top_processes(limit)?;
+            output_processes(&procs, json)
+        }
+        Command::Problematic(threshold) => {
+            let procs = create_monitor().get_problematic_processes(threshold)?;
+            output_processes(&procs, json)?;
+            if !json {
+                print_health_breakdown(&procs);
+            }
+            Ok(())
+        }
+        Command::ConfigShow => {
+            let config = create_config_manager().get_current_config()?;
+            if json {
+                print_json(&config)
+            } else {
+                print_config(&config);
+                Ok(())
+            }
+        }
+        Command::ConfigApply { config, dry_run } => {
+            let mgr = create_config_manager();
+            let changes = mgr.diff(&config)?;
+
+            if json && dry_run {
+                return print_json(&changes);
+            }
+            if changes.is_empty() {
+                println!("配置无变化");
+                return Ok(());
+            }
+            println!("将要修改:");
+            for change in &changes {
+                println!("  {}", change);
+            }
+            if dry_run {
+                return Ok(());
+            }
+
+            if !has_admin_privileges() {
+                return Err(NetOptError::PermissionDenied.into());
+            }
+            mgr.apply_config(&config)?;
+            println!("配置已应用（原配置已自动备份，可用 config restore 回滚）");
+            if mgr.requires_reboot() {
+                println!("注意: 需要重启系统后生效");
+            }
+            Ok(())
+        }
+        Command::ConfigBackups => {
+            let backups = backup::list_backups()?;
+            if json {
+                return print_json(&backups);
+            }
+            if backups.is_empty() {
+                println!("暂无备份");
+            }
+            for path in backups {
+                println!("{}", path.display());
+            }
+            Ok(())
+        }
+        Command::ConfigRestore(file) => {
+            let path = match file {
+                Some(path) => path,
+                None => backup::list_backups()?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| NetOptError::InvalidParameter("没有可用的备份".into()))?,
+            };
+            if !has_admin_privileges() {
+                return Err(NetOptError::PermissionDenied.into());
+            }
+            let mgr = create_config_manager();
+            mgr.restore_config(&path)?;
+            println!("已从 {} 恢复配置", path.display());
+            if mgr.requires_reboot() {
+                println!("注意: 需要重启系统后生效");
+            }
+            Ok(())
+        }
+        Command::Optimize { pid, dry_run } => {
+            let stats = create_monitor().get_process_stats(pid)?;
+            let policy_manager = AppConfig::load().unwrap_or_default().policy_manager;
+
+            let actions = if dry_run {
+                let mut engine = OptimizationEngine::new(policy_manager);
+                if !json {
+                    println!("{}", engine.explain(&stats));
+                }
+                engine.analyze_and_decide(&stats)
+            } else {
+                if !has_admin_privileges() {
+                    return Err(NetOptError::PermissionDenied.into());
+                }
+                let policy = policy_manager.effective_policy(&stats.process_name);
+                vec![create_optimizer().optimize_process(pid, &policy)?]
+            };
+
+            if json {
+                return print_json(&actions);
+            }
+            if actions.is_empty() {
+                println!("{} (PID {}) 无需优化", stats.process_name, pid);
+            }
+            for action in &actions {
+                let prefix = if dry_run { "[dry-run] " } else { "" };
+                println!(
+                    "{}{} (PID {}): {:?} - {}，影响连接数 {}",
+                    prefix, action.process_name, action.pid, action.action_type,
+                    action.reason, action.connections_affected
+                );
+                if let Some(err) = &action.error_message {
+                    println!("  错误: {}", err);
+                }
+            }
+            Ok(())
+        }
+        Command::Simulate => {
+            let stats = create_monitor().get_system_stats()?;
+            let policy_manager = AppConfig::load().unwrap_or_default().policy_manager;
+            let actions = OptimizationEngine::new(policy_manager).simulate(&stats);
+
+            if json {
+                return print_json(&actions);
+            }
+            if actions.is_empty() {
+                println!("启用自动优化后不会触发任何动作");
+                return Ok(());
+            }
+            for action in &actions {
+                println!(
+                    "{} (PID {}): {:?} - {}，影响连接数 {}",
+                    action.process_name, action.pid, action.action_type,
+                    action.reason, action.connections_affected
+                );
+                if let Some(err) = &action.error_message {
+                    println!("  将会失败: {}", err);
+                }
+            }
+            println!("\n启用自动优化后预计: {}", OptimizationSummary::from_actions(&actions));
+            Ok(())
+        }
+        Command::Watch(interval) => watch(Duration::from_secs(interval), json),
+        Command::Leaks { interval, samples, min_growth } => {
+            let monitor = create_monitor();
+            let interval = Duration::from_secs(interval);
+            let mut history = StatsHistory::new(samples);
+            for i in 0..samples {
+                if i > 0 {
+                    std::thread::sleep(interval);
+                }
+                eprint!("\r采样 {}/{}", i + 1, samples);
+                history.push(monitor.get_system_stats()?);
+            }
+            eprintln!();
+
+            let report = LeakReport::from_history(&history, interval * samples as u32, min_growth);
+            if json {
+                return print_json(&report);
+            }
+            print_leak_report(&report);
+            Ok(())
+        }
+    }
+}
+
+fn print_leak_report(report: &LeakReport) {
+    if report.processes.is_empty() {
+        println!("{} 秒内没有进程的 CLOSE_WAIT 持续增长", report.window_secs);
+        return;
+    }
+    println!(
+        "{:<8} {:<24} {:>6} {:>6} {:>6} {:>7} {:>7}  VERDICT",
+        "PID", "PROCESS", "FIRST", "LAST", "PEAK", "GROWTH", "SECS"
+    );
+    for p in &report.processes {
+        let verdict = match p.verdict {
+            LeakVerdict::Confirmed => "确认泄漏",
+            LeakVerdict::Transient => "突发后回落",
+            LeakVerdict::Watching => "观察中",
+        };
+        let restarted = if p.restarted { "（窗口内重启过）" } else { "" };
+        println!(
+            "{:<8} {:<24} {:>6} {:>6} {:>6} {:>+7} {:>7}  {}{}",
+            p.pid, truncate(&p.process_name, 24), p.first, p.last, p.peak, p.growth, p.span_secs, verdict, restarted
+        );
+    }
+}
+
+/// 持续刷新模式：每个周期重新采集并高亮 TIME_WAIT/CLOSE_WAIT 增长的进程
+fn watch(interval: Duration, json: bool) -> Result<(), CliError> {
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = running.clone();
+    ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))
+        .map_err(|e| NetOptError::SystemError(format!("注册 Ctrl-C 处理失败: {}", e)))?;
+
+    if !json {
+        // 切换到备用屏幕并隐藏光标，退出时恢复
+        print!("\x1b[?1049h\x1b[?25l");
+    }
+
+    let monitor = create_monitor();
+    let mut history = StatsHistory::new(2);
+    let mut result = Ok(());
+
+    while running.load(Ordering::SeqCst) {
+        match monitor.get_system_stats() {
+            Ok(stats) => {
+                if json {
+                    match stats.to_json() {
+                        Ok(line) => println!("{}", line),
+                        Err(e) => {
+                            result = Err(e.into());
+                            break;
+                        }
+                    }
+                    history.push(stats);
+                } else {
+                    history.push(stats);
+                    render_watch(&history, interval);
+                }
+            }
+            Err(e) => {
+                result = Err(e.into());
+                break;
+            }
+        }
+
+        // 分段休眠，以便及时响应 Ctrl-C
+        let deadline = Instant::now() + interval;
+        while running.load(Ordering::SeqCst) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    if !json {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = std::io::stdout().flush();
+    }
+    result
+}
+
+fn render_watch(history: &StatsHistory, interval: Duration) {
+    let Some(latest) = history.latest() else { return };
+    let stats = &latest.stats;
+
+    // 清屏并移动光标到左上角
+    print!("\x1b[2J\x1b[H");
+    println!(
+        "netopt-cli watch — 每 {} 秒刷新，Ctrl-C 退出",
+        interval.as_secs()
+    );
+    println!(
+        "总连接: {}  可用端口: {}  端口使用率: {:.1}%  TIME_WAIT: {}  CLOSE_WAIT: {}",
+        stats.total_connections,
+        stats.available_ports,
+        stats.port_usage_percent,
+        stats.by_state.get(&TcpState::TimeWait).unwrap_or(&0),
+        stats.by_state.get(&TcpState::CloseWait).unwrap_or(&0),
+    );
+    println!();
+
+    let by_pid: HashMap<u32, &ProcessTcpStats> = stats.by_process.iter().map(|p| (p.pid, p)).collect();
+
+    // 有上一次采样时按增长速率排序，否则按连接总数
+    let mut rows: Vec<(&ProcessTcpStats, i64, i64, f64)> = history.process_deltas().iter()
+        .filter_map(|d| by_pid.get(&d.pid).map(|p| (*p, d.time_wait_delta, d.close_wait_delta, d.growth_per_sec)))
+        .collect();
+    if history.previous().is_none() {
+        rows = stats.by_process.iter().map(|p| (p, 0, 0, 0.0)).collect();
+    } else {
+        rows.sort_by(|a, b| b.3.total_cmp(&a.3).then(b.0.total_connections.cmp(&a.0.total_connections)));
+    }
+
+    println!(
+        "{:<8} {:<24} {:>7} {:>9} {:>7} {:>10} {:>7} {:>8} {:>6}",
+        "PID", "PROCESS", "TOTAL", "TIME_WAIT", "ΔTW", "CLOSE_WAIT", "ΔCW", "GROWTH/s", "HEALTH"
+    );
+    for (p, tw_delta, cw_delta, rate) in rows.iter().take(30) {
+        let line = format!(
+            "{:<8} {:<24} {:>7} {:>9} {:>+7} {:>10} {:>+7} {:>8.1} {:>5}%",
+            p.pid, truncate(&p.process_name, 24), p.total_connections,
+            p.time_wait, tw_delta, p.close_wait, cw_delta, rate, p.health_score
+        );
+        if *tw_delta > 0 || *cw_delta > 0 {
+            println!("\x1b[31m{}\x1b[0m", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    let _ = std::io::stdout().flush();
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), CliError> {
+    let s = serde_json::to_string_pretty(value).map_err(NetOptError::from)?;
+    println!("{}", s);
+    Ok(())
+}
+
+fn output_processes(procs: &[ProcessTcpStats], json: bool) -> Result<(), CliError> {
+    if json {
+        print_json(&procs)
+    } else {
+        print_process_table(procs);
+        Ok(())
+    }
+}
+
+fn print_system_stats(stats: &SystemTcpStats) {
+    println!("总连接数:   {}", stats.total_connections);
+    println!("可用端口:   {} (已用 {})", stats.available_ports, stats.ephemeral_ports_in_use);
+    if let Some(udp) = stats.udp_ports_used {
+        println!("UDP 占用:   {} (TCP+UDP 可用端口 {})", udp, stats.available_ports_combined());
+    }
+    println!("端口使用率: {:.1}%", stats.port_usage_percent);
+    println!();
+
+    let mut states: Vec<(&TcpState, &usize)> = stats.by_state.iter().collect();
+    states.sort_by(|a, b| b.1.cmp(a.1));
+    println!("{:<14} {:>8}", "STATE", "COUNT");
+    for (state, count) in states {
+        println!("{:<14} {:>8}", state.to_string(), count);
+    }
+    println!();
+
+    print_process_table(&stats.by_process.iter().take(10).cloned().collect::<Vec<_>>());
+}
+
+fn print_process_table(procs: &[ProcessTcpStats]) {
+    println!(
+        "{:<8} {:<24} {:>7} {:>11} {:>9} {:>10} {:>6} {:>6}",
+        "PID", "PROCESS", "TOTAL", "ESTABLISHED", "TIME_WAIT", "CLOSE_WAIT", "LISTEN", "HEALTH"
+    );
+    for p in procs {
+        println!(
+            "{:<8} {:<24} {:>7} {:>11} {:>9} {:>10} {:>6} {:>5}%",
+            p.pid, truncate(&p.process_name, 24), p.total_connections, p.established,
+            p.time_wait, p.close_wait, p.listen, p.health_score
+        );
+    }
+}
+
+/// 输出健康评分的扣分明细
+fn print_health_breakdown(procs: &[ProcessTcpStats]) {
+    for p in procs {
+        let report = calculate_health_report(p);
+        if report.factors.is_empty() {
+            continue;
+        }
+        let factors: Vec<String> = report.factors.iter()
+            .map(|(factor, penalty)| format!("{} ({})", factor.condition(), penalty))
+            .collect();
+        println!("  {} {}: {}", p.pid, p.process_name, factors.join(", "));
+    }
+}
+
+fn print_config(config: &TcpSystemConfig) {
+    for (name, value) in config.fields() {
+        match value {
+            Some(v) => println!("{:<24} {}", name, v),
+            None => println!("{:<24} -", name),
+        }
+    }
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let mut t: String = s.chars().take(max - 1).collect();
+        t.push('…');
+        t
+    }
+}
@@ -0,0 +1,32 @@
+//! 连接表文本解析的基准测试
+//!
+//! 样本位于 benches/fixtures/，每份 1000 行，状态分布和地址格式参照真实输出
+//!
+//! 运行: cargo bench -p netopt-core --bench parsers
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use netopt_core::platform::{netstat, procfs};
+use std::collections::HashMap;
+
+const WINDOWS: &str = include_str!("fixtures/netstat_windows_ano.txt");
+const MACOS: &str = include_str!("fixtures/netstat_macos_anv.txt");
+const PROC_NET_TCP: &str = include_str!("fixtures/proc_net_tcp.txt");
+
+fn bench_parsers(c: &mut Criterion) {
+    // 与 LinuxTcpMonitor 一样，inode 由 /proc/<pid>/fd 映射到 PID
+    let inodes: HashMap<u64, u32> = (20_000..21_000).map(|inode| (inode, inode as u32 % 97 + 1)).collect();
+
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Elements(netstat::parse_windows_tcp(WINDOWS).len() as u64));
+    group.bench_function("netstat_windows", |b| b.iter(|| netstat::parse_windows_tcp(black_box(WINDOWS))));
+    group.throughput(Throughput::Elements(netstat::parse_macos_tcp(MACOS).len() as u64));
+    group.bench_function("netstat_macos", |b| b.iter(|| netstat::parse_macos_tcp(black_box(MACOS))));
+    group.throughput(Throughput::Elements(procfs::parse_tcp(PROC_NET_TCP, false, &inodes).len() as u64));
+    group.bench_function("proc_net_tcp", |b| {
+        b.iter(|| procfs::parse_tcp(black_box(PROC_NET_TCP), false, &inodes))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parsers);
+criterion_main!(benches);
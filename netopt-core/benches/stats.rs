@@ -0,0 +1,74 @@
+//! 系统统计汇总的基准测试
+//!
+//! 运行: cargo bench -p netopt-core --bench stats
+//! 对比并行汇总: cargo bench -p netopt-core --bench stats --features parallel
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use netopt_core::monitor::{aggregate_processes, summarize_processes, StatsOptions, TcpMonitor};
+use netopt_core::platform::mock::MockTcpMonitor;
+use netopt_core::{TcpConnection, TcpState};
+
+/// `processes` 个进程，每个进程 1-8 条连接
+fn synthetic_connections(processes: u32) -> Vec<TcpConnection> {
+    let states = [TcpState::Established, TcpState::TimeWait, TcpState::CloseWait, TcpState::Listen];
+    (1..=processes)
+        .flat_map(|pid| {
+            (0..pid % 8 + 1).map(move |i| TcpConnection {
+                local_addr: "10.0.0.1".into(),
+                local_port: (pid % 60000) as u16 + i as u16,
+                remote_addr: "10.0.0.2".into(),
+                remote_port: 443,
+                state: states[(pid + i) as usize % states.len()],
+                pid,
+                process_name: format!("proc{}", pid),
+                raw_state: None,
+                remote_host: None,
+            })
+        })
+        .collect()
+}
+
+fn bench_aggregate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("aggregate_processes");
+    for processes in [1_000u32, 20_000] {
+        let connections = synthetic_connections(processes);
+        group.bench_with_input(BenchmarkId::new("all", processes), &connections, |b, conns| {
+            b.iter(|| aggregate_processes(black_box(conns), None))
+        });
+        group.bench_with_input(BenchmarkId::new("top_50", processes), &connections, |b, conns| {
+            b.iter(|| aggregate_processes(black_box(conns), Some(50)))
+        });
+    }
+    group.finish();
+}
+
+/// 完整的 `get_system_stats`：按状态计数、进程汇总和健康评分
+fn bench_system_stats(c: &mut Criterion) {
+    // 约 10 万条连接
+    let monitor = MockTcpMonitor::new(synthetic_connections(22_000));
+    c.bench_function("get_system_stats/100k", |b| b.iter(|| monitor.get_system_stats().unwrap()));
+}
+
+/// 顺序与并行汇总（含健康评分）对比，每进程平均 4.5 条连接
+fn bench_summarize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("summarize_processes");
+    group.sample_size(20);
+    for processes in [22_000u32, 111_000] {
+        let connections = synthetic_connections(processes);
+        let sequential = StatsOptions::default();
+        group.bench_with_input(BenchmarkId::new("sequential", connections.len()), &connections, |b, conns| {
+            b.iter(|| summarize_processes(black_box(conns), &sequential))
+        });
+        #[cfg(feature = "parallel")]
+        {
+            let parallel = StatsOptions { parallel: true, ..Default::default() };
+            group.bench_with_input(BenchmarkId::new("parallel", connections.len()), &connections, |b, conns| {
+                b.iter(|| summarize_processes(black_box(conns), &parallel))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_aggregate, bench_system_stats, bench_summarize);
+criterion_main!(benches);
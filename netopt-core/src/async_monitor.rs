@@ -0,0 +1,195 @@
+//! 异步监控接口
+//!
+//! `TcpMonitor` 的调用可能需要解析 netstat 输出，耗时较长。这里把调用放到
+//! tokio 的阻塞线程池中执行，避免阻塞运行时。GUI 中手动 `std::thread::spawn`
+//! 的刷新逻辑也可以改用此接口
+
+use crate::monitor::{StatsOptions, TcpMonitor};
+use crate::{NetOptError, Result, SystemTcpStats, TcpConnection};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 可在异步上下文中调用的 TCP 监控器
+pub trait AsyncTcpMonitor {
+    /// 供阻塞线程池持有的监控器
+    fn shared(&self) -> Arc<dyn TcpMonitor>;
+
+    fn get_system_stats_async(&self) -> impl Future<Output = Result<SystemTcpStats>> + Send {
+        run_blocking(self.shared(), |monitor| monitor.get_system_stats())
+    }
+
+    fn get_all_connections_async(&self) -> impl Future<Output = Result<Vec<TcpConnection>>> + Send {
+        run_blocking(self.shared(), |monitor| monitor.get_all_connections())
+    }
+
+    fn get_connections_and_stats_async(
+        &self,
+        options: StatsOptions,
+    ) -> impl Future<Output = Result<(Vec<TcpConnection>, SystemTcpStats)>> + Send {
+        run_blocking(self.shared(), move |monitor| monitor.get_connections_and_stats_with(&options))
+    }
+}
+
+impl AsyncTcpMonitor for Arc<dyn TcpMonitor> {
+    fn shared(&self) -> Arc<dyn TcpMonitor> {
+        Arc::clone(self)
+    }
+}
+
+/// 在阻塞线程池中执行同步监控调用
+pub async fn run_blocking<T, F>(monitor: Arc<dyn TcpMonitor>, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn TcpMonitor) -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(monitor.as_ref()))
+        .await
+        .map_err(|e| NetOptError::SystemError(format!("后台监控任务失败: {}", e)))?
+}
+
+/// 监控调用失败时的指数退避重试策略
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    /// 首次失败后最多重试的次数
+    pub retries: u32,
+    /// 第一次重试前的等待，之后每次翻倍
+    pub initial: Duration,
+    /// 所有重试等待之和的上限，下一次等待会超出时放弃
+    pub budget: Duration,
+}
+
+impl Backoff {
+    pub fn new(retries: u32, initial: Duration, budget: Duration) -> Self {
+        Self { retries, initial, budget }
+    }
+
+    /// 第 `attempt` 次重试（从 0 开始）前的等待
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial.saturating_mul(1 << attempt.min(16))
+    }
+}
+
+/// 按退避策略重试，全部失败时返回最后一次的错误
+pub async fn retry_with_backoff<T, F, Fut>(backoff: Backoff, mut f: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut waited = Duration::ZERO;
+    let mut attempt = 0;
+    loop {
+        let err = match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        let delay = backoff.delay(attempt);
+        if attempt >= backoff.retries || waited + delay > backoff.budget {
+            return Err(err);
+        }
+        tracing::warn!("监控调用失败，{}ms 后重试: {}", delay.as_millis(), err);
+        tokio::time::sleep(delay).await;
+        waited += delay;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::mock::MockTcpMonitor;
+    use crate::{ProcessTcpStats, TcpConnection, TcpState};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// 前 `failures` 次获取统计失败的监控器
+    struct FlakyMonitor {
+        inner: MockTcpMonitor,
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl FlakyMonitor {
+        fn new(failures: u32) -> Self {
+            Self {
+                inner: MockTcpMonitor::new(Vec::new()),
+                failures,
+                calls: AtomicU32::new(0),
+            }
+        }
+    }
+
+    impl TcpMonitor for FlakyMonitor {
+        fn get_all_connections(&self) -> Result<Vec<TcpConnection>> {
+            self.inner.get_all_connections()
+        }
+
+        fn get_process_connections(&self, pid: u32) -> Result<Vec<TcpConnection>> {
+            self.inner.get_process_connections(pid)
+        }
+
+        fn get_process_stats(&self, pid: u32) -> Result<ProcessTcpStats> {
+            self.inner.get_process_stats(pid)
+        }
+
+        fn get_system_stats(&self) -> Result<SystemTcpStats> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(NetOptError::SystemError("netstat 暂时不可用".into()));
+            }
+            self.inner.get_system_stats()
+        }
+    }
+
+    fn flaky_monitor(failures: u32) -> (Arc<FlakyMonitor>, Arc<dyn TcpMonitor>) {
+        let flaky = Arc::new(FlakyMonitor::new(failures));
+        let monitor: Arc<dyn TcpMonitor> = flaky.clone();
+        (flaky, monitor)
+    }
+
+    #[tokio::test]
+    async fn stats_match_blocking_call() {
+        let conn = TcpConnection {
+            local_addr: "10.0.0.1".into(),
+            local_port: 50000,
+            remote_addr: "10.0.0.2".into(),
+            remote_port: 443,
+            state: TcpState::TimeWait,
+            pid: 7,
+            process_name: "app".into(),
+            raw_state: None,
+            remote_host: None,
+        };
+        let monitor: Arc<dyn TcpMonitor> = Arc::new(MockTcpMonitor::new(vec![conn]));
+
+        let stats = monitor.get_system_stats_async().await.unwrap();
+        let connections = monitor.get_all_connections_async().await.unwrap();
+
+        assert_eq!(stats.total_connections, 1);
+        assert_eq!(stats.by_state.get(&TcpState::TimeWait), Some(&1));
+        assert_eq!(connections.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried() {
+        let (flaky, monitor) = flaky_monitor(2);
+        let backoff = Backoff::new(3, Duration::from_millis(1), Duration::from_secs(1));
+
+        let stats = retry_with_backoff(backoff, || monitor.get_system_stats_async()).await;
+
+        assert!(stats.is_ok());
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_retries_or_budget() {
+        let (flaky, monitor) = flaky_monitor(u32::MAX);
+        let backoff = Backoff::new(2, Duration::from_millis(1), Duration::from_secs(1));
+        assert!(retry_with_backoff(backoff, || monitor.get_system_stats_async()).await.is_err());
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+
+        // 1ms + 2ms 已用完 3ms 预算，第三次重试（4ms）被放弃
+        let (flaky, monitor) = flaky_monitor(u32::MAX);
+        let backoff = Backoff::new(10, Duration::from_millis(1), Duration::from_millis(3));
+        assert!(retry_with_backoff(backoff, || monitor.get_system_stats_async()).await.is_err());
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,182 @@
+//! 优化动作审计日志
+//!
+//! 以 JSON Lines 格式追加记录服务执行的每个优化动作，超过大小上限时轮转为 `.1`
+
+use crate::config::AppConfig;
+use crate::optimizer::OptimizationAction;
+use crate::{NetOptError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 默认轮转大小（5 MiB）
+pub const DEFAULT_MAX_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 单条审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// 记录时间（Unix 时间戳，秒）
+    pub timestamp: u64,
+
+    #[serde(flatten)]
+    pub action: OptimizationAction,
+}
+
+/// 审计日志文件
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+        }
+    }
+
+    /// 配置目录下的 actions.log
+    pub fn open_default() -> Result<Self> {
+        let path = AppConfig::config_path()?.with_file_name("actions.log");
+        Ok(Self::new(path, DEFAULT_MAX_BYTES))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 轮转后的旧日志路径
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".1");
+        self.path.with_file_name(name)
+    }
+
+    /// 追加一条动作记录
+    pub fn record(&self, action: &OptimizationAction) -> Result<()> {
+        self.record_at(action, SystemTime::now())
+    }
+
+    /// 以指定时间追加记录
+    pub fn record_at(&self, action: &OptimizationAction, time: SystemTime) -> Result<()> {
+        self.rotate_if_needed()?;
+
+        let entry = AuditEntry {
+            timestamp: unix_secs(time),
+            action: action.clone(),
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(|e| NetOptError::SystemError(format!("审计记录序列化失败: {}", e)))?;
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// 读取 `since` 之后（含）的记录，按时间先后排列，包含已轮转的旧日志
+    pub fn read_since(&self, since: SystemTime) -> Result<Vec<AuditEntry>> {
+        let since = unix_secs(since);
+        let mut entries = Vec::new();
+
+        for path in [self.rotated_path(), self.path.clone()] {
+            let file = match fs::File::open(&path) {
+                Ok(f) => f,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for line in BufReader::new(file).lines() {
+                // 崩溃时可能留下半行，跳过无法解析的行
+                if let Ok(entry) = serde_json::from_str::<AuditEntry>(&line?) {
+                    if entry.timestamp >= since {
+                        entries.push(entry);
+                    }
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn rotate_if_needed(&self) -> Result<()> {
+        match fs::metadata(&self.path) {
+            Ok(meta) if meta.len() >= self.max_bytes => {
+                fs::rename(&self.path, self.rotated_path())?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 读取默认审计日志中 `since` 之后的动作
+pub fn read_actions(since: SystemTime) -> Result<Vec<AuditEntry>> {
+    AuditLog::open_default()?.read_since(since)
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizer::ActionType;
+    use std::time::Duration;
+
+    fn action(pid: u32) -> OptimizationAction {
+        OptimizationAction {
+            pid,
+            process_name: format!("proc{}", pid),
+            action_type: ActionType::CloseCloseWait,
+            reason: "test".into(),
+            connections_affected: 3,
+            success: true,
+            error_message: None,
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("netopt-audit-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn read_since_filters_by_timestamp() {
+        let dir = temp_dir("since");
+        let log = AuditLog::new(dir.join("actions.log"), DEFAULT_MAX_BYTES);
+        let base = UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+        log.record_at(&action(1), base).unwrap();
+        log.record_at(&action(2), base + Duration::from_secs(60)).unwrap();
+        let recent = log.read_since(base + Duration::from_secs(30)).unwrap();
+        let all = log.read_since(UNIX_EPOCH).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(all.len(), 2);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].action.pid, 2);
+        assert_eq!(recent[0].action.action_type, ActionType::CloseCloseWait);
+    }
+
+    #[test]
+    fn rotates_when_over_size() {
+        let dir = temp_dir("rotate");
+        let log = AuditLog::new(dir.join("actions.log"), 1);
+
+        log.record(&action(1)).unwrap();
+        log.record(&action(2)).unwrap();
+        let rotated = log.rotated_path().exists();
+        let all = log.read_since(UNIX_EPOCH).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(rotated);
+        assert_eq!(all.iter().map(|e| e.action.pid).collect::<Vec<_>>(), vec![1, 2]);
+    }
+}
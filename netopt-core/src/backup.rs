@@ -0,0 +1,95 @@
+//! TCP系统配置备份/恢复
+//!
+//! 每次 `apply_config` 前自动在应用配置目录下保存一份带时间戳的备份，
+//! 只保留最近 `MAX_BACKUPS` 份
+
+use crate::config::AppConfig;
+use crate::tcp_config::TcpConfigManager;
+use crate::{NetOptError, Result, TcpSystemConfig};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 自动备份保留数量
+pub const MAX_BACKUPS: usize = 10;
+
+const BACKUP_PREFIX: &str = "tcp-config-";
+const BACKUP_EXT: &str = ".json";
+
+/// 备份目录（应用配置目录下的 backups/）
+pub fn backup_dir() -> Result<PathBuf> {
+    let config_path = AppConfig::config_path()?;
+    let dir = config_path
+        .parent()
+        .map(|p| p.join("backups"))
+        .unwrap_or_else(|| PathBuf::from("backups"));
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 列出所有自动备份，最新的在前
+pub fn list_backups() -> Result<Vec<PathBuf>> {
+    list_backups_in(&backup_dir()?)
+}
+
+/// 列出指定目录下的备份，最新的在前
+pub fn list_backups_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut backups: Vec<(u128, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let stamp = backup_timestamp(&path)?;
+            Some((stamp, path))
+        })
+        .collect();
+
+    backups.sort_by_key(|b| std::cmp::Reverse(b.0));
+    Ok(backups.into_iter().map(|(_, path)| path).collect())
+}
+
+/// 将配置写入 JSON 文件
+pub fn write_config(path: &Path, config: &TcpSystemConfig) -> Result<()> {
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| NetOptError::SystemError(format!("配置备份序列化失败: {}", e)))?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// 从 JSON 文件读取配置
+pub fn read_config(path: &Path) -> Result<TcpSystemConfig> {
+    let content = fs::read_to_string(path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| NetOptError::ParseError(format!("配置备份解析失败: {}", e)))
+}
+
+/// 在 apply_config 前调用：备份当前配置并清理旧备份
+pub(crate) fn auto_backup<M: TcpConfigManager + ?Sized>(mgr: &M) -> Result<PathBuf> {
+    let dir = backup_dir()?;
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let path = dir.join(format!("{}{}{}", BACKUP_PREFIX, stamp, BACKUP_EXT));
+
+    write_config(&path, &mgr.get_current_config()?)?;
+    prune_backups(&dir, MAX_BACKUPS)?;
+    Ok(path)
+}
+
+/// 只保留最新的 `keep` 份备份
+fn prune_backups(dir: &Path, keep: usize) -> Result<()> {
+    for old in list_backups_in(dir)?.into_iter().skip(keep) {
+        fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+/// 从文件名解析备份时间戳（毫秒）
+fn backup_timestamp(path: &Path) -> Option<u128> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(BACKUP_EXT)?
+        .parse()
+        .ok()
+}
@@ -0,0 +1,252 @@
+//! 短时缓存系统统计的监控器
+//!
+//! 自动刷新、手动刷新和仪表盘可能在一秒内多次调用 `get_system_stats`，
+//! 每次都要完整扫描连接表。`CachingMonitor` 在 TTL 内直接返回上次的统计
+
+use crate::clock::{system_clock, Clock};
+use crate::monitor::{StatsOptions, TcpMonitor};
+use crate::{ProcessTcpStats, Result, SystemTcpStats, TcpConnection, UdpSocketInfo};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 默认缓存有效期
+pub const DEFAULT_TTL: Duration = Duration::from_millis(500);
+
+/// 缓存系统统计的监控器包装，其他调用直接转发给内部监控器
+pub struct CachingMonitor {
+    inner: Box<dyn TcpMonitor>,
+    ttl: Duration,
+    /// 采集时间和完整的系统统计。扫描期间持有锁，并发调用会等待同一次扫描的结果
+    cached: Mutex<Option<(Instant, SystemTcpStats)>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl CachingMonitor {
+    pub fn new(inner: Box<dyn TcpMonitor>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cached: Mutex::new(None),
+            clock: system_clock(),
+        }
+    }
+
+    /// 替换判断缓存过期的时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 同 `get_system_stats`，以指定时间判断缓存是否过期
+    pub fn get_system_stats_at(&self, now: Instant) -> Result<SystemTcpStats> {
+        self.stats_at(&StatsOptions::default(), now)
+    }
+
+    /// 忽略缓存重新采集
+    pub fn force_refresh(&self) -> Result<SystemTcpStats> {
+        self.force_refresh_at(self.clock.now())
+    }
+
+    /// 同 `force_refresh`，以指定时间记录
+    pub fn force_refresh_at(&self, now: Instant) -> Result<SystemTcpStats> {
+        let mut cached = self.cached.lock().unwrap();
+        let stats = self.inner.get_system_stats()?;
+        *cached = Some((now, stats.clone()));
+        Ok(stats)
+    }
+
+    /// 缓存保存的是完整统计，进程已按连接数排序，截断即得到前 N 个进程
+    fn stats_at(&self, options: &StatsOptions, now: Instant) -> Result<SystemTcpStats> {
+        // 缓存按系统端口范围计算，指定范围时直接查询
+        if options.port_range.is_some() {
+            return self.inner.get_system_stats_with(options);
+        }
+        let mut cached = self.cached.lock().unwrap();
+        if let Some((at, stats)) = cached.as_ref() {
+            if now.saturating_duration_since(*at) < self.ttl {
+                let mut stats = stats.clone();
+                if let Some(limit) = options.max_tracked_processes {
+                    stats.by_process.truncate(limit);
+                }
+                return Ok(stats);
+            }
+        }
+
+        // 只截取部分进程的结果不能满足之后的完整查询，不写入缓存
+        if options.max_tracked_processes.is_some() {
+            return self.inner.get_system_stats_with(options);
+        }
+        let stats = self.inner.get_system_stats_with(options)?;
+        *cached = Some((now, stats.clone()));
+        Ok(stats)
+    }
+}
+
+impl TcpMonitor for CachingMonitor {
+    fn get_all_connections(&self) -> Result<Vec<TcpConnection>> {
+        self.inner.get_all_connections()
+    }
+
+    fn get_process_connections(&self, pid: u32) -> Result<Vec<TcpConnection>> {
+        self.inner.get_process_connections(pid)
+    }
+
+    fn get_process_stats(&self, pid: u32) -> Result<ProcessTcpStats> {
+        self.inner.get_process_stats(pid)
+    }
+
+    fn get_system_stats(&self) -> Result<SystemTcpStats> {
+        self.get_system_stats_at(self.clock.now())
+    }
+
+    fn get_system_stats_with(&self, options: &StatsOptions) -> Result<SystemTcpStats> {
+        self.stats_at(options, self.clock.now())
+    }
+
+    fn system_stats_from(&self, connections: &[TcpConnection], options: &StatsOptions) -> Result<SystemTcpStats> {
+        self.inner.system_stats_from(connections, options)
+    }
+
+    fn process_stats_from(&self, pid: u32, connections: &[TcpConnection]) -> Result<ProcessTcpStats> {
+        self.inner.process_stats_from(pid, connections)
+    }
+
+    fn get_udp_sockets(&self) -> Result<Vec<UdpSocketInfo>> {
+        self.inner.get_udp_sockets()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::platform::mock::MockTcpMonitor;
+    use crate::TcpState;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// 统计实际扫描次数
+    struct CountingMonitor {
+        inner: MockTcpMonitor,
+        scans: Arc<AtomicUsize>,
+    }
+
+    impl TcpMonitor for CountingMonitor {
+        fn get_all_connections(&self) -> Result<Vec<TcpConnection>> {
+            self.inner.get_all_connections()
+        }
+
+        fn get_process_connections(&self, pid: u32) -> Result<Vec<TcpConnection>> {
+            self.inner.get_process_connections(pid)
+        }
+
+        fn get_process_stats(&self, pid: u32) -> Result<ProcessTcpStats> {
+            self.inner.get_process_stats(pid)
+        }
+
+        fn get_system_stats(&self) -> Result<SystemTcpStats> {
+            self.get_system_stats_with(&StatsOptions::default())
+        }
+
+        fn get_system_stats_with(&self, options: &StatsOptions) -> Result<SystemTcpStats> {
+            self.scans.fetch_add(1, Ordering::SeqCst);
+            self.inner.get_system_stats_with(options)
+        }
+    }
+
+    fn caching_monitor() -> (CachingMonitor, Arc<AtomicUsize>) {
+        let connections = (1..=20u32)
+            .flat_map(|pid| {
+                (0..pid).map(move |i| TcpConnection {
+                    local_addr: "10.0.0.1".into(),
+                    local_port: 40000 + i as u16,
+                    remote_addr: "10.0.0.2".into(),
+                    remote_port: 443,
+                    state: TcpState::Established,
+                    pid,
+                    process_name: format!("proc{}", pid),
+                    raw_state: None,
+                    remote_host: None,
+                })
+            })
+            .collect();
+        let scans = Arc::new(AtomicUsize::new(0));
+        let inner = CountingMonitor {
+            inner: MockTcpMonitor::new(connections),
+            scans: Arc::clone(&scans),
+        };
+        (CachingMonitor::new(Box::new(inner), DEFAULT_TTL), scans)
+    }
+
+    #[test]
+    fn calls_within_ttl_reuse_stats() {
+        let (monitor, scans) = caching_monitor();
+        let start = Instant::now();
+
+        let first = monitor.get_system_stats_at(start).unwrap();
+        let again = monitor.get_system_stats_at(start + Duration::from_millis(499)).unwrap();
+        assert_eq!(scans.load(Ordering::SeqCst), 1);
+        assert_eq!(again.by_process, first.by_process);
+
+        // 过期后重新扫描
+        monitor.get_system_stats_at(start + Duration::from_millis(500)).unwrap();
+        assert_eq!(scans.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn ttl_follows_injected_clock() {
+        let (monitor, scans) = caching_monitor();
+        let clock = Arc::new(MockClock::new(std::time::SystemTime::UNIX_EPOCH));
+        let monitor = monitor.with_clock(clock.clone());
+
+        monitor.get_system_stats().unwrap();
+        clock.advance(Duration::from_millis(499));
+        monitor.get_system_stats().unwrap();
+        assert_eq!(scans.load(Ordering::SeqCst), 1);
+
+        clock.advance(Duration::from_millis(1));
+        monitor.get_system_stats().unwrap();
+        assert_eq!(scans.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn force_refresh_bypasses_cache() {
+        let (monitor, scans) = caching_monitor();
+        let start = Instant::now();
+
+        monitor.get_system_stats_at(start).unwrap();
+        monitor.force_refresh_at(start + Duration::from_millis(100)).unwrap();
+        assert_eq!(scans.load(Ordering::SeqCst), 2);
+
+        // 缓存时间更新为强制刷新的时间
+        monitor.get_system_stats_at(start + Duration::from_millis(550)).unwrap();
+        assert_eq!(scans.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn top_processes_are_served_from_full_cache() {
+        let (monitor, scans) = caching_monitor();
+        let full = monitor.get_system_stats().unwrap();
+
+        let top = monitor.get_top_processes(3).unwrap();
+        assert_eq!(scans.load(Ordering::SeqCst), 1);
+        assert_eq!(top, full.by_process[..3]);
+    }
+
+    #[test]
+    fn concurrent_callers_share_one_scan() {
+        let (monitor, scans) = caching_monitor();
+        let monitor = Arc::new(monitor);
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let monitor = Arc::clone(&monitor);
+                std::thread::spawn(move || monitor.get_system_stats().unwrap())
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(scans.load(Ordering::SeqCst), 1);
+    }
+}
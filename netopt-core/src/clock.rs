@@ -0,0 +1,100 @@
+//! 可替换的时钟
+//!
+//! 缓存有效期、生效时段等逻辑依赖当前时间。组件持有 `Arc<dyn Clock>`，
+//! 默认使用 `SystemClock`，测试中换成 `MockClock` 手动推进时间
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// 时间来源
+pub trait Clock: Send + Sync {
+    /// 单调时间，用于缓存有效期、间隔等
+    fn now(&self) -> Instant;
+
+    /// 墙上时间，用于时间戳和本地时段
+    fn system_time(&self) -> SystemTime;
+}
+
+/// 系统时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// 默认时钟
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// 只在调用 `advance` / `set` 时前进的时钟
+#[derive(Debug)]
+pub struct MockClock {
+    start: Instant,
+    epoch: SystemTime,
+    elapsed: Mutex<Duration>,
+}
+
+impl MockClock {
+    /// 墙上时间从 `at` 开始
+    pub fn new(at: SystemTime) -> Self {
+        Self {
+            start: Instant::now(),
+            epoch: at,
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// 两种时间同时前进 `by`
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+
+    /// 跳到墙上时间 `at`，早于当前时间时忽略（单调时间不能后退）
+    pub fn set(&self, at: SystemTime) {
+        let mut elapsed = self.elapsed.lock().unwrap();
+        if let Ok(since) = at.duration_since(self.epoch) {
+            *elapsed = (*elapsed).max(since);
+        }
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.epoch + *self.elapsed.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_moves_only_when_told() {
+        let epoch = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let clock = MockClock::new(epoch);
+        let start = clock.now();
+
+        assert_eq!(clock.now(), start);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(clock.now() - start, Duration::from_secs(5));
+        assert_eq!(clock.system_time(), epoch + Duration::from_secs(5));
+
+        clock.set(epoch + Duration::from_secs(60));
+        assert_eq!(clock.now() - start, Duration::from_secs(60));
+        // 不后退
+        clock.set(epoch);
+        assert_eq!(clock.system_time(), epoch + Duration::from_secs(60));
+    }
+}
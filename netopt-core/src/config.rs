@@ -2,14 +2,46 @@
 //! 
 //! 提供策略和设置的保存/加载功能
 
-use crate::policy::PolicyManager;
+use crate::policy::{AppPolicy, PolicyManager};
 use crate::i18n::Language;
 use crate::Result;
 use crate::NetOptError;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
 use std::fs;
 
+/// 当前配置文件版本
+pub const CONFIG_VERSION: u32 = 2;
+
+/// 覆盖配置目录的环境变量
+pub const CONFIG_DIR_ENV: &str = "NETOPT_CONFIG_DIR";
+
+/// GUI 主视图
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum View {
+    #[default]
+    Dashboard,
+    Processes,
+    Policies,
+    Settings,
+    Help,
+}
+
+/// 界面主题
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    /// 跟随系统
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+}
+
 /// 应用配置（持久化）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -24,6 +56,30 @@ pub struct AppConfig {
     
     /// 策略管理器
     pub policy_manager: PolicyManager,
+
+    /// 窗口宽度
+    #[serde(default = "default_window_width")]
+    pub window_width: f32,
+
+    /// 窗口高度
+    #[serde(default = "default_window_height")]
+    pub window_height: f32,
+
+    /// 窗口左上角位置，None 时由系统决定
+    #[serde(default)]
+    pub window_position: Option<[f32; 2]>,
+
+    /// 上次打开的视图
+    #[serde(default)]
+    pub last_view: View,
+
+    /// 界面主题
+    #[serde(default)]
+    pub theme: Theme,
+
+    /// 连接详情中反向解析远端主机名。会产生 DNS 查询，默认关闭
+    #[serde(default)]
+    pub resolve_hostnames: bool,
     
     /// 配置版本（用于迁移）
     pub version: u32,
@@ -36,53 +92,119 @@ impl Default for AppConfig {
             auto_refresh: true,
             refresh_interval: 5,
             policy_manager: PolicyManager::new(),
-            version: 1,
+            window_width: default_window_width(),
+            window_height: default_window_height(),
+            window_position: None,
+            last_view: View::default(),
+            theme: Theme::default(),
+            resolve_hostnames: false,
+            version: CONFIG_VERSION,
         }
     }
 }
 
+fn default_window_width() -> f32 {
+    900.0
+}
+
+fn default_window_height() -> f32 {
+    650.0
+}
+
 impl AppConfig {
     /// 获取配置文件路径
+    ///
+    /// 设置了 `NETOPT_CONFIG_DIR` 时使用该目录（便携模式/多配置隔离）
     pub fn config_path() -> Result<PathBuf> {
-        let config_dir = if cfg!(target_os = "windows") {
-            std::env::var("APPDATA")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| PathBuf::from("."))
-        } else if cfg!(target_os = "macos") {
-            dirs::config_dir().unwrap_or_else(|| PathBuf::from("."))
-        } else {
-            PathBuf::from(".")
+        let app_dir = match std::env::var_os(CONFIG_DIR_ENV) {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => {
+                let config_dir = if cfg!(target_os = "windows") {
+                    std::env::var("APPDATA")
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|_| PathBuf::from("."))
+                } else {
+                    dirs::config_dir().unwrap_or_else(|| PathBuf::from("."))
+                };
+                config_dir.join("smart-tcp-manager")
+            }
         };
-        
-        let app_dir = config_dir.join("smart-tcp-manager");
-        fs::create_dir_all(&app_dir).map_err(|e| NetOptError::IoError(e))?;
+        fs::create_dir_all(&app_dir)?;
         
         Ok(app_dir.join("config.json"))
     }
     
     /// 从文件加载配置
+    ///
+    /// 文件损坏时备份为 `config.json.bak-<时间戳>` 并返回默认配置，避免静默丢失策略
     pub fn load() -> Result<Self> {
-        let path = Self::config_path()?;
-        
+        Self::load_or_recover(&Self::config_path()?)
+    }
+    
+    /// 从文件加载配置，文件损坏时直接返回错误
+    pub fn load_strict() -> Result<Self> {
+        Self::load_from(&Self::config_path()?)
+    }
+    
+    /// 从指定文件加载配置，解析失败时备份损坏文件并返回默认配置
+    pub fn load_or_recover(path: &Path) -> Result<Self> {
+        match Self::load_from(path) {
+            Err(NetOptError::ParseError(msg)) => {
+                let backup = corrupt_backup_path(path);
+                fs::rename(path, &backup)?;
+                tracing::warn!("{}，已将损坏的配置备份到 {}，使用默认配置", msg, backup.display());
+                Ok(Self::default())
+            }
+            other => other,
+        }
+    }
+    
+    /// 从指定文件加载配置，旧版本文件迁移后回写
+    pub fn load_from(path: &Path) -> Result<Self> {
         if !path.exists() {
             return Ok(Self::default());
         }
         
-        let content = fs::read_to_string(&path)?;
-        let config: AppConfig = serde_json::from_str(&content)
-            .map_err(|e| NetOptError::SystemError(format!("配置解析失败: {}", e)))?;
+        let content = fs::read_to_string(path)?;
+        let raw: Value = serde_json::from_str(&content)
+            .map_err(|e| NetOptError::ParseError(format!("配置解析失败: {}", e)))?;
         
+        let version = raw_version(&raw);
+        if version >= CONFIG_VERSION {
+            return serde_json::from_value(raw)
+                .map_err(|e| NetOptError::ParseError(format!("配置解析失败: {}", e)));
+        }
+        
+        let config = Self::migrate(raw)?;
+        config.save_to(path)?;
+        tracing::info!("配置已从 v{} 迁移到 v{}", version, CONFIG_VERSION);
         Ok(config)
     }
     
+    /// 将旧版本配置升级到当前版本
+    pub fn migrate(mut raw: Value) -> Result<AppConfig> {
+        if raw_version(&raw) < 2 {
+            migrate_v1_to_v2(&mut raw);
+        }
+        
+        if let Some(obj) = raw.as_object_mut() {
+            obj.insert("version".into(), CONFIG_VERSION.into());
+        }
+        serde_json::from_value(raw)
+            .map_err(|e| NetOptError::ParseError(format!("配置迁移失败: {}", e)))
+    }
+    
     /// 保存配置到文件
     pub fn save(&self) -> Result<()> {
-        let path = Self::config_path()?;
+        self.save_to(&Self::config_path()?)
+    }
+    
+    /// 保存配置到指定文件（先写临时文件再替换，避免崩溃时留下半截文件）
+    pub fn save_to(&self, path: &Path) -> Result<()> {
         let content = serde_json::to_string_pretty(self)
             .map_err(|e| NetOptError::SystemError(format!("配置序列化失败: {}", e)))?;
         
-        fs::write(&path, content)?;
-        Ok(())
+        write_atomic(path, content.as_bytes())
     }
     
     /// 导出策略到指定文件
@@ -98,13 +220,93 @@ impl AppConfig {
     pub fn import_policies(&mut self, path: &PathBuf) -> Result<()> {
         let content = fs::read_to_string(path)?;
         let policy_manager: PolicyManager = serde_json::from_str(&content)
-            .map_err(|e| NetOptError::SystemError(format!("策略解析失败: {}", e)))?;
+            .map_err(|e| NetOptError::ParseError(format!("策略解析失败: {}", e)))?;
         
         self.policy_manager = policy_manager;
         Ok(())
     }
 }
 
+/// 临时文件路径：`<原文件名>.tmp`，与目标同目录以保证 rename 不跨文件系统
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// 原子写入：写入同目录临时文件并落盘后 rename 覆盖目标
+///
+/// Windows 上 `fs::rename` 使用 MOVEFILE_REPLACE_EXISTING，目标已存在时同样直接替换
+fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    use std::io::Write;
+    
+    let tmp = temp_path(path);
+    let result = fs::File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path));
+    
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    Ok(result?)
+}
+
+/// 损坏配置的备份路径：`<原文件名>.bak-<Unix时间戳>`
+fn corrupt_backup_path(path: &Path) -> PathBuf {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak-{}", stamp));
+    path.with_file_name(name)
+}
+
+/// 读取原始配置中的版本号（缺失视为 v1）
+fn raw_version(raw: &Value) -> u32 {
+    raw.get("version")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(1)
+}
+
+/// v1 -> v2：补齐缺失字段
+///
+/// v1 没有迁移机制，旧文件中缺少后续新增的字段（含每个策略的字段）会导致反序列化失败
+fn migrate_v1_to_v2(raw: &mut Value) {
+    if let Ok(defaults) = serde_json::to_value(AppConfig::default()) {
+        fill_missing(raw, &defaults);
+    }
+    if let Ok(policy_default) = serde_json::to_value(AppPolicy::default()) {
+        if let Some(policies) = raw
+            .pointer_mut("/policy_manager/policies")
+            .and_then(Value::as_object_mut)
+        {
+            for policy in policies.values_mut() {
+                fill_missing(policy, &policy_default);
+            }
+        }
+    }
+}
+
+/// 用默认值递归补齐对象中缺失的键，已有值保持不变
+fn fill_missing(target: &mut Value, defaults: &Value) {
+    let (Some(target), Some(defaults)) = (target.as_object_mut(), defaults.as_object()) else {
+        return;
+    };
+    for (key, default) in defaults {
+        match target.get_mut(key) {
+            Some(existing) => fill_missing(existing, default),
+            None => {
+                target.insert(key.clone(), default.clone());
+            }
+        }
+    }
+}
+
 /// 配置目录相关函数
 pub mod dirs {
     use std::path::PathBuf;
@@ -131,3 +333,150 @@ pub mod dirs {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::ThresholdAction;
+
+    fn v1_blob() -> Value {
+        serde_json::json!({
+            "language": "English",
+            "auto_refresh": false,
+            "refresh_interval": 10,
+            "policy_manager": {
+                "policies": {
+                    "chrome": {
+                        "process_name": "chrome",
+                        "exe_path": null,
+                        "auto_optimize": false,
+                        "time_wait_threshold": 200,
+                        "close_wait_threshold": 10,
+                        "max_connections": null,
+                        "threshold_action": "Optimize"
+                    }
+                },
+                "whitelist": ["sshd"]
+            },
+            "version": 1
+        })
+    }
+
+    #[test]
+    fn migrate_v1_fills_defaults_and_bumps_version() {
+        let config = AppConfig::migrate(v1_blob()).unwrap();
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.language, Language::English);
+        assert_eq!(config.refresh_interval, 10);
+
+        let policy = config.policy_manager.get_policy("chrome");
+        assert!(!policy.auto_optimize);
+        assert_eq!(policy.time_wait_threshold, Some(200));
+        assert_eq!(policy.threshold_action, ThresholdAction::Optimize);
+        // v1 缺失的字段取默认值
+        assert_eq!(policy.priority, AppPolicy::default().priority);
+        assert!(policy.note.is_empty());
+        assert_eq!(config.policy_manager.whitelist, vec!["sshd".to_string()]);
+        assert_eq!(config.window_width, default_window_width());
+        assert_eq!(config.window_position, None);
+        assert_eq!(config.last_view, View::Dashboard);
+        assert_eq!(config.theme, Theme::System);
+        assert_eq!(
+            config.policy_manager.default_policy.close_wait_threshold,
+            AppPolicy::default().close_wait_threshold
+        );
+    }
+
+    #[test]
+    fn load_from_rewrites_migrated_file() {
+        let path = std::env::temp_dir().join(format!("netopt-config-v1-{}.json", std::process::id()));
+        fs::write(&path, v1_blob().to_string()).unwrap();
+
+        let config = AppConfig::load_from(&path).unwrap();
+        let rewritten: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(raw_version(&rewritten), CONFIG_VERSION);
+    }
+
+    #[test]
+    fn corrupt_file_is_backed_up_and_defaults_returned() {
+        let dir = std::env::temp_dir().join(format!("netopt-config-corrupt-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        fs::write(&path, r#"{"language": "English", "policy_manager": {"#).unwrap();
+
+        assert!(AppConfig::load_from(&path).is_err());
+        let config = AppConfig::load_or_recover(&path).unwrap();
+
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("config.json.bak-"))
+            .collect();
+        let backup_content = backups.first().map(|e| fs::read_to_string(e.path()).unwrap());
+        let original_left = path.exists();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(config.language, Language::Chinese);
+        assert_eq!(backups.len(), 1);
+        assert!(backup_content.unwrap().contains("policy_manager"));
+        assert!(!original_left);
+    }
+
+    #[test]
+    fn partial_temp_write_leaves_original_intact() {
+        let dir = std::env::temp_dir().join(format!("netopt-config-atomic-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+
+        let mut config = AppConfig {
+            refresh_interval: 42,
+            ..Default::default()
+        };
+        config.save_to(&path).unwrap();
+
+        // 模拟写临时文件到一半时崩溃
+        fs::write(temp_path(&path), r#"{"language": "Eng"#).unwrap();
+        let loaded = AppConfig::load_from(&path).unwrap();
+
+        // 下次保存覆盖残留的临时文件
+        config.refresh_interval = 7;
+        config.save_to(&path).unwrap();
+        let resaved = AppConfig::load_from(&path).unwrap();
+        let tmp_left = temp_path(&path).exists();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(loaded.refresh_interval, 42);
+        assert_eq!(resaved.refresh_interval, 7);
+        assert!(!tmp_left);
+    }
+
+    #[test]
+    fn saved_policy_drives_engine_action() {
+        use crate::optimizer::{ActionType, OptimizationEngine};
+        use crate::ProcessTcpStats;
+
+        let path = std::env::temp_dir().join(format!("netopt-config-policy-{}.json", std::process::id()));
+        let mut config = AppConfig::default();
+        config.policy_manager.set_policy(AppPolicy::crawler("scraper"));
+        config.save_to(&path).unwrap();
+
+        let loaded = AppConfig::load_from(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        let mut engine = OptimizationEngine::new(loaded.policy_manager);
+        let stats = ProcessTcpStats {
+            pid: 42,
+            process_name: "scraper".into(),
+            close_wait: 25,
+            ..Default::default()
+        };
+        let actions = engine.analyze_and_decide(&stats);
+
+        // 默认策略 CLOSE_WAIT 阈值为 30，只有采集策略（阈值 20）才会触发
+        assert!(actions.iter().any(|a| a.action_type == ActionType::CloseCloseWait && a.pid == 42));
+    }
+}
+
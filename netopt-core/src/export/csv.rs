@@ -0,0 +1,143 @@
+//! CSV 导出
+//!
+//! 将连接快照和进程统计写为 CSV，便于导入表格软件分析
+
+use crate::{ProcessTcpStats, Result, TcpConnection};
+use std::io::Write;
+
+/// 导出连接列表为 CSV（含表头）。未解析主机名时 remote_host 列为空
+pub fn export_connections_csv(conns: &[TcpConnection], mut writer: impl Write) -> Result<()> {
+    writeln!(writer, "local_addr,local_port,remote_addr,remote_port,state,pid,process_name,remote_host")?;
+    for conn in conns {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{}",
+            quote(&conn.local_addr),
+            conn.local_port,
+            quote(&conn.remote_addr),
+            conn.remote_port,
+            conn.state,
+            conn.pid,
+            quote(&conn.process_name),
+            quote(conn.remote_host.as_deref().unwrap_or("")),
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// 导出进程统计为 CSV（含表头）
+pub fn export_process_stats_csv(stats: &[ProcessTcpStats], mut writer: impl Write) -> Result<()> {
+    writeln!(
+        writer,
+        "pid,process_name,exe_path,total_connections,established,time_wait,close_wait,listen,other,health_score"
+    )?;
+    for s in stats {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{},{}",
+            s.pid,
+            quote(&s.process_name),
+            quote(s.exe_path.as_deref().unwrap_or("")),
+            s.total_connections,
+            s.established,
+            s.time_wait,
+            s.close_wait,
+            s.listen,
+            s.other,
+            s.health_score,
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// 按 RFC 4180 规则转义字段：含逗号、引号或换行时用双引号包裹，内部引号加倍
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TcpState;
+
+    /// 按 RFC 4180 解析为记录列表，引号内的逗号和换行属于字段内容
+    fn parse(csv: &str) -> Vec<Vec<String>> {
+        let (mut records, mut record, mut field) = (Vec::new(), Vec::new(), String::new());
+        let mut chars = csv.chars().peekable();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => record.push(std::mem::take(&mut field)),
+                (false, '\n') => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                (false, c) => field.push(c),
+            }
+        }
+        records
+    }
+
+    fn conn(process_name: &str, remote_host: Option<&str>) -> TcpConnection {
+        TcpConnection {
+            local_addr: "10.0.0.1".into(),
+            local_port: 50000,
+            remote_addr: "10.0.0.2".into(),
+            remote_port: 443,
+            state: TcpState::CloseWait,
+            pid: 42,
+            process_name: process_name.into(),
+            raw_state: None,
+            remote_host: remote_host.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn every_row_has_as_many_fields_as_the_header() {
+        let conns = [conn("web", None), conn("a,b", Some("example.com")), conn("line\nbreak", None)];
+        let mut out = Vec::new();
+        export_connections_csv(&conns, &mut out).unwrap();
+        let records = parse(&String::from_utf8(out).unwrap());
+        assert_eq!(records.len(), conns.len() + 1);
+        assert!(records.iter().all(|r| r.len() == records[0].len()), "{:?}", records);
+        assert_eq!(records[2][7], "example.com");
+
+        let stats = [
+            ProcessTcpStats { pid: 1, process_name: "web".into(), ..Default::default() },
+            ProcessTcpStats { pid: 2, process_name: "x,\"y\"".into(), exe_path: Some("C:\\a, b\\x.exe".into()), ..Default::default() },
+        ];
+        let mut out = Vec::new();
+        export_process_stats_csv(&stats, &mut out).unwrap();
+        let records = parse(&String::from_utf8(out).unwrap());
+        assert_eq!(records.len(), stats.len() + 1);
+        assert!(records.iter().all(|r| r.len() == records[0].len()), "{:?}", records);
+        assert_eq!(records[2][2], "C:\\a, b\\x.exe");
+    }
+
+    #[test]
+    fn special_characters_are_quoted_per_rfc_4180() {
+        let name = "say \"hi\", then\nleave";
+        let mut out = Vec::new();
+        export_connections_csv(&[conn(name, None)], &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+
+        assert!(csv.contains(",\"say \"\"hi\"\", then\nleave\","), "{}", csv);
+        assert_eq!(parse(&csv)[1][6], name);
+        // 不含特殊字符的字段保持原样
+        assert_eq!(quote("web"), "web");
+        assert_eq!(quote("a\r\nb"), "\"a\r\nb\"");
+    }
+}
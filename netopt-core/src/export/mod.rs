@@ -0,0 +1,9 @@
+//! 数据导出模块
+//!
+//! 将TCP统计数据转换为外部系统可消费的格式
+
+pub mod prometheus;
+pub mod csv;
+
+pub use prometheus::render_prometheus;
+pub use csv::{export_connections_csv, export_process_stats_csv};
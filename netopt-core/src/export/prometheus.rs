@@ -0,0 +1,114 @@
+//! Prometheus 文本格式导出
+//!
+//! 输出符合 Prometheus exposition format (0.0.4) 的指标文本，供 /metrics 端点使用
+
+use crate::{SystemTcpStats, TcpState};
+use std::fmt::Write;
+
+/// 将系统统计渲染为 Prometheus 文本格式
+pub fn render_prometheus(stats: &SystemTcpStats) -> String {
+    let mut out = String::new();
+
+    write_header(&mut out, "netopt_connections_total", "TCP connections by state");
+    // HashMap 无序，按状态名排序保证输出稳定
+    let mut states: Vec<(&TcpState, &usize)> = stats.by_state.iter().collect();
+    states.sort_by_key(|(state, _)| state.to_string());
+    for (state, count) in states {
+        let _ = writeln!(out, "netopt_connections_total{{state=\"{}\"}} {}", state, count);
+    }
+
+    write_header(&mut out, "netopt_connections", "Total number of TCP connections");
+    let _ = writeln!(out, "netopt_connections {}", stats.total_connections);
+
+    write_header(&mut out, "netopt_available_ports", "Available ephemeral ports");
+    let _ = writeln!(out, "netopt_available_ports {}", stats.available_ports);
+
+    write_header(&mut out, "netopt_ephemeral_ports_in_use", "Distinct ephemeral local ports in use");
+    let _ = writeln!(out, "netopt_ephemeral_ports_in_use {}", stats.ephemeral_ports_in_use);
+
+    write_header(&mut out, "netopt_port_usage_percent", "Ephemeral port usage in percent");
+    let _ = writeln!(out, "netopt_port_usage_percent {:.2}", stats.port_usage_percent);
+
+    write_header(&mut out, "netopt_process_connections", "TCP connections per process and state");
+    for proc in &stats.by_process {
+        let name = escape_label_value(&proc.process_name);
+        let per_state = [
+            (TcpState::Established, proc.established),
+            (TcpState::TimeWait, proc.time_wait),
+            (TcpState::CloseWait, proc.close_wait),
+            (TcpState::Listen, proc.listen),
+        ];
+        for (state, count) in per_state {
+            let _ = writeln!(
+                out,
+                "netopt_process_connections{{pid=\"{}\",name=\"{}\",state=\"{}\"}} {}",
+                proc.pid, name, state, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "netopt_process_connections{{pid=\"{}\",name=\"{}\",state=\"OTHER\"}} {}",
+            proc.pid, name, proc.other
+        );
+    }
+
+    write_header(&mut out, "netopt_process_health_score", "Process health score (0-100)");
+    for proc in &stats.by_process {
+        let _ = writeln!(
+            out,
+            "netopt_process_health_score{{pid=\"{}\",name=\"{}\"}} {}",
+            proc.pid,
+            escape_label_value(&proc.process_name),
+            proc.health_score
+        );
+    }
+
+    out
+}
+
+fn write_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+/// 转义标签值：反斜杠、双引号和换行需要转义
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProcessTcpStats;
+
+    #[test]
+    fn process_names_are_escaped_in_labels() {
+        let stats = SystemTcpStats {
+            by_process: vec![ProcessTcpStats {
+                pid: 7,
+                process_name: "C:\\bin\\\"odd\"\nname".into(),
+                health_score: 90,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let out = render_prometheus(&stats);
+        assert!(
+            out.lines().any(|l| l == r#"netopt_process_health_score{pid="7",name="C:\\bin\\\"odd\"\nname"} 90"#),
+            "{}",
+            out
+        );
+        // 换行被转义，每个样本仍占一行
+        assert!(out.lines().filter(|l| l.contains("pid=\"7\"")).all(|l| l.ends_with(char::is_numeric)));
+    }
+}
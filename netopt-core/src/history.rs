@@ -0,0 +1,528 @@
+//! 统计历史记录
+//!
+//! 保存最近若干次采集结果的有界环形缓冲，用于计算增量和趋势
+
+use crate::monitor::{AnomalyType, ConnectionAnomaly, Severity};
+use crate::{ConnectionKey, SystemTcpStats, TcpConnection, TcpState};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant, SystemTime};
+
+/// 单次采样
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSample {
+    pub timestamp: SystemTime,
+    pub stats: SystemTcpStats,
+}
+
+/// 进程在相邻两次采样之间的变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessDelta {
+    pub pid: u32,
+    pub process_name: String,
+    pub total_delta: i64,
+    pub time_wait_delta: i64,
+    pub close_wait_delta: i64,
+    /// TIME_WAIT + CLOSE_WAIT 每秒增长量
+    pub growth_per_sec: f64,
+}
+
+/// 有界统计历史
+#[derive(Debug, Clone)]
+pub struct StatsHistory {
+    samples: VecDeque<StatsSample>,
+    capacity: usize,
+}
+
+impl StatsHistory {
+    /// 创建最多保留 `capacity` 个采样的历史
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// 以当前时间记录一次采样
+    pub fn push(&mut self, stats: SystemTcpStats) {
+        self.push_at(stats, SystemTime::now());
+    }
+
+    /// 以指定时间记录一次采样，超出容量时丢弃最旧的
+    pub fn push_at(&mut self, stats: SystemTcpStats, timestamp: SystemTime) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(StatsSample { timestamp, stats });
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 所有采样（从旧到新）
+    pub fn samples(&self) -> impl Iterator<Item = &StatsSample> {
+        self.samples.iter()
+    }
+
+    /// 最新采样之前 `span` 时间内的采样（从旧到新）
+    pub fn within(&self, span: Duration) -> impl Iterator<Item = &StatsSample> {
+        let cutoff = self.latest().and_then(|s| s.timestamp.checked_sub(span));
+        self.samples.iter().filter(move |s| cutoff.is_none_or(|c| s.timestamp >= c))
+    }
+
+    /// 最新采样
+    pub fn latest(&self) -> Option<&StatsSample> {
+        self.samples.back()
+    }
+
+    /// 倒数第二次采样
+    pub fn previous(&self) -> Option<&StatsSample> {
+        self.samples.len().checked_sub(2).and_then(|i| self.samples.get(i))
+    }
+
+    /// 清空历史
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// 计算最近两次采样间每个进程的变化，按增长速率降序排列
+    ///
+    /// 新出现的进程以 0 为基线；少于两次采样时返回空列表
+    pub fn process_deltas(&self) -> Vec<ProcessDelta> {
+        let (Some(prev), Some(curr)) = (self.previous(), self.latest()) else {
+            return Vec::new();
+        };
+
+        let elapsed = curr.timestamp
+            .duration_since(prev.timestamp)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0)
+            .max(f64::EPSILON);
+
+        let prev_by_pid: HashMap<u32, _> = prev.stats.by_process.iter().map(|p| (p.pid, p)).collect();
+
+        let mut deltas: Vec<ProcessDelta> = curr.stats.by_process.iter().map(|p| {
+            let (total, tw, cw) = prev_by_pid.get(&p.pid)
+                .map(|old| (old.total_connections, old.time_wait, old.close_wait))
+                .unwrap_or((0, 0, 0));
+            let time_wait_delta = p.time_wait as i64 - tw as i64;
+            let close_wait_delta = p.close_wait as i64 - cw as i64;
+
+            ProcessDelta {
+                pid: p.pid,
+                process_name: p.process_name.clone(),
+                total_delta: p.total_connections as i64 - total as i64,
+                time_wait_delta,
+                close_wait_delta,
+                growth_per_sec: (time_wait_delta + close_wait_delta) as f64 / elapsed,
+            }
+        }).collect();
+
+        deltas.sort_by(|a, b| b.growth_per_sec.total_cmp(&a.growth_per_sec));
+        deltas
+    }
+}
+
+impl Default for StatsHistory {
+    fn default() -> Self {
+        Self::new(120)
+    }
+}
+
+/// 进程在相邻两次连接快照之间的速率
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProcessRate {
+    pub pid: u32,
+    /// 每秒新建连接数
+    pub connections_opened_per_sec: f32,
+    /// TIME_WAIT 每秒净增长（可为负）
+    pub time_wait_growth_per_sec: f32,
+    /// CLOSE_WAIT 每秒净增长（可为负）
+    pub close_wait_growth_per_sec: f32,
+}
+
+/// 连接快照
+struct ConnSnapshot {
+    at: Instant,
+    keys: HashSet<ConnectionKey>,
+    time_wait: HashMap<u32, usize>,
+    close_wait: HashMap<u32, usize>,
+}
+
+/// 连接速率跟踪
+///
+/// 比较相邻两次连接快照：上次不存在的四元组计为新建连接。
+/// TIME_WAIT 数量低但创建速率高的进程同样会耗尽端口
+#[derive(Default)]
+pub struct RateTracker {
+    previous: Option<ConnSnapshot>,
+}
+
+impl RateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 以当前时间记录快照，返回与上次快照相比的速率
+    pub fn update(&mut self, connections: &[TcpConnection]) -> HashMap<u32, ProcessRate> {
+        self.update_at(connections, Instant::now())
+    }
+
+    /// 以指定时间记录快照；首次调用没有基线，返回空
+    pub fn update_at(&mut self, connections: &[TcpConnection], at: Instant) -> HashMap<u32, ProcessRate> {
+        let mut current = ConnSnapshot {
+            at,
+            keys: HashSet::with_capacity(connections.len()),
+            time_wait: HashMap::new(),
+            close_wait: HashMap::new(),
+        };
+        let mut opened: HashMap<u32, usize> = HashMap::new();
+        let mut pids: HashSet<u32> = HashSet::new();
+
+        for conn in connections {
+            pids.insert(conn.pid);
+            let key = conn.key();
+            match conn.state {
+                TcpState::TimeWait => *current.time_wait.entry(conn.pid).or_insert(0) += 1,
+                TcpState::CloseWait => *current.close_wait.entry(conn.pid).or_insert(0) += 1,
+                _ => {}
+            }
+            let is_new = self.previous.as_ref().is_some_and(|prev| !prev.keys.contains(&key));
+            if current.keys.insert(key) && is_new {
+                *opened.entry(conn.pid).or_insert(0) += 1;
+            }
+        }
+
+        let Some(prev) = self.previous.take() else {
+            self.previous = Some(current);
+            return HashMap::new();
+        };
+        let elapsed = at.duration_since(prev.at).as_secs_f32().max(f32::EPSILON);
+
+        // 上次有 TIME_WAIT/CLOSE_WAIT 而本次已无连接的进程也要报告负增长
+        pids.extend(prev.time_wait.keys());
+        pids.extend(prev.close_wait.keys());
+
+        let rates = pids.into_iter().map(|pid| {
+            let growth = |now: &HashMap<u32, usize>, before: &HashMap<u32, usize>| {
+                let now = now.get(&pid).copied().unwrap_or(0) as f32;
+                let before = before.get(&pid).copied().unwrap_or(0) as f32;
+                (now - before) / elapsed
+            };
+            let rate = ProcessRate {
+                pid,
+                connections_opened_per_sec: opened.get(&pid).copied().unwrap_or(0) as f32 / elapsed,
+                time_wait_growth_per_sec: growth(&current.time_wait, &prev.time_wait),
+                close_wait_growth_per_sec: growth(&current.close_wait, &prev.close_wait),
+            };
+            (pid, rate)
+        }).collect();
+
+        self.previous = Some(current);
+        rates
+    }
+
+    /// 将速率写入统计的 `churn_rate` 和 `close_wait_growth`
+    pub fn apply(rates: &HashMap<u32, ProcessRate>, stats: &mut SystemTcpStats) {
+        for proc in &mut stats.by_process {
+            let rate = rates.get(&proc.pid);
+            proc.churn_rate = rate.map(|r| r.connections_opened_per_sec);
+            proc.close_wait_growth = rate.map(|r| r.close_wait_growth_per_sec);
+        }
+    }
+}
+
+/// 默认状态抖动统计窗口
+pub const FLAP_WINDOW: Duration = Duration::from_secs(300);
+/// 窗口内状态变化超过此次数视为抖动
+pub const FLAP_MAX_TRANSITIONS: usize = 4;
+
+/// 状态反复变化的连接
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlappingConnection {
+    pub pid: u32,
+    pub process_name: String,
+    pub local_addr: String,
+    pub local_port: u16,
+    pub remote_addr: String,
+    pub remote_port: u16,
+    /// 窗口内的状态变化次数
+    pub transitions: usize,
+}
+
+/// 单个四元组的跟踪状态
+struct TrackedConn {
+    /// 最近一次快照中的状态，未出现时为 None
+    state: Option<TcpState>,
+    pid: u32,
+    process_name: String,
+    last_seen: Instant,
+    transitions: VecDeque<Instant>,
+}
+
+/// 连接状态变化跟踪
+///
+/// 按四元组比较相邻快照，状态改变或消失后重新出现都计为一次变化。
+/// 只出现一次就消失的短连接不会产生变化，消失超过窗口后被清理
+pub struct ConnectionTracker {
+    window: Duration,
+    max_transitions: usize,
+    tracked: HashMap<ConnectionKey, TrackedConn>,
+}
+
+impl ConnectionTracker {
+    pub fn new(window: Duration, max_transitions: usize) -> Self {
+        Self {
+            window,
+            max_transitions,
+            tracked: HashMap::new(),
+        }
+    }
+
+    /// 以当前时间记录快照，返回窗口内状态变化过多的连接
+    pub fn update(&mut self, connections: &[TcpConnection]) -> Vec<FlappingConnection> {
+        self.update_at(connections, Instant::now())
+    }
+
+    /// 以指定时间记录快照
+    pub fn update_at(&mut self, connections: &[TcpConnection], at: Instant) -> Vec<FlappingConnection> {
+        let mut seen = HashSet::with_capacity(connections.len());
+
+        for conn in connections {
+            let key = conn.key();
+            match self.tracked.get_mut(&key) {
+                Some(tracked) => {
+                    if tracked.state != Some(conn.state) {
+                        tracked.transitions.push_back(at);
+                    }
+                    tracked.state = Some(conn.state);
+                    tracked.pid = conn.pid;
+                    tracked.last_seen = at;
+                }
+                None => {
+                    self.tracked.insert(key.clone(), TrackedConn {
+                        state: Some(conn.state),
+                        pid: conn.pid,
+                        process_name: conn.process_name.clone(),
+                        last_seen: at,
+                        transitions: VecDeque::new(),
+                    });
+                }
+            }
+            seen.insert(key);
+        }
+
+        let window = self.window;
+        self.tracked.retain(|key, tracked| {
+            if !seen.contains(key) {
+                tracked.state = None;
+            }
+            while tracked.transitions.front().is_some_and(|t| at.duration_since(*t) > window) {
+                tracked.transitions.pop_front();
+            }
+            at.duration_since(tracked.last_seen) <= window
+        });
+
+        let mut flapping: Vec<FlappingConnection> = self.tracked.iter()
+            .filter(|(_, tracked)| tracked.transitions.len() > self.max_transitions)
+            .map(|(ConnectionKey { local_addr, local_port, remote_addr, remote_port }, tracked)| FlappingConnection {
+                pid: tracked.pid,
+                process_name: tracked.process_name.clone(),
+                local_addr: local_addr.clone(),
+                local_port: *local_port,
+                remote_addr: remote_addr.clone(),
+                remote_port: *remote_port,
+                transitions: tracked.transitions.len(),
+            })
+            .collect();
+        flapping.sort_by_key(|c| std::cmp::Reverse(c.transitions));
+        flapping
+    }
+
+    /// 跟踪中的四元组数量
+    pub fn len(&self) -> usize {
+        self.tracked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracked.is_empty()
+    }
+}
+
+impl Default for ConnectionTracker {
+    fn default() -> Self {
+        Self::new(FLAP_WINDOW, FLAP_MAX_TRANSITIONS)
+    }
+}
+
+/// 按进程汇总抖动连接为异常
+pub fn flapping_anomalies(flapping: &[FlappingConnection]) -> Vec<ConnectionAnomaly> {
+    let mut by_pid: HashMap<u32, (String, usize)> = HashMap::new();
+    for conn in flapping {
+        by_pid.entry(conn.pid).or_insert_with(|| (conn.process_name.clone(), 0)).1 += 1;
+    }
+
+    let mut anomalies: Vec<ConnectionAnomaly> = by_pid.into_iter()
+        .map(|(pid, (process_name, count))| ConnectionAnomaly {
+            pid,
+            process_name,
+            anomaly_type: AnomalyType::ConnectionFlapping,
+            severity: Severity::Warning,
+            message: format!("{} 个连接状态反复变化", count),
+            suggestion: "可能存在重试风暴，检查重连逻辑是否有退避".into(),
+        })
+        .collect();
+    anomalies.sort_by_key(|a| a.pid);
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn conn(local_port: u16, state: TcpState, pid: u32) -> TcpConnection {
+        TcpConnection {
+            local_addr: "10.0.0.2".into(),
+            local_port,
+            remote_addr: "1.1.1.1".into(),
+            remote_port: 443,
+            state,
+            pid,
+            process_name: String::new(),
+            raw_state: None,
+            remote_host: None,
+        }
+    }
+
+    #[test]
+    fn within_keeps_only_recent_samples() {
+        let mut history = StatsHistory::new(10);
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        for i in 0..5u64 {
+            let stats = SystemTcpStats { total_connections: i as usize, ..Default::default() };
+            history.push_at(stats, start + Duration::from_secs(i * 30));
+        }
+
+        let recent: Vec<usize> = history.within(Duration::from_secs(60)).map(|s| s.stats.total_connections).collect();
+        assert_eq!(recent, vec![2, 3, 4]);
+        assert_eq!(history.within(Duration::from_secs(3600)).count(), 5);
+    }
+
+    #[test]
+    fn first_update_has_no_rates() {
+        let mut tracker = RateTracker::new();
+        assert!(tracker.update(&[conn(50000, TcpState::Established, 1)]).is_empty());
+    }
+
+    #[test]
+    fn rates_from_two_snapshots() {
+        let mut tracker = RateTracker::new();
+        let start = Instant::now();
+
+        // pid 1: 10 个连接中 5 个转入 TIME_WAIT、2 个转入 CLOSE_WAIT，另新建 100 个；pid 2 不变
+        let mut first: Vec<TcpConnection> = (0..10).map(|i| conn(50000 + i, TcpState::Established, 1)).collect();
+        first.push(conn(60000, TcpState::Established, 2));
+        tracker.update_at(&first, start);
+
+        let mut second: Vec<TcpConnection> = (0..5).map(|i| conn(50000 + i, TcpState::TimeWait, 1)).collect();
+        second.extend((5..7).map(|i| conn(50000 + i, TcpState::CloseWait, 1)));
+        second.extend((7..10).map(|i| conn(50000 + i, TcpState::Established, 1)));
+        second.extend((0..100).map(|i| conn(51000 + i, TcpState::Established, 1)));
+        second.push(conn(60000, TcpState::Established, 2));
+        let rates = tracker.update_at(&second, start + Duration::from_secs(10));
+
+        let busy = rates[&1];
+        assert!((busy.connections_opened_per_sec - 10.0).abs() < 1e-3);
+        assert!((busy.time_wait_growth_per_sec - 0.5).abs() < 1e-3);
+        assert!((busy.close_wait_growth_per_sec - 0.2).abs() < 1e-3);
+        assert_eq!(rates.get(&2).map(|r| r.connections_opened_per_sec), Some(0.0));
+
+        let mut stats = SystemTcpStats {
+            by_process: vec![crate::ProcessTcpStats { pid: 1, ..Default::default() }],
+            ..Default::default()
+        };
+        RateTracker::apply(&rates, &mut stats);
+        assert_eq!(stats.by_process[0].churn_rate, Some(busy.connections_opened_per_sec));
+        assert_eq!(stats.by_process[0].close_wait_growth, Some(busy.close_wait_growth_per_sec));
+    }
+
+    #[test]
+    fn flags_tuple_that_keeps_changing_state() {
+        let mut tracker = ConnectionTracker::new(Duration::from_secs(300), 4);
+        let start = Instant::now();
+        let states = [
+            TcpState::Established,
+            TcpState::CloseWait,
+            TcpState::Established,
+            TcpState::CloseWait,
+            TcpState::Established,
+            TcpState::CloseWait,
+        ];
+
+        let mut flapping = Vec::new();
+        for (i, state) in states.into_iter().enumerate() {
+            flapping = tracker.update_at(&[conn(50000, state, 7)], start + Duration::from_secs(i as u64 * 10));
+        }
+
+        assert_eq!(flapping.len(), 1);
+        assert_eq!(flapping[0].transitions, 5);
+        assert_eq!(flapping[0].pid, 7);
+        let anomalies = flapping_anomalies(&flapping);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].anomaly_type, AnomalyType::ConnectionFlapping);
+    }
+
+    #[test]
+    fn reappearing_tuple_counts_as_transition() {
+        let mut tracker = ConnectionTracker::new(Duration::from_secs(300), 2);
+        let start = Instant::now();
+        let present = [conn(50000, TcpState::CloseWait, 7)];
+
+        let mut flapping = Vec::new();
+        for i in 0..6u64 {
+            let snapshot: &[TcpConnection] = if i % 2 == 0 { &present } else { &[] };
+            flapping = tracker.update_at(snapshot, start + Duration::from_secs(i * 10));
+        }
+
+        // 出现 -> 消失 -> 出现 -> 消失 -> 出现 -> 消失：重新出现 2 次，未超过阈值
+        assert!(flapping.is_empty());
+        flapping = tracker.update_at(&present, start + Duration::from_secs(60));
+        assert_eq!(flapping.len(), 1);
+    }
+
+    #[test]
+    fn short_lived_tuples_are_not_flagged_and_expire() {
+        let mut tracker = ConnectionTracker::new(Duration::from_secs(60), 0);
+        let start = Instant::now();
+
+        // 每轮都是新的短连接，各自只出现一次
+        for i in 0..10u16 {
+            let flapping = tracker.update_at(&[conn(50000 + i, TcpState::TimeWait, 1)], start + Duration::from_secs(i as u64 * 10));
+            assert!(flapping.is_empty());
+        }
+        tracker.update_at(&[], start + Duration::from_secs(300));
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn transitions_outside_window_are_forgotten() {
+        let mut tracker = ConnectionTracker::new(Duration::from_secs(60), 1);
+        let start = Instant::now();
+
+        tracker.update_at(&[conn(50000, TcpState::Established, 1)], start);
+        tracker.update_at(&[conn(50000, TcpState::CloseWait, 1)], start + Duration::from_secs(10));
+        assert_eq!(tracker.update_at(&[conn(50000, TcpState::Established, 1)], start + Duration::from_secs(20)).len(), 1);
+        // 之前的变化已移出窗口
+        assert!(tracker.update_at(&[conn(50000, TcpState::Established, 1)], start + Duration::from_secs(100)).is_empty());
+    }
+}
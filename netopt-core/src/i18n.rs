@@ -1,22 +1,37 @@
 //! 国际化支持模块 (i18n)
 //! 
-//! 支持中文和英文界面
+//! 支持简体中文、繁体中文、英文和日文界面
 
+use crate::{NetOptError, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[non_exhaustive]
 pub enum Language {
     #[default]
     Chinese,
     English,
+    TraditionalChinese,
+    Japanese,
 }
 
 impl Language {
+    /// 所有支持的语言（语言选择器按此顺序显示）
+    pub const ALL: [Language; 4] = [
+        Language::Chinese,
+        Language::TraditionalChinese,
+        Language::English,
+        Language::Japanese,
+    ];
+    
     pub fn code(&self) -> &'static str {
         match self {
             Language::Chinese => "zh-CN",
             Language::English => "en-US",
+            Language::TraditionalChinese => "zh-TW",
+            Language::Japanese => "ja-JP",
         }
     }
     
@@ -24,12 +39,14 @@ impl Language {
         match self {
             Language::Chinese => "中文",
             Language::English => "English",
+            Language::TraditionalChinese => "繁體中文",
+            Language::Japanese => "日本語",
         }
     }
 }
 
-/// 翻译文本键
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// 翻译文本键（JSON 翻译文件中以变体名为键）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TextKey {
     // 导航
     AppTitle,
@@ -53,11 +70,28 @@ pub enum TextKey {
     AvailablePorts,
     PortUsage,
     ConnectionStateDistribution,
+    ConnectionTrend,
+    CollectingHistory,
+    TrendTime,
+    LeakReport,
+    NoLeaks,
+    LeakConfirmed,
+    LeakTransient,
+    LeakWatching,
+    ProcessRestarted,
     ActiveConnections,
     WaitingClose,
     NeedsAttention,
     ListeningPorts,
     Top5Processes,
+    SortBy,
+    SortTotal,
+    SortCloseWait,
+    SortTimeWait,
+    SortHealthAsc,
+    LoadingData,
+    NoConnections,
+    NoConnectionsPermission,
     ProcessName,
     Pid,
     Connections,
@@ -67,6 +101,27 @@ pub enum TextKey {
     ProcessDetails,
     AddPolicy,
     PolicyAdded,
+    ForceCleanup,
+    ConnectionControlUnsupported,
+    TopRemotePeers,
+    SearchProcesses,
+    FilterAll,
+    FilterHasCloseWait,
+    FilterHasPolicy,
+    FilterUnhealthy,
+    GroupByApp,
+    ProcessCount,
+    ConnectionDetails,
+    LocalAddress,
+    RemoteAddress,
+    ConnectionState,
+    ConnectionAge,
+    AllStates,
+    CopyToClipboard,
+    CopiedToClipboard,
+    RemoteHost,
+    ResolveHostnames,
+    ResolveHostnamesHint,
     
     // 策略管理
     PolicyManagement,
@@ -89,6 +144,14 @@ pub enum TextKey {
     SavePolicy,
     PolicySaved,
     PolicyTip,
+    LastTriggered,
+    NeverTriggered,
+    PolicyActions,
+    ConnectionsAffected,
+    WarnCloseWaitAboveTimeWait,
+    WarnMaxBelowUsage,
+    PolicyPreview,
+    ProcessNotRunning,
 
     // 全局默认设置
     GlobalDefaultSettings,
@@ -119,6 +182,9 @@ pub enum TextKey {
     // 语言
     LanguageLabel,
     LanguageChanged,
+    ThemeSystem,
+    ThemeLight,
+    ThemeDark,
 
     // 帮助
     Help,
@@ -141,11 +207,230 @@ pub enum TextKey {
     HelpVersion,
 }
 
+/// 全部翻译键（按声明顺序）
+///
+/// 新增 `TextKey` 时必须同步追加到这里，否则下方的编译期断言会失败
+pub const ALL_KEYS: &[TextKey] = &[
+    // 导航
+    TextKey::AppTitle,
+    TextKey::Dashboard,
+    TextKey::Processes,
+    TextKey::Policies,
+    TextKey::Settings,
+
+    // 状态
+    TextKey::AdminRequired,
+    TextKey::AdminGranted,
+    TextKey::RefreshSuccess,
+    TextKey::RefreshFailed,
+    TextKey::Refreshing,
+    TextKey::AutoRefresh,
+    TextKey::RefreshNow,
+
+    // 仪表盘
+    TextKey::SystemOverview,
+    TextKey::TotalConnections,
+    TextKey::AvailablePorts,
+    TextKey::PortUsage,
+    TextKey::ConnectionStateDistribution,
+    TextKey::ConnectionTrend,
+    TextKey::CollectingHistory,
+    TextKey::TrendTime,
+    TextKey::LeakReport,
+    TextKey::NoLeaks,
+    TextKey::LeakConfirmed,
+    TextKey::LeakTransient,
+    TextKey::LeakWatching,
+    TextKey::ProcessRestarted,
+    TextKey::ActiveConnections,
+    TextKey::WaitingClose,
+    TextKey::NeedsAttention,
+    TextKey::ListeningPorts,
+    TextKey::Top5Processes,
+    TextKey::SortBy,
+    TextKey::SortTotal,
+    TextKey::SortCloseWait,
+    TextKey::SortTimeWait,
+    TextKey::SortHealthAsc,
+    TextKey::LoadingData,
+    TextKey::NoConnections,
+    TextKey::NoConnectionsPermission,
+    TextKey::ProcessName,
+    TextKey::Pid,
+    TextKey::Connections,
+    TextKey::HealthScore,
+
+    // 进程列表
+    TextKey::ProcessDetails,
+    TextKey::AddPolicy,
+    TextKey::PolicyAdded,
+    TextKey::ForceCleanup,
+    TextKey::ConnectionControlUnsupported,
+    TextKey::TopRemotePeers,
+    TextKey::SearchProcesses,
+    TextKey::FilterAll,
+    TextKey::FilterHasCloseWait,
+    TextKey::FilterHasPolicy,
+    TextKey::FilterUnhealthy,
+    TextKey::GroupByApp,
+    TextKey::ProcessCount,
+    TextKey::ConnectionDetails,
+    TextKey::LocalAddress,
+    TextKey::RemoteAddress,
+    TextKey::ConnectionState,
+    TextKey::ConnectionAge,
+    TextKey::AllStates,
+    TextKey::CopyToClipboard,
+    TextKey::CopiedToClipboard,
+    TextKey::RemoteHost,
+    TextKey::ResolveHostnames,
+    TextKey::ResolveHostnamesHint,
+
+    // 策略管理
+    TextKey::PolicyManagement,
+    TextKey::PolicyDescription,
+    TextKey::NoPolicies,
+    TextKey::AutoOptimize,
+    TextKey::Enabled,
+    TextKey::Disabled,
+    TextKey::TimeWaitThreshold,
+    TextKey::CloseWaitThreshold,
+    TextKey::MaxConnections,
+    TextKey::Unlimited,
+    TextKey::ThresholdAction,
+    TextKey::ActionAlert,
+    TextKey::ActionOptimize,
+    TextKey::ActionRestart,
+    TextKey::ActionIgnore,
+    TextKey::DeletePolicy,
+    TextKey::PolicyDeleted,
+    TextKey::SavePolicy,
+    TextKey::PolicySaved,
+    TextKey::PolicyTip,
+    TextKey::LastTriggered,
+    TextKey::NeverTriggered,
+    TextKey::PolicyActions,
+    TextKey::ConnectionsAffected,
+    TextKey::WarnCloseWaitAboveTimeWait,
+    TextKey::WarnMaxBelowUsage,
+    TextKey::PolicyPreview,
+    TextKey::ProcessNotRunning,
+
+    // 全局默认设置
+    TextKey::GlobalDefaultSettings,
+    TextKey::GlobalDefaultDesc,
+    TextKey::ResetToDefault,
+    TextKey::DefaultsReset,
+    TextKey::ApplyToAll,
+    TextKey::AppliedToAll,
+
+    // 设置
+    TextKey::TcpSettings,
+    TextKey::AdminRequiredForSettings,
+    TextKey::QuickConfig,
+    TextKey::HighPerformance,
+    TextKey::Conservative,
+    TextKey::ReadCurrent,
+    TextKey::ConfigLoaded,
+    TextKey::DetailedConfig,
+    TextKey::MaxUserPort,
+    TextKey::TimeWaitDelay,
+    TextKey::DynamicPortStart,
+    TextKey::Recommended,
+    TextKey::ApplyConfig,
+    TextKey::ConfigApplied,
+    TextKey::ApplyFailed,
+    TextKey::RebootRequired,
+
+    // 语言
+    TextKey::LanguageLabel,
+    TextKey::LanguageChanged,
+    TextKey::ThemeSystem,
+    TextKey::ThemeLight,
+    TextKey::ThemeDark,
+
+    // 帮助
+    TextKey::Help,
+    TextKey::HelpTitle,
+    TextKey::HelpAbout,
+    TextKey::HelpAboutDesc,
+    TextKey::HelpFeatures,
+    TextKey::HelpDashboard,
+    TextKey::HelpDashboardDesc,
+    TextKey::HelpProcesses,
+    TextKey::HelpProcessesDesc,
+    TextKey::HelpPolicies,
+    TextKey::HelpPoliciesDesc,
+    TextKey::HelpSettingsHelp,
+    TextKey::HelpSettingsDesc,
+    TextKey::HelpTcpStates,
+    TextKey::HelpTcpStatesDesc,
+    TextKey::HelpTroubleshooting,
+    TextKey::HelpTroubleshootingDesc,
+    TextKey::HelpVersion,
+];
+
+// `TextKey` 为无字段枚举，判别值即声明顺序；最后一个变体变化时同步修改此处
+const _: () = assert!(ALL_KEYS.len() == TextKey::HelpVersion as usize + 1);
+
+/// 千位分组格式化数字，如 1234567 -> "1,234,567"
+///
+/// 当前支持的语言均使用逗号分组；导出 CSV/JSON 时应直接输出原始数值
+pub fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// 按语言习惯缩写大数字：英文 1.2k / 3.4M，中日文 1.2万 / 3.4亿
+///
+/// 低于最小单位时退化为 `format_count`
+pub fn format_count_compact(n: usize, lang: Language) -> String {
+    let units: &[(f64, &str)] = match lang {
+        Language::English => &[(1e3, "k"), (1e6, "M"), (1e9, "B")],
+        Language::Chinese => &[(1e4, "万"), (1e8, "亿")],
+        Language::TraditionalChinese => &[(1e4, "萬"), (1e8, "億")],
+        Language::Japanese => &[(1e4, "万"), (1e8, "億")],
+    };
+    let round1 = |v: f64| (v * 10.0).round() / 10.0;
+
+    let Some(mut idx) = units.iter().rposition(|(base, _)| n as f64 >= *base) else {
+        return format_count(n);
+    };
+    let mut value = round1(n as f64 / units[idx].0);
+    // 四舍五入后进位到下一单位，如 999_999 -> 1M 而不是 1000k
+    if let Some((next, _)) = units.get(idx + 1) {
+        if value >= next / units[idx].0 {
+            idx += 1;
+            value = round1(n as f64 / units[idx].0);
+        }
+    }
+
+    if value.fract() == 0.0 {
+        format!("{}{}", value as u64, units[idx].1)
+    } else {
+        format!("{:.1}{}", value, units[idx].1)
+    }
+}
+
+/// 百分比格式化，保留一位小数
+pub fn format_percent(value: f32) -> String {
+    format!("{:.1}%", value)
+}
+
 /// 国际化管理器
 #[derive(Debug, Clone)]
 pub struct I18n {
     current_language: Language,
     texts: HashMap<(Language, TextKey), &'static str>,
+    /// 外部翻译文件覆盖的文本，优先于内置语言
+    overrides: HashMap<TextKey, String>,
 }
 
 impl Default for I18n {
@@ -159,10 +444,22 @@ impl I18n {
         let mut texts = HashMap::new();
         Self::load_chinese(&mut texts);
         Self::load_english(&mut texts);
+        Self::load_traditional_chinese(&mut texts);
+        Self::load_japanese(&mut texts);
+        
+        // 以简体中文为基准，确保其他语言没有漏翻译的键
+        debug_assert!(
+            Language::ALL.iter().all(|lang| {
+                texts.keys().filter(|(l, _)| l == lang).count()
+                    == texts.keys().filter(|(l, _)| *l == Language::Chinese).count()
+            }),
+            "存在未翻译的 TextKey"
+        );
         
         Self {
             current_language: Language::Chinese,
             texts,
+            overrides: HashMap::new(),
         }
     }
     
@@ -174,12 +471,33 @@ impl I18n {
         self.current_language
     }
     
-    pub fn t(&self, key: TextKey) -> &'static str {
+    /// 获取翻译文本：外部覆盖 > 内置当前语言 > "???"
+    pub fn t(&self, key: TextKey) -> &str {
+        if let Some(text) = self.overrides.get(&key) {
+            return text;
+        }
         self.texts
             .get(&(self.current_language, key))
             .copied()
             .unwrap_or("???")
     }
+    
+    /// 从 JSON 文件加载翻译覆盖，格式为 `{"AppTitle": "...", ...}`
+    ///
+    /// 文件中未出现的键回退到内置语言；多次调用时后加载的覆盖先前的
+    pub fn load_overrides(&mut self, path: &Path) -> Result<()> {
+        let content = std::fs::read_to_string(path)?;
+        let overrides: HashMap<TextKey, String> = serde_json::from_str(&content)
+            .map_err(|e| NetOptError::ParseError(format!("翻译文件解析失败: {}", e)))?;
+        
+        self.overrides.extend(overrides);
+        Ok(())
+    }
+    
+    /// 清除所有外部翻译覆盖
+    pub fn clear_overrides(&mut self) {
+        self.overrides.clear();
+    }
 
     fn load_chinese(texts: &mut HashMap<(Language, TextKey), &'static str>) {
         use TextKey::*;
@@ -207,11 +525,28 @@ impl I18n {
         texts.insert((lang, AvailablePorts), "可用端口");
         texts.insert((lang, PortUsage), "端口使用率");
         texts.insert((lang, ConnectionStateDistribution), "连接状态分布");
+        texts.insert((lang, ConnectionTrend), "连接趋势");
+        texts.insert((lang, CollectingHistory), "正在收集数据...");
+        texts.insert((lang, TrendTime), "时间");
+        texts.insert((lang, LeakReport), "连接泄漏");
+        texts.insert((lang, NoLeaks), "暂未发现 CLOSE_WAIT 持续增长的进程");
+        texts.insert((lang, LeakConfirmed), "确认泄漏");
+        texts.insert((lang, LeakTransient), "突发后回落");
+        texts.insert((lang, LeakWatching), "观察中");
+        texts.insert((lang, ProcessRestarted), "窗口内重启过");
         texts.insert((lang, ActiveConnections), "活跃连接");
         texts.insert((lang, WaitingClose), "等待关闭");
         texts.insert((lang, NeedsAttention), "需注意");
         texts.insert((lang, ListeningPorts), "监听端口");
-        texts.insert((lang, Top5Processes), "连接数Top 5进程");
+        texts.insert((lang, Top5Processes), "Top 5 进程");
+        texts.insert((lang, SortBy), "排序");
+        texts.insert((lang, SortTotal), "总连接数");
+        texts.insert((lang, SortCloseWait), "CLOSE_WAIT 最多");
+        texts.insert((lang, SortTimeWait), "TIME_WAIT 最多");
+        texts.insert((lang, SortHealthAsc), "健康评分最低");
+        texts.insert((lang, LoadingData), "正在获取连接数据...");
+        texts.insert((lang, NoConnections), "当前没有 TCP 连接，系统处于空闲状态");
+        texts.insert((lang, NoConnectionsPermission), "未读取到任何连接，可能是权限不足，请尝试以管理员身份运行");
         texts.insert((lang, ProcessName), "进程名");
         texts.insert((lang, Pid), "PID");
         texts.insert((lang, Connections), "连接数");
@@ -221,6 +556,27 @@ impl I18n {
         texts.insert((lang, ProcessDetails), "进程TCP连接详情");
         texts.insert((lang, AddPolicy), "添加策略");
         texts.insert((lang, PolicyAdded), "已添加策略");
+        texts.insert((lang, ForceCleanup), "立即清理");
+        texts.insert((lang, ConnectionControlUnsupported), "当前平台不支持直接关闭其他进程的连接，只能建议进程自行清理（macOS 限制）");
+        texts.insert((lang, TopRemotePeers), "远端连接Top");
+        texts.insert((lang, SearchProcesses), "搜索进程名");
+        texts.insert((lang, FilterAll), "全部进程");
+        texts.insert((lang, FilterHasCloseWait), "有 CLOSE_WAIT");
+        texts.insert((lang, FilterHasPolicy), "已配置策略");
+        texts.insert((lang, FilterUnhealthy), "健康度 < 50");
+        texts.insert((lang, GroupByApp), "按程序合并");
+        texts.insert((lang, ProcessCount), "进程数");
+        texts.insert((lang, ConnectionDetails), "连接详情");
+        texts.insert((lang, LocalAddress), "本地地址");
+        texts.insert((lang, RemoteAddress), "远端地址");
+        texts.insert((lang, ConnectionState), "状态");
+        texts.insert((lang, ConnectionAge), "已观察时长");
+        texts.insert((lang, AllStates), "全部状态");
+        texts.insert((lang, CopyToClipboard), "复制到剪贴板");
+        texts.insert((lang, CopiedToClipboard), "已复制到剪贴板");
+        texts.insert((lang, RemoteHost), "远端主机");
+        texts.insert((lang, ResolveHostnames), "解析主机名");
+        texts.insert((lang, ResolveHostnamesHint), "对远端地址进行反向 DNS 查询，会产生网络请求");
 
         // 策略管理
         texts.insert((lang, PolicyManagement), "应用策略管理");
@@ -243,6 +599,14 @@ impl I18n {
         texts.insert((lang, SavePolicy), "💾 保存修改");
         texts.insert((lang, PolicySaved), "策略已保存");
         texts.insert((lang, PolicyTip), "💡 提示：在进程列表中点击\"添加策略\"为特定进程创建优化规则。每个进程只能有一个策略。");
+        texts.insert((lang, LastTriggered), "距上次触发");
+        texts.insert((lang, NeverTriggered), "从未触发");
+        texts.insert((lang, PolicyActions), "动作次数");
+        texts.insert((lang, ConnectionsAffected), "涉及连接");
+        texts.insert((lang, WarnCloseWaitAboveTimeWait), "⚠️ CLOSE_WAIT 阈值高于 TIME_WAIT 阈值");
+        texts.insert((lang, WarnMaxBelowUsage), "⚠️ 最大连接数低于进程当前连接数");
+        texts.insert((lang, PolicyPreview), "按当前连接将触发");
+        texts.insert((lang, ProcessNotRunning), "进程未运行");
 
         // 全局默认设置
         texts.insert((lang, GlobalDefaultSettings), "🌐 全局默认设置");
@@ -273,6 +637,9 @@ impl I18n {
         // 语言
         texts.insert((lang, LanguageLabel), "🌐 语言");
         texts.insert((lang, LanguageChanged), "语言已切换");
+        texts.insert((lang, ThemeSystem), "跟随系统");
+        texts.insert((lang, ThemeLight), "浅色");
+        texts.insert((lang, ThemeDark), "深色");
 
         // 帮助
         texts.insert((lang, Help), "❓ 帮助");
@@ -321,11 +688,28 @@ impl I18n {
         texts.insert((lang, AvailablePorts), "Available Ports");
         texts.insert((lang, PortUsage), "Port Usage");
         texts.insert((lang, ConnectionStateDistribution), "Connection State Distribution");
+        texts.insert((lang, ConnectionTrend), "Connection Trend");
+        texts.insert((lang, CollectingHistory), "Collecting data...");
+        texts.insert((lang, TrendTime), "Time");
+        texts.insert((lang, LeakReport), "Connection Leaks");
+        texts.insert((lang, NoLeaks), "No process with steadily growing CLOSE_WAIT");
+        texts.insert((lang, LeakConfirmed), "Confirmed leak");
+        texts.insert((lang, LeakTransient), "Transient spike");
+        texts.insert((lang, LeakWatching), "Watching");
+        texts.insert((lang, ProcessRestarted), "Restarted in window");
         texts.insert((lang, ActiveConnections), "Active");
         texts.insert((lang, WaitingClose), "Waiting Close");
         texts.insert((lang, NeedsAttention), "Needs Attention");
         texts.insert((lang, ListeningPorts), "Listening");
-        texts.insert((lang, Top5Processes), "Top 5 Processes by Connections");
+        texts.insert((lang, Top5Processes), "Top 5 Processes");
+        texts.insert((lang, SortBy), "Sort by");
+        texts.insert((lang, SortTotal), "Total connections");
+        texts.insert((lang, SortCloseWait), "Most CLOSE_WAIT");
+        texts.insert((lang, SortTimeWait), "Most TIME_WAIT");
+        texts.insert((lang, SortHealthAsc), "Lowest health score");
+        texts.insert((lang, LoadingData), "Loading connection data...");
+        texts.insert((lang, NoConnections), "No TCP connections right now — the system is idle");
+        texts.insert((lang, NoConnectionsPermission), "No connections were returned; permissions are likely insufficient, try running as administrator");
         texts.insert((lang, ProcessName), "Process");
         texts.insert((lang, Pid), "PID");
         texts.insert((lang, Connections), "Connections");
@@ -335,6 +719,27 @@ impl I18n {
         texts.insert((lang, ProcessDetails), "Process TCP Details");
         texts.insert((lang, AddPolicy), "Add Policy");
         texts.insert((lang, PolicyAdded), "Policy Added");
+        texts.insert((lang, ForceCleanup), "Clean Now");
+        texts.insert((lang, ConnectionControlUnsupported), "This platform cannot close other processes' connections directly; processes can only be asked to clean up (macOS limitation)");
+        texts.insert((lang, TopRemotePeers), "Top Remote Peers");
+        texts.insert((lang, SearchProcesses), "Search process name");
+        texts.insert((lang, FilterAll), "All processes");
+        texts.insert((lang, FilterHasCloseWait), "Has CLOSE_WAIT");
+        texts.insert((lang, FilterHasPolicy), "Has policy");
+        texts.insert((lang, FilterUnhealthy), "Health < 50");
+        texts.insert((lang, GroupByApp), "Group by App");
+        texts.insert((lang, ProcessCount), "Processes");
+        texts.insert((lang, ConnectionDetails), "Connection Details");
+        texts.insert((lang, LocalAddress), "Local Address");
+        texts.insert((lang, RemoteAddress), "Remote Address");
+        texts.insert((lang, ConnectionState), "State");
+        texts.insert((lang, ConnectionAge), "Observed For");
+        texts.insert((lang, AllStates), "All States");
+        texts.insert((lang, CopyToClipboard), "Copy to Clipboard");
+        texts.insert((lang, CopiedToClipboard), "Copied to clipboard");
+        texts.insert((lang, RemoteHost), "Remote Host");
+        texts.insert((lang, ResolveHostnames), "Resolve Hostnames");
+        texts.insert((lang, ResolveHostnamesHint), "Reverse-resolve remote addresses via DNS; this sends network queries");
 
         // Policy Management
         texts.insert((lang, PolicyManagement), "Policy Management");
@@ -357,6 +762,14 @@ impl I18n {
         texts.insert((lang, SavePolicy), "💾 Save");
         texts.insert((lang, PolicySaved), "Policy Saved");
         texts.insert((lang, PolicyTip), "💡 Tip: Click \"Add Policy\" in the process list to create optimization rules. Each process can only have one policy.");
+        texts.insert((lang, LastTriggered), "Since last trigger");
+        texts.insert((lang, NeverTriggered), "Never triggered");
+        texts.insert((lang, PolicyActions), "Actions");
+        texts.insert((lang, ConnectionsAffected), "Connections affected");
+        texts.insert((lang, WarnCloseWaitAboveTimeWait), "⚠️ CLOSE_WAIT threshold is above the TIME_WAIT threshold");
+        texts.insert((lang, WarnMaxBelowUsage), "⚠️ Max connections is below the process's current usage");
+        texts.insert((lang, PolicyPreview), "Would trigger now on");
+        texts.insert((lang, ProcessNotRunning), "Process not running");
 
         // Global default settings
         texts.insert((lang, GlobalDefaultSettings), "🌐 Global Default Settings");
@@ -387,6 +800,9 @@ impl I18n {
         // Language
         texts.insert((lang, LanguageLabel), "🌐 Language");
         texts.insert((lang, LanguageChanged), "Language Changed");
+        texts.insert((lang, ThemeSystem), "System");
+        texts.insert((lang, ThemeLight), "Light");
+        texts.insert((lang, ThemeDark), "Dark");
 
         // Help
         texts.insert((lang, Help), "❓ Help");
@@ -408,5 +824,398 @@ impl I18n {
         texts.insert((lang, HelpTroubleshootingDesc), "Q: Port usage too high?\nA: Increase max user ports, reduce TIME_WAIT delay\n\nQ: Process has many CLOSE_WAIT?\nA: This is a program bug. Restart the process or contact developer\n\nQ: Settings don't take effect?\nA: Some settings require system reboot");
         texts.insert((lang, HelpVersion), "Version");
     }
+
+    fn load_traditional_chinese(texts: &mut HashMap<(Language, TextKey), &'static str>) {
+        use TextKey::*;
+        let lang = Language::TraditionalChinese;
+
+        // 导航
+        texts.insert((lang, AppTitle), "Smart TCP Manager");
+        texts.insert((lang, Dashboard), "📊 儀表板");
+        texts.insert((lang, Processes), "📋 處理程序列表");
+        texts.insert((lang, Policies), "📜 策略管理");
+        texts.insert((lang, Settings), "⚙️ 系統設定");
+
+        // 状态
+        texts.insert((lang, AdminRequired), "⚠️ 需要系統管理員權限");
+        texts.insert((lang, AdminGranted), "✓ 系統管理員");
+        texts.insert((lang, RefreshSuccess), "重新整理成功");
+        texts.insert((lang, RefreshFailed), "重新整理失敗");
+        texts.insert((lang, Refreshing), "正在重新整理...");
+        texts.insert((lang, AutoRefresh), "自動重新整理");
+        texts.insert((lang, RefreshNow), "🔄 立即重新整理");
+
+        // 仪表盘
+        texts.insert((lang, SystemOverview), "系統TCP連線概覽");
+        texts.insert((lang, TotalConnections), "總連線數");
+        texts.insert((lang, AvailablePorts), "可用連接埠");
+        texts.insert((lang, PortUsage), "連接埠使用率");
+        texts.insert((lang, ConnectionStateDistribution), "連線狀態分佈");
+        texts.insert((lang, ConnectionTrend), "連線趨勢");
+        texts.insert((lang, CollectingHistory), "正在收集資料...");
+        texts.insert((lang, TrendTime), "時間");
+        texts.insert((lang, LeakReport), "連線洩漏");
+        texts.insert((lang, NoLeaks), "暫未發現 CLOSE_WAIT 持續增長的行程");
+        texts.insert((lang, LeakConfirmed), "確認洩漏");
+        texts.insert((lang, LeakTransient), "突發後回落");
+        texts.insert((lang, LeakWatching), "觀察中");
+        texts.insert((lang, ProcessRestarted), "期間內曾重新啟動");
+        texts.insert((lang, ActiveConnections), "活躍連線");
+        texts.insert((lang, WaitingClose), "等待關閉");
+        texts.insert((lang, NeedsAttention), "需注意");
+        texts.insert((lang, ListeningPorts), "監聽連接埠");
+        texts.insert((lang, Top5Processes), "Top 5 處理程序");
+        texts.insert((lang, SortBy), "排序");
+        texts.insert((lang, SortTotal), "總連線數");
+        texts.insert((lang, SortCloseWait), "CLOSE_WAIT 最多");
+        texts.insert((lang, SortTimeWait), "TIME_WAIT 最多");
+        texts.insert((lang, SortHealthAsc), "健康評分最低");
+        texts.insert((lang, LoadingData), "正在取得連線資料...");
+        texts.insert((lang, NoConnections), "目前沒有 TCP 連線，系統處於閒置狀態");
+        texts.insert((lang, NoConnectionsPermission), "未讀取到任何連線，可能是權限不足，請嘗試以系統管理員身分執行");
+        texts.insert((lang, ProcessName), "處理程序名稱");
+        texts.insert((lang, Pid), "PID");
+        texts.insert((lang, Connections), "連線數");
+        texts.insert((lang, HealthScore), "健康度");
+
+        // 进程列表
+        texts.insert((lang, ProcessDetails), "處理程序TCP連線詳情");
+        texts.insert((lang, AddPolicy), "新增策略");
+        texts.insert((lang, PolicyAdded), "已新增策略");
+        texts.insert((lang, ForceCleanup), "立即清理");
+        texts.insert((lang, ConnectionControlUnsupported), "目前平台不支援直接關閉其他處理程序的連線，只能建議處理程序自行清理（macOS 限制）");
+        texts.insert((lang, TopRemotePeers), "遠端連線Top");
+        texts.insert((lang, SearchProcesses), "搜尋處理程序名稱");
+        texts.insert((lang, FilterAll), "全部處理程序");
+        texts.insert((lang, FilterHasCloseWait), "有 CLOSE_WAIT");
+        texts.insert((lang, FilterHasPolicy), "已設定策略");
+        texts.insert((lang, FilterUnhealthy), "健康度 < 50");
+        texts.insert((lang, GroupByApp), "按程式合併");
+        texts.insert((lang, ProcessCount), "程序數");
+        texts.insert((lang, ConnectionDetails), "連線詳情");
+        texts.insert((lang, LocalAddress), "本機位址");
+        texts.insert((lang, RemoteAddress), "遠端位址");
+        texts.insert((lang, ConnectionState), "狀態");
+        texts.insert((lang, ConnectionAge), "已觀察時長");
+        texts.insert((lang, AllStates), "全部狀態");
+        texts.insert((lang, CopyToClipboard), "複製到剪貼簿");
+        texts.insert((lang, CopiedToClipboard), "已複製到剪貼簿");
+        texts.insert((lang, RemoteHost), "遠端主機");
+        texts.insert((lang, ResolveHostnames), "解析主機名稱");
+        texts.insert((lang, ResolveHostnamesHint), "對遠端位址進行反向 DNS 查詢，會產生網路請求");
+
+        // 策略管理
+        texts.insert((lang, PolicyManagement), "應用程式策略管理");
+        texts.insert((lang, PolicyDescription), "為不同應用程式設定不同的TCP連線最佳化策略");
+        texts.insert((lang, NoPolicies), "尚無策略，請在處理程序列表中新增");
+        texts.insert((lang, AutoOptimize), "自動最佳化");
+        texts.insert((lang, Enabled), "✓ 開啟");
+        texts.insert((lang, Disabled), "✗ 關閉");
+        texts.insert((lang, TimeWaitThreshold), "TIME_WAIT閾值");
+        texts.insert((lang, CloseWaitThreshold), "CLOSE_WAIT閾值");
+        texts.insert((lang, MaxConnections), "最大連線數");
+        texts.insert((lang, Unlimited), "不限制");
+        texts.insert((lang, ThresholdAction), "超過閾值動作");
+        texts.insert((lang, ActionAlert), "警示");
+        texts.insert((lang, ActionOptimize), "自動最佳化");
+        texts.insert((lang, ActionRestart), "重新啟動處理程序");
+        texts.insert((lang, ActionIgnore), "忽略");
+        texts.insert((lang, DeletePolicy), "🗑 刪除");
+        texts.insert((lang, PolicyDeleted), "已刪除策略");
+        texts.insert((lang, SavePolicy), "💾 儲存變更");
+        texts.insert((lang, PolicySaved), "策略已儲存");
+        texts.insert((lang, PolicyTip), "💡 提示：在處理程序列表中點擊\"新增策略\"為特定處理程序建立最佳化規則。每個處理程序只能有一個策略。");
+        texts.insert((lang, LastTriggered), "距上次觸發");
+        texts.insert((lang, NeverTriggered), "從未觸發");
+        texts.insert((lang, PolicyActions), "動作次數");
+        texts.insert((lang, ConnectionsAffected), "涉及連線");
+        texts.insert((lang, WarnCloseWaitAboveTimeWait), "⚠️ CLOSE_WAIT 閾值高於 TIME_WAIT 閾值");
+        texts.insert((lang, WarnMaxBelowUsage), "⚠️ 最大連線數低於處理程序目前連線數");
+        texts.insert((lang, PolicyPreview), "依目前連線將觸發");
+        texts.insert((lang, ProcessNotRunning), "處理程序未執行");
+
+        // 全局默认设置
+        texts.insert((lang, GlobalDefaultSettings), "🌐 全域預設設定");
+        texts.insert((lang, GlobalDefaultDesc), "沒有個別策略的處理程序將使用此預設設定");
+        texts.insert((lang, ResetToDefault), "🔄 恢復預設值");
+        texts.insert((lang, DefaultsReset), "已恢復預設值");
+        texts.insert((lang, ApplyToAll), "📋 套用到所有策略");
+        texts.insert((lang, AppliedToAll), "已套用到所有策略");
+
+        // 设置
+        texts.insert((lang, TcpSettings), "TCP系統參數設定");
+        texts.insert((lang, AdminRequiredForSettings), "⚠️ 需要系統管理員權限才能修改系統設定");
+        texts.insert((lang, QuickConfig), "快速設定");
+        texts.insert((lang, HighPerformance), "🚀 高效能設定");
+        texts.insert((lang, Conservative), "🛡 保守設定");
+        texts.insert((lang, ReadCurrent), "🔄 讀取目前設定");
+        texts.insert((lang, ConfigLoaded), "設定已載入");
+        texts.insert((lang, DetailedConfig), "詳細設定");
+        texts.insert((lang, MaxUserPort), "最大使用者連接埠 (MaxUserPort)");
+        texts.insert((lang, TimeWaitDelay), "TIME_WAIT等待時間 (秒)");
+        texts.insert((lang, DynamicPortStart), "動態連接埠起始");
+        texts.insert((lang, Recommended), "建議");
+        texts.insert((lang, ApplyConfig), "✅ 套用設定");
+        texts.insert((lang, ConfigApplied), "設定已套用！可能需要重新啟動系統才能生效。");
+        texts.insert((lang, ApplyFailed), "套用失敗");
+        texts.insert((lang, RebootRequired), "⚠️ 修改後需要重新啟動系統才能生效");
+
+        // 语言
+        texts.insert((lang, LanguageLabel), "🌐 語言");
+        texts.insert((lang, LanguageChanged), "語言已切換");
+        texts.insert((lang, ThemeSystem), "跟隨系統");
+        texts.insert((lang, ThemeLight), "淺色");
+        texts.insert((lang, ThemeDark), "深色");
+
+        // 帮助
+        texts.insert((lang, Help), "❓ 說明");
+        texts.insert((lang, HelpTitle), "📖 使用指南");
+        texts.insert((lang, HelpAbout), "關於本軟體");
+        texts.insert((lang, HelpAboutDesc), "Smart TCP Manager 是一款跨平台的 TCP 連線管理與最佳化工具。\n支援 Windows、macOS 和 Linux，協助您即時監控網路連線狀態，\n識別潛在問題，並最佳化 TCP 參數以提升網路效能。");
+        texts.insert((lang, HelpFeatures), "📋 功能說明");
+        texts.insert((lang, HelpDashboard), "📊 儀表板");
+        texts.insert((lang, HelpDashboardDesc), "顯示系統 TCP 連線的整體概覽：\n  • 總連線數、可用連接埠數、連接埠使用率\n  • 各狀態連線分佈（ESTABLISHED、TIME_WAIT 等）\n  • Top 5 佔用連線最多的處理程序及其健康度評分");
+        texts.insert((lang, HelpProcesses), "📋 處理程序列表");
+        texts.insert((lang, HelpProcessesDesc), "檢視每個處理程序的詳細連線資訊：\n  • 處理程序名稱、PID、各狀態連線數量\n  • 健康度評分（100分制，越高越好）\n  • 點擊「新增策略」選擇策略範本：\n    - 📊 預設策略：通用設定\n    - 🚀 高效能：遊戲/下載器\n    - 🕷️ 擷取/爬蟲：積極清理 CLOSE_WAIT\n    - 🖥️ 伺服器：高並行服務\n    - 🔒 受限：限制連線數");
+        texts.insert((lang, HelpPolicies), "📜 策略管理");
+        texts.insert((lang, HelpPoliciesDesc), "為不同應用程式設定個人化的最佳化規則：\n  • TIME_WAIT 閾值：建議 100-500（超過會觸發動作）\n  • CLOSE_WAIT 閾值：建議 20-100（堆積表示程式未正確關閉連線）\n  • 最大連線數：限制單一處理程序的連線數量\n  • 超過閾值動作：警示、自動最佳化、忽略\n\n💡 擷取工具建議使用「🕷️ 擷取/爬蟲」範本，積極清理 CLOSE_WAIT 防止卡死");
+        texts.insert((lang, HelpSettingsHelp), "⚙️ 系統設定");
+        texts.insert((lang, HelpSettingsDesc), "調整作業系統層級的 TCP 參數（需要系統管理員權限）：\n  • 最大使用者連接埠：預設 5000，建議 32768-65534\n  • TIME_WAIT 延遲：預設 120秒，建議 30-60秒\n  • 動態連接埠起始：預設 49152，可依需求調整");
+        texts.insert((lang, HelpTcpStates), "🔍 TCP 狀態說明");
+        texts.insert((lang, HelpTcpStatesDesc), "• ESTABLISHED（綠色）：正常活躍連線\n• TIME_WAIT（黃色）：等待關閉的連線，過多會佔用連接埠\n• CLOSE_WAIT（紅色）：對方已關閉，等待本機關閉，堆積代表程式有問題\n• LISTEN（藍色）：監聽連接埠，等待連線\n• FIN_WAIT/LAST_ACK：正在關閉中的連線");
+        texts.insert((lang, HelpTroubleshooting), "🛠 常見問題");
+        texts.insert((lang, HelpTroubleshootingDesc), "Q: 連接埠使用率過高怎麼辦？\nA: 增加最大使用者連接埠數，減少 TIME_WAIT 延遲時間\n\nQ: 某處理程序 CLOSE_WAIT 很多？\nA: 這是程式問題，建議重新啟動該處理程序或聯絡開發者\n\nQ: 修改設定後沒有生效？\nA: 部分設定需要重新啟動系統才能生效");
+        texts.insert((lang, HelpVersion), "版本");
+    }
+
+    fn load_japanese(texts: &mut HashMap<(Language, TextKey), &'static str>) {
+        use TextKey::*;
+        let lang = Language::Japanese;
+
+        // 导航
+        texts.insert((lang, AppTitle), "Smart TCP Manager");
+        texts.insert((lang, Dashboard), "📊 ダッシュボード");
+        texts.insert((lang, Processes), "📋 プロセス一覧");
+        texts.insert((lang, Policies), "📜 ポリシー管理");
+        texts.insert((lang, Settings), "⚙️ システム設定");
+
+        // 状态
+        texts.insert((lang, AdminRequired), "⚠️ 管理者権限が必要です");
+        texts.insert((lang, AdminGranted), "✓ 管理者");
+        texts.insert((lang, RefreshSuccess), "更新しました");
+        texts.insert((lang, RefreshFailed), "更新に失敗しました");
+        texts.insert((lang, Refreshing), "更新中...");
+        texts.insert((lang, AutoRefresh), "自動更新");
+        texts.insert((lang, RefreshNow), "🔄 今すぐ更新");
+
+        // 仪表盘
+        texts.insert((lang, SystemOverview), "システムTCP接続の概要");
+        texts.insert((lang, TotalConnections), "総接続数");
+        texts.insert((lang, AvailablePorts), "利用可能ポート");
+        texts.insert((lang, PortUsage), "ポート使用率");
+        texts.insert((lang, ConnectionStateDistribution), "接続状態の分布");
+        texts.insert((lang, ConnectionTrend), "接続の推移");
+        texts.insert((lang, CollectingHistory), "データ収集中...");
+        texts.insert((lang, TrendTime), "時刻");
+        texts.insert((lang, LeakReport), "接続リーク");
+        texts.insert((lang, NoLeaks), "CLOSE_WAIT が増え続けているプロセスはありません");
+        texts.insert((lang, LeakConfirmed), "リーク確定");
+        texts.insert((lang, LeakTransient), "一時的な増加");
+        texts.insert((lang, LeakWatching), "監視中");
+        texts.insert((lang, ProcessRestarted), "期間内に再起動");
+        texts.insert((lang, ActiveConnections), "アクティブ");
+        texts.insert((lang, WaitingClose), "クローズ待ち");
+        texts.insert((lang, NeedsAttention), "要注意");
+        texts.insert((lang, ListeningPorts), "待ち受けポート");
+        texts.insert((lang, Top5Processes), "上位5プロセス");
+        texts.insert((lang, SortBy), "並べ替え");
+        texts.insert((lang, SortTotal), "総接続数");
+        texts.insert((lang, SortCloseWait), "CLOSE_WAIT が多い順");
+        texts.insert((lang, SortTimeWait), "TIME_WAIT が多い順");
+        texts.insert((lang, SortHealthAsc), "健全性スコアが低い順");
+        texts.insert((lang, LoadingData), "接続データを取得中...");
+        texts.insert((lang, NoConnections), "現在 TCP 接続はありません（システムはアイドル状態です）");
+        texts.insert((lang, NoConnectionsPermission), "接続を取得できませんでした。権限が不足している可能性があります。管理者として実行してください");
+        texts.insert((lang, ProcessName), "プロセス名");
+        texts.insert((lang, Pid), "PID");
+        texts.insert((lang, Connections), "接続数");
+        texts.insert((lang, HealthScore), "健全性");
+
+        // 进程列表
+        texts.insert((lang, ProcessDetails), "プロセス別TCP接続の詳細");
+        texts.insert((lang, AddPolicy), "ポリシーを追加");
+        texts.insert((lang, PolicyAdded), "ポリシーを追加しました");
+        texts.insert((lang, ForceCleanup), "今すぐクリーンアップ");
+        texts.insert((lang, ConnectionControlUnsupported), "このプラットフォームでは他プロセスの接続を直接閉じられません。プロセスに整理を促すことのみ可能です（macOS の制限）");
+        texts.insert((lang, TopRemotePeers), "接続先トップ");
+        texts.insert((lang, SearchProcesses), "プロセス名を検索");
+        texts.insert((lang, FilterAll), "すべてのプロセス");
+        texts.insert((lang, FilterHasCloseWait), "CLOSE_WAIT あり");
+        texts.insert((lang, FilterHasPolicy), "ポリシー設定済み");
+        texts.insert((lang, FilterUnhealthy), "健全度 < 50");
+        texts.insert((lang, GroupByApp), "アプリごとにまとめる");
+        texts.insert((lang, ProcessCount), "プロセス数");
+        texts.insert((lang, ConnectionDetails), "接続の詳細");
+        texts.insert((lang, LocalAddress), "ローカルアドレス");
+        texts.insert((lang, RemoteAddress), "リモートアドレス");
+        texts.insert((lang, ConnectionState), "状態");
+        texts.insert((lang, ConnectionAge), "観測時間");
+        texts.insert((lang, AllStates), "すべての状態");
+        texts.insert((lang, CopyToClipboard), "クリップボードにコピー");
+        texts.insert((lang, CopiedToClipboard), "クリップボードにコピーしました");
+        texts.insert((lang, RemoteHost), "リモートホスト");
+        texts.insert((lang, ResolveHostnames), "ホスト名を解決");
+        texts.insert((lang, ResolveHostnamesHint), "リモートアドレスを DNS で逆引きします（ネットワーク通信が発生します）");
+
+        // 策略管理
+        texts.insert((lang, PolicyManagement), "アプリケーションポリシー管理");
+        texts.insert((lang, PolicyDescription), "アプリケーションごとに異なるTCP接続最適化ポリシーを設定します");
+        texts.insert((lang, NoPolicies), "ポリシーがありません。プロセス一覧から追加してください");
+        texts.insert((lang, AutoOptimize), "自動最適化");
+        texts.insert((lang, Enabled), "✓ 有効");
+        texts.insert((lang, Disabled), "✗ 無効");
+        texts.insert((lang, TimeWaitThreshold), "TIME_WAITしきい値");
+        texts.insert((lang, CloseWaitThreshold), "CLOSE_WAITしきい値");
+        texts.insert((lang, MaxConnections), "最大接続数");
+        texts.insert((lang, Unlimited), "無制限");
+        texts.insert((lang, ThresholdAction), "しきい値超過時の動作");
+        texts.insert((lang, ActionAlert), "警告");
+        texts.insert((lang, ActionOptimize), "自動最適化");
+        texts.insert((lang, ActionRestart), "プロセスを再起動");
+        texts.insert((lang, ActionIgnore), "無視");
+        texts.insert((lang, DeletePolicy), "🗑 削除");
+        texts.insert((lang, PolicyDeleted), "ポリシーを削除しました");
+        texts.insert((lang, SavePolicy), "💾 保存");
+        texts.insert((lang, PolicySaved), "ポリシーを保存しました");
+        texts.insert((lang, PolicyTip), "💡 ヒント：プロセス一覧で「ポリシーを追加」をクリックすると、特定のプロセス用の最適化ルールを作成できます。1つのプロセスに設定できるポリシーは1つだけです。");
+        texts.insert((lang, LastTriggered), "前回の発動から");
+        texts.insert((lang, NeverTriggered), "未発動");
+        texts.insert((lang, PolicyActions), "アクション数");
+        texts.insert((lang, ConnectionsAffected), "対象接続数");
+        texts.insert((lang, WarnCloseWaitAboveTimeWait), "⚠️ CLOSE_WAIT のしきい値が TIME_WAIT より高くなっています");
+        texts.insert((lang, WarnMaxBelowUsage), "⚠️ 最大接続数がプロセスの現在の接続数を下回っています");
+        texts.insert((lang, PolicyPreview), "現在の接続で発動");
+        texts.insert((lang, ProcessNotRunning), "プロセスは実行されていません");
+
+        // 全局默认设置
+        texts.insert((lang, GlobalDefaultSettings), "🌐 グローバル既定設定");
+        texts.insert((lang, GlobalDefaultDesc), "個別のポリシーがないプロセスにはこの既定設定が使われます");
+        texts.insert((lang, ResetToDefault), "🔄 既定値に戻す");
+        texts.insert((lang, DefaultsReset), "既定値に戻しました");
+        texts.insert((lang, ApplyToAll), "📋 すべてのポリシーに適用");
+        texts.insert((lang, AppliedToAll), "すべてのポリシーに適用しました");
+
+        // 设置
+        texts.insert((lang, TcpSettings), "TCPシステムパラメータ設定");
+        texts.insert((lang, AdminRequiredForSettings), "⚠️ システム設定の変更には管理者権限が必要です");
+        texts.insert((lang, QuickConfig), "クイック設定");
+        texts.insert((lang, HighPerformance), "🚀 高性能設定");
+        texts.insert((lang, Conservative), "🛡 保守的設定");
+        texts.insert((lang, ReadCurrent), "🔄 現在の設定を読み込む");
+        texts.insert((lang, ConfigLoaded), "設定を読み込みました");
+        texts.insert((lang, DetailedConfig), "詳細設定");
+        texts.insert((lang, MaxUserPort), "最大ユーザーポート (MaxUserPort)");
+        texts.insert((lang, TimeWaitDelay), "TIME_WAIT待機時間 (秒)");
+        texts.insert((lang, DynamicPortStart), "動的ポート開始番号");
+        texts.insert((lang, Recommended), "推奨");
+        texts.insert((lang, ApplyConfig), "✅ 設定を適用");
+        texts.insert((lang, ConfigApplied), "設定を適用しました！反映にはシステムの再起動が必要な場合があります。");
+        texts.insert((lang, ApplyFailed), "適用に失敗しました");
+        texts.insert((lang, RebootRequired), "⚠️ 変更の反映にはシステムの再起動が必要です");
+
+        // 语言
+        texts.insert((lang, LanguageLabel), "🌐 言語");
+        texts.insert((lang, LanguageChanged), "言語を切り替えました");
+        texts.insert((lang, ThemeSystem), "システム設定");
+        texts.insert((lang, ThemeLight), "ライト");
+        texts.insert((lang, ThemeDark), "ダーク");
+
+        // 帮助
+        texts.insert((lang, Help), "❓ ヘルプ");
+        texts.insert((lang, HelpTitle), "📖 使い方ガイド");
+        texts.insert((lang, HelpAbout), "このソフトウェアについて");
+        texts.insert((lang, HelpAboutDesc), "Smart TCP Manager はクロスプラットフォームの TCP 接続管理・最適化ツールです。\nWindows、macOS、Linux に対応し、ネットワーク接続の状態をリアルタイムで監視して、\n潜在的な問題を特定し、TCP パラメータを最適化してネットワーク性能を向上させます。");
+        texts.insert((lang, HelpFeatures), "📋 機能説明");
+        texts.insert((lang, HelpDashboard), "📊 ダッシュボード");
+        texts.insert((lang, HelpDashboardDesc), "システム全体の TCP 接続の概要を表示します：\n  • 総接続数、利用可能ポート数、ポート使用率\n  • 状態別の接続分布（ESTABLISHED、TIME_WAIT など）\n  • 接続数上位 5 プロセスとその健全性スコア");
+        texts.insert((lang, HelpProcesses), "📋 プロセス一覧");
+        texts.insert((lang, HelpProcessesDesc), "プロセスごとの詳細な接続情報を表示します：\n  • プロセス名、PID、状態別の接続数\n  • 健全性スコア（100点満点、高いほど良好）\n  • 「ポリシーを追加」をクリックしてテンプレートを選択：\n    - 📊 既定：汎用設定\n    - 🚀 高性能：ゲーム/ダウンローダー\n    - 🕷️ クローラー：CLOSE_WAIT を積極的にクリーンアップ\n    - 🖥️ サーバー：高同時接続サービス\n    - 🔒 制限：接続数を制限");
+        texts.insert((lang, HelpPolicies), "📜 ポリシー管理");
+        texts.insert((lang, HelpPoliciesDesc), "アプリケーションごとに最適化ルールを設定します：\n  • TIME_WAIT しきい値：100-500 を推奨（超えると動作を実行）\n  • CLOSE_WAIT しきい値：20-100 を推奨（蓄積は接続が正しく閉じられていないことを示します）\n  • 最大接続数：1 プロセスあたりの接続数を制限\n  • しきい値超過時の動作：警告、自動最適化、無視\n\n💡 クローラーには「🕷️ クローラー」テンプレートを使い、CLOSE_WAIT を積極的にクリーンアップしてハングを防ぐことを推奨します");
+        texts.insert((lang, HelpSettingsHelp), "⚙️ システム設定");
+        texts.insert((lang, HelpSettingsDesc), "OS レベルの TCP パラメータを調整します（管理者権限が必要）：\n  • 最大ユーザーポート：既定 5000、推奨 32768-65534\n  • TIME_WAIT 待機時間：既定 120秒、推奨 30-60秒\n  • 動的ポート開始番号：既定 49152、必要に応じて調整");
+        texts.insert((lang, HelpTcpStates), "🔍 TCP 状態の説明");
+        texts.insert((lang, HelpTcpStatesDesc), "• ESTABLISHED（緑）：正常なアクティブ接続\n• TIME_WAIT（黄）：クローズ待ちの接続。多すぎるとポートを使い果たします\n• CLOSE_WAIT（赤）：相手側がクローズ済みでローカルのクローズ待ち。蓄積はプログラムの不具合を示します\n• LISTEN（青）：接続を待ち受けているポート\n• FIN_WAIT/LAST_ACK：クローズ処理中の接続");
+        texts.insert((lang, HelpTroubleshooting), "🛠 よくある質問");
+        texts.insert((lang, HelpTroubleshootingDesc), "Q: ポート使用率が高すぎる場合は？\nA: 最大ユーザーポート数を増やし、TIME_WAIT 待機時間を短くしてください\n\nQ: 特定のプロセスに CLOSE_WAIT が多い場合は？\nA: プログラムの問題です。プロセスを再起動するか開発者に連絡してください\n\nQ: 設定を変更しても反映されない場合は？\nA: 一部の設定はシステムの再起動後に反映されます");
+        texts.insert((lang, HelpVersion), "バージョン");
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_keys_in_declaration_order() {
+        for (i, key) in ALL_KEYS.iter().enumerate() {
+            assert_eq!(*key as usize, i, "ALL_KEYS 顺序与 TextKey 声明不一致: {:?}", key);
+        }
+    }
+
+    #[test]
+    fn every_key_translated_for_every_language() {
+        let mut i18n = I18n::new();
+        for lang in Language::ALL {
+            i18n.set_language(lang);
+            for &key in ALL_KEYS {
+                assert_ne!(i18n.t(key), "???", "{:?} 缺少翻译: {:?}", lang, key);
+            }
+        }
+    }
+
+    #[test]
+    fn overrides_take_precedence_and_fall_back() {
+        let path = std::env::temp_dir().join(format!("netopt-i18n-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"AppTitle": "Custom Title"}"#).unwrap();
+
+        let mut i18n = I18n::new();
+        i18n.set_language(Language::English);
+        i18n.load_overrides(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(i18n.t(TextKey::AppTitle), "Custom Title");
+        assert_eq!(i18n.t(TextKey::Dashboard), "📊 Dashboard");
+    }
+
+    #[test]
+    fn format_count_groups_thousands() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_count(999_999), "999,999");
+        assert_eq!(format_count(1_000_000), "1,000,000");
+    }
+
+    #[test]
+    fn format_count_compact_thresholds() {
+        assert_eq!(format_count_compact(999, Language::English), "999");
+        assert_eq!(format_count_compact(1000, Language::English), "1k");
+        assert_eq!(format_count_compact(1200, Language::English), "1.2k");
+        assert_eq!(format_count_compact(999_999, Language::English), "1M");
+        assert_eq!(format_count_compact(1_000_000, Language::English), "1M");
+        assert_eq!(format_count_compact(2_500_000, Language::English), "2.5M");
+
+        assert_eq!(format_count_compact(1000, Language::Chinese), "1,000");
+        assert_eq!(format_count_compact(12_000, Language::Chinese), "1.2万");
+        assert_eq!(format_count_compact(1_000_000, Language::Chinese), "100万");
+        assert_eq!(format_count_compact(1_000_000, Language::Japanese), "100万");
+        assert_eq!(format_count_compact(300_000_000, Language::TraditionalChinese), "3億");
+    }
+
+    #[test]
+    fn format_percent_one_decimal() {
+        assert_eq!(format_percent(0.0), "0.0%");
+        assert_eq!(format_percent(12.345), "12.3%");
+    }
+}
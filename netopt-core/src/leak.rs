@@ -0,0 +1,263 @@
+//! 连接泄漏报告
+//!
+//! 单次采样中 CLOSE_WAIT 偏高可能只是突发。在 `StatsHistory` 的一段窗口内观察每个进程的
+//! CLOSE_WAIT：只增不减且增长明显的才确认为泄漏，增长后回落的视为突发
+
+use crate::history::StatsHistory;
+use crate::ProcessTcpStats;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 确认泄漏至少需要的采样数
+pub const LEAK_MIN_SAMPLES: usize = 3;
+
+/// 泄漏判定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LeakVerdict {
+    /// CLOSE_WAIT 在整个序列中只增不减，且增长不少于 `min_growth`
+    Confirmed,
+    /// 曾增长 `min_growth` 以上但随后回落，多为突发
+    Transient,
+    /// 只增不减，但采样太少或增长不足，需继续观察
+    Watching,
+}
+
+/// 单个进程的 CLOSE_WAIT 序列
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessLeak {
+    pub pid: u32,
+    pub process_name: String,
+    /// 序列起点的 CLOSE_WAIT
+    pub first: usize,
+    /// 最新采样的 CLOSE_WAIT
+    pub last: usize,
+    pub peak: usize,
+    /// `last - first`，突发回落后可能为负
+    pub growth: i64,
+    /// 参与判断的采样数
+    pub samples: usize,
+    /// 序列覆盖的秒数
+    pub span_secs: u64,
+    /// 序列中 CLOSE_WAIT 从未下降
+    pub monotonic: bool,
+    /// 窗口内进程重启过：PID 曾属于其他程序，或同名程序先以其他 PID 运行。
+    /// 序列只从本次启动开始计算，重启前的计数不会被当成回落
+    pub restarted: bool,
+    pub verdict: LeakVerdict,
+}
+
+/// `LeakReport::from_history` 的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeakReport {
+    /// 实际参与分析的窗口秒数
+    pub window_secs: u64,
+    pub min_growth: usize,
+    /// 按判定（确认、突发、观察）和增长量排序
+    pub processes: Vec<ProcessLeak>,
+}
+
+impl LeakReport {
+    /// 分析最新采样之前 `window` 内的历史。只报告最新采样中仍存在、且 CLOSE_WAIT 有过增长的进程。
+    ///
+    /// 每个 PID 的序列从它（以当前进程名和路径）首次出现的采样开始，之后的采样中缺席按 0 计
+    pub fn from_history(history: &StatsHistory, window: Duration, min_growth: usize) -> Self {
+        let samples: Vec<_> = history.within(window).collect();
+        let window_secs = match (samples.first(), samples.last()) {
+            (Some(first), Some(last)) => last.timestamp.duration_since(first.timestamp).unwrap_or_default().as_secs(),
+            _ => 0,
+        };
+        let Some(latest) = samples.last() else {
+            return Self { window_secs, min_growth, processes: Vec::new() };
+        };
+
+        let same_program = |a: &ProcessTcpStats, b: &ProcessTcpStats| a.process_name == b.process_name && a.exe_path == b.exe_path;
+        let find = |index: usize, pid: u32| samples[index].stats.by_process.iter().find(|p| p.pid == pid);
+
+        let mut processes: Vec<ProcessLeak> = latest.stats.by_process.iter().filter_map(|current| {
+            // 从最新往前找本次启动的第一次采样，遇到其他程序占用同一 PID 即停止
+            let mut start = samples.len() - 1;
+            let mut pid_reused = false;
+            for index in (0..samples.len() - 1).rev() {
+                match find(index, current.pid) {
+                    Some(p) if !same_program(p, current) => {
+                        pid_reused = true;
+                        break;
+                    }
+                    Some(_) => start = index,
+                    None => {}
+                }
+            }
+            // 同名程序在本次启动前以其他 PID 运行，且现在已不存在
+            let pid_changed = samples[..start].iter()
+                .flat_map(|s| &s.stats.by_process)
+                .any(|p| p.pid != current.pid
+                    && same_program(p, current)
+                    && !latest.stats.by_process.iter().any(|q| q.pid == p.pid));
+
+            let series: Vec<usize> = (start..samples.len())
+                .map(|index| find(index, current.pid).map_or(0, |p| p.close_wait))
+                .collect();
+            let first = series[0];
+            let last = current.close_wait;
+            let peak = series.iter().copied().max().unwrap_or(0);
+            if peak == first {
+                return None;
+            }
+            let monotonic = series.windows(2).all(|w| w[1] >= w[0]);
+            let growth = last as i64 - first as i64;
+
+            let verdict = if monotonic && growth >= min_growth as i64 && series.len() >= LEAK_MIN_SAMPLES {
+                LeakVerdict::Confirmed
+            } else if monotonic {
+                LeakVerdict::Watching
+            } else if peak - first >= min_growth {
+                LeakVerdict::Transient
+            } else {
+                return None;
+            };
+
+            Some(ProcessLeak {
+                pid: current.pid,
+                process_name: current.process_name.clone(),
+                first,
+                last,
+                peak,
+                growth,
+                samples: series.len(),
+                span_secs: latest.timestamp.duration_since(samples[start].timestamp).unwrap_or_default().as_secs(),
+                monotonic,
+                restarted: pid_reused || pid_changed,
+                verdict,
+            })
+        }).collect();
+
+        processes.sort_by(|a, b| a.verdict.cmp(&b.verdict).then(b.growth.cmp(&a.growth)));
+        Self { window_secs, min_growth, processes }
+    }
+
+    /// 确认泄漏的进程
+    pub fn confirmed(&self) -> impl Iterator<Item = &ProcessLeak> {
+        self.processes.iter().filter(|p| p.verdict == LeakVerdict::Confirmed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemTcpStats;
+    use std::time::SystemTime;
+
+    fn proc(pid: u32, name: &str, close_wait: usize) -> ProcessTcpStats {
+        ProcessTcpStats {
+            pid,
+            process_name: name.into(),
+            close_wait,
+            total_connections: close_wait,
+            ..Default::default()
+        }
+    }
+
+    /// 每 30 秒一次采样
+    fn history(series: &[Vec<ProcessTcpStats>]) -> StatsHistory {
+        let mut history = StatsHistory::new(series.len());
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        for (i, by_process) in series.iter().enumerate() {
+            let stats = SystemTcpStats { by_process: by_process.clone(), ..Default::default() };
+            history.push_at(stats, start + Duration::from_secs(i as u64 * 30));
+        }
+        history
+    }
+
+    fn report(series: &[Vec<ProcessTcpStats>]) -> LeakReport {
+        LeakReport::from_history(&history(series), Duration::from_secs(600), 20)
+    }
+
+    #[test]
+    fn steady_growth_is_a_confirmed_leak() {
+        let series: Vec<_> = [5, 12, 12, 30, 41, 60]
+            .into_iter()
+            .map(|cw| vec![proc(7, "worker", cw), proc(8, "idle", 3)])
+            .collect();
+
+        let report = report(&series);
+        assert_eq!(report.window_secs, 150);
+        assert_eq!(report.processes.len(), 1);
+        let leak = &report.processes[0];
+        assert_eq!((leak.pid, leak.first, leak.last, leak.growth), (7, 5, 60, 55));
+        assert_eq!(leak.samples, 6);
+        assert!(leak.monotonic && !leak.restarted);
+        assert_eq!(leak.verdict, LeakVerdict::Confirmed);
+        assert_eq!(report.confirmed().count(), 1);
+    }
+
+    #[test]
+    fn spike_that_drains_is_transient() {
+        let series: Vec<_> = [5, 80, 40, 10].into_iter().map(|cw| vec![proc(7, "worker", cw)]).collect();
+
+        let leak = &report(&series).processes[0];
+        assert_eq!(leak.verdict, LeakVerdict::Transient);
+        assert_eq!((leak.peak, leak.growth), (80, 5));
+        assert!(!leak.monotonic);
+    }
+
+    #[test]
+    fn small_or_short_growth_is_only_watched() {
+        let small: Vec<_> = [5, 8, 12].into_iter().map(|cw| vec![proc(7, "worker", cw)]).collect();
+        assert_eq!(report(&small).processes[0].verdict, LeakVerdict::Watching);
+
+        let short: Vec<_> = [5, 50].into_iter().map(|cw| vec![proc(7, "worker", cw)]).collect();
+        assert_eq!(report(&short).processes[0].verdict, LeakVerdict::Watching);
+
+        // 有小幅回落且增长不足，不报告
+        let noise: Vec<_> = [5, 9, 6, 8].into_iter().map(|cw| vec![proc(7, "worker", cw)]).collect();
+        assert!(report(&noise).processes.is_empty());
+    }
+
+    #[test]
+    fn absence_counts_as_drop() {
+        let series = vec![
+            vec![proc(7, "worker", 10)],
+            vec![proc(7, "worker", 40)],
+            vec![],
+            vec![proc(7, "worker", 50)],
+        ];
+
+        assert_eq!(report(&series).processes[0].verdict, LeakVerdict::Transient);
+    }
+
+    #[test]
+    fn reused_pid_starts_a_new_series() {
+        // PID 7 先属于 old，随后被 worker 复用，worker 从 0 开始稳定增长
+        let series = vec![
+            vec![proc(7, "old", 90)],
+            vec![proc(7, "old", 95)],
+            vec![proc(7, "worker", 0)],
+            vec![proc(7, "worker", 15)],
+            vec![proc(7, "worker", 30)],
+        ];
+
+        let leak = &report(&series).processes[0];
+        assert!(leak.restarted);
+        assert_eq!((leak.first, leak.samples, leak.span_secs), (0, 3, 60));
+        assert_eq!(leak.verdict, LeakVerdict::Confirmed);
+    }
+
+    #[test]
+    fn restart_under_new_pid_is_not_a_leak() {
+        // worker 重启后换了 PID，计数清零后回到正常水平
+        let series = vec![
+            vec![proc(7, "worker", 80)],
+            vec![proc(7, "worker", 90)],
+            vec![proc(9, "worker", 0)],
+            vec![proc(9, "worker", 4)],
+            vec![proc(9, "worker", 4)],
+        ];
+
+        let report = report(&series);
+        let leak = &report.processes[0];
+        assert_eq!(leak.pid, 9);
+        assert!(leak.restarted);
+        assert_eq!(leak.verdict, LeakVerdict::Watching);
+        assert_eq!(report.confirmed().count(), 0);
+    }
+}
@@ -7,6 +7,10 @@
 //! - 无用连接自动清理
 //! - 国际化支持（中/英）
 //! - 配置持久化
+//! - TCP配置自动备份/回滚
+//!
+//! `TcpState`、`ActionType`、`AnomalyType`、`ThresholdAction`、`Language` 标记为 `#[non_exhaustive]`，
+//! 以后会增加新的变体。本库之外的 `match` 需要 `_` 分支；无法识别的连接状态统一为 `TcpState::Unknown`
 
 pub mod tcp_config;
 pub mod monitor;
@@ -15,6 +19,18 @@ pub mod policy;
 pub mod platform;
 pub mod i18n;
 pub mod config;
+pub mod export;
+pub mod snapshot;
+pub mod history;
+pub mod leak;
+pub mod resolve;
+pub mod backup;
+pub mod audit;
+pub mod shared_monitor;
+pub mod caching_monitor;
+pub mod clock;
+#[cfg(feature = "async")]
+pub mod async_monitor;
 
 pub use tcp_config::*;
 pub use monitor::*;
@@ -22,6 +38,19 @@ pub use optimizer::*;
 pub use policy::*;
 pub use i18n::{I18n, Language, TextKey};
 pub use config::AppConfig;
+pub use snapshot::NetworkSnapshot;
+pub use history::{
+    StatsHistory, StatsSample, ProcessDelta, ProcessRate, RateTracker, ConnectionTracker, FlappingConnection,
+    flapping_anomalies,
+};
+pub use leak::{LeakReport, LeakVerdict, ProcessLeak};
+pub use resolve::{HostResolver, ReverseDns, SystemResolver};
+pub use audit::{AuditLog, AuditEntry};
+pub use shared_monitor::SharedMonitor;
+pub use caching_monitor::CachingMonitor;
+pub use clock::{Clock, MockClock, SystemClock};
+#[cfg(feature = "async")]
+pub use async_monitor::{retry_with_backoff, AsyncTcpMonitor, Backoff};
 
 use thiserror::Error;
 
@@ -38,18 +67,39 @@ pub enum NetOptError {
     
     #[error("系统调用失败: {0}")]
     SystemError(String),
+
+    #[error("找不到命令: {0}，{}", platform::command::missing_hint(.0))]
+    CommandNotFound(String),
+
+    #[error("命令 {command} 执行失败（退出码 {}）: {stderr}", code.map(|c| c.to_string()).unwrap_or_else(|| "无".into()))]
+    CommandFailed {
+        command: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+
+    #[error("配置写入后未生效: {}", tcp_config::describe_mismatches(.0))]
+    ConfigNotApplied(Vec<FieldMismatch>),
+
+    /// 文件或命令输出格式不符，消息中已包含解析对象
+    #[error("{0}")]
+    ParseError(String),
     
     #[error("进程不存在: PID {0}")]
     ProcessNotFound(u32),
     
     #[error("IO错误: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("JSON序列化失败: {0}")]
+    SerializationError(#[from] serde_json::Error),
 }
 
 pub type Result<T> = std::result::Result<T, NetOptError>;
 
 /// TCP连接状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
 pub enum TcpState {
     Listen,
     SynSent,
@@ -84,6 +134,105 @@ impl std::fmt::Display for TcpState {
     }
 }
 
+/// 解析 TCP 状态名，忽略大小写、首尾空白和 `_`/`-` 分隔符，
+/// 兼容各平台工具的不同写法（如 LISTEN / LISTENING、SYN_RCVD / SYN_RECV）
+impl std::str::FromStr for TcpState {
+    type Err = NetOptError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let key: String = s.trim()
+            .chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        let state = match key.as_str() {
+            "LISTEN" | "LISTENING" => TcpState::Listen,
+            "SYNSENT" => TcpState::SynSent,
+            "SYNRECEIVED" | "SYNRCVD" | "SYNRECV" => TcpState::SynReceived,
+            "ESTABLISHED" | "ESTAB" => TcpState::Established,
+            "FINWAIT1" => TcpState::FinWait1,
+            "FINWAIT2" => TcpState::FinWait2,
+            "CLOSEWAIT" => TcpState::CloseWait,
+            "CLOSING" => TcpState::Closing,
+            "LASTACK" => TcpState::LastAck,
+            "TIMEWAIT" => TcpState::TimeWait,
+            "CLOSED" | "CLOSE" => TcpState::Closed,
+            "UNKNOWN" => TcpState::Unknown,
+            _ => return Err(NetOptError::InvalidParameter(format!("未知的 TCP 状态: {}", s.trim()))),
+        };
+        Ok(state)
+    }
+}
+
+impl TcpState {
+    pub const ALL: [TcpState; 12] = [
+        TcpState::Listen,
+        TcpState::SynSent,
+        TcpState::SynReceived,
+        TcpState::Established,
+        TcpState::FinWait1,
+        TcpState::FinWait2,
+        TcpState::CloseWait,
+        TcpState::Closing,
+        TcpState::LastAck,
+        TcpState::TimeWait,
+        TcpState::Closed,
+        TcpState::Unknown,
+    ];
+
+    /// 监听中
+    pub fn is_listening(self) -> bool {
+        self == TcpState::Listen
+    }
+
+    /// 已建立、正在传输数据
+    pub fn is_active(self) -> bool {
+        self == TcpState::Established
+    }
+
+    /// 主动关闭后等待 2MSL
+    pub fn is_waiting(self) -> bool {
+        self == TcpState::TimeWait
+    }
+
+    /// 对端已关闭，等待本端关闭或确认
+    pub fn is_half_closed(self) -> bool {
+        matches!(self, TcpState::CloseWait | TcpState::LastAck)
+    }
+
+    /// 握手或挥手过程中的短暂状态
+    pub fn is_transitional(self) -> bool {
+        matches!(
+            self,
+            TcpState::SynSent | TcpState::SynReceived | TcpState::FinWait1 | TcpState::FinWait2 | TcpState::Closing
+        )
+    }
+
+    /// 已关闭
+    pub fn is_terminal(self) -> bool {
+        self == TcpState::Closed
+    }
+}
+
+/// 连接的地址族
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum AddressFamily {
+    Ipv4,
+    Ipv6,
+    /// 本地和远端地址都无法解析，如 macOS 监听套接字的通配地址 "*"
+    Unknown,
+}
+
+impl std::fmt::Display for AddressFamily {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressFamily::Ipv4 => write!(f, "IPv4"),
+            AddressFamily::Ipv6 => write!(f, "IPv6"),
+            AddressFamily::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
 /// 单个TCP连接信息
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TcpConnection {
@@ -94,10 +243,83 @@ pub struct TcpConnection {
     pub state: TcpState,
     pub pid: u32,
     pub process_name: String,
+    /// 状态无法识别（`TcpState::Unknown`）时平台给出的原始状态文本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_state: Option<String>,
+    /// 远端地址的反向解析结果，只在启用 `ReverseDns` 时填写
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_host: Option<String>,
 }
 
-/// 进程的TCP连接统计
-#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+/// 连接的身份：本地和远端的地址与端口。同一个套接字在不同快照中状态、所属进程可能变化，四元组不变
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub struct ConnectionKey {
+    pub local_addr: String,
+    pub local_port: u16,
+    pub remote_addr: String,
+    pub remote_port: u16,
+}
+
+impl std::fmt::Display for ConnectionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{} -> {}:{}", self.local_addr, self.local_port, self.remote_addr, self.remote_port)
+    }
+}
+
+impl TcpConnection {
+    /// 连接的四元组，可作为跨快照比较的键
+    pub fn key(&self) -> ConnectionKey {
+        ConnectionKey {
+            local_addr: self.local_addr.clone(),
+            local_port: self.local_port,
+            remote_addr: self.remote_addr.clone(),
+            remote_port: self.remote_port,
+        }
+    }
+}
+
+/// 只比较四元组，状态、PID 和进程名不参与
+impl PartialEq for TcpConnection {
+    fn eq(&self, other: &Self) -> bool {
+        self.local_port == other.local_port
+            && self.remote_port == other.remote_port
+            && self.local_addr == other.local_addr
+            && self.remote_addr == other.remote_addr
+    }
+}
+
+impl Eq for TcpConnection {}
+
+impl std::hash::Hash for TcpConnection {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.local_addr.hash(state);
+        self.local_port.hash(state);
+        self.remote_addr.hash(state);
+        self.remote_port.hash(state);
+    }
+}
+
+/// 处于监听状态的端口及其所属进程
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ListeningPort {
+    pub local_addr: String,
+    pub port: u16,
+    pub pid: u32,
+    pub process_name: String,
+}
+
+/// 单个UDP套接字信息
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UdpSocketInfo {
+    pub local_addr: String,
+    pub local_port: u16,
+    pub pid: u32,
+    pub process_name: String,
+}
+
+/// 进程的TCP连接统计。缺少的字段按默认值读取，兼容旧快照
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct ProcessTcpStats {
     pub pid: u32,
     pub process_name: String,
@@ -110,15 +332,404 @@ pub struct ProcessTcpStats {
     pub other: usize,
     /// 健康评分 0-100，越低越需要优化
     pub health_score: u8,
+    /// 每秒新建连接数，有历史数据时才有值
+    pub churn_rate: Option<f32>,
+    /// CLOSE_WAIT 每秒净增长，有历史数据时才有值
+    pub close_wait_growth: Option<f32>,
 }
 
-/// 系统整体TCP统计
+impl ProcessTcpStats {
+    /// 按状态计数一条连接（不含 `total_connections`）。
+    /// `close_wait` 只统计 CLOSE_WAIT，LAST_ACK 计入 `other`
+    pub fn record_state(&mut self, state: TcpState) {
+        if state.is_active() {
+            self.established += 1;
+        } else if state.is_waiting() {
+            self.time_wait += 1;
+        } else if state == TcpState::CloseWait {
+            self.close_wait += 1;
+        } else if state.is_listening() {
+            self.listen += 1;
+        } else {
+            self.other += 1;
+        }
+    }
+}
+
+/// 系统整体TCP统计。缺少的字段按默认值读取，兼容旧快照
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct SystemTcpStats {
     pub total_connections: usize,
     pub by_state: std::collections::HashMap<TcpState, usize>,
     pub by_process: Vec<ProcessTcpStats>,
     pub available_ports: usize,
     pub port_usage_percent: f32,
+
+    /// 动态端口范围内正在使用的不同本地端口数
+    pub ephemeral_ports_in_use: usize,
+
+    /// 动态端口范围 (起始, 结束)
+    pub dynamic_port_range: Option<(u32, u32)>,
+
+    /// 动态端口范围内被 UDP 占用的端口数，仅 `get_system_stats_with_udp` 统计
+    pub udp_ports_used: Option<usize>,
+
+    /// 按地址族统计的连接数
+    pub by_family: std::collections::HashMap<AddressFamily, usize>,
+
+    /// 无法识别的原始状态文本（去重，最多 `UNKNOWN_STATE_SAMPLE_LIMIT` 个），用于从用户快照定位解析问题
+    pub unknown_state_samples: Vec<String>,
+}
+
+impl SystemTcpStats {
+    /// 同时计入 UDP 占用后的可用动态端口数
+    pub fn available_ports_combined(&self) -> usize {
+        self.available_ports.saturating_sub(self.udp_ports_used.unwrap_or(0))
+    }
+
+    /// 满足条件的状态下的连接总数，如 `stats.count_where(TcpState::is_half_closed)`
+    pub fn count_where(&self, f: impl Fn(TcpState) -> bool) -> usize {
+        self.by_state.iter().filter(|(state, _)| f(**state)).map(|(_, n)| n).sum()
+    }
+
+    /// 按程序合并的进程统计，多进程程序（浏览器、nginx worker 等）各占一行，见 `ExecutableTcpStats::group`
+    pub fn by_executable(&self) -> Vec<ExecutableTcpStats> {
+        ExecutableTcpStats::group(&self.by_process)
+    }
+
+    /// 与上一次统计相比，新增、消失和数据有变化的进程
+    pub fn diff(&self, prev: &SystemTcpStats) -> StatsDelta {
+        let old: std::collections::HashMap<u32, &ProcessTcpStats> = prev.by_process.iter().map(|p| (p.pid, p)).collect();
+        let mut delta = StatsDelta::default();
+        for proc in &self.by_process {
+            match old.get(&proc.pid) {
+                None => delta.added.push(proc.pid),
+                Some(old) if *old != proc => delta.changed.push(proc.pid),
+                Some(_) => {}
+            }
+        }
+        let current: std::collections::HashSet<u32> = self.by_process.iter().map(|p| p.pid).collect();
+        delta.removed = prev.by_process.iter().map(|p| p.pid).filter(|pid| !current.contains(pid)).collect();
+
+        delta.added.sort_unstable();
+        delta.removed.sort_unstable();
+        delta.changed.sort_unstable();
+        delta
+    }
+}
+
+/// 同一程序多个进程的连接合计，见 `SystemTcpStats::by_executable`
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExecutableTcpStats {
+    pub process_name: String,
+    /// 按名称分组（读不到路径）时为 None
+    pub exe_path: Option<String>,
+    /// 组内进程，升序
+    pub pids: Vec<u32>,
+    pub total_connections: usize,
+    pub established: usize,
+    pub time_wait: usize,
+    pub close_wait: usize,
+    pub listen: usize,
+    pub other: usize,
+    /// 组内最低的健康评分
+    pub health_score: u8,
+}
+
+impl ExecutableTcpStats {
+    /// 按可执行文件路径合并进程，读不到路径的按进程名合并。同名进程只对应一个路径时，
+    /// 读不到路径的进程也并入该路径。按连接总数降序排列
+    pub fn group(processes: &[ProcessTcpStats]) -> Vec<Self> {
+        use std::collections::{HashMap, HashSet};
+
+        let mut paths_by_name: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for proc in processes {
+            if let Some(path) = &proc.exe_path {
+                paths_by_name.entry(&proc.process_name).or_default().insert(path);
+            }
+        }
+
+        let mut index: HashMap<(Option<&str>, &str), usize> = HashMap::new();
+        let mut groups: Vec<Self> = Vec::new();
+        for proc in processes {
+            let path = proc.exe_path.as_deref().or_else(|| {
+                paths_by_name.get(proc.process_name.as_str())
+                    .filter(|paths| paths.len() == 1)
+                    .and_then(|paths| paths.iter().next().copied())
+            });
+            // 同一路径可能以不同进程名出现（如符号链接），只按路径区分
+            let key = match path {
+                Some(path) => (Some(path), ""),
+                None => (None, proc.process_name.as_str()),
+            };
+            let i = *index.entry(key).or_insert_with(|| {
+                groups.push(Self {
+                    process_name: proc.process_name.clone(),
+                    exe_path: path.map(str::to_string),
+                    health_score: proc.health_score,
+                    ..Default::default()
+                });
+                groups.len() - 1
+            });
+
+            let group = &mut groups[i];
+            group.pids.push(proc.pid);
+            group.total_connections += proc.total_connections;
+            group.established += proc.established;
+            group.time_wait += proc.time_wait;
+            group.close_wait += proc.close_wait;
+            group.listen += proc.listen;
+            group.other += proc.other;
+            group.health_score = group.health_score.min(proc.health_score);
+        }
+
+        for group in &mut groups {
+            group.pids.sort_unstable();
+        }
+        groups.sort_by(|a, b| {
+            b.total_connections.cmp(&a.total_connections)
+                .then_with(|| a.process_name.cmp(&b.process_name))
+                .then_with(|| a.exe_path.cmp(&b.exe_path))
+        });
+        groups
+    }
+}
+
+/// 为统计类型实现 `to_json` / `to_json_pretty` / `from_json`
+macro_rules! impl_json {
+    ($($ty:ty),*) => {$(
+        impl $ty {
+            /// 序列化为单行 JSON
+            pub fn to_json(&self) -> Result<String> {
+                Ok(serde_json::to_string(self)?)
+            }
+
+            /// 序列化为格式化 JSON
+            pub fn to_json_pretty(&self) -> Result<String> {
+                Ok(serde_json::to_string_pretty(self)?)
+            }
+
+            /// 从 JSON 解析，缺少的可选字段取默认值
+            pub fn from_json(json: &str) -> Result<Self> {
+                Ok(serde_json::from_str(json)?)
+            }
+        }
+    )*};
+}
+
+impl_json!(TcpConnection, ProcessTcpStats, SystemTcpStats);
+
+/// `SystemTcpStats::diff` 的结果，均为升序 PID
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct StatsDelta {
+    pub added: Vec<u32>,
+    pub removed: Vec<u32>,
+    pub changed: Vec<u32>,
+}
+
+impl StatsDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// 进程集合是否变化（有新增或消失）
+    pub fn membership_changed(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_known_state_is_in_exactly_one_bucket() {
+        let buckets: [fn(TcpState) -> bool; 6] = [
+            TcpState::is_listening,
+            TcpState::is_active,
+            TcpState::is_waiting,
+            TcpState::is_half_closed,
+            TcpState::is_transitional,
+            TcpState::is_terminal,
+        ];
+        for state in TcpState::ALL {
+            let hits = buckets.iter().filter(|f| f(state)).count();
+            let expected = if state == TcpState::Unknown { 0 } else { 1 };
+            assert_eq!(hits, expected, "{}", state);
+        }
+    }
+
+    #[test]
+    fn parses_display_names_and_platform_spellings() {
+        for state in TcpState::ALL {
+            assert_eq!(state.to_string().parse::<TcpState>().unwrap(), state);
+        }
+        // Windows 为 LISTENING，macOS / Linux 为 LISTEN
+        assert_eq!("LISTENING".parse::<TcpState>().unwrap(), TcpState::Listen);
+        assert_eq!("listen".parse::<TcpState>().unwrap(), TcpState::Listen);
+        assert_eq!(" Syn_Received ".parse::<TcpState>().unwrap(), TcpState::SynReceived);
+        assert_eq!("SYN-RECV".parse::<TcpState>().unwrap(), TcpState::SynReceived);
+        assert_eq!("FIN_WAIT1".parse::<TcpState>().unwrap(), TcpState::FinWait1);
+        assert!("BOGUS".parse::<TcpState>().is_err());
+    }
+
+    #[test]
+    fn connection_identity_ignores_state_and_owner() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashSet;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |conn: &TcpConnection| {
+            let mut hasher = DefaultHasher::new();
+            conn.hash(&mut hasher);
+            hasher.finish()
+        };
+        let before = TcpConnection {
+            local_addr: "10.0.0.1".into(),
+            local_port: 50000,
+            remote_addr: "10.0.0.2".into(),
+            remote_port: 443,
+            state: TcpState::Established,
+            pid: 42,
+            process_name: "web".into(),
+            raw_state: None,
+            remote_host: None,
+        };
+        // 下一次快照中同一个套接字已进入 TIME_WAIT，不再属于任何进程
+        let after = TcpConnection {
+            state: TcpState::TimeWait,
+            pid: 0,
+            process_name: String::new(),
+            ..before.clone()
+        };
+        let other = TcpConnection { remote_port: 8443, ..before.clone() };
+
+        assert_eq!(before, after);
+        assert_eq!(hash(&before), hash(&after));
+        assert_eq!(before.key(), after.key());
+        assert_ne!(before, other);
+        assert_ne!(before.key(), other.key());
+        assert_eq!(before.key().to_string(), "10.0.0.1:50000 -> 10.0.0.2:443");
+
+        let seen: HashSet<TcpConnection> = [before, after, other].into_iter().collect();
+        assert_eq!(seen.len(), 2);
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_processes() {
+        let proc = |pid: u32, close_wait: usize| ProcessTcpStats {
+            pid,
+            process_name: format!("proc{}", pid),
+            close_wait,
+            ..Default::default()
+        };
+        let prev = SystemTcpStats { by_process: vec![proc(3, 0), proc(1, 5), proc(2, 0)], ..Default::default() };
+        let curr = SystemTcpStats { by_process: vec![proc(4, 0), proc(1, 9), proc(3, 0)], ..Default::default() };
+
+        let delta = curr.diff(&prev);
+        assert_eq!(delta, StatsDelta { added: vec![4], removed: vec![2], changed: vec![1] });
+        assert!(delta.membership_changed());
+        assert!(curr.diff(&curr).is_empty());
+    }
+
+    #[test]
+    fn count_where_sums_matching_states() {
+        let stats = SystemTcpStats {
+            by_state: [(TcpState::CloseWait, 3), (TcpState::LastAck, 2), (TcpState::TimeWait, 5)].into(),
+            ..Default::default()
+        };
+        assert_eq!(stats.count_where(TcpState::is_half_closed), 5);
+        assert_eq!(stats.count_where(TcpState::is_terminal), 0);
+    }
+
+    #[test]
+    fn json_helpers_round_trip() {
+        let conn = TcpConnection {
+            local_addr: "10.0.0.2".into(),
+            local_port: 50001,
+            remote_addr: "93.184.216.34".into(),
+            remote_port: 443,
+            state: TcpState::CloseWait,
+            pid: 7,
+            process_name: "worker".into(),
+            raw_state: None,
+            remote_host: Some("example.com".into()),
+        };
+        let parsed = TcpConnection::from_json(&conn.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.key(), conn.key());
+        assert_eq!((parsed.state, parsed.pid, parsed.remote_host.as_deref()), (TcpState::CloseWait, 7, Some("example.com")));
+
+        let proc = ProcessTcpStats {
+            pid: 7,
+            process_name: "worker".into(),
+            close_wait: 3,
+            churn_rate: Some(1.5),
+            ..Default::default()
+        };
+        assert_eq!(ProcessTcpStats::from_json(&proc.to_json_pretty().unwrap()).unwrap(), proc);
+
+        let stats = SystemTcpStats {
+            total_connections: 3,
+            by_state: [(TcpState::CloseWait, 3)].into(),
+            by_family: [(AddressFamily::Ipv4, 3)].into(),
+            by_process: vec![proc],
+            dynamic_port_range: Some((49152, 65535)),
+            ..Default::default()
+        };
+        let json = stats.to_json().unwrap();
+        assert!(!json.contains('\n'));
+        let parsed = SystemTcpStats::from_json(&json).unwrap();
+        assert_eq!(parsed.to_json_pretty().unwrap(), stats.to_json_pretty().unwrap());
+    }
+
+    #[test]
+    fn json_errors_map_to_serialization_error() {
+        assert!(matches!(SystemTcpStats::from_json("{\"total_connections\": -1}"), Err(NetOptError::SerializationError(_))));
+        assert!(matches!(TcpConnection::from_json("not json"), Err(NetOptError::SerializationError(_))));
+    }
+
+    #[test]
+    fn by_executable_merges_processes_of_the_same_program() {
+        let proc = |pid: u32, name: &str, path: Option<&str>, total: usize, health: u8| ProcessTcpStats {
+            pid,
+            process_name: name.into(),
+            exe_path: path.map(str::to_string),
+            total_connections: total,
+            established: total,
+            health_score: health,
+            ..Default::default()
+        };
+        let chrome = Some("/opt/google/chrome/chrome");
+        let stats = SystemTcpStats {
+            by_process: vec![
+                proc(30, "chrome", chrome, 40, 90),
+                proc(12, "chrome", chrome, 25, 60),
+                // 读不到路径，chrome 只有一个路径，并入该组
+                proc(51, "chrome", None, 5, 100),
+                // 两个不同的 python，读不到路径的无法归属，单独按名称合并
+                proc(7, "python3", Some("/usr/bin/python3"), 10, 100),
+                proc(8, "python3", Some("/opt/venv/bin/python3"), 3, 100),
+                proc(9, "python3", None, 2, 80),
+                proc(21, "nginx", None, 8, 100),
+                proc(20, "nginx", None, 8, 70),
+            ],
+            ..Default::default()
+        };
+
+        let groups = stats.by_executable();
+        let summary: Vec<_> = groups.iter()
+            .map(|g| (g.process_name.as_str(), g.exe_path.as_deref(), g.pids.clone(), g.total_connections, g.health_score))
+            .collect();
+        assert_eq!(summary, [
+            ("chrome", chrome, vec![12, 30, 51], 70, 60),
+            ("nginx", None, vec![20, 21], 16, 70),
+            ("python3", Some("/usr/bin/python3"), vec![7], 10, 100),
+            ("python3", Some("/opt/venv/bin/python3"), vec![8], 3, 100),
+            ("python3", None, vec![9], 2, 80),
+        ]);
+        assert_eq!(groups[0].established, 70);
+        assert_eq!(groups.iter().map(|g| g.pids.len()).sum::<usize>(), stats.by_process.len());
+    }
 }
 
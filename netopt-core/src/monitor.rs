@@ -1,7 +1,116 @@
 //! 进程级TCP连接监控模块
 
-use crate::{Result, TcpConnection, TcpState, ProcessTcpStats, SystemTcpStats};
-use std::collections::HashMap;
+use crate::{AddressFamily, ListeningPort, NetOptError, Result, TcpConnection, TcpState, ProcessTcpStats, SystemTcpStats, UdpSocketInfo};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+
+/// 进程排行的排序依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// 总连接数从多到少
+    #[default]
+    Total,
+    /// CLOSE_WAIT 从多到少，用于排查连接泄漏
+    CloseWait,
+    /// TIME_WAIT 从多到少
+    TimeWait,
+    /// 健康评分从低到高
+    HealthAsc,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 4] = [SortKey::Total, SortKey::CloseWait, SortKey::TimeWait, SortKey::HealthAsc];
+
+    /// 稳定排序，并列时按总连接数从多到少
+    pub fn sort(self, procs: &mut [ProcessTcpStats]) {
+        procs.sort_by(|a, b| {
+            let primary = match self {
+                SortKey::Total => b.total_connections.cmp(&a.total_connections),
+                SortKey::CloseWait => b.close_wait.cmp(&a.close_wait),
+                SortKey::TimeWait => b.time_wait.cmp(&a.time_wait),
+                SortKey::HealthAsc => a.health_score.cmp(&b.health_score),
+            };
+            primary.then(b.total_connections.cmp(&a.total_connections))
+        });
+    }
+}
+
+/// 从连接列表中提取监听端口，按 (地址, 端口) 去重，多个进程共享同一端口时保留 PID 最小的
+pub fn listening_ports(connections: &[TcpConnection]) -> Vec<ListeningPort> {
+    let mut ports: Vec<ListeningPort> = connections.iter()
+        .filter(|c| c.state.is_listening())
+        .map(|c| ListeningPort {
+            local_addr: c.local_addr.clone(),
+            port: c.local_port,
+            pid: c.pid,
+            process_name: c.process_name.clone(),
+        })
+        .collect();
+    ports.sort_by(|a, b| (a.port, &a.local_addr, a.pid).cmp(&(b.port, &b.local_addr, b.pid)));
+    ports.dedup_by(|b, a| a.port == b.port && a.local_addr == b.local_addr);
+    ports
+}
+
+/// 查找被多个进程同时监听的 (地址, 端口)。
+/// 进程名相同时多为 SO_REUSEPORT 的多进程服务，记为 Info；进程名不同记为 Warning
+pub fn duplicate_listener_anomalies(connections: &[TcpConnection]) -> Vec<ConnectionAnomaly> {
+    let mut by_port: BTreeMap<(u16, &str), BTreeMap<u32, &str>> = BTreeMap::new();
+    for conn in connections.iter().filter(|c| c.state.is_listening()) {
+        by_port.entry((conn.local_port, conn.local_addr.as_str()))
+            .or_default()
+            .insert(conn.pid, conn.process_name.as_str());
+    }
+
+    by_port.into_iter()
+        .filter(|(_, owners)| owners.len() > 1)
+        .map(|((port, addr), owners)| {
+            let (&pid, &process_name) = owners.iter().next().expect("至少两个进程");
+            let same_name = owners.values().all(|name| *name == process_name);
+            let list = owners.iter()
+                .map(|(pid, name)| format!("{}({})", name, pid))
+                .collect::<Vec<_>>()
+                .join(", ");
+            ConnectionAnomaly {
+                pid,
+                process_name: process_name.to_string(),
+                anomaly_type: AnomalyType::DuplicateListener,
+                severity: if same_name { Severity::Info } else { Severity::Warning },
+                message: format!("端口 {}:{} 被 {} 个进程监听: {}", addr, port, owners.len(), list),
+                suggestion: if same_name {
+                    "同名进程共享端口通常是 SO_REUSEPORT，可忽略".into()
+                } else {
+                    "不同程序争用同一端口，检查是否有残留进程或重复启动".into()
+                },
+            }
+        })
+        .collect()
+}
+
+/// TIME_WAIT、CLOSE_WAIT 或总连接数是否超过对应阈值
+pub fn exceeds_thresholds(
+    stats: &ProcessTcpStats,
+    time_wait_threshold: usize,
+    close_wait_threshold: usize,
+    max_connections: usize,
+) -> bool {
+    stats.time_wait > time_wait_threshold
+        || stats.close_wait > close_wait_threshold
+        || stats.total_connections > max_connections
+}
+
+/// `get_system_stats_with` 的选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsOptions {
+    /// 只保留连接数最多的 N 个进程，None 为全部。连接总数、按状态统计和端口使用率仍包含所有连接
+    pub max_tracked_processes: Option<usize>,
+    /// 分块并行汇总进程并计算健康评分，结果与顺序汇总相同。未启用 `parallel` feature 时忽略
+    pub parallel: bool,
+    /// 计算端口使用率时使用的动态端口范围 `(start, end)`，None 时读取系统配置。
+    /// 容器或 VPN 环境下系统报告的范围可能与实际不符。`get_system_stats_with` 的默认实现忽略此项
+    pub port_range: Option<(u32, u32)>,
+}
 
 /// TCP连接监控器 trait
 pub trait TcpMonitor: Send + Sync {
@@ -16,12 +125,202 @@ pub trait TcpMonitor: Send + Sync {
     
     /// 获取系统整体统计
     fn get_system_stats(&self) -> Result<SystemTcpStats>;
+
+    /// 按选项获取系统统计。默认实现截断 `get_system_stats` 的进程列表，
+    /// 平台实现在汇总时就只保留前 N 个进程，跳过其余进程的路径查询
+    fn get_system_stats_with(&self, options: &StatsOptions) -> Result<SystemTcpStats> {
+        let mut stats = self.get_system_stats()?;
+        if let Some(limit) = options.max_tracked_processes {
+            stats.by_process.truncate(limit);
+        }
+        Ok(stats)
+    }
     
+    /// 在已获取的连接表上计算系统统计，调用方可复用同一次扫描的结果。
+    /// 默认实现忽略 `connections`，重新调用 `get_system_stats_with`；平台实现直接在其上计算
+    fn system_stats_from(&self, connections: &[TcpConnection], options: &StatsOptions) -> Result<SystemTcpStats> {
+        let _ = connections;
+        self.get_system_stats_with(options)
+    }
+
+    /// 在已获取的连接表上计算进程统计，默认实现同样忽略 `connections`
+    fn process_stats_from(&self, pid: u32, connections: &[TcpConnection]) -> Result<ProcessTcpStats> {
+        let _ = connections;
+        self.get_process_stats(pid)
+    }
+
+    /// 扫描一次连接表，同时返回连接列表和系统统计
+    fn get_connections_and_stats(&self) -> Result<(Vec<TcpConnection>, SystemTcpStats)> {
+        self.get_connections_and_stats_with(&StatsOptions::default())
+    }
+
+    /// 同 `get_connections_and_stats`，按选项计算统计
+    fn get_connections_and_stats_with(&self, options: &StatsOptions) -> Result<(Vec<TcpConnection>, SystemTcpStats)> {
+        let connections = self.get_all_connections()?;
+        let stats = self.system_stats_from(&connections, options)?;
+        Ok((connections, stats))
+    }
+
     /// 获取占用连接最多的进程列表
-    fn get_top_processes(&self, limit: usize) -> Result<Vec<ProcessTcpStats>>;
+    fn get_top_processes(&self, limit: usize) -> Result<Vec<ProcessTcpStats>> {
+        self.get_top_processes_by(limit, SortKey::Total)
+    }
+
+    /// 按指定依据排序后取前 `limit` 个进程
+    fn get_top_processes_by(&self, limit: usize, key: SortKey) -> Result<Vec<ProcessTcpStats>> {
+        // 按总连接数排行时不必汇总全部进程
+        if key == SortKey::Total {
+            let options = StatsOptions { max_tracked_processes: Some(limit), ..Default::default() };
+            return Ok(self.get_system_stats_with(&options)?.by_process);
+        }
+        let mut procs = self.get_system_stats()?.by_process;
+        key.sort(&mut procs);
+        procs.truncate(limit);
+        Ok(procs)
+    }
     
-    /// 获取问题进程（TIME_WAIT或CLOSE_WAIT过多）
-    fn get_problematic_processes(&self, threshold: usize) -> Result<Vec<ProcessTcpStats>>;
+    /// 获取问题进程（TIME_WAIT 超过 `threshold` 或 CLOSE_WAIT 超过 `threshold / 4`）
+    fn get_problematic_processes(&self, threshold: usize) -> Result<Vec<ProcessTcpStats>> {
+        self.get_problematic_processes_with(threshold, threshold / 4, usize::MAX)
+    }
+
+    /// 获取超过任一阈值的问题进程，各阈值相互独立
+    fn get_problematic_processes_with(
+        &self,
+        time_wait_threshold: usize,
+        close_wait_threshold: usize,
+        max_connections: usize,
+    ) -> Result<Vec<ProcessTcpStats>> {
+        let stats = self.get_system_stats()?;
+        Ok(stats.by_process.into_iter()
+            .filter(|p| exceeds_thresholds(p, time_wait_threshold, close_wait_threshold, max_connections))
+            .collect())
+    }
+
+    /// 获取所有监听端口及其所属进程，按端口排序
+    fn get_listening_ports(&self) -> Result<Vec<ListeningPort>> {
+        Ok(listening_ports(&self.get_all_connections()?))
+    }
+
+    /// 获取所有UDP套接字
+    fn get_udp_sockets(&self) -> Result<Vec<UdpSocketInfo>> {
+        Err(NetOptError::UnsupportedPlatform("当前监控器不支持 UDP".into()))
+    }
+
+    /// 获取系统统计，端口使用率同时计入动态端口范围内已绑定的 UDP 端口
+    fn get_system_stats_with_udp(&self, options: &StatsOptions) -> Result<SystemTcpStats> {
+        let mut stats = self.get_system_stats_with(options)?;
+        let Some((start, end)) = stats.dynamic_port_range else {
+            return Ok(stats);
+        };
+
+        let udp_ports: HashSet<u16> = self.get_udp_sockets()?
+            .iter()
+            .map(|s| s.local_port)
+            .filter(|&p| (start..=end).contains(&(p as u32)))
+            .collect();
+        stats.udp_ports_used = Some(udp_ports.len());
+
+        stats.port_usage_percent = port_usage(stats.ephemeral_ports_in_use + udp_ports.len(), start, end).percent;
+        Ok(stats)
+    }
+
+    /// 按条件查询连接，默认在 `get_all_connections` 结果上过滤
+    fn query(&self, filter: &ConnectionFilter) -> Result<Vec<TcpConnection>> {
+        Ok(self.get_all_connections()?
+            .into_iter()
+            .filter(|c| filter.matches(c))
+            .collect())
+    }
+
+    /// 进程连接最多的 `n` 个远端地址，`include_loopback` 为 false 时排除本地回环连接
+    fn top_remote_peers(&self, pid: u32, n: usize, include_loopback: bool) -> Result<Vec<(IpAddr, usize)>> {
+        let mut filter = ConnectionFilter::new().by_pid(pid);
+        if !include_loopback {
+            filter = filter.only_loopback(false);
+        }
+        let mut peers: Vec<(IpAddr, usize)> = group_by_remote(&self.query(&filter)?).into_iter().collect();
+        peers.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        peers.truncate(n);
+        Ok(peers)
+    }
+}
+
+/// 连接查询条件，各条件之间为“与”关系，未设置的条件不限制
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionFilter {
+    states: Vec<TcpState>,
+    pid: Option<u32>,
+    local_ports: Option<RangeInclusive<u16>>,
+    remote_addr: Option<IpAddr>,
+    loopback: Option<bool>,
+}
+
+impl ConnectionFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按状态过滤，多次调用时匹配其中任一状态
+    pub fn by_state(mut self, state: TcpState) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    pub fn by_pid(mut self, pid: u32) -> Self {
+        self.pid = Some(pid);
+        self
+    }
+
+    /// 本地端口在 `start..=end` 内
+    pub fn by_local_port_range(mut self, start: u16, end: u16) -> Self {
+        self.local_ports = Some(start..=end);
+        self
+    }
+
+    pub fn by_remote_addr(mut self, addr: IpAddr) -> Self {
+        self.remote_addr = Some(addr);
+        self
+    }
+
+    /// `true` 只保留本地回环连接，`false` 排除本地回环连接
+    pub fn only_loopback(mut self, loopback: bool) -> Self {
+        self.loopback = Some(loopback);
+        self
+    }
+
+    /// 连接是否满足全部条件
+    pub fn matches(&self, conn: &TcpConnection) -> bool {
+        if !self.states.is_empty() && !self.states.contains(&conn.state) {
+            return false;
+        }
+        if self.pid.is_some_and(|pid| pid != conn.pid) {
+            return false;
+        }
+        if let Some(ports) = &self.local_ports {
+            if !ports.contains(&conn.local_port) {
+                return false;
+            }
+        }
+        if let Some(addr) = self.remote_addr {
+            if parse_ip(&conn.remote_addr) != Some(addr) {
+                return false;
+            }
+        }
+        if let Some(loopback) = self.loopback {
+            let is_loopback = parse_ip(&conn.local_addr).is_some_and(|ip| ip.is_loopback());
+            if is_loopback != loopback {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 解析地址字符串，IPv4 映射的 IPv6 地址转换为 IPv4
+pub(crate) fn parse_ip(addr: &str) -> Option<IpAddr> {
+    let ip: IpAddr = addr.trim_matches(|c| c == '[' || c == ']').parse().ok()?;
+    Some(ip.to_canonical())
 }
 
 /// 连接统计工具函数
@@ -33,6 +332,178 @@ pub fn calculate_stats(connections: &[TcpConnection]) -> HashMap<TcpState, usize
     stats
 }
 
+/// 连接的地址族。IPv4 映射的 IPv6 地址（双栈套接字上的 IPv4 连接）计为 IPv4，
+/// 本地地址无法解析时看远端地址
+pub fn address_family(conn: &TcpConnection) -> AddressFamily {
+    [&conn.local_addr, &conn.remote_addr]
+        .into_iter()
+        // 去掉链路本地地址的区域标识，如 "fe80::1%en0"
+        .find_map(|addr| parse_ip(addr.split('%').next().unwrap_or(addr)))
+        .map_or(AddressFamily::Unknown, |ip| match ip {
+            IpAddr::V4(_) => AddressFamily::Ipv4,
+            IpAddr::V6(_) => AddressFamily::Ipv6,
+        })
+}
+
+/// 按地址族统计连接数
+pub fn calculate_family_stats(connections: &[TcpConnection]) -> HashMap<AddressFamily, usize> {
+    let mut stats = HashMap::new();
+    for conn in connections {
+        *stats.entry(address_family(conn)).or_insert(0) += 1;
+    }
+    stats
+}
+
+/// `SystemTcpStats::unknown_state_samples` 最多保留的条数
+pub const UNKNOWN_STATE_SAMPLE_LIMIT: usize = 16;
+
+/// 无法识别的原始状态文本，按首次出现的顺序去重
+pub fn unknown_state_samples(connections: &[TcpConnection]) -> Vec<String> {
+    let mut samples: Vec<String> = Vec::new();
+    for raw in connections.iter().filter_map(|c| c.raw_state.as_ref()) {
+        if samples.len() >= UNKNOWN_STATE_SAMPLE_LIMIT {
+            break;
+        }
+        if !samples.contains(raw) {
+            samples.push(raw.clone());
+        }
+    }
+    samples
+}
+
+/// 连接数从多到少，相同时 PID 从小到大
+fn by_connections_desc(a: &ProcessTcpStats, b: &ProcessTcpStats) -> Ordering {
+    b.total_connections.cmp(&a.total_connections).then(a.pid.cmp(&b.pid))
+}
+
+/// 堆中按 `by_connections_desc` 排在前面的进程更大
+struct Ranked(ProcessTcpStats);
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        by_connections_desc(&other.0, &self.0)
+    }
+}
+
+/// 按进程汇总连接，按连接数从多到少排列（相同时 PID 从小到大）。
+/// 指定 `limit` 时用大小为 `limit` 的小顶堆选出前 N 个，不对全部进程排序。
+/// 返回的统计未计算健康评分和可执行文件路径，由调用方对保留的进程补充
+pub fn aggregate_processes(connections: &[TcpConnection], limit: Option<usize>) -> Vec<ProcessTcpStats> {
+    rank_processes(count_by_pid(connections), limit)
+}
+
+/// 按 PID 计数，进程名取该 PID 的第一条连接
+fn count_by_pid(connections: &[TcpConnection]) -> HashMap<u32, ProcessTcpStats> {
+    let mut by_pid: HashMap<u32, ProcessTcpStats> = HashMap::new();
+    for conn in connections {
+        let entry = by_pid.entry(conn.pid).or_insert_with(|| ProcessTcpStats {
+            pid: conn.pid,
+            process_name: conn.process_name.clone(),
+            ..Default::default()
+        });
+        entry.total_connections += 1;
+        entry.record_state(conn.state);
+    }
+    by_pid
+}
+
+/// 按 `by_connections_desc` 排列，指定 `limit` 时只保留前 N 个
+fn rank_processes(by_pid: HashMap<u32, ProcessTcpStats>, limit: Option<usize>) -> Vec<ProcessTcpStats> {
+    let Some(limit) = limit else {
+        let mut all: Vec<_> = by_pid.into_values().collect();
+        all.sort_by(by_connections_desc);
+        return all;
+    };
+
+    let mut heap = BinaryHeap::with_capacity(limit + 1);
+    for stats in by_pid.into_values() {
+        heap.push(Reverse(Ranked(stats)));
+        if heap.len() > limit {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec().into_iter().map(|Reverse(Ranked(stats))| stats).collect()
+}
+
+/// 按选项汇总进程并计算健康评分，可执行文件路径由平台实现补充
+pub fn summarize_processes(connections: &[TcpConnection], options: &StatsOptions) -> Vec<ProcessTcpStats> {
+    #[cfg(feature = "parallel")]
+    if options.parallel {
+        return parallel::summarize_processes(connections, options.max_tracked_processes);
+    }
+    let mut by_process = aggregate_processes(connections, options.max_tracked_processes);
+    for p in &mut by_process {
+        p.health_score = calculate_health_score(p);
+    }
+    by_process
+}
+
+#[cfg(feature = "parallel")]
+mod parallel {
+    use super::{by_connections_desc, calculate_health_score, count_by_pid, rank_processes};
+    use crate::{ProcessTcpStats, TcpConnection};
+    use rayon::prelude::*;
+    use std::collections::HashMap;
+
+    /// 每个分块的连接数，太小时合并 HashMap 的开销会超过并行收益
+    const CHUNK: usize = 16 * 1024;
+
+    pub(super) fn summarize_processes(connections: &[TcpConnection], limit: Option<usize>) -> Vec<ProcessTcpStats> {
+        // rayon 的 reduce 保持分块顺序，左侧先出现的进程名优先，与顺序汇总一致
+        let by_pid = connections
+            .par_chunks(CHUNK)
+            .map(count_by_pid)
+            .reduce(HashMap::new, merge);
+
+        let mut by_process = match limit {
+            Some(_) => rank_processes(by_pid, limit),
+            None => {
+                let mut all: Vec<_> = by_pid.into_values().collect();
+                all.par_sort_unstable_by(by_connections_desc);
+                all
+            }
+        };
+        by_process.par_iter_mut().for_each(|p| p.health_score = calculate_health_score(p));
+        by_process
+    }
+
+    fn merge(
+        mut left: HashMap<u32, ProcessTcpStats>,
+        right: HashMap<u32, ProcessTcpStats>,
+    ) -> HashMap<u32, ProcessTcpStats> {
+        for (pid, stats) in right {
+            match left.get_mut(&pid) {
+                Some(entry) => {
+                    entry.total_connections += stats.total_connections;
+                    entry.established += stats.established;
+                    entry.time_wait += stats.time_wait;
+                    entry.close_wait += stats.close_wait;
+                    entry.listen += stats.listen;
+                    entry.other += stats.other;
+                }
+                None => {
+                    left.insert(pid, stats);
+                }
+            }
+        }
+        left
+    }
+}
+
 /// 按进程分组连接
 pub fn group_by_process(connections: &[TcpConnection]) -> HashMap<u32, Vec<&TcpConnection>> {
     let mut groups: HashMap<u32, Vec<&TcpConnection>> = HashMap::new();
@@ -42,6 +513,78 @@ pub fn group_by_process(connections: &[TcpConnection]) -> HashMap<u32, Vec<&TcpC
     groups
 }
 
+/// 动态端口使用情况
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortUsage {
+    pub total: usize,
+    pub available: usize,
+    /// 使用率，范围 [0, 100]
+    pub percent: f32,
+}
+
+/// 根据已用端口数和动态端口范围 `[start, end]` 计算可用端口与使用率
+///
+/// start == end 是只有一个端口的有效范围；start > end 时视为没有可用端口，使用率为 0，
+/// 并对每个无效范围只记录一次警告（每轮刷新都会调用）
+pub fn port_usage(used: usize, start: u32, end: u32) -> PortUsage {
+    if start > end && first_warning_for(start, end) {
+        tracing::warn!("动态端口范围无效: {}-{}", start, end);
+    }
+    let total = if start <= end { (end - start) as usize + 1 } else { 0 };
+    let percent = if total == 0 {
+        0.0
+    } else {
+        (used as f32 / total as f32 * 100.0).clamp(0.0, 100.0)
+    };
+
+    PortUsage {
+        total,
+        available: total.saturating_sub(used),
+        percent,
+    }
+}
+
+/// 该无效范围是否第一次出现
+fn first_warning_for(start: u32, end: u32) -> bool {
+    static WARNED: std::sync::Mutex<Vec<(u32, u32)>> = std::sync::Mutex::new(Vec::new());
+    let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+    if warned.contains(&(start, end)) {
+        false
+    } else {
+        warned.push((start, end));
+        true
+    }
+}
+
+/// 统计动态端口范围 `[start, end]` 内被出站连接占用的不同本地端口数
+///
+/// 监听套接字以及连到监听端口的入站连接不消耗动态端口，不计入
+pub fn ephemeral_ports_in_use(connections: &[TcpConnection], start: u32, end: u32) -> usize {
+    let listening: HashSet<u16> = connections.iter()
+        .filter(|c| c.state == TcpState::Listen)
+        .map(|c| c.local_port)
+        .collect();
+
+    connections.iter()
+        .filter(|c| c.state != TcpState::Listen && !listening.contains(&c.local_port))
+        .filter(|c| c.remote_port != 0 && !parse_ip(&c.remote_addr).is_some_and(|ip| ip.is_unspecified()))
+        .filter(|c| (start..=end).contains(&(c.local_port as u32)))
+        .map(|c| c.local_port)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// 按远端地址统计连接数，忽略未连接（0.0.0.0 / ::）的套接字
+pub fn group_by_remote(connections: &[TcpConnection]) -> HashMap<IpAddr, usize> {
+    let mut groups = HashMap::new();
+    for ip in connections.iter().filter_map(|c| parse_ip(&c.remote_addr)) {
+        if !ip.is_unspecified() {
+            *groups.entry(ip).or_insert(0) += 1;
+        }
+    }
+    groups
+}
+
 /// 计算进程健康评分
 /// 评分规则：
 /// - 基础分 100
@@ -50,33 +593,184 @@ pub fn group_by_process(connections: &[TcpConnection]) -> HashMap<u32, Vec<&TcpC
 /// - CLOSE_WAIT > 50: -25分（更严重，可能是程序bug）
 /// - 总连接数 > 1000: -10分
 pub fn calculate_health_score(stats: &ProcessTcpStats) -> u8 {
+    calculate_health_report(stats).score
+}
+
+/// 健康评分扣分项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum HealthFactor {
+    /// TIME_WAIT > 100
+    TimeWaitHigh,
+    /// TIME_WAIT > 500
+    TimeWaitSevere,
+    /// CLOSE_WAIT > 50（这通常意味着程序bug）
+    CloseWaitHigh,
+    /// CLOSE_WAIT > 200
+    CloseWaitSevere,
+    /// 总连接数 > 1000
+    ConnectionsHigh,
+    /// 总连接数 > 5000
+    ConnectionsSevere,
+}
+
+impl HealthFactor {
+    /// 触发条件，与语言无关，可直接用于界面显示
+    pub fn condition(&self) -> &'static str {
+        match self {
+            HealthFactor::TimeWaitHigh => "TIME_WAIT > 100",
+            HealthFactor::TimeWaitSevere => "TIME_WAIT > 500",
+            HealthFactor::CloseWaitHigh => "CLOSE_WAIT > 50",
+            HealthFactor::CloseWaitSevere => "CLOSE_WAIT > 200",
+            HealthFactor::ConnectionsHigh => "TOTAL > 1000",
+            HealthFactor::ConnectionsSevere => "TOTAL > 5000",
+        }
+    }
+}
+
+/// 健康评分及各扣分项
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct HealthReport {
+    pub score: u8,
+    /// 实际生效的扣分（负数），总和等于 `score - 100`
+    pub factors: Vec<(HealthFactor, i32)>,
+}
+
+/// 计算健康评分并列出扣分原因
+pub fn calculate_health_report(stats: &ProcessTcpStats) -> HealthReport {
+    let rules = [
+        (HealthFactor::TimeWaitHigh, stats.time_wait > 100, 20),
+        (HealthFactor::TimeWaitSevere, stats.time_wait > 500, 30),
+        (HealthFactor::CloseWaitHigh, stats.close_wait > 50, 25),
+        (HealthFactor::CloseWaitSevere, stats.close_wait > 200, 25),
+        (HealthFactor::ConnectionsHigh, stats.total_connections > CONNECTIONS_WARNING_LIMIT, 10),
+        (HealthFactor::ConnectionsSevere, stats.total_connections > CONNECTIONS_CRITICAL_LIMIT, 15),
+    ];
+
     let mut score: i32 = 100;
-    
-    // TIME_WAIT 过多扣分
-    if stats.time_wait > 100 {
-        score -= 20;
+    let mut factors = Vec::new();
+    for (factor, triggered, penalty) in rules {
+        // 分数最低为 0，超出的扣分不计入
+        let applied = penalty.min(score);
+        if triggered && applied > 0 {
+            score -= applied;
+            factors.push((factor, -applied));
+        }
     }
-    if stats.time_wait > 500 {
-        score -= 30;
+
+    HealthReport {
+        score: score as u8,
+        factors,
     }
-    
-    // CLOSE_WAIT 过多扣分（这通常意味着程序bug）
-    if stats.close_wait > 50 {
-        score -= 25;
+}
+
+/// 系统健康等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum HealthGrade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl HealthGrade {
+    /// A >= 90，B >= 80，C >= 70，D >= 60，其余为 F
+    pub fn from_score(score: u8) -> Self {
+        match score {
+            90.. => HealthGrade::A,
+            80..=89 => HealthGrade::B,
+            70..=79 => HealthGrade::C,
+            60..=69 => HealthGrade::D,
+            _ => HealthGrade::F,
+        }
     }
-    if stats.close_wait > 200 {
-        score -= 25;
+}
+
+impl std::fmt::Display for HealthGrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
     }
-    
-    // 总连接数过多
-    if stats.total_connections > 1000 {
-        score -= 10;
+}
+
+/// 系统健康扣分项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum SystemHealthFactor {
+    /// 端口使用率 > 50%
+    PortUsageHigh,
+    /// 端口使用率 > 80%
+    PortUsageCritical,
+    /// 全系统 CLOSE_WAIT > 100
+    CloseWaitHigh,
+    /// 全系统 CLOSE_WAIT > 500
+    CloseWaitSevere,
+    /// 评分最低的进程
+    UnhealthyProcess { pid: u32, score: u8 },
+}
+
+impl SystemHealthFactor {
+    /// 与语言无关的简短描述
+    pub fn description(&self) -> String {
+        match self {
+            SystemHealthFactor::PortUsageHigh => "PORT USAGE > 50%".into(),
+            SystemHealthFactor::PortUsageCritical => "PORT USAGE > 80%".into(),
+            SystemHealthFactor::CloseWaitHigh => "CLOSE_WAIT > 100".into(),
+            SystemHealthFactor::CloseWaitSevere => "CLOSE_WAIT > 500".into(),
+            SystemHealthFactor::UnhealthyProcess { pid, score } => format!("PID {}: {}%", pid, score),
+        }
     }
-    if stats.total_connections > 5000 {
-        score -= 15;
+}
+
+/// 系统整体健康状况
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SystemHealth {
+    pub score: u8,
+    pub grade: HealthGrade,
+    /// 扣分最多的至多三项（负数，按扣分从多到少）
+    pub issues: Vec<(SystemHealthFactor, i32)>,
+}
+
+/// 计算系统整体健康状况
+///
+/// 基础分 100，依次扣分后截断到 0：
+/// - 端口使用率 > 50%: -15，> 80%: 再 -25
+/// - 全系统 CLOSE_WAIT > 100: -15，> 500: 再 -15
+/// - 评分最低的进程: -(100 - 该进程评分) / 2
+pub fn system_health(stats: &SystemTcpStats) -> SystemHealth {
+    let close_wait = stats.by_state.get(&TcpState::CloseWait).copied().unwrap_or(0);
+    let mut penalties = Vec::new();
+
+    if stats.port_usage_percent > 50.0 {
+        penalties.push((SystemHealthFactor::PortUsageHigh, -15));
+    }
+    if stats.port_usage_percent > 80.0 {
+        penalties.push((SystemHealthFactor::PortUsageCritical, -25));
+    }
+    if close_wait > 100 {
+        penalties.push((SystemHealthFactor::CloseWaitHigh, -15));
+    }
+    if close_wait > 500 {
+        penalties.push((SystemHealthFactor::CloseWaitSevere, -15));
+    }
+    if let Some(worst) = stats.by_process.iter().min_by_key(|p| p.health_score) {
+        let penalty = (100 - worst.health_score.min(100) as i32) / 2;
+        if penalty > 0 {
+            penalties.push((
+                SystemHealthFactor::UnhealthyProcess { pid: worst.pid, score: worst.health_score },
+                -penalty,
+            ));
+        }
+    }
+
+    let total: i32 = penalties.iter().map(|(_, p)| p).sum();
+    let score = (100 + total).clamp(0, 100) as u8;
+    penalties.sort_by_key(|(_, p)| *p);
+    penalties.truncate(3);
+
+    SystemHealth {
+        score,
+        grade: HealthGrade::from_score(score),
+        issues: penalties,
     }
-    
-    score.max(0).min(100) as u8
 }
 
 /// 连接异常检测结果
@@ -90,13 +784,20 @@ pub struct ConnectionAnomaly {
     pub suggestion: String,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
 pub enum AnomalyType {
     TooManyTimeWait,
     TooManyCloseWait,
     TooManyConnections,
     PortExhaustion,
     ConnectionLeak,
+    HighChurn,
+    ConnectionFlapping,
+    /// 监控本身连续失败
+    MonitorUnavailable,
+    /// 多个进程监听同一地址和端口
+    DuplicateListener,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
@@ -106,6 +807,18 @@ pub enum Severity {
     Critical,
 }
 
+/// 连接创建速率告警阈值（每秒）
+pub const CHURN_WARNING_RATE: f32 = 100.0;
+/// 连接创建速率严重阈值（每秒）
+pub const CHURN_CRITICAL_RATE: f32 = 300.0;
+
+/// 进程连接数告警阈值，与健康评分的分级一致
+pub const CONNECTIONS_WARNING_LIMIT: usize = 1000;
+/// 进程连接数严重阈值
+pub const CONNECTIONS_CRITICAL_LIMIT: usize = 5000;
+/// CLOSE_WAIT 超过此值且仍在增长时判定为连接泄漏
+pub const LEAK_CLOSE_WAIT_THRESHOLD: usize = 50;
+
 /// 检测连接异常
 pub fn detect_anomalies(stats: &ProcessTcpStats) -> Vec<ConnectionAnomaly> {
     let mut anomalies = Vec::new();
@@ -131,6 +844,29 @@ pub fn detect_anomalies(stats: &ProcessTcpStats) -> Vec<ConnectionAnomaly> {
         });
     }
     
+    // 连接创建速率：TIME_WAIT 总量不高时也可能很快耗尽端口
+    if let Some(rate) = stats.churn_rate {
+        if rate > CHURN_CRITICAL_RATE {
+            anomalies.push(ConnectionAnomaly {
+                pid: stats.pid,
+                process_name: stats.process_name.clone(),
+                anomaly_type: AnomalyType::HighChurn,
+                severity: Severity::Critical,
+                message: format!("连接创建速率过高: {:.0}/秒", rate),
+                suggestion: "短连接过多会快速耗尽动态端口，建议使用连接池或长连接".into(),
+            });
+        } else if rate > CHURN_WARNING_RATE {
+            anomalies.push(ConnectionAnomaly {
+                pid: stats.pid,
+                process_name: stats.process_name.clone(),
+                anomaly_type: AnomalyType::HighChurn,
+                severity: Severity::Warning,
+                message: format!("连接创建速率较高: {:.0}/秒", rate),
+                suggestion: "关注连接复用，考虑使用连接池".into(),
+            });
+        }
+    }
+
     // CLOSE_WAIT 检测（更严重）
     if stats.close_wait > 50 {
         anomalies.push(ConnectionAnomaly {
@@ -142,7 +878,589 @@ pub fn detect_anomalies(stats: &ProcessTcpStats) -> Vec<ConnectionAnomaly> {
             suggestion: "检查程序是否正确关闭socket，可能需要重启应用".into(),
         });
     }
-    
+
+    // CLOSE_WAIT 持续增长：对端已关闭但程序一直未 close
+    if let Some(growth) = stats.close_wait_growth {
+        if stats.close_wait > LEAK_CLOSE_WAIT_THRESHOLD && growth > 0.0 {
+            anomalies.push(ConnectionAnomaly {
+                pid: stats.pid,
+                process_name: stats.process_name.clone(),
+                anomaly_type: AnomalyType::ConnectionLeak,
+                severity: Severity::Critical,
+                message: format!("CLOSE_WAIT持续增长: {} (+{:.1}/秒)", stats.close_wait, growth),
+                suggestion: "程序未关闭对端已断开的连接，检查读到EOF后是否调用close，以及异常分支是否释放socket".into(),
+            });
+        }
+    }
+
+    // 连接总数
+    if stats.total_connections > CONNECTIONS_CRITICAL_LIMIT {
+        anomalies.push(ConnectionAnomaly {
+            pid: stats.pid,
+            process_name: stats.process_name.clone(),
+            anomaly_type: AnomalyType::TooManyConnections,
+            severity: Severity::Critical,
+            message: format!("连接总数过多: {}", stats.total_connections),
+            suggestion: "检查是否存在连接未复用或未释放，考虑限制并发或使用连接池".into(),
+        });
+    } else if stats.total_connections > CONNECTIONS_WARNING_LIMIT {
+        anomalies.push(ConnectionAnomaly {
+            pid: stats.pid,
+            process_name: stats.process_name.clone(),
+            anomaly_type: AnomalyType::TooManyConnections,
+            severity: Severity::Warning,
+            message: format!("连接总数较高: {}", stats.total_connections),
+            suggestion: "关注连接数趋势，必要时为该应用设置 max_connections 策略".into(),
+        });
+    }
+
+    anomalies
+}
+
+/// 检测连接异常，只返回不低于 `min_severity` 的部分
+pub fn detect_anomalies_at(stats: &ProcessTcpStats, min_severity: Severity) -> Vec<ConnectionAnomaly> {
+    let mut anomalies = detect_anomalies(stats);
+    anomalies.retain(|a| a.severity >= min_severity);
     anomalies
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::mock::MockTcpMonitor;
+
+    fn conn(local: &str, local_port: u16, remote: &str, state: TcpState, pid: u32) -> TcpConnection {
+        TcpConnection {
+            local_addr: local.into(),
+            local_port,
+            remote_addr: remote.into(),
+            remote_port: 443,
+            state,
+            pid,
+            process_name: format!("proc{}", pid),
+            raw_state: None,
+            remote_host: None,
+        }
+    }
+
+    fn monitor() -> MockTcpMonitor {
+        MockTcpMonitor::new(vec![
+            conn("10.0.0.2", 50001, "93.184.216.34", TcpState::Established, 1),
+            conn("10.0.0.2", 50002, "93.184.216.34", TcpState::CloseWait, 1),
+            conn("10.0.0.2", 8080, "10.0.0.9", TcpState::CloseWait, 2),
+            conn("127.0.0.1", 50003, "127.0.0.1", TcpState::TimeWait, 1),
+            conn("::ffff:10.0.0.2", 50004, "::ffff:93.184.216.34", TcpState::TimeWait, 3),
+        ])
+    }
+
+    fn ports(conns: &[TcpConnection]) -> Vec<u16> {
+        conns.iter().map(|c| c.local_port).collect()
+    }
+
+    #[test]
+    fn bounded_stats_keep_top_processes_and_full_totals() {
+        let mut connections = Vec::new();
+        for pid in 1..=50u32 {
+            for i in 0..pid % 7 + 1 {
+                let state = if i % 2 == 0 { TcpState::Established } else { TcpState::TimeWait };
+                connections.push(conn("10.0.0.1", 40000 + i as u16, "10.0.0.2", state, pid));
+            }
+        }
+        let monitor = MockTcpMonitor::new(connections);
+
+        let full = monitor.get_system_stats().unwrap();
+        let bounded = monitor.get_system_stats_with(&StatsOptions { max_tracked_processes: Some(5), ..Default::default() }).unwrap();
+
+        assert_eq!(bounded.by_process.len(), 5);
+        assert_eq!(
+            bounded.by_process.iter().map(|p| p.pid).collect::<Vec<_>>(),
+            full.by_process.iter().take(5).map(|p| p.pid).collect::<Vec<_>>(),
+        );
+        assert_eq!(bounded.total_connections, full.total_connections);
+        assert_eq!(bounded.by_state, full.by_state);
+        assert_eq!(bounded.available_ports, full.available_ports);
+        assert_eq!(bounded.ephemeral_ports_in_use, full.ephemeral_ports_in_use);
+        // 6 个连接的进程并列时按 PID 从小到大
+        assert_eq!(bounded.by_process[0].pid, 6);
+        assert!(bounded.by_process[0].health_score > 0);
+        assert!(monitor.get_system_stats_with(&StatsOptions { max_tracked_processes: Some(0), ..Default::default() }).unwrap().by_process.is_empty());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_summary_matches_sequential() {
+        // 跨越多个分块，同一 PID 在不同分块中的进程名不同
+        let states = [TcpState::Established, TcpState::TimeWait, TcpState::CloseWait, TcpState::Listen, TcpState::LastAck];
+        let connections: Vec<TcpConnection> = (0..100_000u32)
+            .map(|i| TcpConnection {
+                process_name: format!("p{}-{}", i % 997, i / 40_000),
+                ..conn("10.0.0.1", (i % 60000) as u16, "10.0.0.2", states[i as usize % states.len()], i % 997)
+            })
+            .collect();
+
+        for limit in [None, Some(10)] {
+            let sequential = StatsOptions { max_tracked_processes: limit, parallel: false, ..Default::default() };
+            let parallel = StatsOptions { parallel: true, ..sequential };
+            assert_eq!(
+                summarize_processes(&connections, &parallel),
+                summarize_processes(&connections, &sequential),
+            );
+        }
+    }
+
+    #[test]
+    fn each_stats_call_scans_connections_once() {
+        // 使用 MockTcpMonitor 自身的实现和 TcpMonitor 的默认方法，不经过测试替身
+        let monitor = monitor();
+
+        let stats = monitor.get_system_stats().unwrap();
+        assert_eq!(monitor.scans(), 1);
+        monitor.get_top_processes(2).unwrap();
+        assert_eq!(monitor.scans(), 2);
+        monitor.get_problematic_processes(10).unwrap();
+        assert_eq!(monitor.scans(), 3);
+        monitor.get_process_stats(100).unwrap();
+        assert_eq!(monitor.scans(), 4);
+
+        let (connections, combined) = monitor.get_connections_and_stats().unwrap();
+        assert_eq!(monitor.scans(), 5);
+        assert_eq!(combined.total_connections, connections.len());
+        assert_eq!(combined.by_process, stats.by_process);
+    }
+
+    #[test]
+    fn unknown_state_samples_are_distinct_and_capped() {
+        let unknown = |raw: String| TcpConnection {
+            raw_state: Some(raw),
+            remote_host: None,
+            ..conn("10.0.0.1", 50000, "10.0.0.2", TcpState::Unknown, 1)
+        };
+        let mut connections = vec![conn("10.0.0.1", 50000, "10.0.0.2", TcpState::Established, 1)];
+        connections.extend(["BOUND", "0C", "BOUND"].map(|raw| unknown(raw.to_string())));
+
+        let stats = MockTcpMonitor::new(connections).get_system_stats().unwrap();
+        assert_eq!(stats.unknown_state_samples, vec!["BOUND", "0C"]);
+
+        let many: Vec<TcpConnection> = (0..100).map(|i| unknown(format!("S{}", i))).collect();
+        assert_eq!(unknown_state_samples(&many).len(), UNKNOWN_STATE_SAMPLE_LIMIT);
+
+        // 旧快照中的连接没有 raw_state
+        let old: TcpConnection = serde_json::from_str(
+            r#"{"local_addr":"10.0.0.1","local_port":1,"remote_addr":"10.0.0.2","remote_port":2,"state":"Established","pid":1,"process_name":"a"}"#,
+        ).unwrap();
+        assert_eq!(old.raw_state, None);
+    }
+
+    #[test]
+    fn connections_are_counted_by_address_family() {
+        let connections = vec![
+            conn("10.0.0.1", 50000, "10.0.0.2", TcpState::Established, 1),
+            conn("[2001:db8::1]", 50001, "[2001:db8::2]", TcpState::Established, 1),
+            conn("fe80::1%en0", 50002, "fe80::2%en0", TcpState::TimeWait, 2),
+            // 双栈套接字上的 IPv4 连接
+            conn("::ffff:10.0.0.1", 50003, "::ffff:10.0.0.3", TcpState::Established, 2),
+            // 通配监听地址看远端，远端也是通配时无法判断
+            conn("*", 8080, "10.0.0.4", TcpState::Listen, 3),
+            conn("*", 8081, "*", TcpState::Listen, 3),
+        ];
+
+        let stats = MockTcpMonitor::new(connections).get_system_stats().unwrap();
+        assert_eq!(stats.by_family.get(&AddressFamily::Ipv4), Some(&3));
+        assert_eq!(stats.by_family.get(&AddressFamily::Ipv6), Some(&2));
+        assert_eq!(stats.by_family.get(&AddressFamily::Unknown), Some(&1));
+        assert_eq!(stats.by_family.values().sum::<usize>(), stats.total_connections);
+    }
+
+    #[test]
+    fn empty_filter_returns_everything() {
+        let result = monitor().query(&ConnectionFilter::new()).unwrap();
+        assert_eq!(result.len(), 5);
+    }
+
+    #[test]
+    fn combined_filters_intersect() {
+        let filter = ConnectionFilter::new()
+            .by_pid(1)
+            .by_state(TcpState::CloseWait)
+            .by_state(TcpState::TimeWait)
+            .only_loopback(false);
+        assert_eq!(ports(&monitor().query(&filter).unwrap()), vec![50002]);
+
+        let filter = ConnectionFilter::new()
+            .by_local_port_range(50000, 50010)
+            .by_remote_addr("93.184.216.34".parse().unwrap());
+        assert_eq!(ports(&monitor().query(&filter).unwrap()), vec![50001, 50002, 50004]);
+    }
+
+    #[test]
+    fn top_remote_peers_skewed() {
+        let mut conns: Vec<TcpConnection> = (0..40)
+            .map(|i| conn("10.0.0.2", 40000 + i, "1.1.1.1", TcpState::Established, 7))
+            .collect();
+        conns.extend((0..5).map(|i| conn("10.0.0.2", 41000 + i, "8.8.8.8", TcpState::TimeWait, 7)));
+        conns.push(conn("10.0.0.2", 42000, "9.9.9.9", TcpState::Established, 7));
+        conns.extend((0..100).map(|i| conn("127.0.0.1", 43000 + i, "127.0.0.1", TcpState::TimeWait, 7)));
+        conns.push(conn("0.0.0.0", 80, "0.0.0.0", TcpState::Listen, 7));
+        conns.push(conn("10.0.0.2", 44000, "1.1.1.1", TcpState::Established, 8));
+        let monitor = MockTcpMonitor::new(conns);
+
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(
+            monitor.top_remote_peers(7, 2, false).unwrap(),
+            vec![(ip("1.1.1.1"), 40), (ip("8.8.8.8"), 5)]
+        );
+        assert_eq!(monitor.top_remote_peers(7, 1, true).unwrap(), vec![(ip("127.0.0.1"), 100)]);
+        assert_eq!(group_by_remote(&monitor.get_process_connections(7).unwrap()).len(), 4);
+    }
+
+    #[test]
+    fn udp_ports_reduce_combined_availability() {
+        let udp = |port: u16| UdpSocketInfo {
+            local_addr: "0.0.0.0".into(),
+            local_port: port,
+            pid: 9,
+            process_name: "dns".into(),
+        };
+        let monitor = MockTcpMonitor::new(vec![conn("10.0.0.2", 60001, "1.1.1.1", TcpState::Established, 1)])
+            .with_port_range(60000, 60099)
+            // 53 不在动态端口范围内，60010 两个套接字（IPv4/IPv6）只算一个端口
+            .with_udp_sockets(vec![udp(53), udp(60010), udp(60010), udp(60020)]);
+
+        let tcp_only = monitor.get_system_stats().unwrap();
+        assert_eq!(tcp_only.udp_ports_used, None);
+        assert_eq!(tcp_only.available_ports_combined(), tcp_only.available_ports);
+
+        let stats = monitor.get_system_stats_with_udp(&StatsOptions::default()).unwrap();
+        assert_eq!(stats.udp_ports_used, Some(2));
+        assert_eq!(stats.available_ports_combined(), stats.available_ports - 2);
+        assert!((stats.port_usage_percent - 3.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn high_churn_flagged_with_low_counts() {
+        let stats = ProcessTcpStats {
+            pid: 1,
+            process_name: "crawler".into(),
+            time_wait: 20,
+            churn_rate: Some(450.0),
+            ..Default::default()
+        };
+        let anomalies = detect_anomalies(&stats);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].anomaly_type, AnomalyType::HighChurn);
+        assert_eq!(anomalies[0].severity, Severity::Critical);
+
+        let calm = ProcessTcpStats { churn_rate: Some(5.0), ..stats };
+        assert!(detect_anomalies(&calm).is_empty());
+    }
+
+    #[test]
+    fn inbound_connections_do_not_use_ephemeral_ports() {
+        // 服务器：443 监听，1 万个入站连接（本地端口 443，对端使用各自的动态端口）
+        let mut conns = vec![conn("0.0.0.0", 443, "0.0.0.0", TcpState::Listen, 1)];
+        conns[0].remote_port = 0;
+        conns.extend((0..10_000u32).map(|i| {
+            let mut c = conn("10.0.0.2", 443, &format!("172.16.{}.{}", i / 250, i % 250), TcpState::Established, 1);
+            c.remote_port = 49152 + (i % 16000) as u16;
+            c
+        }));
+        // 另有 3 个出站连接占用动态端口
+        conns.extend((0..3).map(|i| conn("10.0.0.2", 50000 + i, "1.1.1.1", TcpState::Established, 2)));
+
+        let stats = MockTcpMonitor::new(conns).with_port_range(49152, 65535).get_system_stats().unwrap();
+        assert_eq!(stats.total_connections, 10_004);
+        assert_eq!(stats.available_ports, 16384 - 3);
+        assert!(stats.port_usage_percent < 0.1);
+    }
+
+    #[test]
+    fn repeated_local_port_counted_once() {
+        // 快速连接/关闭时同一本地端口会以不同状态出现多行
+        let conns = vec![
+            conn("10.0.0.2", 50000, "1.1.1.1", TcpState::TimeWait, 0),
+            conn("10.0.0.2", 50000, "8.8.8.8", TcpState::SynSent, 1),
+            conn("10.0.0.2", 50001, "1.1.1.1", TcpState::Established, 1),
+        ];
+        let stats = MockTcpMonitor::new(conns).with_port_range(50000, 50009).get_system_stats().unwrap();
+        assert_eq!(stats.ephemeral_ports_in_use, 2);
+        assert_eq!(stats.available_ports, 8);
+        assert!((stats.port_usage_percent - 20.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn port_usage_degenerate_ranges() {
+        // 反向范围：没有可用端口，不出现 NaN
+        let inverted = port_usage(10, 60000, 50000);
+        assert_eq!(inverted.total, 0);
+        assert_eq!(inverted.available, 0);
+        assert_eq!(inverted.percent, 0.0);
+
+        // 单个端口的范围被多个连接占用时不超过 100%
+        let single = port_usage(5, 50000, 50000);
+        assert_eq!(single.total, 1);
+        assert_eq!(single.percent, 100.0);
+
+        let full_range = port_usage(0, 0, u32::MAX);
+        assert!(full_range.percent.is_finite());
+
+        let normal = port_usage(25, 1, 100);
+        assert_eq!(normal.available, 75);
+        assert!((normal.percent - 25.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn invalid_port_range_is_reported_once() {
+        assert!(first_warning_for(7, 3));
+        assert!(!first_warning_for(7, 3));
+        assert!(first_warning_for(9, 3));
+    }
+
+    #[test]
+    fn mock_with_inverted_range_reports_zero_usage() {
+        let stats = MockTcpMonitor::new(vec![conn("10.0.0.2", 50000, "1.1.1.1", TcpState::Established, 1)])
+            .with_port_range(60000, 50000)
+            .get_system_stats()
+            .unwrap();
+        assert_eq!(stats.available_ports, 0);
+        assert_eq!(stats.port_usage_percent, 0.0);
+    }
+
+    #[test]
+    fn port_range_override_replaces_system_range() {
+        // 系统报告 49152-65535，实际可用的只有 60000-60099
+        let monitor = MockTcpMonitor::new(
+            (0..10).map(|i| conn("10.0.0.2", 60000 + i, "1.1.1.1", TcpState::Established, 1)).collect(),
+        );
+
+        let detected = monitor.get_system_stats().unwrap();
+        assert_eq!(detected.available_ports, 16384 - 10);
+
+        let options = StatsOptions { port_range: Some((60000, 60099)), ..Default::default() };
+        let stats = monitor.get_system_stats_with(&options).unwrap();
+        assert_eq!(stats.dynamic_port_range, Some((60000, 60099)));
+        assert_eq!(stats.available_ports, 90);
+        assert!((stats.port_usage_percent - 10.0).abs() < 0.01);
+
+        // 连接不在指定范围内时不计入
+        let options = StatsOptions { port_range: Some((61000, 61099)), ..Default::default() };
+        let stats = monitor.get_system_stats_with(&options).unwrap();
+        assert_eq!(stats.available_ports, 100);
+        assert_eq!(stats.port_usage_percent, 0.0);
+    }
+
+    #[test]
+    fn listening_port_inside_range_is_not_ephemeral() {
+        let mut listen = conn("0.0.0.0", 50000, "0.0.0.0", TcpState::Listen, 1);
+        listen.remote_port = 0;
+        let conns = vec![
+            listen,
+            conn("10.0.0.2", 50000, "10.0.0.9", TcpState::Established, 1),
+            conn("10.0.0.2", 50001, "1.1.1.1", TcpState::TimeWait, 0),
+        ];
+        assert_eq!(ephemeral_ports_in_use(&conns, 49152, 65535), 1);
+    }
+
+    #[test]
+    fn loopback_filter() {
+        let filter = ConnectionFilter::new().only_loopback(true);
+        assert_eq!(ports(&monitor().query(&filter).unwrap()), vec![50003]);
+    }
+
+    #[test]
+    fn health_factors_sum_to_score_deficit() {
+        let cases = [
+            (0, 0, 0),
+            (150, 0, 200),
+            (600, 60, 1200),
+            // 全部触发时扣分超过 100，超出部分不计入
+            (600, 300, 6000),
+        ];
+        for (time_wait, close_wait, total_connections) in cases {
+            let stats = ProcessTcpStats {
+                time_wait,
+                close_wait,
+                total_connections,
+                ..Default::default()
+            };
+            let report = calculate_health_report(&stats);
+            let sum: i32 = report.factors.iter().map(|(_, p)| p).sum();
+
+            assert_eq!(sum, report.score as i32 - 100);
+            assert_eq!(report.score, calculate_health_score(&stats));
+        }
+
+        let stats = ProcessTcpStats { time_wait: 150, ..Default::default() };
+        assert_eq!(calculate_health_report(&stats).factors, vec![(HealthFactor::TimeWaitHigh, -20)]);
+    }
+
+    #[test]
+    fn min_severity_drops_lower_anomalies() {
+        let stats = ProcessTcpStats {
+            time_wait: 150,
+            close_wait: 80,
+            ..Default::default()
+        };
+
+        assert_eq!(detect_anomalies(&stats).len(), 2);
+        assert_eq!(detect_anomalies_at(&stats, Severity::Info).len(), 2);
+        let critical = detect_anomalies_at(&stats, Severity::Critical);
+        assert_eq!(critical.len(), 1);
+        assert_eq!(critical[0].anomaly_type, AnomalyType::TooManyCloseWait);
+    }
+
+    #[test]
+    fn too_many_connections_by_tier() {
+        let kinds = |total| -> Vec<(AnomalyType, Severity)> {
+            let stats = ProcessTcpStats { total_connections: total, ..Default::default() };
+            detect_anomalies(&stats).iter().map(|a| (a.anomaly_type, a.severity)).collect()
+        };
+
+        assert!(kinds(CONNECTIONS_WARNING_LIMIT).is_empty());
+        assert_eq!(kinds(CONNECTIONS_WARNING_LIMIT + 1), vec![(AnomalyType::TooManyConnections, Severity::Warning)]);
+        assert_eq!(kinds(CONNECTIONS_CRITICAL_LIMIT + 1), vec![(AnomalyType::TooManyConnections, Severity::Critical)]);
+    }
+
+    #[test]
+    fn connection_leak_requires_high_and_growing_close_wait() {
+        let leak = |close_wait, growth| {
+            let stats = ProcessTcpStats { close_wait, close_wait_growth: growth, ..Default::default() };
+            detect_anomalies(&stats).iter().any(|a| a.anomaly_type == AnomalyType::ConnectionLeak)
+        };
+
+        assert!(leak(80, Some(0.5)));
+        // 数量高但不再增长，或没有历史数据
+        assert!(!leak(80, Some(0.0)));
+        assert!(!leak(80, None));
+        // 在增长但数量不高
+        assert!(!leak(10, Some(0.5)));
+    }
+
+    #[test]
+    fn grade_boundaries() {
+        assert_eq!(HealthGrade::from_score(100), HealthGrade::A);
+        assert_eq!(HealthGrade::from_score(90), HealthGrade::A);
+        assert_eq!(HealthGrade::from_score(89), HealthGrade::B);
+        assert_eq!(HealthGrade::from_score(80), HealthGrade::B);
+        assert_eq!(HealthGrade::from_score(79), HealthGrade::C);
+        assert_eq!(HealthGrade::from_score(70), HealthGrade::C);
+        assert_eq!(HealthGrade::from_score(69), HealthGrade::D);
+        assert_eq!(HealthGrade::from_score(60), HealthGrade::D);
+        assert_eq!(HealthGrade::from_score(59), HealthGrade::F);
+        assert_eq!(HealthGrade::from_score(0), HealthGrade::F);
+    }
+
+    #[test]
+    fn system_health_combines_factors() {
+        let process = |pid, health_score| ProcessTcpStats { pid, health_score, ..Default::default() };
+
+        let healthy = SystemTcpStats {
+            port_usage_percent: 10.0,
+            by_process: vec![process(1, 100)],
+            ..Default::default()
+        };
+        let report = system_health(&healthy);
+        assert_eq!((report.score, report.grade), (100, HealthGrade::A));
+        assert!(report.issues.is_empty());
+
+        // 端口 > 50% (-15)，最差进程 80 分 (-10)：75 分
+        let busy = SystemTcpStats {
+            port_usage_percent: 60.0,
+            by_process: vec![process(1, 95), process(2, 80)],
+            ..Default::default()
+        };
+        let report = system_health(&busy);
+        assert_eq!((report.score, report.grade), (75, HealthGrade::C));
+        assert_eq!(report.issues, vec![
+            (SystemHealthFactor::PortUsageHigh, -15),
+            (SystemHealthFactor::UnhealthyProcess { pid: 2, score: 80 }, -10),
+        ]);
+
+        // 所有扣分项都触发：只保留扣分最多的三项，分数截断为 0
+        let mut by_state = HashMap::new();
+        by_state.insert(TcpState::CloseWait, 600);
+        let broken = SystemTcpStats {
+            port_usage_percent: 95.0,
+            by_state,
+            by_process: vec![process(3, 0)],
+            ..Default::default()
+        };
+        let report = system_health(&broken);
+        assert_eq!((report.score, report.grade), (0, HealthGrade::F));
+        assert_eq!(report.issues.len(), 3);
+        assert_eq!(report.issues[0], (SystemHealthFactor::UnhealthyProcess { pid: 3, score: 0 }, -50));
+        assert_eq!(report.issues[1], (SystemHealthFactor::PortUsageCritical, -25));
+    }
+
+    #[test]
+    fn close_wait_sort_surfaces_leaky_process() {
+        // proc1 连接最多，proc2 的 CLOSE_WAIT 已超过健康阈值
+        let mut conns: Vec<_> = (0..100)
+            .map(|i| conn("10.0.0.2", 51000 + i, "93.184.216.34", TcpState::Established, 1))
+            .collect();
+        conns.extend((0..60).map(|i| conn("10.0.0.2", 52000 + i, "10.0.0.9", TcpState::CloseWait, 2)));
+        let monitor = MockTcpMonitor::new(conns);
+
+        assert_eq!(monitor.get_top_processes(1).unwrap()[0].pid, 1);
+        assert_eq!(monitor.get_top_processes_by(1, SortKey::CloseWait).unwrap()[0].pid, 2);
+        assert_eq!(monitor.get_top_processes_by(1, SortKey::HealthAsc).unwrap()[0].pid, 2);
+    }
+
+    #[test]
+    fn close_wait_limit_is_independent_of_time_wait() {
+        // proc1 有 10 个 TIME_WAIT，proc2 只有 3 个 CLOSE_WAIT
+        let mut conns: Vec<_> = (0..10)
+            .map(|i| conn("10.0.0.2", 51000 + i, "93.184.216.34", TcpState::TimeWait, 1))
+            .collect();
+        conns.extend((0..3).map(|i| conn("10.0.0.2", 52000 + i, "10.0.0.9", TcpState::CloseWait, 2)));
+        let monitor = MockTcpMonitor::new(conns);
+
+        let pids = |procs: Vec<ProcessTcpStats>| procs.iter().map(|p| p.pid).collect::<Vec<_>>();
+        assert_eq!(pids(monitor.get_problematic_processes_with(100, 2, usize::MAX).unwrap()), vec![2]);
+        // 旧接口的 CLOSE_WAIT 阈值为 threshold / 4，同样的 TIME_WAIT 阈值下报不出 proc2
+        assert!(monitor.get_problematic_processes(100).unwrap().is_empty());
+        assert_eq!(pids(monitor.get_problematic_processes_with(100, 100, 5).unwrap()), vec![1]);
+    }
+
+    #[test]
+    fn listening_ports_are_deduplicated_and_sorted() {
+        let monitor = MockTcpMonitor::new(vec![
+            conn("0.0.0.0", 8080, "0.0.0.0", TcpState::Listen, 9),
+            conn("0.0.0.0", 8080, "0.0.0.0", TcpState::Listen, 4),
+            conn("::", 8080, "::", TcpState::Listen, 4),
+            conn("127.0.0.1", 22, "0.0.0.0", TcpState::Listen, 1),
+            conn("10.0.0.2", 50001, "93.184.216.34", TcpState::Established, 1),
+        ]);
+
+        let ports = monitor.get_listening_ports().unwrap();
+        let keys: Vec<_> = ports.iter().map(|p| (p.port, p.local_addr.as_str(), p.pid)).collect();
+        assert_eq!(keys, vec![(22, "127.0.0.1", 1), (8080, "0.0.0.0", 4), (8080, "::", 4)]);
+        assert_eq!(ports[1].process_name, "proc4");
+    }
+
+    #[test]
+    fn duplicate_listeners_by_name() {
+        let mut worker = conn("0.0.0.0", 80, "0.0.0.0", TcpState::Listen, 11);
+        worker.process_name = "nginx".into();
+        let mut master = conn("0.0.0.0", 80, "0.0.0.0", TcpState::Listen, 10);
+        master.process_name = "nginx".into();
+        let connections = vec![
+            worker,
+            master,
+            conn("127.0.0.1", 5432, "0.0.0.0", TcpState::Listen, 20),
+            conn("127.0.0.1", 5432, "0.0.0.0", TcpState::Listen, 21),
+            // 同一进程的多个套接字和不同地址都不算冲突
+            conn("0.0.0.0", 22, "0.0.0.0", TcpState::Listen, 1),
+            conn("0.0.0.0", 22, "0.0.0.0", TcpState::Listen, 1),
+            conn("::", 80, "::", TcpState::Listen, 30),
+        ];
+
+        let anomalies = duplicate_listener_anomalies(&connections);
+
+        assert_eq!(anomalies.len(), 2);
+        assert_eq!(anomalies[0].pid, 10);
+        assert_eq!(anomalies[0].severity, Severity::Info);
+        assert_eq!(anomalies[1].pid, 20);
+        assert_eq!(anomalies[1].severity, Severity::Warning);
+        assert!(anomalies[1].message.contains("proc20(20), proc21(21)"));
+    }
+}
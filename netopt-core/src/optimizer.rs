@@ -2,8 +2,14 @@
 //! 
 //! 提供按应用的动态TCP连接优化功能
 
-use crate::{Result, ProcessTcpStats, TcpConnection, TcpState};
+use crate::{NetOptError, Result, ProcessTcpStats, SystemTcpStats, TcpConnection, TcpState};
 use crate::policy::{PolicyManager, AppPolicy, ThresholdAction};
+use crate::clock::{system_clock, Clock};
+use chrono::Timelike;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 /// 优化动作
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -17,12 +23,15 @@ pub struct OptimizationAction {
     pub error_message: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
 pub enum ActionType {
     /// 关闭TIME_WAIT连接（需要系统支持）
     CloseTimeWait,
     /// 关闭CLOSE_WAIT连接
     CloseCloseWait,
+    /// 连接数超过 `max_connections`，关闭可回收连接直到降回上限
+    CloseExcessConnections,
     /// 发送RST重置连接
     ResetConnection,
     /// 通知进程优雅关闭
@@ -31,102 +40,722 @@ pub enum ActionType {
     None,
 }
 
+/// 只有 TIME_WAIT / CLOSE_WAIT 可以安全回收，关闭其他状态（尤其是 ESTABLISHED）可能导致服务中断
+pub fn is_reapable(state: TcpState) -> bool {
+    matches!(state, TcpState::TimeWait | TcpState::CloseWait)
+}
+
+/// 拒绝关闭不可回收状态的连接
+pub fn ensure_reapable(state: TcpState) -> Result<()> {
+    if is_reapable(state) {
+        Ok(())
+    } else {
+        Err(NetOptError::InvalidParameter(format!("拒绝关闭 {} 状态的连接", state)))
+    }
+}
+
+/// 总连接数超过 `max` 时应关闭的连接，最多关闭超出的数量。
+/// 先选 TIME_WAIT（两端都已关闭），再选 CLOSE_WAIT（对端已关闭、本端闲置），同一状态内按系统返回的顺序；
+/// 各平台都不提供连接的建立时间，无法按时长挑选。
+/// ESTABLISHED 等不可回收状态和受保护端口上的连接永不选中，因此结果可能不足以降回上限
+pub fn excess_connections(
+    connections: &[TcpConnection],
+    max: usize,
+    is_protected: impl Fn(&TcpConnection) -> bool,
+) -> Vec<&TcpConnection> {
+    let excess = connections.len().saturating_sub(max);
+    [TcpState::TimeWait, TcpState::CloseWait].into_iter()
+        .flat_map(|state| connections.iter().filter(move |c| c.state == state))
+        .filter(|c| !is_protected(c))
+        .take(excess)
+        .collect()
+}
+
+/// 关闭 `connections` 中指定状态、不受 `policy` 保护的连接。关闭成功的连接从列表中移除
+pub fn close_unprotected<O: ConnectionOptimizer + ?Sized>(
+    optimizer: &O,
+    connections: &mut Vec<TcpConnection>,
+    state: TcpState,
+    policy: &AppPolicy,
+) -> Result<usize> {
+    ensure_reapable(state)?;
+
+    let before = connections.len();
+    connections.retain(|conn| {
+        conn.state != state
+            || policy.is_protected(conn)
+            || optimizer.close_connection(conn).is_err()
+    });
+    Ok(before - connections.len())
+}
+
+/// 按策略阈值清理 `connections` 中属于 `pid` 的连接：超过 TIME_WAIT / CLOSE_WAIT 阈值时关闭该状态的连接，
+/// 剩余连接仍超过 `max_connections` 时按 `excess_connections` 关闭。受保护端口上的连接始终跳过。
+/// 供能逐条关闭连接的平台实现 `optimize_process_from`
+pub fn reap_by_policy<O: ConnectionOptimizer + ?Sized>(
+    optimizer: &O,
+    pid: u32,
+    policy: &AppPolicy,
+    connections: &[TcpConnection],
+) -> Result<OptimizationAction> {
+    let mut connections: Vec<TcpConnection> = connections.iter().filter(|c| c.pid == pid).cloned().collect();
+    let count = |connections: &[TcpConnection], state| connections.iter().filter(|c| c.state == state).count();
+    let time_wait = count(&connections, TcpState::TimeWait);
+    let close_wait = count(&connections, TcpState::CloseWait);
+    let mut connections_affected = 0;
+    let mut action_type = ActionType::None;
+    let mut reason = String::new();
+
+    // 检查 TIME_WAIT 阈值
+    if let Some(threshold) = policy.time_wait_threshold {
+        if time_wait > threshold {
+            reason = format!("TIME_WAIT({}) 超过阈值({}), 正在清理", time_wait, threshold);
+            connections_affected += close_unprotected(optimizer, &mut connections, TcpState::TimeWait, policy)?;
+            action_type = ActionType::CloseTimeWait;
+        }
+    }
+
+    // 检查 CLOSE_WAIT 阈值
+    if let Some(threshold) = policy.close_wait_threshold {
+        if close_wait > threshold {
+            if !reason.is_empty() {
+                reason.push_str("; ");
+            }
+            reason.push_str(&format!("CLOSE_WAIT({}) 超过阈值({}), 正在清理", close_wait, threshold));
+            connections_affected += close_unprotected(optimizer, &mut connections, TcpState::CloseWait, policy)?;
+            action_type = ActionType::CloseCloseWait;
+        }
+    }
+
+    // 检查最大连接数，不含前面已关闭的连接
+    if let Some(max) = policy.max_connections {
+        if connections.len() > max {
+            if !reason.is_empty() {
+                reason.push_str("; ");
+            }
+            reason.push_str(&format!("连接数({}) 超过最大连接数({}), 正在清理", connections.len(), max));
+            let excess = excess_connections(&connections, max, |c| policy.is_protected(c));
+            connections_affected += excess.into_iter().filter(|c| optimizer.close_connection(c).is_ok()).count();
+            action_type = ActionType::CloseExcessConnections;
+        }
+    }
+
+    if reason.is_empty() {
+        reason = "连接状态正常，无需优化".into();
+    }
+
+    Ok(OptimizationAction {
+        pid,
+        process_name: policy.process_name.clone(),
+        action_type,
+        reason,
+        connections_affected,
+        success: true,
+        error_message: None,
+    })
+}
+
 /// 连接优化器 trait
 pub trait ConnectionOptimizer: Send + Sync {
     /// 关闭指定连接
     fn close_connection(&self, conn: &TcpConnection) -> Result<()>;
     
-    /// 批量关闭进程的特定状态连接
-    fn close_connections_by_state(&self, pid: u32, state: TcpState) -> Result<usize>;
+    /// 批量关闭进程的特定状态连接，跳过 `policy` 保护的端口。
+    /// 默认实现在 `connection_table` 上逐条调用 `close_connection`
+    fn close_connections_by_state(&self, pid: u32, state: TcpState, policy: &AppPolicy) -> Result<usize> {
+        let mut connections: Vec<TcpConnection> = self.connection_table()?.into_iter().filter(|c| c.pid == pid).collect();
+        close_unprotected(self, &mut connections, state, policy)
+    }
     
     /// 根据策略优化进程连接
     fn optimize_process(&self, pid: u32, policy: &AppPolicy) -> Result<OptimizationAction>;
     
     /// 检查是否支持连接级别操作（某些操作需要特权）
     fn supports_connection_control(&self) -> bool;
+
+    /// 获取全部连接，`optimize_processes` 只调用一次。默认不支持
+    fn connection_table(&self) -> Result<Vec<TcpConnection>> {
+        Err(NetOptError::UnsupportedPlatform("不支持批量获取连接表".into()))
+    }
+
+    /// 在已获取的连接表上优化进程，调用方可复用同一次扫描的结果。
+    /// 默认实现忽略 `connections`，重新调用 `optimize_process`
+    fn optimize_process_from(&self, pid: u32, policy: &AppPolicy, connections: &[TcpConnection]) -> Result<OptimizationAction> {
+        let _ = connections;
+        self.optimize_process(pid, policy)
+    }
+
+    /// 批量优化：扫描一次连接表，按 PID 分组后以各进程的有效策略调用 `optimize_process_from`。
+    /// 结果顺序与 `pids` 一致，单个进程优化失败时返回 `success: false` 的动作；
+    /// 当前没有连接的进程无法得知进程名，使用默认策略。
+    /// `connection_table` 不可用时退回逐个调用 `optimize_process`，此时进程名未知，全部使用默认策略
+    fn optimize_processes(&self, pids: &[u32], policies: &PolicyManager) -> Vec<OptimizationAction> {
+        let table = match self.connection_table() {
+            Ok(table) => table,
+            Err(_) => {
+                let policy = policies.effective_policy("");
+                return pids.iter()
+                    .map(|&pid| self.optimize_process(pid, &policy).unwrap_or_else(|e| failed_action(pid, &policy, e.to_string())))
+                    .collect();
+            }
+        };
+
+        let wanted: HashSet<u32> = pids.iter().copied().collect();
+        let mut by_pid: HashMap<u32, Vec<TcpConnection>> = HashMap::new();
+        for conn in table {
+            if wanted.contains(&conn.pid) {
+                by_pid.entry(conn.pid).or_default().push(conn);
+            }
+        }
+
+        pids.iter().map(|&pid| {
+            let connections = by_pid.get(&pid).map(Vec::as_slice).unwrap_or_default();
+            let process_name = connections.first().map(|c| c.process_name.as_str()).unwrap_or_default();
+            let mut policy = policies.effective_policy(process_name);
+            policy.process_name = process_name.to_string();
+            self.optimize_process_from(pid, &policy, connections)
+                .unwrap_or_else(|e| failed_action(pid, &policy, e.to_string()))
+        }).collect()
+    }
+
+    /// 立即关闭进程的全部 CLOSE_WAIT（`include_time_wait` 时也关闭 TIME_WAIT），不检查策略阈值，
+    /// 但跳过 `policy` 保护的端口，调用方应传入 `PolicyManager::effective_policy` 的结果。
+    /// 每个清理的状态返回一个动作（先 CLOSE_WAIT 后 TIME_WAIT），不支持连接控制的平台返回一个优雅关闭动作。
+    /// 返回动作的 `process_name` 由调用方填写
+    fn force_cleanup(&self, pid: u32, include_time_wait: bool, policy: &AppPolicy) -> Result<Vec<OptimizationAction>> {
+        let action = |action_type, reason: &str, connections_affected| OptimizationAction {
+            pid,
+            process_name: String::new(),
+            action_type,
+            reason: reason.into(),
+            connections_affected,
+            success: true,
+            error_message: None,
+        };
+
+        if !self.supports_connection_control() {
+            return Ok(vec![action(ActionType::GracefulShutdown, "当前平台不支持直接关闭连接，仅能建议进程自行清理", 0)]);
+        }
+
+        let mut actions = vec![action(
+            ActionType::CloseCloseWait,
+            "手动清理 CLOSE_WAIT",
+            self.close_connections_by_state(pid, TcpState::CloseWait, policy)?,
+        )];
+        if include_time_wait {
+            actions.push(action(
+                ActionType::CloseTimeWait,
+                "手动清理 TIME_WAIT",
+                self.close_connections_by_state(pid, TcpState::TimeWait, policy)?,
+            ));
+        }
+        Ok(actions)
+    }
+}
+
+/// 有界并发的优化执行池
+///
+/// 单个进程的清理可能要逐条调用 `SetTcpEntry`，串行执行时一个慢进程会拖住整轮优化
+#[derive(Debug, Clone, Copy)]
+pub struct OptimizationPool {
+    parallelism: usize,
+}
+
+impl OptimizationPool {
+    /// `parallelism` 为同时优化的进程数上限（至少为 1）
+    pub fn new(parallelism: usize) -> Self {
+        Self {
+            parallelism: parallelism.max(1),
+        }
+    }
+
+    /// 对每个 (PID, 策略) 调用 `optimize_process`，结果顺序与输入一致。
+    /// 优化失败的进程返回 `success: false` 的动作
+    pub fn run(&self, optimizer: &dyn ConnectionOptimizer, jobs: &[(u32, AppPolicy)]) -> Vec<OptimizationAction> {
+        self.run_with(jobs, |pid, policy| optimizer.optimize_process(pid, policy))
+    }
+
+    /// 同 `run`，但在调用方已扫描的连接表上调用 `optimize_process_from`，不再逐个进程扫描
+    pub fn run_from(
+        &self,
+        optimizer: &dyn ConnectionOptimizer,
+        jobs: &[(u32, AppPolicy)],
+        connections: &[TcpConnection],
+    ) -> Vec<OptimizationAction> {
+        self.run_with(jobs, |pid, policy| optimizer.optimize_process_from(pid, policy, connections))
+    }
+
+    fn run_with(
+        &self,
+        jobs: &[(u32, AppPolicy)],
+        optimize: impl Fn(u32, &AppPolicy) -> Result<OptimizationAction> + Sync,
+    ) -> Vec<OptimizationAction> {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![None; jobs.len()]);
+        let workers = self.parallelism.min(jobs.len());
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((pid, policy)) = jobs.get(index) else {
+                        break;
+                    };
+                    let action = optimize(*pid, policy)
+                        .unwrap_or_else(|e| failed_action(*pid, policy, e.to_string()));
+                    results.lock().unwrap()[index] = Some(action);
+                });
+            }
+        });
+
+        results.into_inner().unwrap().into_iter().flatten().collect()
+    }
+}
+
+fn failed_action(pid: u32, policy: &AppPolicy, error: String) -> OptimizationAction {
+    OptimizationAction {
+        pid,
+        process_name: policy.process_name.clone(),
+        action_type: ActionType::None,
+        reason: "优化失败".into(),
+        connections_affected: 0,
+        success: false,
+        error_message: Some(error),
+    }
+}
+
+/// 一轮优化动作的汇总
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct OptimizationSummary {
+    pub total_actions: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub connections_affected: usize,
+    /// 各动作类型的数量
+    pub by_type: BTreeMap<ActionType, usize>,
+    /// 涉及的进程 PID -> 进程名
+    pub processes: BTreeMap<u32, String>,
+}
+
+impl OptimizationSummary {
+    pub fn from_actions(actions: &[OptimizationAction]) -> Self {
+        let mut summary = Self::default();
+        for action in actions {
+            summary.total_actions += 1;
+            if action.success {
+                summary.succeeded += 1;
+            } else {
+                summary.failed += 1;
+            }
+            summary.connections_affected += action.connections_affected;
+            *summary.by_type.entry(action.action_type).or_insert(0) += 1;
+            summary.processes.entry(action.pid).or_insert_with(|| action.process_name.clone());
+        }
+        summary
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total_actions == 0
+    }
+}
+
+impl std::fmt::Display for OptimizationSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} 个动作（成功 {}，失败 {}），影响 {} 个连接，涉及 {} 个进程",
+            self.total_actions, self.succeeded, self.failed, self.connections_affected, self.processes.len()
+        )?;
+        if !self.by_type.is_empty() {
+            let types = self.by_type.iter()
+                .map(|(t, n)| format!("{:?}×{}", t, n))
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(f, "：{}", types)?;
+        }
+        Ok(())
+    }
+}
+
+/// 策略的运行统计，只保存在内存中，不写入策略配置
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PolicyStats {
+    /// 最近一次产生动作的时间
+    pub last_triggered: Option<SystemTime>,
+    /// 累计产生的动作数
+    pub total_actions: u64,
+    /// 累计涉及的连接数
+    pub connections_affected: u64,
+}
+
+impl PolicyStats {
+    /// 记录一次触发
+    pub fn record(&mut self, actions: usize, connections_affected: usize, at: SystemTime) {
+        self.last_triggered = Some(at);
+        self.total_actions += actions as u64;
+        self.connections_affected += connections_affected as u64;
+    }
+}
+
+/// 一次阈值比较
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ThresholdCheck {
+    pub state: TcpState,
+    /// 进程当前该状态的连接数
+    pub value: usize,
+    /// 策略阈值，None 表示不限制
+    pub threshold: Option<usize>,
+    pub exceeded: bool,
+}
+
+impl ThresholdCheck {
+    fn new(state: TcpState, value: usize, threshold: Option<usize>) -> Self {
+        Self {
+            state,
+            value,
+            threshold,
+            exceeded: threshold.is_some_and(|t| value > t),
+        }
+    }
+}
+
+/// 总连接数与策略 `max_connections` 的比较
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LimitCheck {
+    /// 进程当前的总连接数
+    pub value: usize,
+    pub limit: usize,
+    pub exceeded: bool,
+}
+
+/// `OptimizationEngine::explain` 的结果
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PolicyDecision {
+    pub pid: u32,
+    pub process_name: String,
+    /// 匹配到的策略名，使用默认策略时为 None
+    pub matched_policy: Option<String>,
+    /// 命中白名单时不做任何干预
+    pub whitelisted: bool,
+    /// 仅供参考，黑名单目前不影响决策
+    pub blacklisted: bool,
+    pub auto_optimize: bool,
+    pub threshold_action: ThresholdAction,
+    /// 按比较顺序排列，白名单或未启用自动优化时为空
+    pub checks: Vec<ThresholdCheck>,
+    /// 策略设置了最大连接数时的比较结果
+    #[serde(default)]
+    pub connection_limit: Option<LimitCheck>,
+    /// 超过阈值但健康评分不低于 `min_health_to_skip`，关闭连接的动作已被跳过
+    #[serde(default)]
+    pub skipped_healthy: bool,
+    /// 不在策略的生效时段内，关闭连接的动作已降级为告警
+    #[serde(default)]
+    pub outside_active_hours: bool,
+    pub actions: Vec<OptimizationAction>,
+}
+
+impl std::fmt::Display for PolicyDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.matched_policy {
+            Some(name) => write!(f, "策略: {}", name)?,
+            None => write!(f, "策略: 默认")?,
+        }
+        if self.whitelisted {
+            return write!(f, "；进程在白名单中，不做干预");
+        }
+        if !self.auto_optimize {
+            return write!(f, "；未启用自动优化");
+        }
+        for check in &self.checks {
+            match check.threshold {
+                Some(t) if check.exceeded => write!(f, "；{} {} > {}", check.state, check.value, t)?,
+                Some(t) => write!(f, "；{} {} ≤ {}", check.state, check.value, t)?,
+                None => write!(f, "；{} {} 不限制", check.state, check.value)?,
+            }
+        }
+        if let Some(limit) = &self.connection_limit {
+            let op = if limit.exceeded { ">" } else { "≤" };
+            write!(f, "；连接数 {} {} {}", limit.value, op, limit.limit)?;
+        }
+        if self.skipped_healthy {
+            write!(f, "；进程健康，跳过优化")?;
+        }
+        if self.outside_active_hours {
+            write!(f, "；不在生效时段，仅告警")?;
+        }
+        if self.actions.is_empty() {
+            write!(f, "；无动作")
+        } else {
+            let actions = self.actions.iter()
+                .map(|a| format!("{:?}", a.action_type))
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(f, " → {}", actions)
+        }
+    }
 }
 
 /// 优化决策引擎
 pub struct OptimizationEngine {
     policy_manager: PolicyManager,
+    /// 策略名 -> 运行统计，默认策略的键为空字符串
+    policy_stats: HashMap<String, PolicyStats>,
+    clock: Arc<dyn Clock>,
 }
 
 impl OptimizationEngine {
     pub fn new(policy_manager: PolicyManager) -> Self {
-        Self { policy_manager }
+        Self {
+            policy_manager,
+            policy_stats: HashMap::new(),
+            clock: system_clock(),
+        }
     }
-    
+
+    /// 替换时钟，用于生效时段和策略触发时间
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// 分析进程并决定优化动作
-    pub fn analyze_and_decide(&self, stats: &ProcessTcpStats) -> Vec<OptimizationAction> {
-        let mut actions = Vec::new();
-        
-        // 检查白名单
-        if self.policy_manager.is_whitelisted(&stats.process_name) {
-            return actions;
-        }
-        
-        let policy = self.policy_manager.get_policy(&stats.process_name);
-        
-        // 检查是否需要优化
-        if !policy.auto_optimize {
-            return actions;
-        }
-        
-        // TIME_WAIT 超阈值
-        if let Some(threshold) = policy.time_wait_threshold {
-            if stats.time_wait > threshold {
-                let action = match policy.threshold_action {
-                    ThresholdAction::Optimize => OptimizationAction {
-                        pid: stats.pid,
-                        process_name: stats.process_name.clone(),
-                        action_type: ActionType::CloseTimeWait,
-                        reason: format!(
-                            "TIME_WAIT({})超过阈值({})",
-                            stats.time_wait, threshold
-                        ),
-                        connections_affected: stats.time_wait - threshold,
-                        success: false,
-                        error_message: None,
-                    },
-                    ThresholdAction::Alert => OptimizationAction {
-                        pid: stats.pid,
-                        process_name: stats.process_name.clone(),
-                        action_type: ActionType::None,
-                        reason: format!(
-                            "告警: TIME_WAIT({})超过阈值({})",
-                            stats.time_wait, threshold
-                        ),
-                        connections_affected: 0,
-                        success: true,
-                        error_message: None,
-                    },
-                    _ => continue_action(stats),
-                };
-                actions.push(action);
-            }
+    pub fn analyze_and_decide(&mut self, stats: &ProcessTcpStats) -> Vec<OptimizationAction> {
+        self.analyze_and_decide_at(stats, self.clock.system_time())
+    }
+
+    /// 分析进程并决定优化动作，以指定时间记录策略触发
+    pub fn analyze_and_decide_at(&mut self, stats: &ProcessTcpStats, now: SystemTime) -> Vec<OptimizationAction> {
+        let actions = self.decide(stats, now);
+        if !actions.is_empty() {
+            let policy = self.policy_manager.get_policy(&stats.process_name);
+            let affected = actions.iter().map(|a| a.connections_affected).sum();
+            self.policy_stats.entry(policy.process_name.clone()).or_default().record(actions.len(), affected, now);
         }
+        actions
+    }
 
-        // CLOSE_WAIT 超阈值（更严重）
-        if let Some(threshold) = policy.close_wait_threshold {
-            if stats.close_wait > threshold {
-                actions.push(OptimizationAction {
+    /// 模拟对整个系统启用自动优化：按策略为每个进程决策，不关闭连接也不记录策略统计。
+    /// 动作的 `success` 表示实际执行时能否成功
+    pub fn simulate(&self, system: &SystemTcpStats) -> Vec<OptimizationAction> {
+        self.simulate_with(system, crate::platform::platform_capabilities().can_close_connections)
+    }
+
+    /// 同 `simulate`，`can_close_connections` 为 false 时关闭连接的动作视为会失败
+    pub fn simulate_with(&self, system: &SystemTcpStats, can_close_connections: bool) -> Vec<OptimizationAction> {
+        system.by_process.iter()
+            .flat_map(|stats| self.decide(stats, self.clock.system_time()))
+            .map(|mut action| {
+                let closes = matches!(
+                    action.action_type,
+                    ActionType::CloseTimeWait | ActionType::CloseCloseWait | ActionType::CloseExcessConnections
+                );
+                action.success = !closes || can_close_connections;
+                if !action.success {
+                    action.error_message = Some("当前平台不支持直接关闭连接".into());
+                }
+                action
+            })
+            .collect()
+    }
+
+    /// 策略的运行统计，从未触发时为 None。默认策略传入空字符串
+    pub fn policy_stats(&self, policy_name: &str) -> Option<&PolicyStats> {
+        self.policy_stats.get(policy_name)
+    }
+
+    /// 所有策略的运行统计
+    pub fn all_policy_stats(&self) -> &HashMap<String, PolicyStats> {
+        &self.policy_stats
+    }
+
+    /// 解释策略对进程的决策过程：匹配的策略、白名单、阈值比较和产生的动作。
+    /// 与 `analyze_and_decide` 走同一套逻辑，但不记录策略统计
+    pub fn explain(&self, stats: &ProcessTcpStats) -> PolicyDecision {
+        self.explain_at(stats, self.clock.system_time())
+    }
+
+    /// 同 `explain`，按 `now` 的本地时间判断策略的生效时段
+    pub fn explain_at(&self, stats: &ProcessTcpStats, now: SystemTime) -> PolicyDecision {
+        let manager = &self.policy_manager;
+        let policy = manager.get_policy(&stats.process_name);
+        let mut decision = PolicyDecision {
+            pid: stats.pid,
+            process_name: stats.process_name.clone(),
+            matched_policy: manager.find_policy(&stats.process_name).map(|p| p.process_name.clone()),
+            whitelisted: manager.is_whitelisted(&stats.process_name),
+            blacklisted: manager.is_blacklisted(&stats.process_name),
+            auto_optimize: policy.auto_optimize,
+            threshold_action: policy.threshold_action,
+            checks: Vec::new(),
+            connection_limit: None,
+            skipped_healthy: false,
+            outside_active_hours: false,
+            actions: Vec::new(),
+        };
+
+        // 白名单进程和未启用自动优化的策略不比较阈值
+        if decision.whitelisted || !policy.auto_optimize {
+            return decision;
+        }
+
+        // TIME_WAIT 超阈值
+        let time_wait = ThresholdCheck::new(TcpState::TimeWait, stats.time_wait, policy.time_wait_threshold);
+        if let (true, Some(threshold)) = (time_wait.exceeded, time_wait.threshold) {
+            let action = match policy.threshold_action {
+                ThresholdAction::Optimize => OptimizationAction {
                     pid: stats.pid,
                     process_name: stats.process_name.clone(),
-                    action_type: ActionType::CloseCloseWait,
+                    action_type: ActionType::CloseTimeWait,
                     reason: format!(
-                        "CLOSE_WAIT({})超过阈值({})，可能存在连接泄漏",
-                        stats.close_wait, threshold
+                        "TIME_WAIT({})超过阈值({})",
+                        stats.time_wait, threshold
                     ),
-                    connections_affected: stats.close_wait,
+                    connections_affected: stats.time_wait - threshold,
                     success: false,
                     error_message: None,
-                });
+                },
+                ThresholdAction::Alert => OptimizationAction {
+                    pid: stats.pid,
+                    process_name: stats.process_name.clone(),
+                    action_type: ActionType::None,
+                    reason: format!(
+                        "告警: TIME_WAIT({})超过阈值({})",
+                        stats.time_wait, threshold
+                    ),
+                    connections_affected: 0,
+                    success: true,
+                    error_message: None,
+                },
+                _ => continue_action(stats),
+            };
+            decision.actions.push(action);
+        }
+        decision.checks.push(time_wait);
+
+        // CLOSE_WAIT 超阈值（更严重）
+        let close_wait = ThresholdCheck::new(TcpState::CloseWait, stats.close_wait, policy.close_wait_threshold);
+        if let (true, Some(threshold)) = (close_wait.exceeded, close_wait.threshold) {
+            decision.actions.push(OptimizationAction {
+                pid: stats.pid,
+                process_name: stats.process_name.clone(),
+                action_type: ActionType::CloseCloseWait,
+                reason: format!(
+                    "CLOSE_WAIT({})超过阈值({})，可能存在连接泄漏",
+                    stats.close_wait, threshold
+                ),
+                connections_affected: stats.close_wait,
+                success: false,
+                error_message: None,
+            });
+        }
+        decision.checks.push(close_wait);
+
+        // 总连接数超过上限，只能靠回收 TIME_WAIT / CLOSE_WAIT 降低，见 `excess_connections`
+        if let Some(max) = policy.max_connections {
+            let limit = LimitCheck {
+                value: stats.total_connections,
+                limit: max,
+                exceeded: stats.total_connections > max,
+            };
+            if limit.exceeded {
+                let alert = |reason: String| OptimizationAction {
+                    pid: stats.pid,
+                    process_name: stats.process_name.clone(),
+                    action_type: ActionType::None,
+                    reason,
+                    connections_affected: 0,
+                    success: true,
+                    error_message: None,
+                };
+                let action = match policy.threshold_action {
+                    ThresholdAction::Optimize => {
+                        // 前面的动作已经关闭的连接不再重复计算
+                        let closing: usize = decision.actions.iter()
+                            .filter(|a| a.action_type != ActionType::None)
+                            .map(|a| a.connections_affected)
+                            .sum();
+                        let remaining = (stats.total_connections - max).saturating_sub(closing);
+                        let reapable = (stats.time_wait + stats.close_wait).saturating_sub(closing);
+                        match remaining.min(reapable) {
+                            0 if remaining == 0 => None,
+                            0 => Some(alert(format!(
+                                "告警: 连接数({})超过最大连接数({})，但没有可回收的连接",
+                                stats.total_connections, max
+                            ))),
+                            excess => Some(OptimizationAction {
+                                pid: stats.pid,
+                                process_name: stats.process_name.clone(),
+                                action_type: ActionType::CloseExcessConnections,
+                                reason: format!(
+                                    "连接数({})超过最大连接数({})",
+                                    stats.total_connections, max
+                                ),
+                                connections_affected: excess,
+                                success: false,
+                                error_message: None,
+                            }),
+                        }
+                    }
+                    ThresholdAction::Alert => Some(alert(format!(
+                        "告警: 连接数({})超过最大连接数({})",
+                        stats.total_connections, max
+                    ))),
+                    _ => Some(continue_action(stats)),
+                };
+                decision.actions.extend(action);
             }
+            decision.connection_limit = Some(limit);
         }
-        
-        actions
+
+        // 健康的进程只保留告警，不关闭连接
+        if policy.min_health_to_skip.is_some_and(|floor| stats.health_score >= floor) {
+            let before = decision.actions.len();
+            decision.actions.retain(|a| a.action_type == ActionType::None);
+            decision.skipped_healthy = decision.actions.len() < before;
+        }
+
+        // 生效时段外降级为告警
+        let hour = chrono::DateTime::<chrono::Local>::from(now).hour() as u8;
+        if !policy.is_active_at(hour) {
+            for action in decision.actions.iter_mut().filter(|a| a.action_type != ActionType::None) {
+                action.action_type = ActionType::None;
+                action.reason = format!("告警（不在生效时段）: {}", action.reason);
+                action.connections_affected = 0;
+                action.success = true;
+                decision.outside_active_hours = true;
+            }
+        }
+
+        decision
+    }
+
+    fn decide(&self, stats: &ProcessTcpStats, now: SystemTime) -> Vec<OptimizationAction> {
+        self.explain_at(stats, now).actions
     }
     
+    /// 经安全检查后关闭指定状态的连接，返回成功关闭的数量。
+    /// 不可回收的状态直接拒绝；全局白名单端口和所属进程策略中的受保护端口会被跳过
+    pub fn reap_connections(
+        &self,
+        optimizer: &dyn ConnectionOptimizer,
+        connections: &[TcpConnection],
+        state: TcpState,
+    ) -> Result<usize> {
+        ensure_reapable(state)?;
+
+        let mut closed = 0;
+        for conn in connections.iter().filter(|c| c.state == state) {
+            if self.policy_manager.is_protected(conn) {
+                continue;
+            }
+            if optimizer.close_connection(conn).is_ok() {
+                closed += 1;
+            }
+        }
+        Ok(closed)
+    }
+
     /// 获取策略管理器引用
     pub fn policy_manager(&self) -> &PolicyManager {
         &self.policy_manager
@@ -150,3 +779,654 @@ fn continue_action(stats: &ProcessTcpStats) -> OptimizationAction {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NetOptError;
+    use std::time::{Duration, Instant};
+
+    /// 每次优化耗时固定时长，并记录最大并发数
+    #[derive(Default)]
+    struct SlowOptimizer {
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+    }
+
+    impl ConnectionOptimizer for SlowOptimizer {
+        fn close_connection(&self, _conn: &TcpConnection) -> Result<()> {
+            Ok(())
+        }
+
+        fn close_connections_by_state(&self, _pid: u32, _state: TcpState, _policy: &AppPolicy) -> Result<usize> {
+            Ok(0)
+        }
+
+        fn optimize_process(&self, pid: u32, policy: &AppPolicy) -> Result<OptimizationAction> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(100));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+
+            if pid == 0 {
+                return Err(NetOptError::ProcessNotFound(pid));
+            }
+            Ok(OptimizationAction {
+                pid,
+                process_name: policy.process_name.clone(),
+                action_type: ActionType::CloseCloseWait,
+                reason: String::new(),
+                connections_affected: 1,
+                success: true,
+                error_message: None,
+            })
+        }
+
+        fn supports_connection_control(&self) -> bool {
+            true
+        }
+    }
+
+    /// 记录传入 close_connection 的连接和 close_connections_by_state 的状态
+    #[derive(Default)]
+    struct RecordingOptimizer(Mutex<Vec<TcpConnection>>, Mutex<Vec<TcpState>>);
+
+    impl ConnectionOptimizer for RecordingOptimizer {
+        fn close_connection(&self, conn: &TcpConnection) -> Result<()> {
+            self.0.lock().unwrap().push(conn.clone());
+            Ok(())
+        }
+
+        fn close_connections_by_state(&self, _pid: u32, state: TcpState, _policy: &AppPolicy) -> Result<usize> {
+            self.1.lock().unwrap().push(state);
+            Ok(3)
+        }
+
+        fn optimize_process(&self, pid: u32, policy: &AppPolicy) -> Result<OptimizationAction> {
+            Ok(failed_action(pid, policy, String::new()))
+        }
+
+        fn supports_connection_control(&self) -> bool {
+            true
+        }
+    }
+
+    fn conn(local_port: u16, remote_port: u16, state: TcpState) -> TcpConnection {
+        TcpConnection {
+            local_addr: "10.0.0.1".into(),
+            local_port,
+            remote_addr: "10.0.0.2".into(),
+            remote_port,
+            state,
+            pid: 42,
+            process_name: "web".into(),
+            raw_state: None,
+            remote_host: None,
+        }
+    }
+
+    #[test]
+    fn established_connections_are_never_closed() {
+        let optimizer = RecordingOptimizer::default();
+        let engine = OptimizationEngine::new(PolicyManager::new());
+        let connections = vec![
+            conn(50000, 443, TcpState::Established),
+            conn(50001, 443, TcpState::TimeWait),
+        ];
+
+        assert!(engine.reap_connections(&optimizer, &connections, TcpState::Established).is_err());
+        assert_eq!(engine.reap_connections(&optimizer, &connections, TcpState::TimeWait).unwrap(), 1);
+
+        let closed = optimizer.0.lock().unwrap();
+        assert_eq!(closed.len(), 1);
+        assert!(closed.iter().all(|c| c.state != TcpState::Established));
+    }
+
+    #[test]
+    fn protected_ports_are_skipped() {
+        let optimizer = RecordingOptimizer::default();
+        let mut policies = PolicyManager::new();
+        let mut policy = AppPolicy::server("web");
+        policy.protected_ports = vec![22, 443];
+        policies.set_policy(policy);
+        let engine = OptimizationEngine::new(policies);
+        let connections = vec![
+            conn(50000, 443, TcpState::CloseWait),
+            conn(22, 50001, TcpState::CloseWait),
+            conn(50002, 8080, TcpState::CloseWait),
+        ];
+
+        assert_eq!(engine.reap_connections(&optimizer, &connections, TcpState::CloseWait).unwrap(), 1);
+        assert_eq!(optimizer.0.lock().unwrap()[0].remote_port, 8080);
+    }
+
+    #[test]
+    fn whitelisted_ports_are_never_closed() {
+        let optimizer = RecordingOptimizer::default();
+        let mut policies = PolicyManager::new();
+        // 进程策略要求积极清理
+        policies.set_policy(AppPolicy::restricted("web"));
+        policies.whitelist_ports = vec![5432];
+        policies.whitelist_port_ranges = vec![(3306, 3307)];
+        let engine = OptimizationEngine::new(policies);
+        let connections = vec![
+            conn(50000, 5432, TcpState::CloseWait),
+            conn(3306, 50001, TcpState::CloseWait),
+            conn(50002, 3307, TcpState::CloseWait),
+            conn(50003, 8080, TcpState::CloseWait),
+        ];
+
+        assert_eq!(engine.reap_connections(&optimizer, &connections, TcpState::CloseWait).unwrap(), 1);
+        let closed = optimizer.0.lock().unwrap();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].remote_port, 8080);
+    }
+
+    fn jobs(pids: impl IntoIterator<Item = u32>) -> Vec<(u32, AppPolicy)> {
+        pids.into_iter().map(|pid| (pid, AppPolicy::high_performance(&format!("proc{}", pid)))).collect()
+    }
+
+    #[test]
+    fn pool_runs_processes_concurrently_up_to_limit() {
+        let optimizer = SlowOptimizer::default();
+        let start = Instant::now();
+        let actions = OptimizationPool::new(4).run(&optimizer, &jobs(1..=8));
+        let elapsed = start.elapsed();
+
+        assert_eq!(actions.iter().map(|a| a.pid).collect::<Vec<_>>(), (1..=8).collect::<Vec<_>>());
+        assert_eq!(optimizer.max_running.load(Ordering::SeqCst), 4);
+        // 串行需要 800ms，4 路并发约 200ms
+        assert!(elapsed < Duration::from_millis(600), "elapsed {:?}", elapsed);
+    }
+
+    #[test]
+    fn pool_reports_failures_as_unsuccessful_actions() {
+        let optimizer = SlowOptimizer::default();
+        let actions = OptimizationPool::new(2).run(&optimizer, &jobs([1, 0]));
+
+        assert!(actions[0].success);
+        assert!(!actions[1].success);
+        assert!(actions[1].error_message.as_deref().unwrap().contains("PID 0"));
+    }
+
+    #[test]
+    fn force_cleanup_ignores_thresholds() {
+        let optimizer = RecordingOptimizer::default();
+        let summary = |actions: &[OptimizationAction]| actions.iter().map(|a| (a.action_type, a.connections_affected)).collect::<Vec<_>>();
+
+        let actions = optimizer.force_cleanup(42, false, &AppPolicy::default()).unwrap();
+        assert_eq!(summary(&actions), vec![(ActionType::CloseCloseWait, 3)]);
+        assert_eq!(*optimizer.1.lock().unwrap(), vec![TcpState::CloseWait]);
+
+        // 两种状态分别报告
+        let actions = optimizer.force_cleanup(42, true, &AppPolicy::default()).unwrap();
+        assert_eq!(summary(&actions), vec![(ActionType::CloseCloseWait, 3), (ActionType::CloseTimeWait, 3)]);
+        assert_eq!(*optimizer.1.lock().unwrap(), vec![TcpState::CloseWait, TcpState::CloseWait, TcpState::TimeWait]);
+    }
+
+    #[test]
+    fn force_cleanup_without_control_is_graceful() {
+        struct NoControl;
+
+        impl ConnectionOptimizer for NoControl {
+            fn close_connection(&self, _conn: &TcpConnection) -> Result<()> {
+                unreachable!()
+            }
+
+            fn close_connections_by_state(&self, _pid: u32, _state: TcpState, _policy: &AppPolicy) -> Result<usize> {
+                unreachable!()
+            }
+
+            fn optimize_process(&self, pid: u32, policy: &AppPolicy) -> Result<OptimizationAction> {
+                Ok(failed_action(pid, policy, String::new()))
+            }
+
+            fn supports_connection_control(&self) -> bool {
+                false
+            }
+        }
+
+        let actions = NoControl.force_cleanup(42, true, &AppPolicy::default()).unwrap();
+        assert_eq!(actions.len(), 1);
+        let action = &actions[0];
+        assert_eq!(action.action_type, ActionType::GracefulShutdown);
+        assert_eq!(action.connections_affected, 0);
+        assert!(action.success);
+    }
+
+    /// 连接表固定，按 `reap_by_policy` 优化，记录实际关闭的连接
+    struct TableCloser {
+        table: Vec<TcpConnection>,
+        closed: Mutex<Vec<TcpConnection>>,
+    }
+
+    impl ConnectionOptimizer for TableCloser {
+        fn close_connection(&self, conn: &TcpConnection) -> Result<()> {
+            self.closed.lock().unwrap().push(conn.clone());
+            Ok(())
+        }
+
+        fn optimize_process(&self, pid: u32, policy: &AppPolicy) -> Result<OptimizationAction> {
+            reap_by_policy(self, pid, policy, &self.table)
+        }
+
+        fn supports_connection_control(&self) -> bool {
+            true
+        }
+
+        fn connection_table(&self) -> Result<Vec<TcpConnection>> {
+            Ok(self.table.clone())
+        }
+    }
+
+    #[test]
+    fn protected_close_wait_survives_force_cleanup_and_auto_optimize() {
+        let mut policies = PolicyManager::new();
+        let mut policy = AppPolicy::server("web");
+        policy.protected_ports = vec![22];
+        policy.close_wait_threshold = Some(1);
+        policies.set_policy(policy);
+        policies.whitelist_ports = vec![443];
+        let optimizer = TableCloser {
+            table: vec![
+                conn(50000, 443, TcpState::CloseWait),
+                conn(22, 50001, TcpState::CloseWait),
+                conn(50002, 8080, TcpState::CloseWait),
+                conn(50003, 8080, TcpState::TimeWait),
+            ],
+            closed: Mutex::default(),
+        };
+        let policy = policies.effective_policy("web");
+        let closed_ports = || optimizer.closed.lock().unwrap().drain(..).map(|c| c.local_port).collect::<Vec<_>>();
+
+        // 手动清理不检查阈值，但不越过端口保护
+        let actions = optimizer.force_cleanup(42, true, &policy).unwrap();
+        assert_eq!(actions.iter().map(|a| a.connections_affected).collect::<Vec<_>>(), vec![1, 1]);
+        assert_eq!(closed_ports(), vec![50002, 50003]);
+
+        // GUI 自动优化以有效策略调用 optimize_process
+        let action = optimizer.optimize_process(42, &policy).unwrap();
+        assert_eq!(action.action_type, ActionType::CloseCloseWait);
+        assert_eq!(action.connections_affected, 1);
+        assert_eq!(closed_ports(), vec![50002]);
+    }
+
+    #[test]
+    fn batch_optimize_scans_connection_table_once() {
+        /// 连接表固定，记录扫描次数和每个进程收到的策略、连接数
+        #[derive(Default)]
+        struct TableOptimizer {
+            table: Vec<TcpConnection>,
+            scans: AtomicUsize,
+            seen: Mutex<Vec<(u32, Option<usize>, usize)>>,
+        }
+
+        impl ConnectionOptimizer for TableOptimizer {
+            fn close_connection(&self, _conn: &TcpConnection) -> Result<()> {
+                Ok(())
+            }
+
+            fn close_connections_by_state(&self, _pid: u32, _state: TcpState, _policy: &AppPolicy) -> Result<usize> {
+                Ok(0)
+            }
+
+            fn optimize_process(&self, _pid: u32, _policy: &AppPolicy) -> Result<OptimizationAction> {
+                unreachable!("批量优化不应逐个扫描")
+            }
+
+            fn supports_connection_control(&self) -> bool {
+                true
+            }
+
+            fn connection_table(&self) -> Result<Vec<TcpConnection>> {
+                self.scans.fetch_add(1, Ordering::SeqCst);
+                Ok(self.table.clone())
+            }
+
+            fn optimize_process_from(&self, pid: u32, policy: &AppPolicy, connections: &[TcpConnection]) -> Result<OptimizationAction> {
+                self.seen.lock().unwrap().push((pid, policy.close_wait_threshold, connections.len()));
+                if pid == 3 {
+                    return Err(NetOptError::PermissionDenied);
+                }
+                Ok(OptimizationAction { success: true, error_message: None, ..failed_action(pid, policy, String::new()) })
+            }
+        }
+
+        let owned = |pid: u32, name: &str| TcpConnection { pid, process_name: name.into(), ..conn(50000, 443, TcpState::CloseWait) };
+        let optimizer = TableOptimizer {
+            table: vec![owned(1, "scraper"), owned(2, "web"), owned(1, "scraper"), owned(3, "db"), owned(9, "other")],
+            ..Default::default()
+        };
+        let mut policies = PolicyManager::new();
+        policies.set_policy(AppPolicy::crawler("scraper"));
+
+        let actions = optimizer.optimize_processes(&[1, 2, 3, 4], &policies);
+
+        assert_eq!(optimizer.scans.load(Ordering::SeqCst), 1);
+        assert_eq!(actions.iter().map(|a| a.pid).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(actions[0].process_name, "scraper");
+        let crawler = AppPolicy::crawler("scraper").close_wait_threshold;
+        let default = AppPolicy::default().close_wait_threshold;
+        assert_eq!(
+            *optimizer.seen.lock().unwrap(),
+            vec![(1, crawler, 2), (2, default, 1), (3, default, 1), (4, default, 0)],
+        );
+        // 单个进程失败不影响其他进程
+        assert_eq!(actions.iter().map(|a| a.success).collect::<Vec<_>>(), vec![true, true, false, true]);
+        assert!(actions[2].error_message.as_deref().unwrap().contains("权限"));
+    }
+
+    #[test]
+    fn batch_optimize_falls_back_to_per_process_without_table() {
+        // SlowOptimizer 没有实现 connection_table
+        let optimizer = SlowOptimizer::default();
+        let actions = optimizer.optimize_processes(&[1, 0], &PolicyManager::new());
+
+        assert_eq!(actions.iter().map(|a| (a.pid, a.success)).collect::<Vec<_>>(), vec![(1, true), (0, false)]);
+        assert_eq!(actions[0].process_name, AppPolicy::default().process_name);
+    }
+
+    #[test]
+    fn simulate_reports_system_wide_impact() {
+        use crate::platform::mock::MockTcpMonitor;
+        use crate::monitor::TcpMonitor;
+
+        let conn = |pid: u32, name: &str, state: TcpState| TcpConnection {
+            local_addr: "10.0.0.1".into(),
+            local_port: 40000,
+            remote_addr: "10.0.0.2".into(),
+            remote_port: 443,
+            state,
+            pid,
+            process_name: name.into(),
+            raw_state: None,
+            remote_host: None,
+        };
+        let mut connections = Vec::new();
+        connections.extend((0..30).map(|_| conn(1, "scraper", TcpState::CloseWait)));
+        connections.extend((0..600).map(|_| conn(1, "scraper", TcpState::TimeWait)));
+        connections.extend((0..40).map(|_| conn(2, "idle", TcpState::Established)));
+        let system = MockTcpMonitor::new(connections).get_system_stats().unwrap();
+
+        let mut policies = PolicyManager::new();
+        policies.set_policy(AppPolicy::crawler("scraper"));
+        let engine = OptimizationEngine::new(policies);
+
+        let actions = engine.simulate_with(&system, true);
+        let summary = OptimizationSummary::from_actions(&actions);
+        assert_eq!(summary.total_actions, 2);
+        assert_eq!(summary.succeeded, 2);
+        // TIME_WAIT 只清理超出阈值的 100 个，CLOSE_WAIT 全部清理
+        assert_eq!(summary.connections_affected, 130);
+        assert_eq!(summary.processes.len(), 1);
+
+        let actions = engine.simulate_with(&system, false);
+        assert!(actions.iter().all(|a| !a.success && a.error_message.is_some()));
+        // 模拟不计入策略统计
+        assert!(engine.policy_stats("scraper").is_none());
+    }
+
+    #[test]
+    fn explain_short_circuits_on_whitelist() {
+        let mut policies = PolicyManager::new();
+        policies.set_policy(AppPolicy::crawler("scraper"));
+        policies.whitelist = vec!["scraper".into()];
+        let engine = OptimizationEngine::new(policies);
+        let stats = ProcessTcpStats {
+            pid: 42,
+            process_name: "scraper".into(),
+            close_wait: 500,
+            ..Default::default()
+        };
+
+        let decision = engine.explain(&stats);
+        assert_eq!(decision.matched_policy.as_deref(), Some("scraper"));
+        assert!(decision.whitelisted);
+        assert!(decision.checks.is_empty());
+        assert!(decision.actions.is_empty());
+        assert!(decision.to_string().contains("白名单"));
+    }
+
+    #[test]
+    fn explain_records_thresholds_not_met() {
+        let engine = OptimizationEngine::new(PolicyManager::new());
+        let stats = ProcessTcpStats {
+            pid: 7,
+            process_name: "web".into(),
+            time_wait: 400,
+            close_wait: 10,
+            ..Default::default()
+        };
+
+        let decision = engine.explain(&stats);
+        assert_eq!(decision.matched_policy, None);
+        assert_eq!(decision.checks, vec![
+            ThresholdCheck { state: TcpState::TimeWait, value: 400, threshold: Some(300), exceeded: true },
+            ThresholdCheck { state: TcpState::CloseWait, value: 10, threshold: Some(30), exceeded: false },
+        ]);
+        // 默认策略只告警
+        assert_eq!(decision.actions.len(), 1);
+        assert_eq!(decision.actions[0].action_type, ActionType::None);
+        assert_eq!(decision.to_string(), "策略: 默认；TIME_WAIT 400 > 300；CLOSE_WAIT 10 ≤ 30 → None");
+        assert_eq!(decision.actions.len(), engine.decide(&stats, SystemTime::now()).len());
+    }
+
+    #[test]
+    fn healthy_process_over_threshold_is_not_optimized() {
+        let mut policy = AppPolicy::crawler("scraper");
+        policy.min_health_to_skip = Some(60);
+        let mut policies = PolicyManager::new();
+        policies.set_policy(policy);
+        let engine = OptimizationEngine::new(policies);
+        let busy = |health_score: u8| ProcessTcpStats {
+            pid: 42,
+            process_name: "scraper".into(),
+            time_wait: 600,
+            close_wait: 25,
+            health_score,
+            ..Default::default()
+        };
+
+        let healthy = engine.explain(&busy(85));
+        assert!(healthy.checks.iter().all(|c| c.exceeded));
+        assert!(healthy.skipped_healthy);
+        assert!(healthy.actions.is_empty());
+        assert!(healthy.to_string().contains("进程健康"));
+
+        let unhealthy = engine.explain(&busy(40));
+        assert!(!unhealthy.skipped_healthy);
+        assert_eq!(
+            unhealthy.actions.iter().map(|a| a.action_type).collect::<Vec<_>>(),
+            vec![ActionType::CloseTimeWait, ActionType::CloseCloseWait],
+        );
+    }
+
+    #[test]
+    fn actions_downgrade_to_alert_outside_active_hours() {
+        use chrono::TimeZone;
+
+        let mut policy = AppPolicy::crawler("scraper");
+        policy.active_hours = Some((22, 6));
+        let mut policies = PolicyManager::new();
+        policies.set_policy(policy);
+        let engine = OptimizationEngine::new(policies);
+        let stats = ProcessTcpStats {
+            pid: 42,
+            process_name: "scraper".into(),
+            close_wait: 25,
+            ..Default::default()
+        };
+        let at = |hour| SystemTime::from(chrono::Local.with_ymd_and_hms(2024, 1, 15, hour, 30, 0).unwrap());
+
+        // 跨午夜窗口的两侧都生效
+        for hour in [23, 2] {
+            let decision = engine.explain_at(&stats, at(hour));
+            assert!(!decision.outside_active_hours);
+            assert_eq!(decision.actions[0].action_type, ActionType::CloseCloseWait);
+        }
+
+        let decision = engine.explain_at(&stats, at(10));
+        assert!(decision.outside_active_hours);
+        assert_eq!(decision.actions[0].action_type, ActionType::None);
+        assert_eq!(decision.actions[0].connections_affected, 0);
+        assert!(decision.to_string().contains("不在生效时段"));
+    }
+
+    #[test]
+    fn excess_connections_prefers_time_wait_and_skips_protected() {
+        let connections = vec![
+            conn(50000, 443, TcpState::Established),
+            conn(50001, 443, TcpState::CloseWait),
+            conn(50002, 22, TcpState::TimeWait),
+            conn(50003, 443, TcpState::TimeWait),
+            conn(50004, 443, TcpState::CloseWait),
+        ];
+        let protected = |c: &TcpConnection| c.remote_port == 22;
+
+        let ports = |max| excess_connections(&connections, max, protected).iter().map(|c| c.local_port).collect::<Vec<_>>();
+        assert_eq!(ports(3), vec![50003, 50001]);
+        assert_eq!(ports(5), Vec::<u16>::new());
+        // 可回收的连接不足，ESTABLISHED 和受保护端口保持不动
+        assert_eq!(ports(0), vec![50003, 50001, 50004]);
+    }
+
+    #[test]
+    fn connection_limit_follows_threshold_action() {
+        let over_limit = ProcessTcpStats {
+            pid: 42,
+            process_name: "web".into(),
+            total_connections: 150,
+            established: 120,
+            time_wait: 25,
+            close_wait: 5,
+            ..Default::default()
+        };
+        let engine = |threshold_action| {
+            let mut policy = AppPolicy::restricted("web");
+            policy.time_wait_threshold = None;
+            policy.close_wait_threshold = None;
+            policy.threshold_action = threshold_action;
+            let mut policies = PolicyManager::new();
+            policies.set_policy(policy);
+            OptimizationEngine::new(policies)
+        };
+
+        let decision = engine(ThresholdAction::Optimize).explain(&over_limit);
+        assert_eq!(decision.connection_limit, Some(LimitCheck { value: 150, limit: 100, exceeded: true }));
+        assert_eq!(decision.actions.len(), 1);
+        assert_eq!(decision.actions[0].action_type, ActionType::CloseExcessConnections);
+        // 只有 30 个可回收连接
+        assert_eq!(decision.actions[0].connections_affected, 30);
+        assert!(decision.to_string().contains("连接数 150 > 100"));
+
+        let decision = engine(ThresholdAction::Alert).explain(&over_limit);
+        assert_eq!(decision.actions[0].action_type, ActionType::None);
+        assert!(decision.actions[0].reason.starts_with("告警"));
+
+        for action in [ThresholdAction::Ignore, ThresholdAction::RestartProcess] {
+            let decision = engine(action).explain(&over_limit);
+            assert_eq!(decision.actions[0].action_type, ActionType::None);
+            assert_eq!(decision.actions[0].connections_affected, 0);
+        }
+
+        // 全部是 ESTABLISHED 时无法回收，只告警
+        let all_established = ProcessTcpStats { established: 150, time_wait: 0, close_wait: 0, ..over_limit.clone() };
+        let decision = engine(ThresholdAction::Optimize).explain(&all_established);
+        assert_eq!(decision.actions[0].action_type, ActionType::None);
+        assert!(decision.actions[0].reason.contains("没有可回收"));
+
+        let under_limit = ProcessTcpStats { total_connections: 80, ..over_limit };
+        let decision = engine(ThresholdAction::Optimize).explain(&under_limit);
+        assert!(!decision.connection_limit.unwrap().exceeded);
+        assert!(decision.actions.is_empty());
+    }
+
+    #[test]
+    fn connection_limit_counts_threshold_cleanup() {
+        let mut policies = PolicyManager::new();
+        policies.set_policy(AppPolicy::restricted("web"));
+        let engine = OptimizationEngine::new(policies);
+        let stats = ProcessTcpStats {
+            pid: 42,
+            process_name: "web".into(),
+            total_connections: 130,
+            established: 40,
+            time_wait: 80,
+            close_wait: 10,
+            ..Default::default()
+        };
+
+        // TIME_WAIT 超阈值 50 关闭 30 个，剩余超出的 0 个不再单独清理
+        let decision = engine.explain(&stats);
+        let types = decision.actions.iter().map(|a| a.action_type).collect::<Vec<_>>();
+        assert_eq!(types, vec![ActionType::CloseTimeWait]);
+
+        let decision = engine.explain(&ProcessTcpStats { total_connections: 140, established: 50, ..stats });
+        assert_eq!(decision.actions[1].action_type, ActionType::CloseExcessConnections);
+        assert_eq!(decision.actions[1].connections_affected, 10);
+    }
+
+    #[test]
+    fn engine_counts_policy_triggers() {
+        let mut policies = PolicyManager::new();
+        policies.set_policy(AppPolicy::crawler("scraper"));
+        let mut engine = OptimizationEngine::new(policies);
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let busy = ProcessTcpStats {
+            pid: 42,
+            process_name: "scraper".into(),
+            close_wait: 25,
+            ..Default::default()
+        };
+        let idle = ProcessTcpStats {
+            pid: 7,
+            process_name: "idle".into(),
+            ..Default::default()
+        };
+
+        assert!(engine.policy_stats("scraper").is_none());
+        engine.analyze_and_decide_at(&busy, start);
+        engine.analyze_and_decide_at(&busy, start + Duration::from_secs(60));
+        engine.analyze_and_decide_at(&idle, start + Duration::from_secs(60));
+
+        let stats = engine.policy_stats("scraper").unwrap();
+        assert_eq!(stats.total_actions, 2);
+        assert_eq!(stats.connections_affected, 50);
+        assert_eq!(stats.last_triggered, Some(start + Duration::from_secs(60)));
+        // 未触发的默认策略不产生统计
+        assert!(engine.policy_stats("").is_none());
+    }
+
+    #[test]
+    fn summary_counts_mixed_results() {
+        let action = |pid: u32, action_type, affected, success| OptimizationAction {
+            pid,
+            process_name: format!("proc{}", pid),
+            action_type,
+            reason: String::new(),
+            connections_affected: affected,
+            success,
+            error_message: (!success).then(|| "拒绝访问".to_string()),
+        };
+        let actions = [
+            action(1, ActionType::CloseCloseWait, 10, true),
+            action(1, ActionType::CloseTimeWait, 5, true),
+            action(2, ActionType::CloseCloseWait, 0, false),
+            action(3, ActionType::GracefulShutdown, 0, true),
+        ];
+
+        let summary = OptimizationSummary::from_actions(&actions);
+
+        assert_eq!((summary.total_actions, summary.succeeded, summary.failed), (4, 3, 1));
+        assert_eq!(summary.connections_affected, 15);
+        assert_eq!(summary.by_type.get(&ActionType::CloseCloseWait), Some(&2));
+        assert_eq!(summary.processes.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(
+            summary.to_string(),
+            "4 个动作（成功 3，失败 1），影响 15 个连接，涉及 3 个进程：CloseTimeWait×1, CloseCloseWait×2, GracefulShutdown×1"
+        );
+
+        let json = serde_json::to_string(&summary).unwrap();
+        assert_eq!(serde_json::from_str::<OptimizationSummary>(&json).unwrap(), summary);
+    }
+}
@@ -0,0 +1,103 @@
+//! 按 PID 缓存的进程信息
+//!
+//! PID 会被系统回收给新进程，缓存项超过 TTL 后视为过期并重新查询
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// 默认缓存有效期
+pub const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+/// 带过期时间的 PID 缓存，时间由调用方传入便于测试
+#[derive(Debug)]
+pub struct PidCache<V> {
+    entries: HashMap<u32, (Instant, V)>,
+    ttl: Duration,
+}
+
+impl<V: Clone> PidCache<V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            ttl,
+        }
+    }
+
+    /// 获取未过期的缓存项，过期的会被移除
+    pub fn get(&mut self, pid: u32, now: Instant) -> Option<V> {
+        match self.entries.get(&pid) {
+            Some((at, value)) if now.saturating_duration_since(*at) < self.ttl => Some(value.clone()),
+            Some(_) => {
+                self.entries.remove(&pid);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&mut self, pid: u32, value: V, now: Instant) {
+        self.entries.insert(pid, (now, value));
+    }
+
+    /// 使缓存项失效
+    pub fn remove(&mut self, pid: u32) {
+        self.entries.remove(&pid);
+    }
+
+    /// 只保留 `keep` 返回 true 的 PID
+    pub fn retain(&mut self, mut keep: impl FnMut(u32) -> bool) {
+        self.entries.retain(|pid, _| keep(*pid));
+    }
+
+    /// 命中时返回缓存值，否则调用 `lookup` 查询并缓存
+    pub fn get_or_insert_with(&mut self, pid: u32, now: Instant, lookup: impl FnOnce() -> V) -> V {
+        if let Some(value) = self.get(pid, now) {
+            return value;
+        }
+        let value = lookup();
+        self.insert(pid, value.clone(), now);
+        value
+    }
+}
+
+impl<V: Clone> Default for PidCache<V> {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recycled_pid_is_looked_up_again_after_ttl() {
+        let mut cache = PidCache::new(Duration::from_secs(30));
+        let start = Instant::now();
+        let mut lookups = 0;
+
+        let mut lookup = |name: &str| {
+            lookups += 1;
+            name.to_string()
+        };
+
+        assert_eq!(cache.get_or_insert_with(1234, start, || lookup("chrome.exe")), "chrome.exe");
+        // TTL 内命中缓存，即使 PID 已被回收
+        let within = start + Duration::from_secs(29);
+        assert_eq!(cache.get_or_insert_with(1234, within, || lookup("python.exe")), "chrome.exe");
+        // 过期后重新查询到新进程名
+        let after = start + Duration::from_secs(30);
+        assert_eq!(cache.get_or_insert_with(1234, after, || lookup("python.exe")), "python.exe");
+
+        assert_eq!(lookups, 2);
+    }
+
+    #[test]
+    fn remove_invalidates_entry() {
+        let mut cache = PidCache::new(DEFAULT_TTL);
+        let now = Instant::now();
+        cache.insert(7, "svc".to_string(), now);
+        cache.remove(7);
+        assert_eq!(cache.get(7, now), None);
+    }
+}
@@ -0,0 +1,87 @@
+//! 外部命令调用（sysctl / netstat / ps / tasklist 等）
+//!
+//! 把启动失败、权限不足和非零退出码区分为不同的错误，便于界面给出对应提示
+
+use crate::{NetOptError, Result};
+use std::io::ErrorKind;
+use std::process::{Command, Output};
+
+/// stderr 中表示权限不足的常见文本
+const PERMISSION_MARKERS: &[&str] = &[
+    "Permission denied",
+    "Operation not permitted",
+    "Access is denied",
+    "拒绝访问",
+];
+
+/// 找不到命令时给用户的提示
+pub fn missing_hint(program: &str) -> &'static str {
+    match program {
+        // 精简容器常不带 net-tools
+        "netstat" => "请安装 net-tools 或将其加入 PATH（Windows 默认使用 IP Helper API、Linux 读取 /proc，均不需要 netstat）",
+        _ => "请确认已安装并在 PATH 中",
+    }
+}
+
+/// 执行命令并等待结束，退出码非零时返回错误
+pub fn run_command(program: &str, args: &[&str]) -> Result<Output> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => NetOptError::CommandNotFound(program.to_string()),
+            ErrorKind::PermissionDenied => NetOptError::PermissionDenied,
+            _ => NetOptError::SystemError(format!("启动 {} 失败: {}", program, e)),
+        })?;
+
+    if output.status.success() {
+        return Ok(output);
+    }
+    Err(failure(program, output.status.code(), &String::from_utf8_lossy(&output.stderr)))
+}
+
+/// 按 stderr 内容把非零退出归类为权限不足或一般失败
+fn failure(program: &str, code: Option<i32>, stderr: &str) -> NetOptError {
+    if PERMISSION_MARKERS.iter().any(|m| stderr.contains(m)) {
+        return NetOptError::PermissionDenied;
+    }
+    NetOptError::CommandFailed {
+        command: program.to_string(),
+        code,
+        stderr: stderr.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_binary_is_command_not_found() {
+        let err = run_command("netopt-no-such-command", &[]).unwrap_err();
+        assert!(matches!(err, NetOptError::CommandNotFound(ref name) if name == "netopt-no-such-command"));
+        assert!(err.to_string().contains("PATH"));
+        assert!(NetOptError::CommandNotFound("netstat".into()).to_string().contains("net-tools"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_zero_exit_keeps_code_and_stderr() {
+        let err = run_command("sh", &["-c", "echo oops >&2; exit 3"]).unwrap_err();
+        match err {
+            NetOptError::CommandFailed { command, code, stderr } => {
+                assert_eq!(command, "sh");
+                assert_eq!(code, Some(3));
+                assert_eq!(stderr, "oops");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn permission_text_maps_to_permission_denied() {
+        let err = failure("sysctl", Some(1), "sysctl: net.inet.tcp.msl: Operation not permitted\n");
+        assert!(matches!(err, NetOptError::PermissionDenied));
+        assert!(matches!(failure("sysctl", Some(1), "unknown oid"), NetOptError::CommandFailed { .. }));
+    }
+}
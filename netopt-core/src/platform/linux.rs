@@ -0,0 +1,493 @@
+//! Linux平台TCP管理实现
+//!
+//! 通过 /proc/sys/net/ipv4 读写内核参数，解析 /proc/net/tcp{,6} 获取连接
+
+use crate::{
+    Result, NetOptError, TcpConnection, TcpState, ProcessTcpStats, SystemTcpStats,
+    TcpSystemConfig, UdpSocketInfo,
+};
+use crate::tcp_config::{ConfigBackend, TcpConfigManager, TwReuse};
+use crate::monitor::{
+    TcpMonitor, StatsOptions, calculate_family_stats, calculate_health_score, calculate_stats, ephemeral_ports_in_use, port_usage, summarize_processes, unknown_state_samples,
+};
+use crate::optimizer::ConnectionOptimizer;
+use crate::policy::AppPolicy;
+use super::cache::PidCache;
+use super::procfs;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+const SYSCTL_ROOT: &str = "/proc/sys/net/ipv4";
+
+/// 检查是否有root权限
+pub fn is_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// 以文件形式读写 `root` 下的内核参数，默认为 /proc/sys/net/ipv4
+pub struct ProcSysBackend {
+    root: PathBuf,
+}
+
+impl ProcSysBackend {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Default for ProcSysBackend {
+    fn default() -> Self {
+        Self::new(SYSCTL_ROOT)
+    }
+}
+
+impl ConfigBackend for ProcSysBackend {
+    fn read(&self, key: &str) -> Option<String> {
+        fs::read_to_string(self.root.join(key))
+            .ok()
+            .map(|s| s.trim().to_string())
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<()> {
+        fs::write(self.root.join(key), value).map_err(|e| match e.kind() {
+            ErrorKind::PermissionDenied => NetOptError::PermissionDenied,
+            _ => NetOptError::SystemError(format!("写入 net.ipv4.{} 失败: {}", key, e)),
+        })
+    }
+}
+
+/// Linux TCP配置管理器
+pub struct LinuxTcpConfigManager {
+    backend: Arc<dyn ConfigBackend>,
+}
+
+impl Default for LinuxTcpConfigManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinuxTcpConfigManager {
+    pub fn new() -> Self {
+        Self::with_backend(Arc::new(ProcSysBackend::default()))
+    }
+
+    /// 使用指定的参数存储，如测试用的 `MemoryBackend`
+    pub fn with_backend(backend: Arc<dyn ConfigBackend>) -> Self {
+        Self { backend }
+    }
+
+    fn sysctl_get(&self, name: &str) -> Option<u32> {
+        self.backend.read_u32(name)
+    }
+
+    fn sysctl_set(&self, name: &str, value: &str) -> Result<()> {
+        self.backend.write(name, value)
+    }
+
+    /// 读取 ip_local_port_range，返回 (起始, 结束)
+    fn port_range(&self) -> Option<(u32, u32)> {
+        let range = self.backend.read("ip_local_port_range")?;
+        let mut parts = range.split_whitespace().map(|p| p.parse().ok());
+        Some((parts.next()??, parts.next()??))
+    }
+}
+
+impl TcpConfigManager for LinuxTcpConfigManager {
+    fn get_current_config(&self) -> Result<TcpSystemConfig> {
+        let range = self.port_range();
+        Ok(TcpSystemConfig {
+            max_user_port: range.map(|(_, end)| end),
+            // Linux TIME_WAIT 固定为 60 秒，最接近的可调参数是 tcp_fin_timeout
+            time_wait_delay: self.sysctl_get("tcp_fin_timeout"),
+            dynamic_port_start: range.map(|(start, _)| start),
+            max_syn_retransmissions: self.sysctl_get("tcp_syn_retries"),
+            keep_alive_time: self.sysctl_get("tcp_keepalive_time"),
+            keep_alive_interval: self.sysctl_get("tcp_keepalive_intvl"),
+            // tcp_tw_reuse: 0=关闭, 1=开启, 2=仅回环（默认）
+            tw_reuse: self.sysctl_get("tcp_tw_reuse").and_then(TwReuse::from_u32),
+            max_tw_buckets: self.sysctl_get("tcp_max_tw_buckets"),
+        })
+    }
+
+    fn apply_config(&self, config: &TcpSystemConfig) -> Result<()> {
+        config.validate()?;
+        if self.backend.is_system() {
+            crate::backup::auto_backup(self)?;
+        }
+
+        if config.dynamic_port_start.is_some() || config.max_user_port.is_some() {
+            // 端口范围必须同时写入，缺省的一端保持当前值
+            let (cur_start, cur_end) = self.port_range().unwrap_or((32768, 60999));
+            let start = config.dynamic_port_start.unwrap_or(cur_start);
+            let end = config.max_user_port.unwrap_or(cur_end);
+            if start >= end {
+                return Err(NetOptError::InvalidParameter(
+                    format!("dynamic_port_start ({}) 必须小于 max_user_port ({})", start, end)
+                ));
+            }
+            self.sysctl_set("ip_local_port_range", &format!("{} {}", start, end))?;
+        }
+        if let Some(v) = config.time_wait_delay {
+            self.sysctl_set("tcp_fin_timeout", &v.to_string())?;
+        }
+        if let Some(v) = config.max_syn_retransmissions {
+            self.sysctl_set("tcp_syn_retries", &v.to_string())?;
+        }
+        if let Some(v) = config.keep_alive_time {
+            self.sysctl_set("tcp_keepalive_time", &v.to_string())?;
+        }
+        if let Some(v) = config.keep_alive_interval {
+            self.sysctl_set("tcp_keepalive_intvl", &v.to_string())?;
+        }
+        if let Some(v) = config.tw_reuse {
+            self.sysctl_set("tcp_tw_reuse", &v.as_u32().to_string())?;
+        }
+        if let Some(v) = config.max_tw_buckets {
+            self.sysctl_set("tcp_max_tw_buckets", &v.to_string())?;
+        }
+
+        crate::tcp_config::verify_applied(self, config)
+    }
+
+    fn get_default_config(&self) -> TcpSystemConfig {
+        TcpSystemConfig {
+            max_user_port: Some(60999),
+            time_wait_delay: Some(60),
+            dynamic_port_start: Some(32768),
+            max_syn_retransmissions: Some(6),
+            keep_alive_time: Some(7200),
+            keep_alive_interval: Some(75),
+            tw_reuse: Some(TwReuse::LoopbackOnly),
+            max_tw_buckets: Some(262144),
+        }
+    }
+
+    fn has_admin_privileges(&self) -> bool {
+        is_root()
+    }
+
+    fn requires_reboot(&self) -> bool {
+        false // /proc/sys 修改立即生效
+    }
+}
+
+/// Linux TCP监控器
+#[derive(Default)]
+pub struct LinuxTcpMonitor {
+    /// 可执行文件路径缓存 (PID -> 路径)
+    exe_cache: Mutex<PidCache<Option<String>>>,
+}
+
+impl LinuxTcpMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 解析 /proc/net/tcp 和 /proc/net/tcp6
+    fn parse_proc_net(&self) -> Result<Vec<TcpConnection>> {
+        let inodes = Self::socket_inode_map();
+        let mut names: HashMap<u32, String> = HashMap::new();
+        let mut connections = Vec::new();
+
+        for (path, ipv6) in [("/proc/net/tcp", false), ("/proc/net/tcp6", true)] {
+            let Some(content) = Self::read_proc_table(path, ipv6)? else {
+                continue;
+            };
+            for mut conn in procfs::parse_tcp(&content, ipv6, &inodes) {
+                if conn.pid > 0 {
+                    conn.process_name = names
+                        .entry(conn.pid)
+                        .or_insert_with(|| Self::get_process_name(conn.pid))
+                        .clone();
+                }
+                connections.push(conn);
+            }
+        }
+
+        Ok(connections)
+    }
+
+    /// 解析 /proc/net/udp 和 /proc/net/udp6，格式与 tcp 相同
+    fn parse_proc_udp(&self) -> Result<Vec<UdpSocketInfo>> {
+        let inodes = Self::socket_inode_map();
+        let mut names: HashMap<u32, String> = HashMap::new();
+        let mut sockets = Vec::new();
+
+        for (path, ipv6) in [("/proc/net/udp", false), ("/proc/net/udp6", true)] {
+            let Some(content) = Self::read_proc_table(path, ipv6)? else {
+                continue;
+            };
+            for mut socket in procfs::parse_udp(&content, ipv6, &inodes) {
+                if socket.pid > 0 {
+                    socket.process_name = names
+                        .entry(socket.pid)
+                        .or_insert_with(|| Self::get_process_name(socket.pid))
+                        .clone();
+                }
+                sockets.push(socket);
+            }
+        }
+
+        Ok(sockets)
+    }
+
+    /// 读取 /proc/net 下的连接表，未启用 IPv6 时 *6 文件不存在，返回 None
+    fn read_proc_table(path: &str, ipv6: bool) -> Result<Option<String>> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if ipv6 && e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(NetOptError::SystemError(format!("读取 {} 失败: {}", path, e))),
+        }
+    }
+
+    /// 遍历 /proc/<pid>/fd，建立 socket inode -> pid 映射
+    ///
+    /// 非 root 时只能看到当前用户的进程
+    fn socket_inode_map() -> HashMap<u64, u32> {
+        let mut map = HashMap::new();
+        let Ok(procs) = fs::read_dir("/proc") else {
+            return map;
+        };
+
+        for entry in procs.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+                continue;
+            };
+            let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+                continue;
+            };
+            for fd in fds.flatten() {
+                let Ok(target) = fs::read_link(fd.path()) else {
+                    continue;
+                };
+                let inode = target
+                    .to_str()
+                    .and_then(|t| t.strip_prefix("socket:["))
+                    .and_then(|t| t.strip_suffix(']'))
+                    .and_then(|t| t.parse().ok());
+                if let Some(inode) = inode {
+                    map.insert(inode, pid);
+                }
+            }
+        }
+
+        map
+    }
+
+    /// 读取 /proc/<pid>/exe 获取可执行文件路径，结果按 PID 缓存
+    ///
+    /// 非 root 时读取其他用户的进程会失败，返回 None
+    fn get_exe_path(&self, pid: u32) -> Option<String> {
+        if pid == 0 {
+            return None;
+        }
+        let mut cache = self.exe_cache.lock().unwrap();
+        cache.get_or_insert_with(pid, Instant::now(), || {
+            fs::read_link(format!("/proc/{}/exe", pid))
+                .ok()
+                .map(|p| p.to_string_lossy().into_owned())
+        })
+    }
+
+    fn get_process_name(pid: u32) -> String {
+        fs::read_to_string(format!("/proc/{}/comm", pid))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl TcpMonitor for LinuxTcpMonitor {
+    fn get_all_connections(&self) -> Result<Vec<TcpConnection>> {
+        self.parse_proc_net()
+    }
+
+    fn get_udp_sockets(&self) -> Result<Vec<UdpSocketInfo>> {
+        self.parse_proc_udp()
+    }
+
+    fn get_process_connections(&self, pid: u32) -> Result<Vec<TcpConnection>> {
+        let all = self.get_all_connections()?;
+        Ok(all.into_iter().filter(|c| c.pid == pid).collect())
+    }
+
+    fn get_process_stats(&self, pid: u32) -> Result<ProcessTcpStats> {
+        self.process_stats_from(pid, &self.get_all_connections()?)
+    }
+
+    fn process_stats_from(&self, pid: u32, connections: &[TcpConnection]) -> Result<ProcessTcpStats> {
+        let connections: Vec<&TcpConnection> = connections.iter().filter(|c| c.pid == pid).collect();
+        let process_name = if connections.is_empty() {
+            Self::get_process_name(pid)
+        } else {
+            connections[0].process_name.clone()
+        };
+
+        let mut stats = ProcessTcpStats {
+            pid,
+            process_name,
+            exe_path: self.get_exe_path(pid),
+            total_connections: connections.len(),
+            ..Default::default()
+        };
+
+        for conn in &connections {
+            stats.record_state(conn.state);
+        }
+
+        stats.health_score = calculate_health_score(&stats);
+        Ok(stats)
+    }
+
+    fn get_system_stats(&self) -> Result<SystemTcpStats> {
+        self.get_system_stats_with(&StatsOptions::default())
+    }
+
+    fn get_system_stats_with(&self, options: &StatsOptions) -> Result<SystemTcpStats> {
+        self.system_stats_from(&self.get_all_connections()?, options)
+    }
+
+    fn system_stats_from(&self, connections: &[TcpConnection], options: &StatsOptions) -> Result<SystemTcpStats> {
+        let mut by_process = summarize_processes(connections, options);
+        for p in &mut by_process {
+            p.exe_path = self.get_exe_path(p.pid);
+        }
+
+        // 获取端口范围计算可用端口，调用方指定时不读取系统配置
+        let (port_start, port_end) = options.port_range.unwrap_or_else(|| {
+            let config = LinuxTcpConfigManager::new().get_current_config().unwrap_or_default();
+            (config.dynamic_port_start.unwrap_or(32768), config.max_user_port.unwrap_or(60999))
+        });
+        let used_ports = ephemeral_ports_in_use(connections, port_start, port_end);
+        let usage = port_usage(used_ports, port_start, port_end);
+
+        Ok(SystemTcpStats {
+            total_connections: connections.len(),
+            by_state: calculate_stats(connections),
+            by_family: calculate_family_stats(connections),
+            by_process,
+            available_ports: usage.available,
+            ephemeral_ports_in_use: used_ports,
+            port_usage_percent: usage.percent,
+            dynamic_port_range: Some((port_start, port_end)),
+            udp_ports_used: None,
+            unknown_state_samples: unknown_state_samples(connections),
+        })
+    }
+}
+
+/// Linux 连接优化器
+#[derive(Default)]
+pub struct LinuxConnectionOptimizer;
+
+impl LinuxConnectionOptimizer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ConnectionOptimizer for LinuxConnectionOptimizer {
+    fn close_connection(&self, _conn: &TcpConnection) -> Result<()> {
+        // 需要 ss -K（CONFIG_INET_DIAG_DESTROY），暂不支持
+        Err(NetOptError::UnsupportedPlatform(
+            "Linux暂不支持直接关闭TCP连接".into()
+        ))
+    }
+
+    fn close_connections_by_state(&self, _pid: u32, _state: TcpState, _policy: &AppPolicy) -> Result<usize> {
+        Err(NetOptError::UnsupportedPlatform(
+            "Linux暂不支持直接关闭TCP连接".into()
+        ))
+    }
+
+    fn optimize_process(&self, pid: u32, policy: &AppPolicy) -> Result<crate::optimizer::OptimizationAction> {
+        // 无法直接关闭连接，只能按策略发送信号建议进程自己清理
+        super::signal::signal_if_exceeded(pid, policy, || LinuxTcpMonitor::new().get_process_stats(pid))
+    }
+
+    fn optimize_process_from(
+        &self,
+        pid: u32,
+        policy: &AppPolicy,
+        connections: &[TcpConnection],
+    ) -> Result<crate::optimizer::OptimizationAction> {
+        super::signal::signal_if_exceeded(pid, policy, || LinuxTcpMonitor::new().process_stats_from(pid, connections))
+    }
+
+    fn supports_connection_control(&self) -> bool {
+        false
+    }
+
+    fn connection_table(&self) -> Result<Vec<TcpConnection>> {
+        LinuxTcpMonitor::new().get_all_connections()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp_config::MemoryBackend;
+
+    #[test]
+    fn config_round_trips_through_backend() {
+        let backend = Arc::new(
+            MemoryBackend::new()
+                .with("ip_local_port_range", "32768\t60999")
+                .with("tcp_tw_reuse", 2),
+        );
+        let mgr = LinuxTcpConfigManager::with_backend(backend.clone());
+
+        let current = mgr.get_current_config().unwrap();
+        assert_eq!((current.dynamic_port_start, current.max_user_port), (Some(32768), Some(60999)));
+        assert_eq!(current.tw_reuse, Some(TwReuse::LoopbackOnly));
+
+        // 只改起始端口，结束端口保持当前值
+        let config = TcpSystemConfig::builder().dynamic_port_start(20000).tw_reuse(true).keep_alive_time(600).build();
+        mgr.apply_config(&config).unwrap();
+        assert_eq!(backend.read("ip_local_port_range").as_deref(), Some("20000 60999"));
+        assert_eq!(backend.read("tcp_tw_reuse").as_deref(), Some("1"));
+
+        // 校验失败时不写入
+        assert!(mgr.apply_config(&TcpSystemConfig::builder().keep_alive_time(0).build()).is_err());
+        assert_eq!(backend.read_u32("tcp_keepalive_time"), Some(600));
+    }
+
+    #[test]
+    fn loopback_only_tw_reuse_survives_backup_and_reset() {
+        let backend = Arc::new(MemoryBackend::new().with("tcp_tw_reuse", 2));
+        let mgr = LinuxTcpConfigManager::with_backend(backend.clone());
+        let path = std::env::temp_dir().join(format!("netopt-tw-reuse-{}.json", std::process::id()));
+
+        crate::backup::write_config(&path, &mgr.get_current_config().unwrap()).unwrap();
+        mgr.apply_config(&TcpSystemConfig::builder().tw_reuse(true).build()).unwrap();
+        assert_eq!(backend.read_u32("tcp_tw_reuse"), Some(1));
+
+        // 恢复备份写回 2，而不是 0
+        mgr.apply_config(&crate::backup::read_config(&path).unwrap()).unwrap();
+        assert_eq!(backend.read_u32("tcp_tw_reuse"), Some(2));
+        fs::remove_file(&path).unwrap();
+
+        mgr.apply_config(&TcpSystemConfig::builder().tw_reuse(false).build()).unwrap();
+        mgr.reset_to_default(&["tw_reuse"]).unwrap();
+        assert_eq!(backend.read_u32("tcp_tw_reuse"), Some(2));
+    }
+
+    #[test]
+    fn proc_sys_backend_uses_custom_root() {
+        let root = std::env::temp_dir().join(format!("netopt-procsys-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("tcp_fin_timeout"), "60\n").unwrap();
+        let backend = ProcSysBackend::new(&root);
+
+        assert_eq!(backend.read_u32("tcp_fin_timeout"), Some(60));
+        backend.write("tcp_fin_timeout", "30").unwrap();
+        assert_eq!(fs::read_to_string(root.join("tcp_fin_timeout")).unwrap(), "30");
+        assert_eq!(backend.read("tcp_syn_retries"), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
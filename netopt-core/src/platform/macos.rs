@@ -4,58 +4,63 @@
 
 use crate::{
     Result, NetOptError, TcpConnection, TcpState, ProcessTcpStats, SystemTcpStats,
-    TcpSystemConfig,
+    TcpSystemConfig, UdpSocketInfo,
+};
+use crate::tcp_config::{ConfigBackend, TcpConfigManager, millis_to_secs, secs_to_millis};
+use crate::monitor::{
+    TcpMonitor, StatsOptions, calculate_family_stats, calculate_health_score, calculate_stats, ephemeral_ports_in_use, port_usage, summarize_processes, unknown_state_samples,
 };
-use crate::tcp_config::TcpConfigManager;
-use crate::monitor::{TcpMonitor, calculate_health_score};
 use crate::optimizer::ConnectionOptimizer;
 use crate::policy::AppPolicy;
-use std::collections::HashMap;
-use std::process::Command;
+use super::cache::PidCache;
+use super::command::run_command;
+use super::netstat;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 /// 检查是否有root权限
 pub fn is_root() -> bool {
     unsafe { libc::geteuid() == 0 }
 }
 
+/// 通过 sysctl 命令读写内核参数
+#[derive(Debug, Default)]
+pub struct SysctlBackend;
+
+impl ConfigBackend for SysctlBackend {
+    fn read(&self, key: &str) -> Option<String> {
+        run_command("sysctl", &["-n", key])
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<()> {
+        run_command("sysctl", &["-w", &format!("{}={}", key, value)])?;
+        Ok(())
+    }
+}
+
 /// macOS TCP配置管理器
-pub struct MacOsTcpConfigManager;
+pub struct MacOsTcpConfigManager {
+    backend: Arc<dyn ConfigBackend>,
+}
 
 impl MacOsTcpConfigManager {
     pub fn new() -> Self {
-        Self
+        Self::with_backend(Arc::new(SysctlBackend))
+    }
+
+    /// 使用指定的参数存储，如测试用的 `MemoryBackend`
+    pub fn with_backend(backend: Arc<dyn ConfigBackend>) -> Self {
+        Self { backend }
     }
     
     fn sysctl_get(&self, name: &str) -> Option<u32> {
-        Command::new("sysctl")
-            .arg("-n")
-            .arg(name)
-            .output()
-            .ok()
-            .and_then(|o| {
-                String::from_utf8_lossy(&o.stdout)
-                    .trim()
-                    .parse()
-                    .ok()
-            })
+        self.backend.read_u32(name)
     }
     
     fn sysctl_set(&self, name: &str, value: u32) -> Result<()> {
-        let output = Command::new("sysctl")
-            .arg("-w")
-            .arg(format!("{}={}", name, value))
-            .output()
-            .map_err(|e| NetOptError::SystemError(e.to_string()))?;
-        
-        if !output.status.success() {
-            let err = String::from_utf8_lossy(&output.stderr);
-            if err.contains("Permission denied") || err.contains("Operation not permitted") {
-                return Err(NetOptError::PermissionDenied);
-            }
-            return Err(NetOptError::SystemError(err.to_string()));
-        }
-        
-        Ok(())
+        self.backend.write(name, &value.to_string())
     }
 }
 
@@ -66,14 +71,20 @@ impl TcpConfigManager for MacOsTcpConfigManager {
             time_wait_delay: self.sysctl_get("net.inet.tcp.msl").map(|v| v * 2), // MSL * 2 = TIME_WAIT
             dynamic_port_start: self.sysctl_get("net.inet.ip.portrange.first"),
             max_syn_retransmissions: self.sysctl_get("net.inet.tcp.keepinit")
-                .map(|v| v / 1000), // 转换为秒
-            keep_alive_time: self.sysctl_get("net.inet.tcp.keepidle"),
-            keep_alive_interval: self.sysctl_get("net.inet.tcp.keepintvl"),
+                .map(millis_to_secs), // 转换为秒
+            // keepidle/keepintvl 单位为毫秒
+            keep_alive_time: self.sysctl_get("net.inet.tcp.keepidle").map(millis_to_secs),
+            keep_alive_interval: self.sysctl_get("net.inet.tcp.keepintvl").map(millis_to_secs),
+            tw_reuse: None, // 仅 Linux
+            max_tw_buckets: None,
         })
     }
     
     fn apply_config(&self, config: &TcpSystemConfig) -> Result<()> {
         config.validate()?;
+        if self.backend.is_system() {
+            crate::backup::auto_backup(self)?;
+        }
         
         if let Some(v) = config.max_user_port {
             self.sysctl_set("net.inet.ip.portrange.last", v)?;
@@ -86,13 +97,21 @@ impl TcpConfigManager for MacOsTcpConfigManager {
             self.sysctl_set("net.inet.ip.portrange.first", v)?;
         }
         if let Some(v) = config.keep_alive_time {
-            self.sysctl_set("net.inet.tcp.keepidle", v)?;
+            self.sysctl_set("net.inet.tcp.keepidle", secs_to_millis(v))?;
         }
         if let Some(v) = config.keep_alive_interval {
-            self.sysctl_set("net.inet.tcp.keepintvl", v)?;
+            self.sysctl_set("net.inet.tcp.keepintvl", secs_to_millis(v))?;
         }
         
-        Ok(())
+        // 只校验实际写入的字段；MSL 取整后 TIME_WAIT 读回为偶数
+        let expected = TcpSystemConfig {
+            time_wait_delay: config.time_wait_delay.map(|v| v / 2 * 2),
+            max_syn_retransmissions: None,
+            tw_reuse: None,
+            max_tw_buckets: None,
+            ..config.clone()
+        };
+        crate::tcp_config::verify_applied(self, &expected)
     }
     
     fn get_default_config(&self) -> TcpSystemConfig {
@@ -103,6 +122,8 @@ impl TcpConfigManager for MacOsTcpConfigManager {
             max_syn_retransmissions: Some(3),
             keep_alive_time: Some(7200),
             keep_alive_interval: Some(75),
+            tw_reuse: None, // 仅 Linux
+            max_tw_buckets: None,
         }
     }
     
@@ -116,99 +137,78 @@ impl TcpConfigManager for MacOsTcpConfigManager {
 }
 
 /// macOS TCP监控器
-pub struct MacOsTcpMonitor;
+pub struct MacOsTcpMonitor {
+    /// 可执行文件路径缓存 (PID -> 路径)
+    exe_cache: Mutex<PidCache<Option<String>>>,
+}
 
 impl MacOsTcpMonitor {
     pub fn new() -> Self {
-        Self
+        Self {
+            exe_cache: Mutex::new(PidCache::default()),
+        }
     }
     
     /// 使用 netstat 获取连接信息
+    ///
+    /// 不走 libproc 的 `PROC_PIDFDSOCKETINFO`：TIME_WAIT 套接字已无归属 fd，
+    /// 按进程枚举会漏掉它们，而这正是本工具的核心指标
     fn parse_netstat(&self) -> Result<Vec<TcpConnection>> {
-        let output = Command::new("netstat")
-            .args(["-anv", "-p", "tcp"])
-            .output()
-            .map_err(|e| NetOptError::SystemError(e.to_string()))?;
+        let output = run_command("netstat", &["-anv", "-p", "tcp"])?;
         
         let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut connections = Vec::new();
-        
-        for line in stdout.lines().skip(2) {
-            if let Some(conn) = self.parse_netstat_line(line) {
-                connections.push(conn);
-            }
-        }
-        
-        Ok(connections)
+        Ok(netstat::parse_macos_tcp(&stdout))
     }
     
-    fn parse_netstat_line(&self, line: &str) -> Option<TcpConnection> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 9 {
+    /// 使用 netstat 获取 UDP 套接字
+    fn parse_netstat_udp(&self) -> Result<Vec<UdpSocketInfo>> {
+        let output = run_command("netstat", &["-anv", "-p", "udp"])?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(netstat::parse_macos_udp(&stdout))
+    }
+
+    /// 通过 proc_pidpath 获取可执行文件路径，结果按 PID 缓存
+    fn get_exe_path(&self, pid: u32) -> Option<String> {
+        if pid == 0 {
             return None;
         }
-
-        // 解析本地地址和端口
-        let (local_addr, local_port) = Self::parse_addr_port(parts[3])?;
-        let (remote_addr, remote_port) = Self::parse_addr_port(parts[4])?;
-        let state = Self::parse_state(parts[5]);
-
-        // macOS netstat -v 的格式是 "process:pid" 在第11列 (索引10)
-        // 例如: "PacketTunnel:6508" 或 "Code Helper (Plu:68231"
-        let (process_name, pid) = parts.get(10)
-            .map(|s| Self::parse_process_pid(s))
-            .unwrap_or((String::new(), 0));
-
-        Some(TcpConnection {
-            local_addr,
-            local_port,
-            remote_addr,
-            remote_port,
-            state,
-            pid,
-            process_name,
-        })
+        let mut cache = self.exe_cache.lock().unwrap();
+        cache.get_or_insert_with(pid, Instant::now(), || Self::query_exe_path(pid))
     }
 
-    /// 解析 "process:pid" 格式，如 "PacketTunnel:6508"
-    fn parse_process_pid(s: &str) -> (String, u32) {
-        if let Some(idx) = s.rfind(':') {
-            let name = s[..idx].to_string();
-            let pid = s[idx + 1..].parse().unwrap_or(0);
-            (name, pid)
-        } else {
-            (String::new(), 0)
+    fn query_exe_path(pid: u32) -> Option<String> {
+        let mut buf = vec![0u8; libc::PROC_PIDPATHINFO_MAXSIZE as usize];
+        let len = unsafe { libc::proc_pidpath(pid as i32, buf.as_mut_ptr() as *mut libc::c_void, buf.len() as u32) };
+        if len <= 0 {
+            return None;
         }
+        buf.truncate(len as usize);
+        Some(String::from_utf8_lossy(&buf).into_owned())
     }
-    
-    fn parse_addr_port(s: &str) -> Option<(String, u16)> {
-        let idx = s.rfind('.')?;
-        let addr = s[..idx].to_string();
-        let port = s[idx + 1..].parse().ok()?;
-        Some((addr, port))
+
+    /// 获取进程名：优先 libproc 的 proc_name，失败时回退到 ps。
+    /// 启用 `text-fallback` 特性时始终使用 ps
+    fn get_process_name(&self, pid: u32) -> String {
+        if !cfg!(feature = "text-fallback") {
+            if let Some(name) = Self::query_process_name(pid) {
+                return name;
+            }
+        }
+        Self::ps_process_name(pid)
     }
-    
-    fn parse_state(s: &str) -> TcpState {
-        match s {
-            "LISTEN" => TcpState::Listen,
-            "ESTABLISHED" => TcpState::Established,
-            "TIME_WAIT" => TcpState::TimeWait,
-            "CLOSE_WAIT" => TcpState::CloseWait,
-            "FIN_WAIT_1" => TcpState::FinWait1,
-            "FIN_WAIT_2" => TcpState::FinWait2,
-            "SYN_SENT" => TcpState::SynSent,
-            "SYN_RECEIVED" => TcpState::SynReceived,
-            "CLOSING" => TcpState::Closing,
-            "LAST_ACK" => TcpState::LastAck,
-            "CLOSED" => TcpState::Closed,
-            _ => TcpState::Unknown,
+
+    fn query_process_name(pid: u32) -> Option<String> {
+        let mut buf = [0u8; 256];
+        let len = unsafe { libc::proc_name(pid as i32, buf.as_mut_ptr() as *mut libc::c_void, buf.len() as u32) };
+        if len <= 0 {
+            return None;
         }
+        Some(String::from_utf8_lossy(&buf[..len as usize]).into_owned())
     }
 
-    fn get_process_name(&self, pid: u32) -> String {
-        Command::new("ps")
-            .args(["-p", &pid.to_string(), "-o", "comm="])
-            .output()
+    fn ps_process_name(pid: u32) -> String {
+        run_command("ps", &["-p", &pid.to_string(), "-o", "comm="])
             .ok()
             .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
             .unwrap_or_default()
@@ -227,13 +227,27 @@ impl TcpMonitor for MacOsTcpMonitor {
         Ok(connections)
     }
 
+    fn get_udp_sockets(&self) -> Result<Vec<UdpSocketInfo>> {
+        let mut sockets = self.parse_netstat_udp()?;
+        for socket in &mut sockets {
+            if socket.pid > 0 && socket.process_name.is_empty() {
+                socket.process_name = self.get_process_name(socket.pid);
+            }
+        }
+        Ok(sockets)
+    }
+
     fn get_process_connections(&self, pid: u32) -> Result<Vec<TcpConnection>> {
         let all = self.get_all_connections()?;
         Ok(all.into_iter().filter(|c| c.pid == pid).collect())
     }
 
     fn get_process_stats(&self, pid: u32) -> Result<ProcessTcpStats> {
-        let connections = self.get_process_connections(pid)?;
+        self.process_stats_from(pid, &self.get_all_connections()?)
+    }
+
+    fn process_stats_from(&self, pid: u32, connections: &[TcpConnection]) -> Result<ProcessTcpStats> {
+        let connections: Vec<&TcpConnection> = connections.iter().filter(|c| c.pid == pid).collect();
         let process_name = if connections.is_empty() {
             self.get_process_name(pid)
         } else {
@@ -243,18 +257,13 @@ impl TcpMonitor for MacOsTcpMonitor {
         let mut stats = ProcessTcpStats {
             pid,
             process_name,
+            exe_path: self.get_exe_path(pid),
             total_connections: connections.len(),
             ..Default::default()
         };
 
         for conn in &connections {
-            match conn.state {
-                TcpState::Established => stats.established += 1,
-                TcpState::TimeWait => stats.time_wait += 1,
-                TcpState::CloseWait => stats.close_wait += 1,
-                TcpState::Listen => stats.listen += 1,
-                _ => stats.other += 1,
-            }
+            stats.record_state(conn.state);
         }
 
         stats.health_score = calculate_health_score(&stats);
@@ -262,71 +271,52 @@ impl TcpMonitor for MacOsTcpMonitor {
     }
 
     fn get_system_stats(&self) -> Result<SystemTcpStats> {
-        let connections = self.get_all_connections()?;
-        let mut by_state = HashMap::new();
-        let mut by_process_map: HashMap<u32, ProcessTcpStats> = HashMap::new();
+        self.get_system_stats_with(&StatsOptions::default())
+    }
 
-        for conn in &connections {
-            *by_state.entry(conn.state).or_insert(0) += 1;
-
-            let entry = by_process_map.entry(conn.pid).or_insert_with(|| ProcessTcpStats {
-                pid: conn.pid,
-                process_name: conn.process_name.clone(),
-                ..Default::default()
-            });
-
-            entry.total_connections += 1;
-            match conn.state {
-                TcpState::Established => entry.established += 1,
-                TcpState::TimeWait => entry.time_wait += 1,
-                TcpState::CloseWait => entry.close_wait += 1,
-                TcpState::Listen => entry.listen += 1,
-                _ => entry.other += 1,
-            }
-        }
+    fn get_system_stats_with(&self, options: &StatsOptions) -> Result<SystemTcpStats> {
+        self.system_stats_from(&self.get_all_connections()?, options)
+    }
 
-        let mut by_process: Vec<_> = by_process_map.into_values().collect();
+    fn system_stats_from(&self, connections: &[TcpConnection], options: &StatsOptions) -> Result<SystemTcpStats> {
+        let mut by_process = summarize_processes(connections, options);
         for p in &mut by_process {
-            p.health_score = calculate_health_score(p);
+            p.exe_path = self.get_exe_path(p.pid);
         }
-        by_process.sort_by(|a, b| b.total_connections.cmp(&a.total_connections));
 
-        // 获取端口范围计算可用端口
-        let config_mgr = MacOsTcpConfigManager::new();
-        let config = config_mgr.get_current_config().unwrap_or_default();
-        let port_start = config.dynamic_port_start.unwrap_or(49152) as usize;
-        let port_end = config.max_user_port.unwrap_or(65535) as usize;
-        let total_ports = port_end - port_start + 1;
-        let used_ports = connections.len();
+        // 获取端口范围计算可用端口，调用方指定时不读取系统配置
+        let (port_start, port_end) = options.port_range.unwrap_or_else(|| {
+            let config = MacOsTcpConfigManager::new().get_current_config().unwrap_or_default();
+            (config.dynamic_port_start.unwrap_or(49152), config.max_user_port.unwrap_or(65535))
+        });
+        let used_ports = ephemeral_ports_in_use(connections, port_start, port_end);
+        let usage = port_usage(used_ports, port_start, port_end);
 
         Ok(SystemTcpStats {
             total_connections: connections.len(),
-            by_state,
+            by_state: calculate_stats(connections),
+            by_family: calculate_family_stats(connections),
             by_process,
-            available_ports: total_ports.saturating_sub(used_ports),
-            port_usage_percent: (used_ports as f32 / total_ports as f32) * 100.0,
+            available_ports: usage.available,
+            ephemeral_ports_in_use: used_ports,
+            port_usage_percent: usage.percent,
+            dynamic_port_range: Some((port_start, port_end)),
+            udp_ports_used: None,
+            unknown_state_samples: unknown_state_samples(connections),
         })
     }
-
-    fn get_top_processes(&self, limit: usize) -> Result<Vec<ProcessTcpStats>> {
-        let stats = self.get_system_stats()?;
-        Ok(stats.by_process.into_iter().take(limit).collect())
-    }
-
-    fn get_problematic_processes(&self, threshold: usize) -> Result<Vec<ProcessTcpStats>> {
-        let stats = self.get_system_stats()?;
-        Ok(stats.by_process.into_iter()
-            .filter(|p| p.time_wait > threshold || p.close_wait > threshold / 4)
-            .collect())
-    }
 }
 
 /// macOS 连接优化器
-pub struct MacOsConnectionOptimizer;
+pub struct MacOsConnectionOptimizer {
+    monitor: MacOsTcpMonitor,
+}
 
 impl MacOsConnectionOptimizer {
     pub fn new() -> Self {
-        Self
+        Self {
+            monitor: MacOsTcpMonitor::new(),
+        }
     }
 }
 
@@ -338,23 +328,27 @@ impl ConnectionOptimizer for MacOsConnectionOptimizer {
         ))
     }
 
-    fn close_connections_by_state(&self, _pid: u32, _state: TcpState) -> Result<usize> {
+    fn close_connections_by_state(&self, _pid: u32, _state: TcpState, _policy: &AppPolicy) -> Result<usize> {
         Err(NetOptError::UnsupportedPlatform(
             "macOS不支持直接关闭TCP连接".into()
         ))
     }
 
     fn optimize_process(&self, pid: u32, policy: &AppPolicy) -> Result<crate::optimizer::OptimizationAction> {
-        // 对于 macOS，只能发送信号建议进程自己清理
-        Ok(crate::optimizer::OptimizationAction {
-            pid,
-            process_name: policy.process_name.clone(),
-            action_type: crate::optimizer::ActionType::GracefulShutdown,
-            reason: "macOS仅支持发送信号建议进程清理连接".into(),
-            connections_affected: 0,
-            success: true,
-            error_message: None,
-        })
+        super::signal::signal_if_exceeded(pid, policy, || self.monitor.get_process_stats(pid))
+    }
+
+    fn connection_table(&self) -> Result<Vec<TcpConnection>> {
+        self.monitor.get_all_connections()
+    }
+
+    fn optimize_process_from(
+        &self,
+        pid: u32,
+        policy: &AppPolicy,
+        connections: &[TcpConnection],
+    ) -> Result<crate::optimizer::OptimizationAction> {
+        super::signal::signal_if_exceeded(pid, policy, || self.monitor.process_stats_from(pid, connections))
     }
 
     fn supports_connection_control(&self) -> bool {
@@ -362,3 +356,32 @@ impl ConnectionOptimizer for MacOsConnectionOptimizer {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp_config::MemoryBackend;
+
+    #[test]
+    fn keepalive_is_stored_in_milliseconds() {
+        let backend = Arc::new(
+            MemoryBackend::new()
+                .with("net.inet.tcp.keepidle", 7_200_000)
+                .with("net.inet.tcp.keepintvl", 75_000)
+                .with("net.inet.tcp.msl", 15),
+        );
+        let mgr = MacOsTcpConfigManager::with_backend(backend.clone());
+
+        let current = mgr.get_current_config().unwrap();
+        assert_eq!(current.keep_alive_time, Some(7200));
+        assert_eq!(current.keep_alive_interval, Some(75));
+        assert_eq!(current.time_wait_delay, Some(30));
+
+        let config = TcpSystemConfig::builder().keep_alive_time(600).keep_alive_interval(10).time_wait_delay(45).build();
+        mgr.apply_config(&config).unwrap();
+        assert_eq!(backend.read_u32("net.inet.tcp.keepidle"), Some(600_000));
+        assert_eq!(backend.read_u32("net.inet.tcp.keepintvl"), Some(10_000));
+        // TIME_WAIT = 2 * MSL，奇数向下取整
+        assert_eq!(backend.read_u32("net.inet.tcp.msl"), Some(22));
+        assert_eq!(mgr.get_current_config().unwrap().keep_alive_time, Some(600));
+    }
+}
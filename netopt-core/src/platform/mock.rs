@@ -0,0 +1,135 @@
+//! 模拟监控器
+//!
+//! 基于固定连接表实现 `TcpMonitor`，用于离线回放快照和测试
+
+use crate::monitor::{
+    TcpMonitor, StatsOptions, calculate_family_stats, calculate_health_score, calculate_stats, ephemeral_ports_in_use, port_usage, summarize_processes, unknown_state_samples,
+};
+use crate::snapshot::NetworkSnapshot;
+use crate::{Result, TcpConnection, ProcessTcpStats, SystemTcpStats, UdpSocketInfo};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// 模拟TCP监控器
+#[derive(Debug, Clone)]
+pub struct MockTcpMonitor {
+    connections: Vec<TcpConnection>,
+    udp_sockets: Vec<UdpSocketInfo>,
+    /// 动态端口范围 (起始, 结束)
+    port_range: (u32, u32),
+    /// 读取连接表的次数，对应平台实现中扫描系统连接表的次数
+    scans: Arc<AtomicUsize>,
+}
+
+impl MockTcpMonitor {
+    pub fn new(connections: Vec<TcpConnection>) -> Self {
+        Self {
+            connections,
+            udp_sockets: Vec::new(),
+            port_range: (49152, 65535),
+            scans: Arc::default(),
+        }
+    }
+
+    /// 从快照构造，复现用户当时的连接表和端口范围
+    pub fn from_snapshot(snapshot: &NetworkSnapshot) -> Self {
+        let mut monitor = Self::new(snapshot.connections.clone());
+        if let Some(config) = &snapshot.tcp_config {
+            if let (Some(start), Some(end)) = (config.dynamic_port_start, config.max_user_port) {
+                monitor.port_range = (start, end);
+            }
+        }
+        monitor
+    }
+
+    /// 设置动态端口范围
+    pub fn with_port_range(mut self, start: u32, end: u32) -> Self {
+        self.port_range = (start, end);
+        self
+    }
+
+    /// 设置 UDP 套接字表
+    pub fn with_udp_sockets(mut self, sockets: Vec<UdpSocketInfo>) -> Self {
+        self.udp_sockets = sockets;
+        self
+    }
+
+    /// 连接表被读取的次数（克隆的监控器共享计数）
+    pub fn scans(&self) -> usize {
+        self.scans.load(Ordering::SeqCst)
+    }
+
+    /// 读取连接表，相当于平台实现的一次系统扫描
+    fn scan(&self) -> &[TcpConnection] {
+        self.scans.fetch_add(1, Ordering::SeqCst);
+        &self.connections
+    }
+
+    fn build_process_stats(pid: u32, connections: &[&TcpConnection]) -> ProcessTcpStats {
+        let mut stats = ProcessTcpStats {
+            pid,
+            process_name: connections.first().map(|c| c.process_name.clone()).unwrap_or_default(),
+            total_connections: connections.len(),
+            ..Default::default()
+        };
+
+        for conn in connections {
+            stats.record_state(conn.state);
+        }
+
+        stats.health_score = calculate_health_score(&stats);
+        stats
+    }
+}
+
+impl TcpMonitor for MockTcpMonitor {
+    fn get_all_connections(&self) -> Result<Vec<TcpConnection>> {
+        Ok(self.scan().to_vec())
+    }
+
+    fn get_process_connections(&self, pid: u32) -> Result<Vec<TcpConnection>> {
+        Ok(self.scan().iter().filter(|c| c.pid == pid).cloned().collect())
+    }
+
+    fn get_process_stats(&self, pid: u32) -> Result<ProcessTcpStats> {
+        self.process_stats_from(pid, self.scan())
+    }
+
+    fn process_stats_from(&self, pid: u32, connections: &[TcpConnection]) -> Result<ProcessTcpStats> {
+        let connections: Vec<&TcpConnection> = connections.iter().filter(|c| c.pid == pid).collect();
+        Ok(Self::build_process_stats(pid, &connections))
+    }
+
+    fn get_system_stats(&self) -> Result<SystemTcpStats> {
+        self.get_system_stats_with(&StatsOptions::default())
+    }
+
+    fn get_system_stats_with(&self, options: &StatsOptions) -> Result<SystemTcpStats> {
+        self.system_stats_from(self.scan(), options)
+    }
+
+    fn system_stats_from(&self, connections: &[TcpConnection], options: &StatsOptions) -> Result<SystemTcpStats> {
+        let by_process = summarize_processes(connections, options);
+
+        let (port_start, port_end) = options.port_range.unwrap_or(self.port_range);
+        let used_ports = ephemeral_ports_in_use(connections, port_start, port_end);
+        let usage = port_usage(used_ports, port_start, port_end);
+
+        Ok(SystemTcpStats {
+            total_connections: connections.len(),
+            by_state: calculate_stats(connections),
+            by_family: calculate_family_stats(connections),
+            by_process,
+            available_ports: usage.available,
+            ephemeral_ports_in_use: used_ports,
+            port_usage_percent: usage.percent,
+            dynamic_port_range: Some((port_start, port_end)),
+            udp_ports_used: None,
+            unknown_state_samples: unknown_state_samples(connections),
+        })
+    }
+
+    fn get_udp_sockets(&self) -> Result<Vec<UdpSocketInfo>> {
+        Ok(self.udp_sockets.clone())
+    }
+}
@@ -8,9 +8,79 @@ pub mod windows;
 #[cfg(target_os = "macos")]
 pub mod macos;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+pub mod mock;
+pub mod cache;
+pub mod command;
+pub mod netstat;
+pub mod netsh;
+pub mod procfs;
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub mod signal;
+
 use crate::tcp_config::TcpConfigManager;
 use crate::monitor::TcpMonitor;
 use crate::optimizer::ConnectionOptimizer;
+use serde::Serialize;
+
+/// 平台能力描述，供界面隐藏当前平台不支持的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PlatformCapabilities {
+    /// 能否直接关闭其他进程的连接（与 `supports_connection_control` 一致）
+    pub can_close_connections: bool,
+    /// 能否关闭 IPv6 连接
+    pub supports_ipv6_close: bool,
+    /// 部分配置修改需要重启才能生效，`requires_reboot` 返回是否已应用过这样的修改
+    pub config_requires_reboot: bool,
+    /// 能否配置 TIME_WAIT 重用（`tw_reuse`）
+    pub supports_tw_reuse: bool,
+    /// 系统是否提供 netlink（sock_diag）接口
+    pub has_netlink: bool,
+}
+
+impl PlatformCapabilities {
+    pub const WINDOWS: Self = Self {
+        can_close_connections: true,
+        // SetTcpEntry 只接受 IPv4 连接
+        supports_ipv6_close: false,
+        config_requires_reboot: true,
+        supports_tw_reuse: false,
+        has_netlink: false,
+    };
+
+    pub const MACOS: Self = Self {
+        can_close_connections: false,
+        supports_ipv6_close: false,
+        config_requires_reboot: false,
+        supports_tw_reuse: false,
+        has_netlink: false,
+    };
+
+    pub const LINUX: Self = Self {
+        can_close_connections: false,
+        supports_ipv6_close: false,
+        config_requires_reboot: false,
+        supports_tw_reuse: true,
+        has_netlink: true,
+    };
+}
+
+/// 获取当前平台的能力描述
+pub fn platform_capabilities() -> PlatformCapabilities {
+    #[cfg(target_os = "windows")]
+    { PlatformCapabilities::WINDOWS }
+
+    #[cfg(target_os = "macos")]
+    { PlatformCapabilities::MACOS }
+
+    #[cfg(target_os = "linux")]
+    { PlatformCapabilities::LINUX }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    { PlatformCapabilities::MACOS }
+}
 
 /// 创建平台特定的配置管理器
 pub fn create_config_manager() -> Box<dyn TcpConfigManager> {
@@ -24,7 +94,12 @@ pub fn create_config_manager() -> Box<dyn TcpConfigManager> {
         Box::new(macos::MacOsTcpConfigManager::new())
     }
     
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(linux::LinuxTcpConfigManager::new())
+    }
+    
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
         compile_error!("Unsupported platform");
     }
@@ -42,7 +117,12 @@ pub fn create_monitor() -> Box<dyn TcpMonitor> {
         Box::new(macos::MacOsTcpMonitor::new())
     }
     
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(linux::LinuxTcpMonitor::new())
+    }
+    
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
         compile_error!("Unsupported platform");
     }
@@ -60,7 +140,12 @@ pub fn create_optimizer() -> Box<dyn ConnectionOptimizer> {
         Box::new(macos::MacOsConnectionOptimizer::new())
     }
     
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(linux::LinuxConnectionOptimizer::new())
+    }
+    
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
         compile_error!("Unsupported platform");
     }
@@ -74,7 +159,10 @@ pub fn platform_name() -> &'static str {
     #[cfg(target_os = "macos")]
     { "macOS" }
     
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[cfg(target_os = "linux")]
+    { "Linux" }
+    
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     { "Unknown" }
 }
 
@@ -90,7 +178,33 @@ pub fn has_admin_privileges() -> bool {
         macos::is_root()
     }
     
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[cfg(target_os = "linux")]
+    {
+        linux::is_root()
+    }
+    
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     { false }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macos_cannot_close_connections() {
+        let caps = PlatformCapabilities::MACOS;
+        assert!(!caps.can_close_connections);
+        assert!(!caps.supports_ipv6_close);
+        #[cfg(target_os = "macos")]
+        assert_eq!(caps.can_close_connections, macos::MacOsConnectionOptimizer::new().supports_connection_control());
+    }
+
+    #[test]
+    fn capabilities_match_platform_impls() {
+        let caps = platform_capabilities();
+        assert_eq!(caps.can_close_connections, create_optimizer().supports_connection_control());
+        // 尚未应用任何修改
+        assert!(!create_config_manager().requires_reboot());
+    }
+}
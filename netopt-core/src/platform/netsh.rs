@@ -0,0 +1,160 @@
+//! `netsh int ipv4 show/set dynamicport tcp` 的参数和输出解析（Windows 动态端口范围）
+//!
+//! 输出的标签会被本地化，如 "Start Port" / "Startport" / "启动端口"，
+//! 因此不匹配标签文本，只按顺序取冒号后为数字的行：第一行为起始端口，第二行为端口数
+
+use crate::{NetOptError, Result};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// netsh 允许的最小起始端口
+pub const MIN_START: u32 = 1025;
+/// netsh 允许的最少端口数
+pub const MIN_COUNT: u32 = 255;
+
+/// 动态端口范围，对应 netsh 的 start= 和 num=
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynamicPortRange {
+    pub start: u32,
+    pub count: u32,
+}
+
+impl DynamicPortRange {
+    /// Vista 之后的系统默认值 49152-65535
+    pub const DEFAULT: Self = Self { start: 49152, count: 16384 };
+
+    /// 由起止端口（均包含）构造，按 netsh 的限制检查
+    pub fn from_bounds(start: u32, end: u32) -> Result<Self> {
+        if start < MIN_START || end > 65535 || end < start {
+            return Err(NetOptError::InvalidParameter(format!(
+                "动态端口范围 {}-{} 无效，起始端口不能小于 {}，结束端口不能大于 65535",
+                start, end, MIN_START
+            )));
+        }
+        let count = end - start + 1;
+        if count < MIN_COUNT {
+            return Err(NetOptError::InvalidParameter(format!(
+                "动态端口范围至少需要 {} 个端口，当前为 {}",
+                MIN_COUNT, count
+            )));
+        }
+        Ok(Self { start, count })
+    }
+
+    /// 最后一个端口
+    pub fn end(&self) -> u32 {
+        self.start + self.count - 1
+    }
+
+    /// `netsh int ipv4 set dynamicport tcp start=X num=Y` 的参数
+    pub fn set_args(&self) -> Vec<String> {
+        ["int", "ipv4", "set", "dynamicport", "tcp"]
+            .into_iter()
+            .map(String::from)
+            .chain([format!("start={}", self.start), format!("num={}", self.count)])
+            .collect()
+    }
+}
+
+/// `netsh int ipv4 show dynamicport tcp` 的参数
+pub const SHOW_ARGS: [&str; 5] = ["int", "ipv4", "show", "dynamicport", "tcp"];
+
+/// 解析 `netsh int ipv4 show dynamicport tcp` 的输出
+pub fn parse_dynamic_port_range(output: &str) -> Option<DynamicPortRange> {
+    let mut values = output
+        .lines()
+        .filter_map(|line| line.rsplit_once(':'))
+        .filter_map(|(_, value)| value.trim().parse::<u32>().ok());
+    Some(DynamicPortRange {
+        start: values.next()?,
+        count: values.next()?,
+    })
+}
+
+/// 有效的动态端口范围 (起始, 结束) 的缓存。读取需要启动 netsh 进程，不能在每轮统计时都执行；
+/// 修改范围后调用 `invalidate`，系统中被其他工具修改的范围在 TTL 过期后重新读取
+#[derive(Debug)]
+pub struct PortRangeCache {
+    entry: Mutex<Option<(Instant, (u32, u32))>>,
+    ttl: Duration,
+}
+
+impl PortRangeCache {
+    pub const fn new(ttl: Duration) -> Self {
+        Self { entry: Mutex::new(None), ttl }
+    }
+
+    /// 缓存未过期时直接返回，否则调用 `read` 重新读取
+    pub fn get_or_refresh(&self, now: Instant, read: impl FnOnce() -> (u32, u32)) -> (u32, u32) {
+        let mut entry = self.entry.lock().unwrap_or_else(|e| e.into_inner());
+        match *entry {
+            Some((at, range)) if now.saturating_duration_since(at) < self.ttl => range,
+            _ => {
+                let range = read();
+                *entry = Some((now, range));
+                range
+            }
+        }
+    }
+
+    pub fn invalidate(&self) {
+        *self.entry.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// 进程内共享：监控器读取，配置管理器写入范围后使其失效
+pub static PORT_RANGE_CACHE: PortRangeCache = PortRangeCache::new(Duration::from_secs(300));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn port_range_cache_reads_once_until_expired_or_invalidated() {
+        let cache = PortRangeCache::new(Duration::from_secs(60));
+        let reads = std::cell::Cell::new(0);
+        let read = || {
+            reads.set(reads.get() + 1);
+            (49152, 65535)
+        };
+        let start = Instant::now();
+
+        assert_eq!(cache.get_or_refresh(start, read), (49152, 65535));
+        cache.get_or_refresh(start + Duration::from_secs(59), read);
+        assert_eq!(reads.get(), 1);
+
+        cache.get_or_refresh(start + Duration::from_secs(60), read);
+        assert_eq!(reads.get(), 2);
+
+        cache.invalidate();
+        cache.get_or_refresh(start + Duration::from_secs(61), read);
+        assert_eq!(reads.get(), 3);
+    }
+
+    const EN_US: &str = include_str!("fixtures/netsh_dynamicport_en_us.txt");
+    const DE_DE: &str = include_str!("fixtures/netsh_dynamicport_de_de.txt");
+    const ZH_CN: &str = include_str!("fixtures/netsh_dynamicport_zh_cn.txt");
+
+    #[test]
+    fn parses_localized_output() {
+        assert_eq!(parse_dynamic_port_range(EN_US), Some(DynamicPortRange::DEFAULT));
+        assert_eq!(parse_dynamic_port_range(ZH_CN), Some(DynamicPortRange::DEFAULT));
+
+        let range = parse_dynamic_port_range(DE_DE).unwrap();
+        assert_eq!((range.start, range.count, range.end()), (10000, 55535, 65534));
+
+        assert_eq!(parse_dynamic_port_range("Start Port : 49152\r\n"), None);
+        assert_eq!(parse_dynamic_port_range(""), None);
+    }
+
+    #[test]
+    fn bounds_follow_netsh_limits() {
+        let range = DynamicPortRange::from_bounds(10000, 65535).unwrap();
+        assert_eq!(range.count, 55536);
+        assert_eq!(range.set_args().join(" "), "int ipv4 set dynamicport tcp start=10000 num=55536");
+
+        assert!(DynamicPortRange::from_bounds(1024, 5000).is_err());
+        assert!(DynamicPortRange::from_bounds(50000, 50100).is_err());
+        assert!(DynamicPortRange::from_bounds(60000, 70000).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! netstat 文本输出解析（Windows / macOS 备用方案）
+//!
+//! 非英文系统上表头和部分状态名会被本地化，因此不按固定行数跳过表头，
+//! 而是只接受以协议名开头、字段可完整解析的行
+
+use crate::{TcpConnection, TcpState, UdpSocketInfo};
+
+/// 解析 TCP 状态名。标准写法交给 `TcpState::from_str`，这里额外兼容本地化的 Windows 状态名
+pub fn parse_state(s: &str) -> TcpState {
+    if let Ok(state) = s.parse() {
+        return state;
+    }
+    let s = s.trim().to_uppercase();
+    match s.as_str() {
+        "HERGESTELLT" => TcpState::Established,
+        "WARTEND" => TcpState::TimeWait,
+        "SCHLIESSEN_WARTEN" => TcpState::CloseWait,
+        "FIN_WARTEN_1" => TcpState::FinWait1,
+        "FIN_WARTEN_2" => TcpState::FinWait2,
+        "SYN_GESENDET" => TcpState::SynSent,
+        "SYN_EMPFANGEN" => TcpState::SynReceived,
+        "SCHLIESSEN" => TcpState::Closing,
+        "LETZTES_ACK" => TcpState::LastAck,
+        "GESCHLOSSEN" => TcpState::Closed,
+        // "ABHÖREN"：netstat 以 OEM 代码页输出，Ö 解码后可能变成替换字符
+        _ if s.starts_with("ABH") && s.ends_with("REN") => TcpState::Listen,
+        _ => TcpState::Unknown,
+    }
+}
+
+/// 解析 Windows `netstat -ano -p tcp` 输出
+pub fn parse_windows_tcp(output: &str) -> Vec<TcpConnection> {
+    output.lines().filter_map(parse_windows_tcp_line).collect()
+}
+
+/// 格式: TCP  0.0.0.0:135  0.0.0.0:0  LISTENING  1000
+fn parse_windows_tcp_line(line: &str) -> Option<TcpConnection> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 5 || !parts[0].eq_ignore_ascii_case("TCP") {
+        return None;
+    }
+
+    let (local_addr, local_port) = parse_colon_addr_port(parts[1])?;
+    let (remote_addr, remote_port) = parse_colon_addr_port(parts[2])?;
+    let state = parse_state(parts[3]);
+    let pid = parts[4].parse().ok()?;
+
+    Some(TcpConnection {
+        local_addr,
+        local_port,
+        remote_addr,
+        remote_port,
+        state,
+        pid,
+        process_name: String::new(),
+        raw_state: (state == TcpState::Unknown).then(|| parts[3].to_string()),
+        remote_host: None,
+    })
+}
+
+/// 解析 Windows `netstat -ano -p udp` 输出
+pub fn parse_windows_udp(output: &str) -> Vec<UdpSocketInfo> {
+    output.lines().filter_map(parse_windows_udp_line).collect()
+}
+
+/// 格式: UDP  0.0.0.0:500  *:*  4444（UDP 没有状态列）
+fn parse_windows_udp_line(line: &str) -> Option<UdpSocketInfo> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 4 || !parts[0].eq_ignore_ascii_case("UDP") {
+        return None;
+    }
+
+    let (local_addr, local_port) = parse_colon_addr_port(parts[1])?;
+    let pid = parts[3].parse().ok()?;
+
+    Some(UdpSocketInfo {
+        local_addr,
+        local_port,
+        pid,
+        process_name: String::new(),
+    })
+}
+
+/// "addr:port" 格式，IPv6 为 "[::1]:port"
+fn parse_colon_addr_port(s: &str) -> Option<(String, u16)> {
+    let idx = s.rfind(':')?;
+    let addr = s[..idx].to_string();
+    let port = s[idx + 1..].parse().ok()?;
+    Some((addr, port))
+}
+
+/// 解析 macOS `netstat -anv -p tcp` 输出
+pub fn parse_macos_tcp(output: &str) -> Vec<TcpConnection> {
+    output.lines().filter_map(parse_macos_tcp_line).collect()
+}
+
+fn parse_macos_tcp_line(line: &str) -> Option<TcpConnection> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 9 || !parts[0].to_ascii_lowercase().starts_with("tcp") {
+        return None;
+    }
+
+    // 解析本地地址和端口
+    let (local_addr, local_port) = parse_dot_addr_port(parts[3])?;
+    let (remote_addr, remote_port) = parse_dot_addr_port(parts[4])?;
+    let state = parse_state(parts[5]);
+
+    // macOS netstat -v 的格式是 "process:pid" 在第11列 (索引10)
+    // 例如: "PacketTunnel:6508" 或 "Code Helper (Plu:68231"
+    let (process_name, pid) = parts.get(10)
+        .map(|s| parse_process_pid(s))
+        .unwrap_or((String::new(), 0));
+
+    Some(TcpConnection {
+        local_addr,
+        local_port,
+        remote_addr,
+        remote_port,
+        state,
+        pid,
+        process_name,
+        raw_state: (state == TcpState::Unknown).then(|| parts[5].to_string()),
+        remote_host: None,
+    })
+}
+
+/// 解析 macOS `netstat -anv -p udp` 输出
+pub fn parse_macos_udp(output: &str) -> Vec<UdpSocketInfo> {
+    output.lines().filter_map(parse_macos_udp_line).collect()
+}
+
+fn parse_macos_udp_line(line: &str) -> Option<UdpSocketInfo> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 6 || !parts[0].to_ascii_lowercase().starts_with("udp") {
+        return None;
+    }
+
+    let (local_addr, local_port) = parse_dot_addr_port(parts[3])?;
+    // UDP 没有状态列，"process:pid" 的位置比 TCP 靠前，按格式查找
+    let (process_name, pid) = parts[5..].iter()
+        .map(|s| parse_process_pid(s))
+        .find(|(_, pid)| *pid > 0)
+        .unwrap_or((String::new(), 0));
+
+    Some(UdpSocketInfo {
+        local_addr,
+        local_port,
+        pid,
+        process_name,
+    })
+}
+
+/// 解析 "process:pid" 格式，如 "PacketTunnel:6508"
+fn parse_process_pid(s: &str) -> (String, u32) {
+    if let Some(idx) = s.rfind(':') {
+        let name = s[..idx].to_string();
+        let pid = s[idx + 1..].parse().unwrap_or(0);
+        (name, pid)
+    } else {
+        (String::new(), 0)
+    }
+}
+
+/// "addr.port" 格式，通配端口 "*" 视为 0
+fn parse_dot_addr_port(s: &str) -> Option<(String, u16)> {
+    let idx = s.rfind('.')?;
+    let addr = s[..idx].to_string();
+    let port = match &s[idx + 1..] {
+        "*" => 0,
+        port => port.parse().ok()?,
+    };
+    Some((addr, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOWS_ZH_CN: &str = include_str!("fixtures/netstat_windows_zh_cn.txt");
+    const WINDOWS_DE_DE: &str = include_str!("fixtures/netstat_windows_de_de.txt");
+    const MACOS: &str = include_str!("fixtures/netstat_macos.txt");
+
+    fn states(conns: &[TcpConnection]) -> Vec<TcpState> {
+        conns.iter().map(|c| c.state).collect()
+    }
+
+    #[test]
+    fn parses_zh_cn_windows_output() {
+        let conns = parse_windows_tcp(WINDOWS_ZH_CN);
+
+        assert_eq!(conns.len(), 5);
+        assert_eq!(states(&conns), vec![
+            TcpState::Listen,
+            TcpState::Listen,
+            TcpState::Established,
+            TcpState::TimeWait,
+            TcpState::CloseWait,
+        ]);
+        assert_eq!(conns[2].local_addr, "192.168.1.23");
+        assert_eq!(conns[2].local_port, 52344);
+        assert_eq!(conns[2].remote_addr, "140.82.112.26");
+        assert_eq!(conns[2].remote_port, 443);
+        assert_eq!(conns[2].pid, 8812);
+    }
+
+    #[test]
+    fn parses_de_de_windows_output() {
+        let conns = parse_windows_tcp(WINDOWS_DE_DE);
+
+        assert_eq!(conns.len(), 5);
+        assert_eq!(states(&conns), vec![
+            TcpState::Listen,
+            TcpState::Listen,
+            TcpState::Established,
+            TcpState::TimeWait,
+            TcpState::CloseWait,
+        ]);
+        assert_eq!(conns[4].pid, 3120);
+    }
+
+    #[test]
+    fn parses_windows_udp_and_skips_tcp_rows() {
+        let sockets = parse_windows_udp("\n  UDP    0.0.0.0:500    *:*    4444\n  TCP    0.0.0.0:135    0.0.0.0:0    LISTENING    1000\n");
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].local_port, 500);
+        assert_eq!(sockets[0].pid, 4444);
+    }
+
+    #[test]
+    fn parses_macos_output() {
+        let conns = parse_macos_tcp(MACOS);
+
+        assert_eq!(conns.len(), 3);
+        assert_eq!(states(&conns), vec![TcpState::Listen, TcpState::Established, TcpState::TimeWait]);
+        assert_eq!(conns[0].remote_port, 0);
+        assert_eq!(conns[1].process_name, "PacketTunnel");
+        assert_eq!(conns[1].pid, 6508);
+    }
+
+    #[test]
+    fn unrecognized_state_keeps_raw_text() {
+        let conns = parse_windows_tcp("  TCP    10.0.0.1:5000    10.0.0.2:443    ÉTABLIE    42\n");
+        assert_eq!(conns[0].state, TcpState::Unknown);
+        assert_eq!(conns[0].raw_state.as_deref(), Some("ÉTABLIE"));
+
+        assert!(parse_macos_tcp(MACOS).iter().all(|c| c.raw_state.is_none()));
+    }
+
+    #[test]
+    fn state_matching_ignores_case_and_whitespace() {
+        assert_eq!(parse_state(" established "), TcpState::Established);
+        assert_eq!(parse_state("Time_Wait"), TcpState::TimeWait);
+        assert_eq!(parse_state("ABH\u{FFFD}REN"), TcpState::Listen);
+        assert_eq!(parse_state("LISTENING"), TcpState::Listen);
+        assert_eq!(parse_state("LISTEN"), TcpState::Listen);
+        assert_eq!(parse_state("LETZTES_ACK"), TcpState::LastAck);
+        assert_eq!(parse_state("???"), TcpState::Unknown);
+    }
+}
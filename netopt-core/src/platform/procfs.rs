@@ -0,0 +1,158 @@
+//! /proc/net/{tcp,udp}{,6} 文本解析
+//!
+//! 只处理文本本身，inode 到 PID 的映射和进程名由调用方提供，
+//! 因此在非 Linux 平台上也能编译，便于测试和基准测试
+
+use crate::{TcpConnection, TcpState, UdpSocketInfo};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// 解析 /proc/net/tcp 或 tcp6 的完整内容（含表头），进程名留空
+pub fn parse_tcp(content: &str, ipv6: bool, inodes: &HashMap<u64, u32>) -> Vec<TcpConnection> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| parse_tcp_line(line, ipv6, inodes))
+        .collect()
+}
+
+/// 解析 /proc/net/udp 或 udp6 的完整内容（含表头），进程名留空
+pub fn parse_udp(content: &str, ipv6: bool, inodes: &HashMap<u64, u32>) -> Vec<UdpSocketInfo> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| parse_udp_line(line, ipv6, inodes))
+        .collect()
+}
+
+/// 格式: sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode
+fn parse_tcp_line(line: &str, ipv6: bool, inodes: &HashMap<u64, u32>) -> Option<TcpConnection> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 10 {
+        return None;
+    }
+
+    let (local_addr, local_port) = parse_addr_port(parts[1], ipv6)?;
+    let (remote_addr, remote_port) = parse_addr_port(parts[2], ipv6)?;
+    let state = parse_state(parts[3]);
+
+    Some(TcpConnection {
+        local_addr,
+        local_port,
+        remote_addr,
+        remote_port,
+        state,
+        pid: inode_pid(parts[9], inodes),
+        process_name: String::new(),
+        raw_state: (state == TcpState::Unknown).then(|| parts[3].to_string()),
+        remote_host: None,
+    })
+}
+
+/// 格式与 tcp 相同，只取本地地址
+fn parse_udp_line(line: &str, ipv6: bool, inodes: &HashMap<u64, u32>) -> Option<UdpSocketInfo> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 10 {
+        return None;
+    }
+
+    let (local_addr, local_port) = parse_addr_port(parts[1], ipv6)?;
+
+    Some(UdpSocketInfo {
+        local_addr,
+        local_port,
+        pid: inode_pid(parts[9], inodes),
+        process_name: String::new(),
+    })
+}
+
+/// TIME_WAIT 等已脱离进程的连接 inode 为 0，查不到时 PID 为 0
+fn inode_pid(inode: &str, inodes: &HashMap<u64, u32>) -> u32 {
+    inode
+        .parse::<u64>()
+        .ok()
+        .and_then(|inode| inodes.get(&inode).copied())
+        .unwrap_or(0)
+}
+
+/// 解析 "0100007F:1F90" 格式（地址为主机字节序的十六进制）
+fn parse_addr_port(s: &str, ipv6: bool) -> Option<(String, u16)> {
+    let (addr, port) = s.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let addr = if ipv6 {
+        if addr.len() != 32 {
+            return None;
+        }
+        let mut bytes = [0u8; 16];
+        for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+            let word = u32::from_str_radix(&addr[i * 8..i * 8 + 8], 16).ok()?;
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        Ipv6Addr::from(bytes).to_string()
+    } else {
+        let word = u32::from_str_radix(addr, 16).ok()?;
+        Ipv4Addr::from(word.to_le_bytes()).to_string()
+    };
+
+    Some((addr, port))
+}
+
+/// 内核 tcp_states.h 中的状态编号
+fn parse_state(s: &str) -> TcpState {
+    match s {
+        "01" => TcpState::Established,
+        "02" => TcpState::SynSent,
+        "03" => TcpState::SynReceived,
+        "04" => TcpState::FinWait1,
+        "05" => TcpState::FinWait2,
+        "06" => TcpState::TimeWait,
+        "07" => TcpState::Closed,
+        "08" => TcpState::CloseWait,
+        "09" => TcpState::LastAck,
+        "0A" => TcpState::Listen,
+        "0B" => TcpState::Closing,
+        _ => TcpState::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:07E8 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 662 1 00000000e45c0dea 100 0 0 10 0
+   1: 0100007F:BC8F 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 927 1 00000000d38b4ae4 20 4 30 10 -1
+   2: 0100007F:BC90 0100007F:1F90 06 00000000:00000000 03:00001234 00000000     0        0 0 3 0000000000000000
+";
+    const TCP6: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:0277 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 31 1 0000000000000000 100 0 0 10 0
+";
+
+    #[test]
+    fn parses_ipv4_rows_and_maps_inodes() {
+        let inodes = HashMap::from([(927, 4242)]);
+        let conns = parse_tcp(TCP, false, &inodes);
+
+        assert_eq!(conns.len(), 3);
+        assert_eq!(conns[0].state, TcpState::Listen);
+        assert_eq!(conns[0].local_port, 2024);
+        assert_eq!(conns[1].local_addr, "127.0.0.1");
+        assert_eq!(conns[1].remote_port, 8080);
+        assert_eq!(conns[1].pid, 4242);
+        assert_eq!(conns[2].state, TcpState::TimeWait);
+        assert_eq!(conns[2].pid, 0);
+    }
+
+    #[test]
+    fn parses_ipv6_rows_and_skips_malformed() {
+        let conns = parse_tcp(TCP6, true, &HashMap::new());
+        assert_eq!(conns.len(), 1);
+        assert_eq!(conns[0].local_addr, "::1");
+        assert_eq!(conns[0].local_port, 631);
+
+        // IPv4 文本按 IPv6 解析时地址长度不符，整行丢弃
+        assert!(parse_tcp(TCP, true, &HashMap::new()).is_empty());
+        assert_eq!(parse_udp(TCP, false, &HashMap::new()).len(), 3);
+    }
+}
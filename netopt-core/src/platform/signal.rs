@@ -0,0 +1,156 @@
+//! 向进程发送信号（macOS / Linux）
+//!
+//! 无法直接关闭其他进程连接的平台上，通过信号通知进程自行清理连接
+
+use crate::optimizer::{ActionType, OptimizationAction};
+use crate::policy::{AppPolicy, GracefulSignal};
+use crate::{NetOptError, ProcessTcpStats, Result};
+
+fn raw_signal(signal: GracefulSignal) -> libc::c_int {
+    match signal {
+        GracefulSignal::Term => libc::SIGTERM,
+        GracefulSignal::Hup => libc::SIGHUP,
+    }
+}
+
+/// 发送信号，进程不存在时返回 `ProcessNotFound`
+pub fn send_signal(pid: u32, signal: GracefulSignal) -> Result<()> {
+    // kill(0) 和负数 PID 会发给整个进程组
+    if pid == 0 || pid > i32::MAX as u32 {
+        return Err(NetOptError::InvalidParameter(format!("无效的 PID: {}", pid)));
+    }
+
+    if unsafe { libc::kill(pid as libc::pid_t, raw_signal(signal)) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::ESRCH) => Err(NetOptError::ProcessNotFound(pid)),
+        Some(libc::EPERM) => Err(NetOptError::PermissionDenied),
+        _ => Err(NetOptError::SystemError(format!("发送信号到 PID {} 失败: {}", pid, err))),
+    }
+}
+
+/// 策略启用了信号通知且进程超过阈值时发送信号，建议进程自己清理连接。
+/// `stats` 只在策略启用了信号通知时才调用；没有 root 权限时返回 `PermissionDenied`
+pub fn signal_if_exceeded(
+    pid: u32,
+    policy: &AppPolicy,
+    stats: impl FnOnce() -> Result<ProcessTcpStats>,
+) -> Result<OptimizationAction> {
+    let mut action = OptimizationAction {
+        pid,
+        process_name: policy.process_name.clone(),
+        action_type: ActionType::None,
+        reason: String::new(),
+        connections_affected: 0,
+        success: true,
+        error_message: None,
+    };
+
+    let Some(signal) = policy.graceful_signal else {
+        action.reason = "策略未启用信号通知，当前平台无法直接关闭连接".into();
+        return Ok(action);
+    };
+
+    let stats = stats()?;
+    let exceeded = crate::monitor::exceeds_thresholds(
+        &stats,
+        policy.time_wait_threshold.unwrap_or(usize::MAX),
+        policy.close_wait_threshold.unwrap_or(usize::MAX),
+        policy.max_connections.unwrap_or(usize::MAX),
+    );
+    if !exceeded {
+        action.reason = "连接状态正常，无需优化".into();
+        return Ok(action);
+    }
+
+    if !super::has_admin_privileges() {
+        return Err(NetOptError::PermissionDenied);
+    }
+
+    action.action_type = ActionType::GracefulShutdown;
+    action.reason = format!(
+        "TIME_WAIT({}) / CLOSE_WAIT({}) 超过阈值，发送 {:?} 信号",
+        stats.time_wait, stats.close_wait, signal
+    );
+    match send_signal(pid, signal) {
+        Ok(()) => action.connections_affected = stats.time_wait + stats.close_wait,
+        Err(NetOptError::ProcessNotFound(pid)) => return Err(NetOptError::ProcessNotFound(pid)),
+        Err(e) => {
+            action.success = false;
+            action.error_message = Some(e.to_string());
+        }
+    }
+    Ok(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    fn sleeper() -> std::process::Child {
+        Command::new("sleep").arg("30").spawn().unwrap()
+    }
+
+    #[test]
+    fn delivers_configured_signal() {
+        for (signal, raw) in [(GracefulSignal::Term, libc::SIGTERM), (GracefulSignal::Hup, libc::SIGHUP)] {
+            let mut child = sleeper();
+            send_signal(child.id(), signal).unwrap();
+            assert_eq!(child.wait().unwrap().signal(), Some(raw));
+        }
+    }
+
+    #[test]
+    fn exited_process_is_not_found() {
+        let mut child = sleeper();
+        let pid = child.id();
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert!(matches!(send_signal(pid, GracefulSignal::Term), Err(NetOptError::ProcessNotFound(p)) if p == pid));
+    }
+
+    #[test]
+    fn signals_only_when_enabled_and_exceeded() {
+        let mut child = sleeper();
+        let pid = child.id();
+        let leaky = || Ok(ProcessTcpStats { pid, close_wait: 100, ..Default::default() });
+        let mut policy = AppPolicy { close_wait_threshold: Some(10), ..Default::default() };
+
+        // 策略未启用信号通知
+        let action = signal_if_exceeded(pid, &policy, || unreachable!()).unwrap();
+        assert_eq!(action.action_type, ActionType::None);
+
+        // 未超过阈值
+        policy.graceful_signal = Some(GracefulSignal::Term);
+        let action = signal_if_exceeded(pid, &policy, || Ok(ProcessTcpStats { pid, close_wait: 5, ..Default::default() })).unwrap();
+        assert_eq!(action.action_type, ActionType::None);
+
+        match signal_if_exceeded(pid, &policy, leaky) {
+            Ok(action) => {
+                assert_eq!(action.action_type, ActionType::GracefulShutdown);
+                assert!(action.success);
+                assert_eq!(action.connections_affected, 100);
+                assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGTERM));
+                // 进程已退出
+                assert!(matches!(signal_if_exceeded(pid, &policy, leaky), Err(NetOptError::ProcessNotFound(_))));
+            }
+            Err(e) => {
+                assert!(!super::super::has_admin_privileges());
+                assert!(matches!(e, NetOptError::PermissionDenied));
+                child.kill().unwrap();
+                child.wait().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_process_group_pids() {
+        assert!(matches!(send_signal(0, GracefulSignal::Term), Err(NetOptError::InvalidParameter(_))));
+        assert!(matches!(send_signal(u32::MAX, GracefulSignal::Term), Err(NetOptError::InvalidParameter(_))));
+    }
+}
@@ -4,13 +4,20 @@
 
 use crate::{
     Result, NetOptError, TcpConnection, TcpState, ProcessTcpStats, SystemTcpStats,
-    TcpSystemConfig,
+    TcpSystemConfig, UdpSocketInfo,
+};
+use crate::tcp_config::{ConfigBackend, RebootTracker, TcpConfigManager, millis_to_secs, secs_to_millis};
+use crate::monitor::{
+    TcpMonitor, StatsOptions, calculate_family_stats, calculate_health_score, calculate_stats, ephemeral_ports_in_use, port_usage, summarize_processes, unknown_state_samples,
 };
-use crate::tcp_config::TcpConfigManager;
-use crate::monitor::{TcpMonitor, calculate_health_score};
 use crate::optimizer::ConnectionOptimizer;
 use crate::policy::AppPolicy;
+use super::cache::{PidCache, DEFAULT_TTL};
+use super::command::run_command;
+use super::netsh::DynamicPortRange;
+use super::netstat;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[cfg(target_os = "windows")]
 use windows::{
@@ -20,78 +27,122 @@ use windows::{
 
 const TCP_PARAMS_PATH: &str = r"SYSTEM\CurrentControlSet\Services\Tcpip\Parameters";
 
+/// 配置字段对应的注册表值名，动态端口范围由 netsh 管理，tw_reuse 等仅 Linux
+fn registry_value_name(field: &str) -> Option<&'static str> {
+    match field {
+        "time_wait_delay" => Some("TcpTimedWaitDelay"),
+        "max_syn_retransmissions" => Some("TcpMaxConnectRetransmissions"),
+        "keep_alive_time" => Some("KeepAliveTime"),
+        "keep_alive_interval" => Some("KeepAliveInterval"),
+        _ => None,
+    }
+}
+
+/// 由 netsh 管理的字段：dynamic_port_start 为起始端口，max_user_port 为最后一个端口
+fn is_port_range_field(field: &str) -> bool {
+    matches!(field, "dynamic_port_start" | "max_user_port")
+}
+
 /// 检查是否有管理员权限
 #[cfg(target_os = "windows")]
 pub fn is_elevated() -> bool {
-    use std::process::Command;
-    Command::new("net")
-        .args(["session"])
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+    match token_is_elevated() {
+        Ok(elevated) => elevated,
+        Err(e) => {
+            tracing::debug!("查询进程令牌失败，回退到 net session: {}", e);
+            net_session_succeeds()
+        }
+    }
+}
+
+/// 读取当前进程令牌的 TokenElevation
+#[cfg(target_os = "windows")]
+fn token_is_elevated() -> windows::core::Result<bool> {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token)?;
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut len = 0u32;
+        let result = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut TOKEN_ELEVATION as *mut std::ffi::c_void),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut len,
+        );
+        let _ = CloseHandle(token);
+        result?;
+        Ok(elevation.TokenIsElevated != 0)
+    }
+}
+
+/// `net session` 只有管理员能成功执行（备用方案）
+#[cfg(target_os = "windows")]
+fn net_session_succeeds() -> bool {
+    super::command::run_command("net", &["session"]).is_ok()
 }
 
 #[cfg(not(target_os = "windows"))]
 pub fn is_elevated() -> bool { false }
 
-/// Windows TCP配置管理器
-pub struct WindowsTcpConfigManager;
+/// Tcpip\Parameters 下的 DWORD 注册表值
+#[derive(Debug, Default)]
+pub struct RegistryBackend;
 
-impl WindowsTcpConfigManager {
-    pub fn new() -> Self {
-        Self
+#[cfg(target_os = "windows")]
+impl RegistryBackend {
+    fn open(access: REG_SAM_FLAGS) -> Option<HKEY> {
+        let path: Vec<u16> = TCP_PARAMS_PATH.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut key = HKEY::default();
+        unsafe {
+            RegOpenKeyExW(HKEY_LOCAL_MACHINE, PCWSTR(path.as_ptr()), 0, access, &mut key)
+                .is_ok()
+                .then_some(key)
+        }
     }
-    
+
+    fn wide(name: &str) -> Vec<u16> {
+        name.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+}
+
+impl ConfigBackend for RegistryBackend {
     #[cfg(target_os = "windows")]
-    fn read_dword(&self, name: &str) -> Option<u32> {
+    fn read(&self, key: &str) -> Option<String> {
+        let hkey = Self::open(KEY_READ)?;
+        let name = Self::wide(key);
+        let mut data: u32 = 0;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let result = unsafe {
+            RegQueryValueExW(
+                hkey,
+                PCWSTR(name.as_ptr()),
+                None,
+                None,
+                Some(&mut data as *mut u32 as *mut u8),
+                Some(&mut size),
+            )
+        };
         unsafe {
-            let mut key = HKEY::default();
-            let path: Vec<u16> = TCP_PARAMS_PATH.encode_utf16().chain(std::iter::once(0)).collect();
-            
-            if RegOpenKeyExW(HKEY_LOCAL_MACHINE, PCWSTR(path.as_ptr()), 0, KEY_READ, &mut key).is_ok() {
-                let name_wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
-                let mut data: u32 = 0;
-                let mut size = std::mem::size_of::<u32>() as u32;
-                
-                if RegQueryValueExW(
-                    key,
-                    PCWSTR(name_wide.as_ptr()),
-                    None,
-                    None,
-                    Some(&mut data as *mut u32 as *mut u8),
-                    Some(&mut size),
-                ).is_ok() {
-                    let _ = RegCloseKey(key);
-                    return Some(data);
-                }
-                let _ = RegCloseKey(key);
-            }
-            None
+            let _ = RegCloseKey(hkey);
         }
+        result.is_ok().then(|| data.to_string())
     }
-    
+
     #[cfg(target_os = "windows")]
-    fn write_dword(&self, name: &str, value: u32) -> Result<()> {
-        unsafe {
-            let mut key = HKEY::default();
-            let path: Vec<u16> = TCP_PARAMS_PATH.encode_utf16().chain(std::iter::once(0)).collect();
-            
-            let result = RegOpenKeyExW(
-                HKEY_LOCAL_MACHINE,
-                PCWSTR(path.as_ptr()),
-                0,
-                KEY_WRITE,
-                &mut key,
-            );
-            
-            if result.is_err() {
-                return Err(NetOptError::PermissionDenied);
-            }
-            
-            let name_wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    fn write(&self, key: &str, value: &str) -> Result<()> {
+        let value: u32 = value.parse()
+            .map_err(|_| NetOptError::InvalidParameter(format!("{} 不是有效的 DWORD: {}", key, value)))?;
+        let hkey = Self::open(KEY_WRITE).ok_or(NetOptError::PermissionDenied)?;
+        let name = Self::wide(key);
+        let result = unsafe {
             let result = RegSetValueExW(
-                key,
-                PCWSTR(name_wide.as_ptr()),
+                hkey,
+                PCWSTR(name.as_ptr()),
                 0,
                 REG_DWORD,
                 Some(std::slice::from_raw_parts(
@@ -99,69 +150,177 @@ impl WindowsTcpConfigManager {
                     std::mem::size_of::<u32>(),
                 )),
             );
-            
-            let _ = RegCloseKey(key);
-            
-            if result.is_err() {
-                return Err(NetOptError::SystemError("写入注册表失败".into()));
-            }
-            
-            Ok(())
+            let _ = RegCloseKey(hkey);
+            result
+        };
+
+        if result.is_err() {
+            return Err(NetOptError::SystemError("写入注册表失败".into()));
+        }
+        Ok(())
+    }
+
+    /// 删除注册表值，使 TCP/IP 栈回到内置默认值。值本来就不存在时视为成功
+    #[cfg(target_os = "windows")]
+    fn remove(&self, key: &str) -> Result<()> {
+        use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+
+        let hkey = Self::open(KEY_WRITE).ok_or(NetOptError::PermissionDenied)?;
+        let name = Self::wide(key);
+        let result = unsafe {
+            let result = RegDeleteValueW(hkey, PCWSTR(name.as_ptr()));
+            let _ = RegCloseKey(hkey);
+            result
+        };
+
+        if result.is_err() && result != ERROR_FILE_NOT_FOUND {
+            return Err(NetOptError::SystemError(format!("删除注册表值 {} 失败", key)));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn read(&self, _key: &str) -> Option<String> {
+        None
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn write(&self, _key: &str, _value: &str) -> Result<()> {
+        Err(NetOptError::UnsupportedPlatform("Not Windows".into()))
+    }
+}
+
+/// Windows TCP配置管理器
+pub struct WindowsTcpConfigManager {
+    backend: Arc<dyn ConfigBackend>,
+    /// Tcpip\Parameters 下的注册表值重启后才生效，netsh 动态端口范围立即生效
+    reboot: RebootTracker,
+}
+
+impl WindowsTcpConfigManager {
+    pub fn new() -> Self {
+        Self::with_backend(Arc::new(RegistryBackend))
+    }
+
+    /// 使用指定的注册表值存储，如测试用的 `MemoryBackend`。动态端口范围仍通过 netsh 读写
+    pub fn with_backend(backend: Arc<dyn ConfigBackend>) -> Self {
+        Self {
+            backend,
+            reboot: RebootTracker::default(),
         }
     }
+
+    fn read_dword(&self, name: &str) -> Option<u32> {
+        self.backend.read_u32(name)
+    }
+
+    fn write_dword(&self, name: &str, value: u32) -> Result<()> {
+        self.backend.write(name, &value.to_string())
+    }
+
+    /// 读取 netsh 的动态端口范围
+    fn dynamic_port_range(&self) -> Option<DynamicPortRange> {
+        let output = run_command("netsh", &super::netsh::SHOW_ARGS).ok()?;
+        // 只取冒号后的数字，OEM 代码页下本地化标签解码失败不影响结果
+        super::netsh::parse_dynamic_port_range(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// 写入动态端口范围，缺省的一端保持当前值
+    fn set_dynamic_port_range(&self, start: Option<u32>, end: Option<u32>) -> Result<()> {
+        let current = self.dynamic_port_range().unwrap_or(DynamicPortRange::DEFAULT);
+        let range = DynamicPortRange::from_bounds(start.unwrap_or(current.start), end.unwrap_or(current.end()))?;
+        let args = range.set_args();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let result = run_command("netsh", &args).map(|_| ());
+        super::netsh::PORT_RANGE_CACHE.invalidate();
+        result
+    }
 }
 
 impl TcpConfigManager for WindowsTcpConfigManager {
     fn get_current_config(&self) -> Result<TcpSystemConfig> {
-        #[cfg(target_os = "windows")]
-        {
-            Ok(TcpSystemConfig {
-                max_user_port: self.read_dword("MaxUserPort"),
-                time_wait_delay: self.read_dword("TcpTimedWaitDelay"),
-                dynamic_port_start: None, // 需要使用 netsh 获取
-                max_syn_retransmissions: self.read_dword("TcpMaxConnectRetransmissions"),
-                keep_alive_time: self.read_dword("KeepAliveTime"),
-                keep_alive_interval: self.read_dword("KeepAliveInterval"),
-            })
-        }
-        #[cfg(not(target_os = "windows"))]
-        Err(NetOptError::UnsupportedPlatform("Not Windows".into()))
+        // Vista 之后生效的是 netsh 的动态端口范围，MaxUserPort 只在 netsh 不可用时作为参考
+        let range = self.dynamic_port_range();
+        Ok(TcpSystemConfig {
+            max_user_port: range.map(|r| r.end()).or_else(|| self.read_dword("MaxUserPort")),
+            time_wait_delay: self.read_dword("TcpTimedWaitDelay"),
+            dynamic_port_start: range.map(|r| r.start),
+            max_syn_retransmissions: self.read_dword("TcpMaxConnectRetransmissions"),
+            // 注册表中 KeepAlive 参数单位为毫秒
+            keep_alive_time: self.read_dword("KeepAliveTime").map(millis_to_secs),
+            keep_alive_interval: self.read_dword("KeepAliveInterval").map(millis_to_secs),
+            tw_reuse: None, // 仅 Linux
+            max_tw_buckets: None,
+        })
     }
     
     fn apply_config(&self, config: &TcpSystemConfig) -> Result<()> {
         config.validate()?;
+        if self.backend.is_system() {
+            crate::backup::auto_backup(self)?;
+        }
         
-        #[cfg(target_os = "windows")]
-        {
-            if let Some(v) = config.max_user_port {
-                self.write_dword("MaxUserPort", v)?;
-            }
-            if let Some(v) = config.time_wait_delay {
-                self.write_dword("TcpTimedWaitDelay", v)?;
-            }
-            if let Some(v) = config.max_syn_retransmissions {
-                self.write_dword("TcpMaxConnectRetransmissions", v)?;
-            }
-            if let Some(v) = config.keep_alive_time {
-                self.write_dword("KeepAliveTime", v)?;
-            }
-            if let Some(v) = config.keep_alive_interval {
-                self.write_dword("KeepAliveInterval", v)?;
-            }
-            Ok(())
+        let before = self.get_current_config()?;
+        if config.dynamic_port_start.is_some() || config.max_user_port.is_some() {
+            self.set_dynamic_port_range(config.dynamic_port_start, config.max_user_port)?;
         }
-        #[cfg(not(target_os = "windows"))]
-        Err(NetOptError::UnsupportedPlatform("Not Windows".into()))
+        if let Some(v) = config.time_wait_delay {
+            self.write_dword("TcpTimedWaitDelay", v)?;
+        }
+        if let Some(v) = config.max_syn_retransmissions {
+            self.write_dword("TcpMaxConnectRetransmissions", v)?;
+        }
+        if let Some(v) = config.keep_alive_time {
+            self.write_dword("KeepAliveTime", secs_to_millis(v))?;
+        }
+        if let Some(v) = config.keep_alive_interval {
+            self.write_dword("KeepAliveInterval", secs_to_millis(v))?;
+        }
+        // 读回的是注册表和 netsh 中的值，重启前即可校验写入是否被组策略拦截
+        let expected = TcpSystemConfig {
+            tw_reuse: None,
+            max_tw_buckets: None,
+            ..config.clone()
+        };
+        self.reboot.record(&before, &expected, |field| registry_value_name(field).is_some());
+        crate::tcp_config::verify_applied(self, &expected)
+    }
+
+    /// 删除对应的注册表值而不是写入固定数值，各 Windows 版本的内置默认值并不相同。
+    /// 动态端口范围没有注册表值，重置为 netsh 的默认范围
+    fn reset_to_default(&self, fields: &[&str]) -> Result<()> {
+        TcpSystemConfig::default().only(fields)?;
+        if let Some(field) = fields.iter().find(|f| registry_value_name(f).is_none() && !is_port_range_field(f)) {
+            return Err(NetOptError::UnsupportedPlatform(format!("Windows 不支持重置 {}", field)));
+        }
+        if self.backend.is_system() {
+            crate::backup::auto_backup(self)?;
+        }
+
+        if fields.iter().any(|f| is_port_range_field(f)) {
+            let default = DynamicPortRange::DEFAULT;
+            self.set_dynamic_port_range(
+                fields.contains(&"dynamic_port_start").then_some(default.start),
+                fields.contains(&"max_user_port").then_some(default.end()),
+            )?;
+        }
+        for name in fields.iter().filter_map(|f| registry_value_name(f)) {
+            self.backend.remove(name)?;
+            self.reboot.mark();
+        }
+        Ok(())
     }
     
     fn get_default_config(&self) -> TcpSystemConfig {
         TcpSystemConfig {
-            max_user_port: Some(5000),
+            max_user_port: Some(DynamicPortRange::DEFAULT.end()),
             time_wait_delay: Some(240),
-            dynamic_port_start: Some(1025),
+            dynamic_port_start: Some(DynamicPortRange::DEFAULT.start),
             max_syn_retransmissions: Some(2),
-            keep_alive_time: Some(7200000),
-            keep_alive_interval: Some(1000),
+            keep_alive_time: Some(7200),   // 7200000 ms
+            keep_alive_interval: Some(1),  // 1000 ms
+            tw_reuse: None, // 仅 Linux
+            max_tw_buckets: None,
         }
     }
     
@@ -170,41 +329,80 @@ impl TcpConfigManager for WindowsTcpConfigManager {
     }
     
     fn requires_reboot(&self) -> bool {
-        true // Windows修改TCP参数通常需要重启
+        self.reboot.is_pending()
     }
 }
 
 /// Windows TCP监控器
 /// 使用 IP Helper API (GetExtendedTcpTable)
 pub struct WindowsTcpMonitor {
-    /// 进程名缓存 (PID -> 进程名)
-    process_cache: std::sync::Mutex<std::collections::HashMap<u32, String>>,
+    /// 进程名缓存 (PID -> 进程名)，过期后重新查询以应对 PID 回收
+    process_cache: std::sync::Mutex<PidCache<String>>,
+    /// 可执行文件路径缓存 (PID -> 路径)
+    exe_cache: std::sync::Mutex<PidCache<Option<String>>>,
 }
 
 impl WindowsTcpMonitor {
     pub fn new() -> Self {
+        Self::with_cache_ttl(DEFAULT_TTL)
+    }
+
+    /// 指定进程信息缓存的有效期
+    pub fn with_cache_ttl(ttl: std::time::Duration) -> Self {
         Self {
-            process_cache: std::sync::Mutex::new(std::collections::HashMap::new()),
+            process_cache: std::sync::Mutex::new(PidCache::new(ttl)),
+            exe_cache: std::sync::Mutex::new(PidCache::new(ttl)),
         }
     }
 
-    /// 使用 netstat 命令获取连接（备用方案）
-    fn parse_netstat(&self) -> Result<Vec<TcpConnection>> {
-        use std::process::Command;
+    /// 获取可执行文件路径，结果按 PID 缓存
+    fn get_exe_path(&self, pid: u32) -> Option<String> {
+        // PID 0/4 为 System Idle / System，没有映像路径
+        if pid == 0 || pid == 4 {
+            return None;
+        }
+        let mut cache = self.exe_cache.lock().unwrap();
+        cache.get_or_insert_with(pid, std::time::Instant::now(), || Self::query_exe_path(pid))
+    }
 
-        let output = Command::new("netstat")
-            .args(["-ano", "-p", "tcp"])
-            .output()
-            .map_err(|e| NetOptError::SystemError(e.to_string()))?;
+    #[cfg(target_os = "windows")]
+    fn query_exe_path(pid: u32) -> Option<String> {
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+        };
+        use windows::core::PWSTR;
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut connections = Vec::new();
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+            let mut buf = [0u16; 1024];
+            let mut len = buf.len() as u32;
+            let result = QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR(buf.as_mut_ptr()), &mut len);
+            let _ = CloseHandle(handle);
+            result.ok()?;
+            Some(String::from_utf16_lossy(&buf[..len as usize]))
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn query_exe_path(_pid: u32) -> Option<String> {
+        None
+    }
 
-        for line in stdout.lines().skip(4) {
-            if let Some(conn) = self.parse_netstat_line(line) {
-                connections.push(conn);
+    /// 获取 TCP 连接：优先 GetExtendedTcpTable，失败时回退到 netstat。
+    /// 启用 `text-fallback` 特性时始终使用 netstat
+    fn query_connections(&self) -> Result<Vec<TcpConnection>> {
+        let mut connections = if cfg!(feature = "text-fallback") {
+            self.parse_netstat()?
+        } else {
+            match Self::get_tcp_table() {
+                Ok(connections) => connections,
+                Err(e) => {
+                    tracing::debug!("GetExtendedTcpTable 失败，回退到 netstat: {}", e);
+                    self.parse_netstat()?
+                }
             }
-        }
+        };
 
         // 收集需要查询的唯一 PID
         let unique_pids: std::collections::HashSet<u32> = connections.iter()
@@ -227,48 +425,227 @@ impl WindowsTcpMonitor {
         Ok(connections)
     }
 
-    /// 批量获取进程名，使用缓存减少 tasklist 调用
-    fn get_process_names_batch(&self, pids: &std::collections::HashSet<u32>) -> std::collections::HashMap<u32, String> {
-        use std::process::Command;
+    /// 通过 GetExtendedTcpTable 读取 IPv4 连接表（与 `netstat -p tcp` 范围一致）
+    #[cfg(target_os = "windows")]
+    fn get_tcp_table() -> Result<Vec<TcpConnection>> {
+        use windows::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+        use windows::Win32::NetworkManagement::IpHelper::{
+            GetExtendedTcpTable, MIB_TCPROW_OWNER_PID, MIB_TCPTABLE_OWNER_PID, TCP_TABLE_OWNER_PID_ALL,
+        };
+        use windows::Win32::Networking::WinSock::AF_INET;
+
+        let mut size = 0u32;
+        let mut buf: Vec<u8> = Vec::new();
+        // 两次调用之间连接数可能增长，缓冲区不足时重试
+        for _ in 0..3 {
+            buf.resize(size as usize, 0);
+            let ptr = if buf.is_empty() { None } else { Some(buf.as_mut_ptr() as *mut std::ffi::c_void) };
+            let ret = unsafe {
+                GetExtendedTcpTable(ptr, &mut size, false, AF_INET.0 as u32, TCP_TABLE_OWNER_PID_ALL, 0)
+            };
+            if ret == NO_ERROR.0 && !buf.is_empty() {
+                let table = buf.as_ptr() as *const MIB_TCPTABLE_OWNER_PID;
+                let rows = unsafe {
+                    let count = std::ptr::read_unaligned(std::ptr::addr_of!((*table).dwNumEntries)) as usize;
+                    let first = std::ptr::addr_of!((*table).table) as *const MIB_TCPROW_OWNER_PID;
+                    (0..count).map(|i| std::ptr::read_unaligned(first.add(i))).collect::<Vec<_>>()
+                };
+                return Ok(rows.iter().map(|row| Self::row_to_connection(
+                    row.dwState, row.dwLocalAddr, row.dwLocalPort,
+                    row.dwRemoteAddr, row.dwRemotePort, row.dwOwningPid,
+                )).collect());
+            }
+            if ret != ERROR_INSUFFICIENT_BUFFER.0 && !(ret == NO_ERROR.0 && buf.is_empty()) {
+                return Err(NetOptError::SystemError(format!("GetExtendedTcpTable 失败, 错误码: {}", ret)));
+            }
+        }
+        Err(NetOptError::SystemError("GetExtendedTcpTable 缓冲区大小持续变化".into()))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn get_tcp_table() -> Result<Vec<TcpConnection>> {
+        Err(NetOptError::UnsupportedPlatform("Not Windows".into()))
+    }
+
+    /// MIB_TCPROW_OWNER_PID 转换为连接，地址和端口均为网络字节序
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn row_to_connection(
+        state: u32,
+        local_addr: u32,
+        local_port: u32,
+        remote_addr: u32,
+        remote_port: u32,
+        pid: u32,
+    ) -> TcpConnection {
+        use std::net::Ipv4Addr;
+
+        let remote_port = u16::from_be(remote_port as u16);
+        // 监听套接字的远端端口字段无意义，与 netstat 保持一致显示为 0
+        let remote_port = if state == 2 { 0 } else { remote_port };
+
+        let mib_state = Self::mib_state(state);
+        TcpConnection {
+            local_addr: Ipv4Addr::from(local_addr.to_ne_bytes()).to_string(),
+            local_port: u16::from_be(local_port as u16),
+            remote_addr: Ipv4Addr::from(remote_addr.to_ne_bytes()).to_string(),
+            remote_port,
+            state: mib_state,
+            pid,
+            process_name: String::new(),
+            raw_state: (mib_state == TcpState::Unknown).then(|| format!("MIB_TCP_STATE {}", state)),
+            remote_host: None,
+        }
+    }
+
+    /// MIB_TCP_STATE 取值
+    fn mib_state(state: u32) -> TcpState {
+        match state {
+            1 => TcpState::Closed,
+            2 => TcpState::Listen,
+            3 => TcpState::SynSent,
+            4 => TcpState::SynReceived,
+            5 => TcpState::Established,
+            6 => TcpState::FinWait1,
+            7 => TcpState::FinWait2,
+            8 => TcpState::CloseWait,
+            9 => TcpState::Closing,
+            10 => TcpState::LastAck,
+            11 => TcpState::TimeWait,
+            _ => TcpState::Unknown,
+        }
+    }
+
+    /// 使用 netstat 命令获取连接（备用方案）
+    fn parse_netstat(&self) -> Result<Vec<TcpConnection>> {
+        let output = run_command("netstat", &["-ano", "-p", "tcp"])?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(netstat::parse_windows_tcp(&stdout))
+    }
 
+    /// 使用 netstat 命令获取 UDP 套接字
+    fn parse_netstat_udp(&self) -> Result<Vec<UdpSocketInfo>> {
+        let output = run_command("netstat", &["-ano", "-p", "udp"])?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut sockets = netstat::parse_windows_udp(&stdout);
+
+        let unique_pids: std::collections::HashSet<u32> = sockets.iter()
+            .filter(|s| s.pid > 0)
+            .map(|s| s.pid)
+            .collect();
+        let process_names = self.get_process_names_batch(&unique_pids);
+        for socket in &mut sockets {
+            if let Some(name) = process_names.get(&socket.pid) {
+                socket.process_name = name.clone();
+            }
+        }
+
+        Ok(sockets)
+    }
+
+    /// 批量获取进程名，使用缓存减少进程枚举
+    fn get_process_names_batch(&self, pids: &std::collections::HashSet<u32>) -> std::collections::HashMap<u32, String> {
+        let now = std::time::Instant::now();
         let mut result = std::collections::HashMap::new();
         let mut cache = self.process_cache.lock().unwrap();
 
-        // 先从缓存获取
+        // 先从缓存获取（过期项会被移除）
         let mut missing_pids: Vec<u32> = Vec::new();
         for &pid in pids {
-            if let Some(name) = cache.get(&pid) {
+            if let Some(name) = cache.get(pid, now) {
                 result.insert(pid, name.clone());
             } else {
                 missing_pids.push(pid);
             }
         }
 
-        // 如果有缺失的 PID，一次性调用 tasklist 获取所有进程
+        // 如果有缺失的 PID，一次性枚举所有进程
         if !missing_pids.is_empty() {
-            if let Ok(output) = Command::new("tasklist").args(["/FO", "CSV", "/NH"]).output() {
-                // Windows tasklist 输出使用系统默认编码 (GBK/CP936 for Chinese Windows)
-                // 尝试将输出解码为正确的字符串
-                let stdout = Self::decode_windows_output(&output.stdout);
-                for line in stdout.lines() {
-                    // 格式: "进程名","PID",...
-                    let parts: Vec<&str> = line.split(',').collect();
-                    if parts.len() >= 2 {
-                        let name = parts[0].trim_matches('"').to_string();
-                        if let Ok(pid) = parts[1].trim_matches('"').parse::<u32>() {
-                            cache.insert(pid, name.clone());
-                            if pids.contains(&pid) {
-                                result.insert(pid, name);
-                            }
-                        }
-                    }
+            let running = Self::list_processes();
+            for (&pid, name) in &running {
+                cache.insert(pid, name.clone(), now);
+                if pids.contains(&pid) {
+                    result.insert(pid, name.clone());
                 }
             }
+            // 不在进程列表中的 PID 名称无法确认，丢弃其缓存
+            if !running.is_empty() {
+                cache.retain(|pid| running.contains_key(&pid));
+            }
         }
 
         result
     }
 
+    /// 枚举全部进程 (PID -> 进程名)：优先 Toolhelp 快照，失败时回退到 tasklist。
+    /// 启用 `text-fallback` 特性时始终使用 tasklist
+    fn list_processes() -> HashMap<u32, String> {
+        if !cfg!(feature = "text-fallback") {
+            match Self::snapshot_processes() {
+                Ok(processes) => return processes,
+                Err(e) => tracing::debug!("进程快照失败，回退到 tasklist: {}", e),
+            }
+        }
+        Self::tasklist_processes()
+    }
+
+    #[cfg(target_os = "windows")]
+    fn snapshot_processes() -> Result<HashMap<u32, String>> {
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Diagnostics::ToolHelp::{
+            CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+        };
+
+        let mut processes = HashMap::new();
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)
+                .map_err(|e| NetOptError::SystemError(format!("CreateToolhelp32Snapshot 失败: {}", e)))?;
+            let mut entry = PROCESSENTRY32W {
+                dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+                ..Default::default()
+            };
+            let mut next = Process32FirstW(snapshot, &mut entry);
+            while next.is_ok() {
+                let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+                processes.insert(entry.th32ProcessID, String::from_utf16_lossy(&entry.szExeFile[..len]));
+                next = Process32NextW(snapshot, &mut entry);
+            }
+            let _ = CloseHandle(snapshot);
+        }
+
+        if processes.is_empty() {
+            return Err(NetOptError::SystemError("进程快照为空".into()));
+        }
+        Ok(processes)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn snapshot_processes() -> Result<HashMap<u32, String>> {
+        Err(NetOptError::UnsupportedPlatform("Not Windows".into()))
+    }
+
+    /// 解析 tasklist 输出（备用方案）
+    fn tasklist_processes() -> HashMap<u32, String> {
+        let mut processes = HashMap::new();
+        if let Ok(output) = run_command("tasklist", &["/FO", "CSV", "/NH"]) {
+            // Windows tasklist 输出使用系统默认编码 (GBK/CP936 for Chinese Windows)
+            // 尝试将输出解码为正确的字符串
+            let stdout = Self::decode_windows_output(&output.stdout);
+            for line in stdout.lines() {
+                // 格式: "进程名","PID",...
+                let parts: Vec<&str> = line.split(',').collect();
+                if parts.len() >= 2 {
+                    let name = parts[0].trim_matches('"').to_string();
+                    if let Ok(pid) = parts[1].trim_matches('"').parse::<u32>() {
+                        processes.insert(pid, name);
+                    }
+                }
+            }
+        }
+        processes
+    }
+
     /// 解码 Windows 命令行输出（处理中文编码）
     #[cfg(target_os = "windows")]
     fn decode_windows_output(bytes: &[u8]) -> String {
@@ -321,57 +698,15 @@ impl WindowsTcpMonitor {
     fn decode_windows_output(bytes: &[u8]) -> String {
         String::from_utf8_lossy(bytes).to_string()
     }
-
-    fn parse_netstat_line(&self, line: &str) -> Option<TcpConnection> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 5 {
-            return None;
-        }
-
-        let (local_addr, local_port) = Self::parse_addr_port(parts[1])?;
-        let (remote_addr, remote_port) = Self::parse_addr_port(parts[2])?;
-        let state = Self::parse_state(parts[3]);
-        let pid = parts[4].parse().ok()?;
-
-        Some(TcpConnection {
-            local_addr,
-            local_port,
-            remote_addr,
-            remote_port,
-            state,
-            pid,
-            process_name: String::new(),
-        })
-    }
-
-    fn parse_addr_port(s: &str) -> Option<(String, u16)> {
-        let idx = s.rfind(':')?;
-        let addr = s[..idx].to_string();
-        let port = s[idx + 1..].parse().ok()?;
-        Some((addr, port))
-    }
-
-    fn parse_state(s: &str) -> TcpState {
-        match s {
-            "LISTENING" => TcpState::Listen,
-            "ESTABLISHED" => TcpState::Established,
-            "TIME_WAIT" => TcpState::TimeWait,
-            "CLOSE_WAIT" => TcpState::CloseWait,
-            "FIN_WAIT_1" => TcpState::FinWait1,
-            "FIN_WAIT_2" => TcpState::FinWait2,
-            "SYN_SENT" => TcpState::SynSent,
-            "SYN_RECEIVED" => TcpState::SynReceived,
-            "CLOSING" => TcpState::Closing,
-            "LAST_ACK" => TcpState::LastAck,
-            "CLOSED" => TcpState::Closed,
-            _ => TcpState::Unknown,
-        }
-    }
 }
 
 impl TcpMonitor for WindowsTcpMonitor {
     fn get_all_connections(&self) -> Result<Vec<TcpConnection>> {
-        self.parse_netstat()
+        self.query_connections()
+    }
+
+    fn get_udp_sockets(&self) -> Result<Vec<UdpSocketInfo>> {
+        self.parse_netstat_udp()
     }
 
     fn get_process_connections(&self, pid: u32) -> Result<Vec<TcpConnection>> {
@@ -380,7 +715,11 @@ impl TcpMonitor for WindowsTcpMonitor {
     }
 
     fn get_process_stats(&self, pid: u32) -> Result<ProcessTcpStats> {
-        let connections = self.get_process_connections(pid)?;
+        self.process_stats_from(pid, &self.get_all_connections()?)
+    }
+
+    fn process_stats_from(&self, pid: u32, connections: &[TcpConnection]) -> Result<ProcessTcpStats> {
+        let connections: Vec<&TcpConnection> = connections.iter().filter(|c| c.pid == pid).collect();
         let process_name = if connections.is_empty() {
             // 如果没有连接，批量查询获取进程名
             let mut pids = std::collections::HashSet::new();
@@ -393,18 +732,13 @@ impl TcpMonitor for WindowsTcpMonitor {
         let mut stats = ProcessTcpStats {
             pid,
             process_name,
+            exe_path: self.get_exe_path(pid),
             total_connections: connections.len(),
             ..Default::default()
         };
 
         for conn in &connections {
-            match conn.state {
-                TcpState::Established => stats.established += 1,
-                TcpState::TimeWait => stats.time_wait += 1,
-                TcpState::CloseWait => stats.close_wait += 1,
-                TcpState::Listen => stats.listen += 1,
-                _ => stats.other += 1,
-            }
+            stats.record_state(conn.state);
         }
 
         stats.health_score = calculate_health_score(&stats);
@@ -412,63 +746,46 @@ impl TcpMonitor for WindowsTcpMonitor {
     }
 
     fn get_system_stats(&self) -> Result<SystemTcpStats> {
-        let connections = self.get_all_connections()?;
-        let mut by_state = HashMap::new();
-        let mut by_process_map: HashMap<u32, ProcessTcpStats> = HashMap::new();
-
-        for conn in &connections {
-            *by_state.entry(conn.state).or_insert(0) += 1;
+        self.get_system_stats_with(&StatsOptions::default())
+    }
 
-            let entry = by_process_map.entry(conn.pid).or_insert_with(|| ProcessTcpStats {
-                pid: conn.pid,
-                process_name: conn.process_name.clone(),
-                ..Default::default()
-            });
-
-            entry.total_connections += 1;
-            match conn.state {
-                TcpState::Established => entry.established += 1,
-                TcpState::TimeWait => entry.time_wait += 1,
-                TcpState::CloseWait => entry.close_wait += 1,
-                TcpState::Listen => entry.listen += 1,
-                _ => entry.other += 1,
-            }
-        }
+    fn get_system_stats_with(&self, options: &StatsOptions) -> Result<SystemTcpStats> {
+        self.system_stats_from(&self.get_all_connections()?, options)
+    }
 
-        let mut by_process: Vec<_> = by_process_map.into_values().collect();
+    fn system_stats_from(&self, connections: &[TcpConnection], options: &StatsOptions) -> Result<SystemTcpStats> {
+        let mut by_process = summarize_processes(connections, options);
         for p in &mut by_process {
-            p.health_score = calculate_health_score(p);
+            p.exe_path = self.get_exe_path(p.pid);
         }
-        by_process.sort_by(|a, b| b.total_connections.cmp(&a.total_connections));
 
-        // 获取端口范围
-        let config_mgr = WindowsTcpConfigManager::new();
-        let config = config_mgr.get_current_config().unwrap_or_default();
-        let port_start = config.dynamic_port_start.unwrap_or(1025) as usize;
-        let port_end = config.max_user_port.unwrap_or(5000) as usize;
-        let total_ports = port_end - port_start + 1;
-        let used_ports = connections.len();
+        // 获取端口范围计算可用端口，调用方指定时不读取系统配置。
+        // 读取系统范围要启动 netsh，结果缓存到范围被修改或过期
+        let (port_start, port_end) = options.port_range.unwrap_or_else(|| {
+            super::netsh::PORT_RANGE_CACHE.get_or_refresh(std::time::Instant::now(), || {
+                let config = WindowsTcpConfigManager::new().get_current_config().unwrap_or_default();
+                (
+                    config.dynamic_port_start.unwrap_or(DynamicPortRange::DEFAULT.start),
+                    config.max_user_port.unwrap_or(DynamicPortRange::DEFAULT.end()),
+                )
+            })
+        });
+        let used_ports = ephemeral_ports_in_use(connections, port_start, port_end);
+        let usage = port_usage(used_ports, port_start, port_end);
 
         Ok(SystemTcpStats {
             total_connections: connections.len(),
-            by_state,
+            by_state: calculate_stats(connections),
+            by_family: calculate_family_stats(connections),
             by_process,
-            available_ports: total_ports.saturating_sub(used_ports),
-            port_usage_percent: (used_ports as f32 / total_ports as f32) * 100.0,
+            available_ports: usage.available,
+            ephemeral_ports_in_use: used_ports,
+            port_usage_percent: usage.percent,
+            dynamic_port_range: Some((port_start, port_end)),
+            udp_ports_used: None,
+            unknown_state_samples: unknown_state_samples(connections),
         })
     }
-
-    fn get_top_processes(&self, limit: usize) -> Result<Vec<ProcessTcpStats>> {
-        let stats = self.get_system_stats()?;
-        Ok(stats.by_process.into_iter().take(limit).collect())
-    }
-
-    fn get_problematic_processes(&self, threshold: usize) -> Result<Vec<ProcessTcpStats>> {
-        let stats = self.get_system_stats()?;
-        Ok(stats.by_process.into_iter()
-            .filter(|p| p.time_wait > threshold || p.close_wait > threshold / 4)
-            .collect())
-    }
 }
 
 /// Windows 连接优化器
@@ -509,6 +826,8 @@ impl WindowsConnectionOptimizer {
 
 impl ConnectionOptimizer for WindowsConnectionOptimizer {
     fn close_connection(&self, conn: &TcpConnection) -> Result<()> {
+        crate::optimizer::ensure_reapable(conn.state)?;
+
         #[cfg(target_os = "windows")]
         {
             #[allow(unused_imports)]
@@ -581,74 +900,26 @@ impl ConnectionOptimizer for WindowsConnectionOptimizer {
         }
     }
 
-    fn close_connections_by_state(&self, pid: u32, state: TcpState) -> Result<usize> {
-        // 获取进程的所有连接
-        let connections = self.monitor.get_process_connections(pid)?;
-
-        let mut closed = 0;
-        for conn in connections {
-            if conn.state == state {
-                if self.close_connection(&conn).is_ok() {
-                    closed += 1;
-                }
-            }
-        }
-
-        Ok(closed)
+    fn close_connections_by_state(&self, pid: u32, state: TcpState, policy: &AppPolicy) -> Result<usize> {
+        let mut connections = self.monitor.get_process_connections(pid)?;
+        crate::optimizer::close_unprotected(self, &mut connections, state, policy)
     }
 
     fn optimize_process(&self, pid: u32, policy: &AppPolicy) -> Result<crate::optimizer::OptimizationAction> {
-        use crate::optimizer::{OptimizationAction, ActionType};
-
-        let stats = self.monitor.get_process_stats(pid)?;
-        let mut connections_affected = 0;
-        let mut action_type = ActionType::None;
-        let mut reason = String::new();
-
-        // 检查 TIME_WAIT 阈值
-        if let Some(threshold) = policy.time_wait_threshold {
-            if stats.time_wait > threshold {
-                reason = format!(
-                    "TIME_WAIT({}) 超过阈值({}), 正在清理",
-                    stats.time_wait, threshold
-                );
-
-                let closed = self.close_connections_by_state(pid, TcpState::TimeWait)?;
-                connections_affected += closed;
-                action_type = ActionType::CloseTimeWait;
-            }
-        }
-
-        // 检查 CLOSE_WAIT 阈值
-        if let Some(threshold) = policy.close_wait_threshold {
-            if stats.close_wait > threshold {
-                if !reason.is_empty() {
-                    reason.push_str("; ");
-                }
-                reason.push_str(&format!(
-                    "CLOSE_WAIT({}) 超过阈值({}), 正在清理",
-                    stats.close_wait, threshold
-                ));
-
-                let closed = self.close_connections_by_state(pid, TcpState::CloseWait)?;
-                connections_affected += closed;
-                action_type = ActionType::CloseCloseWait;
-            }
-        }
+        self.optimize_process_from(pid, policy, &self.monitor.get_all_connections()?)
+    }
 
-        if reason.is_empty() {
-            reason = "连接状态正常，无需优化".into();
-        }
+    fn connection_table(&self) -> Result<Vec<TcpConnection>> {
+        self.monitor.get_all_connections()
+    }
 
-        Ok(OptimizationAction {
-            pid,
-            process_name: policy.process_name.clone(),
-            action_type,
-            reason,
-            connections_affected,
-            success: true,
-            error_message: None,
-        })
+    fn optimize_process_from(
+        &self,
+        pid: u32,
+        policy: &AppPolicy,
+        connections: &[TcpConnection],
+    ) -> Result<crate::optimizer::OptimizationAction> {
+        crate::optimizer::reap_by_policy(self, pid, policy, connections)
     }
 
     fn supports_connection_control(&self) -> bool {
@@ -656,3 +927,34 @@ impl ConnectionOptimizer for WindowsConnectionOptimizer {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp_config::MemoryBackend;
+
+    #[test]
+    fn keepalive_registry_values_are_milliseconds() {
+        let backend = Arc::new(
+            MemoryBackend::new()
+                .with("KeepAliveTime", 7_200_000)
+                .with("KeepAliveInterval", 1000),
+        );
+        let mgr = WindowsTcpConfigManager::with_backend(backend.clone());
+
+        let current = mgr.get_current_config().unwrap();
+        assert_eq!(current.keep_alive_time, Some(7200));
+        assert_eq!(current.keep_alive_interval, Some(1));
+        assert!(!mgr.requires_reboot());
+
+        mgr.apply_config(&TcpSystemConfig::builder().keep_alive_time(600).keep_alive_interval(10).build()).unwrap();
+        assert_eq!(backend.read_u32("KeepAliveTime"), Some(600_000));
+        assert_eq!(backend.read_u32("KeepAliveInterval"), Some(10_000));
+        assert!(mgr.requires_reboot());
+
+        // 重置删除注册表值
+        mgr.reset_to_default(&["keep_alive_time"]).unwrap();
+        assert_eq!(backend.read("KeepAliveTime"), None);
+        assert_eq!(mgr.get_current_config().unwrap().keep_alive_time, None);
+    }
+}
@@ -23,12 +23,37 @@ pub struct AppPolicy {
     /// CLOSE_WAIT连接阈值，超过则告警 (None表示不限制)
     pub close_wait_threshold: Option<usize>,
 
-    /// 最大允许连接数 (None表示不限制)
+    /// 最大允许连接数 (None表示不限制)。超过时按 `threshold_action` 处理，
+    /// Optimize 只关闭 TIME_WAIT / CLOSE_WAIT，见 `optimizer::excess_connections`
     pub max_connections: Option<usize>,
 
     /// 当超过阈值时的动作
     pub threshold_action: ThresholdAction,
 
+    /// 进程健康评分不低于此值时，即使超过阈值也不关闭连接（告警照常）。
+    /// 繁忙服务器上 TIME_WAIT 偏高是正常现象，避免反复清理。None 表示不检查
+    #[serde(default)]
+    pub min_health_to_skip: Option<u8>,
+
+    /// 自动优化的生效时段 `(起始小时, 结束小时)`，本地时间 24 小时制，左闭右开。
+    /// 起始大于结束表示跨午夜（如 `(22, 6)`），两者相等表示全天。
+    /// 时段外超过阈值只告警，不关闭连接。None 表示全天生效
+    #[serde(default)]
+    pub active_hours: Option<(u8, u8)>,
+
+    /// 受保护端口，本地或远端端口在列表中的连接永不关闭
+    #[serde(default)]
+    pub protected_ports: Vec<u16>,
+
+    /// 受保护端口范围（闭区间）
+    #[serde(default)]
+    pub protected_port_ranges: Vec<(u16, u16)>,
+
+    /// 无法直接关闭连接的平台（macOS）上，超过阈值时发送给进程的信号。
+    /// None 表示不发送
+    #[serde(default)]
+    pub graceful_signal: Option<GracefulSignal>,
+
     /// 优先级（数字越小优先级越高）
     pub priority: u8,
 
@@ -36,8 +61,28 @@ pub struct AppPolicy {
     pub note: String,
 }
 
+/// 策略中不合理的参数组合
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyIssue {
+    /// CLOSE_WAIT 阈值高于 TIME_WAIT 阈值（CLOSE_WAIT 是泄漏信号，阈值应更低）
+    CloseWaitAboveTimeWait,
+    /// 最大连接数低于进程当前连接数，启用后会立即触发
+    MaxConnectionsBelowUsage { current: usize },
+}
+
+/// 通知进程清理连接的信号
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GracefulSignal {
+    /// SIGTERM，请求进程退出
+    #[default]
+    Term,
+    /// SIGHUP，多数守护进程会重新加载并重建连接
+    Hup,
+}
+
 /// 超过阈值时的动作
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum ThresholdAction {
     /// 仅告警
     Alert,
@@ -63,6 +108,11 @@ impl Default for AppPolicy {
             close_wait_threshold: Some(30),
             max_connections: None,
             threshold_action: ThresholdAction::Alert,
+            min_health_to_skip: None,
+            active_hours: None,
+            protected_ports: Vec::new(),
+            protected_port_ranges: Vec::new(),
+            graceful_signal: None,
             priority: 100,
             note: String::new(),
         }
@@ -70,6 +120,38 @@ impl Default for AppPolicy {
 }
 
 impl AppPolicy {
+    /// 检查参数组合，`current` 为该进程当前的统计（未运行时为 None）
+    pub fn check(&self, current: Option<&crate::ProcessTcpStats>) -> Vec<PolicyIssue> {
+        let mut issues = Vec::new();
+        if let (Some(cw), Some(tw)) = (self.close_wait_threshold, self.time_wait_threshold) {
+            if cw > tw {
+                issues.push(PolicyIssue::CloseWaitAboveTimeWait);
+            }
+        }
+        if let (Some(max), Some(stats)) = (self.max_connections, current) {
+            if max < stats.total_connections {
+                issues.push(PolicyIssue::MaxConnectionsBelowUsage { current: stats.total_connections });
+            }
+        }
+        issues
+    }
+
+    /// 连接的本地或远端端口是否受保护
+    pub fn is_protected(&self, conn: &crate::TcpConnection) -> bool {
+        connection_matches(&self.protected_ports, &self.protected_port_ranges, conn)
+    }
+
+    /// 本地时间 `hour` 点是否在自动优化的生效时段内
+    pub fn is_active_at(&self, hour: u8) -> bool {
+        match self.active_hours {
+            None => true,
+            Some((start, end)) if start == end => true,
+            Some((start, end)) if start < end => (start..end).contains(&hour),
+            // 跨午夜，如 22-6 包含 22:00-23:59 和 0:00-5:59
+            Some((start, end)) => hour >= start || hour < end,
+        }
+    }
+
     /// 创建高性能应用策略（如游戏、下载器）
     /// TIME_WAIT 允许较多（正常行为），但 CLOSE_WAIT 严格控制
     pub fn high_performance(process_name: &str) -> Self {
@@ -134,6 +216,15 @@ impl AppPolicy {
     }
 }
 
+/// 端口在列表中或落在任一闭区间内
+fn port_matches(ports: &[u16], ranges: &[(u16, u16)], port: u16) -> bool {
+    ports.contains(&port) || ranges.iter().any(|&(start, end)| (start..=end).contains(&port))
+}
+
+fn connection_matches(ports: &[u16], ranges: &[(u16, u16)], conn: &crate::TcpConnection) -> bool {
+    port_matches(ports, ranges, conn.local_port) || port_matches(ports, ranges, conn.remote_port)
+}
+
 /// 策略管理器
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PolicyManager {
@@ -148,13 +239,22 @@ pub struct PolicyManager {
     
     /// 黑名单进程（总是限制）
     pub blacklist: Vec<String>,
+
+    /// 全局白名单端口，本地或远端端口命中的连接不论进程策略如何都不会关闭
+    #[serde(default)]
+    pub whitelist_ports: Vec<u16>,
+
+    /// 全局白名单端口范围（闭区间）
+    #[serde(default)]
+    pub whitelist_port_ranges: Vec<(u16, u16)>,
 }
 
 impl PolicyManager {
     pub fn new() -> Self {
-        let mut manager = Self::default();
-        manager.default_policy = AppPolicy::default();
-        manager
+        Self {
+            default_policy: AppPolicy::default(),
+            ..Default::default()
+        }
     }
     
     /// 添加或更新策略
@@ -164,7 +264,12 @@ impl PolicyManager {
     
     /// 获取进程的策略（如果没有特定策略则返回默认）
     pub fn get_policy(&self, process_name: &str) -> &AppPolicy {
-        self.policies.get(process_name).unwrap_or(&self.default_policy)
+        self.find_policy(process_name).unwrap_or(&self.default_policy)
+    }
+
+    /// 获取进程的专属策略，没有时返回 None
+    pub fn find_policy(&self, process_name: &str) -> Option<&AppPolicy> {
+        self.policies.get(process_name)
     }
     
     /// 移除策略
@@ -176,15 +281,138 @@ impl PolicyManager {
     pub fn all_policies(&self) -> Vec<&AppPolicy> {
         self.policies.values().collect()
     }
+
+    /// 将默认策略的阈值和超阈值动作复制到所有进程策略，返回修改的策略数
+    pub fn apply_defaults_to_all(&mut self) -> usize {
+        let default = &self.default_policy;
+        for policy in self.policies.values_mut() {
+            policy.time_wait_threshold = default.time_wait_threshold;
+            policy.close_wait_threshold = default.close_wait_threshold;
+            policy.threshold_action = default.threshold_action;
+        }
+        self.policies.len()
+    }
+
+    /// 恢复内置默认策略
+    pub fn reset_default_policy(&mut self) {
+        self.default_policy = AppPolicy::default();
+    }
     
     /// 进程是否在白名单
     pub fn is_whitelisted(&self, process_name: &str) -> bool {
         self.whitelist.iter().any(|w| process_name.contains(w))
     }
     
+    /// 连接是否受全局白名单端口或所属进程策略保护
+    pub fn is_protected(&self, conn: &crate::TcpConnection) -> bool {
+        connection_matches(&self.whitelist_ports, &self.whitelist_port_ranges, conn)
+            || self.get_policy(&conn.process_name).is_protected(conn)
+    }
+
+    /// 进程的策略，合并全局白名单端口后交给 `ConnectionOptimizer::optimize_process`
+    pub fn effective_policy(&self, process_name: &str) -> AppPolicy {
+        let mut policy = self.get_policy(process_name).clone();
+        policy.protected_ports.extend(&self.whitelist_ports);
+        policy.protected_port_ranges.extend(&self.whitelist_port_ranges);
+        policy
+    }
+
     /// 进程是否在黑名单
     pub fn is_blacklisted(&self, process_name: &str) -> bool {
         self.blacklist.iter().any(|b| process_name.contains(b))
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_hours_handle_midnight_wrap() {
+        let mut policy = AppPolicy::default();
+        assert!((0..24).all(|h| policy.is_active_at(h)));
+
+        policy.active_hours = Some((9, 18));
+        assert!(policy.is_active_at(9) && policy.is_active_at(17));
+        assert!(!policy.is_active_at(18) && !policy.is_active_at(3));
+
+        policy.active_hours = Some((22, 6));
+        assert!(policy.is_active_at(22) && policy.is_active_at(23) && policy.is_active_at(0) && policy.is_active_at(5));
+        assert!(!policy.is_active_at(6) && !policy.is_active_at(12) && !policy.is_active_at(21));
+
+        policy.active_hours = Some((8, 8));
+        assert!((0..24).all(|h| policy.is_active_at(h)));
+    }
+
+    #[test]
+    fn apply_defaults_overwrites_thresholds_only() {
+        let mut manager = PolicyManager::new();
+        manager.set_policy(AppPolicy::crawler("scraper"));
+        manager.set_policy(AppPolicy::server("nginx"));
+        manager.default_policy.time_wait_threshold = Some(123);
+        manager.default_policy.close_wait_threshold = Some(12);
+
+        assert_eq!(manager.apply_defaults_to_all(), 2);
+
+        for policy in manager.all_policies() {
+            assert_eq!(policy.time_wait_threshold, Some(123));
+            assert_eq!(policy.close_wait_threshold, Some(12));
+            assert_eq!(policy.threshold_action, ThresholdAction::Alert);
+        }
+        // 其他字段保持各自的设置
+        assert!(!manager.get_policy("scraper").note.is_empty());
+
+        manager.reset_default_policy();
+        assert_eq!(manager.default_policy.time_wait_threshold, AppPolicy::default().time_wait_threshold);
+    }
+
+    #[test]
+    fn global_port_whitelist_applies_to_every_process() {
+        let conn = |process_name: &str, local_port: u16, remote_port: u16| crate::TcpConnection {
+            local_addr: "10.0.0.1".into(),
+            local_port,
+            remote_addr: "10.0.0.2".into(),
+            remote_port,
+            state: crate::TcpState::CloseWait,
+            pid: 1,
+            process_name: process_name.into(),
+            raw_state: None,
+            remote_host: None,
+        };
+        let mut manager = PolicyManager::new();
+        manager.set_policy(AppPolicy::crawler("scraper"));
+        manager.whitelist_ports = vec![5432];
+        manager.whitelist_port_ranges = vec![(3300, 3310)];
+
+        assert!(manager.is_protected(&conn("scraper", 50000, 5432)));
+        assert!(manager.is_protected(&conn("other", 3306, 50000)));
+        assert!(!manager.is_protected(&conn("scraper", 50000, 3311)));
+
+        // 传给平台优化器的策略同样带上全局白名单
+        let policy = manager.effective_policy("scraper");
+        assert!(policy.is_protected(&conn("scraper", 50000, 5432)));
+        assert!(policy.is_protected(&conn("scraper", 3310, 50000)));
+        assert_eq!(policy.threshold_action, ThresholdAction::Optimize);
+    }
+
+    #[test]
+    fn check_flags_nonsensical_thresholds() {
+        let stats = crate::ProcessTcpStats {
+            process_name: "web".into(),
+            total_connections: 800,
+            ..Default::default()
+        };
+        let mut policy = AppPolicy::server("web");
+        policy.time_wait_threshold = Some(100);
+        policy.close_wait_threshold = Some(150);
+        policy.max_connections = Some(500);
+
+        assert_eq!(policy.check(Some(&stats)), vec![
+            PolicyIssue::CloseWaitAboveTimeWait,
+            PolicyIssue::MaxConnectionsBelowUsage { current: 800 },
+        ]);
+        // 进程未运行时不检查当前用量
+        assert_eq!(policy.check(None), vec![PolicyIssue::CloseWaitAboveTimeWait]);
+        assert!(AppPolicy::default().check(Some(&stats)).is_empty());
+    }
+}
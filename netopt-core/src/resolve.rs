@@ -0,0 +1,410 @@
+//! 远端地址反向解析
+//!
+//! 排查外连时主机名比 IP 直观，但反向解析会产生 DNS 查询，因此默认不启用：只有调用方创建
+//! `ReverseDns` 并调用 `annotate` 时才会查询。结果（包括查询失败）缓存在 LRU 中；
+//! 单次标注最多等待 `timeout`，超时的查询在后台继续，完成后写入缓存供下次刷新使用
+
+use crate::monitor::parse_ip;
+use crate::TcpConnection;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// 默认缓存的地址数
+pub const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// 默认单次标注的等待上限
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// 同时进行的查询数上限
+const MAX_WORKERS: usize = 4;
+
+/// 反向解析实现
+pub trait HostResolver: Send + Sync {
+    /// 查询地址对应的主机名，没有 PTR 记录或查询失败时返回 None。可能阻塞较长时间
+    fn reverse_lookup(&self, addr: IpAddr) -> Option<String>;
+}
+
+/// 使用系统解析器（getnameinfo / GetNameInfoW）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl HostResolver for SystemResolver {
+    fn reverse_lookup(&self, addr: IpAddr) -> Option<String> {
+        sys::reverse_lookup(addr)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// 地址 -> (主机名, 最近使用序号)
+    cache: HashMap<IpAddr, (Option<String>, u64)>,
+    tick: u64,
+    /// 等待查询的地址
+    queue: VecDeque<IpAddr>,
+    /// 排队或正在查询的地址
+    pending: HashSet<IpAddr>,
+    workers: usize,
+}
+
+impl State {
+    fn get(&mut self, addr: &IpAddr) -> Option<Option<String>> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.cache.get_mut(addr).map(|(host, used)| {
+            *used = tick;
+            host.clone()
+        })
+    }
+
+    /// 写入缓存，已满时淘汰最久未使用的地址
+    fn insert(&mut self, addr: IpAddr, host: Option<String>, capacity: usize) {
+        if !self.cache.contains_key(&addr) && self.cache.len() >= capacity {
+            if let Some(oldest) = self.cache.iter().min_by_key(|(_, (_, used))| *used).map(|(addr, _)| *addr) {
+                self.cache.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.cache.insert(addr, (host, self.tick));
+    }
+}
+
+struct Shared {
+    resolver: Arc<dyn HostResolver>,
+    capacity: usize,
+    state: Mutex<State>,
+    resolved: Condvar,
+}
+
+impl Shared {
+    /// 查询线程：取完队列后退出
+    fn work(&self) {
+        loop {
+            let addr = {
+                let mut state = self.state.lock().unwrap();
+                match state.queue.pop_front() {
+                    Some(addr) => addr,
+                    None => {
+                        state.workers -= 1;
+                        return;
+                    }
+                }
+            };
+            let host = self.resolver.reverse_lookup(addr);
+            let mut state = self.state.lock().unwrap();
+            state.pending.remove(&addr);
+            state.insert(addr, host, self.capacity);
+            self.resolved.notify_all();
+        }
+    }
+}
+
+/// 带 LRU 缓存和超时的反向解析，克隆后共享同一缓存
+#[derive(Clone)]
+pub struct ReverseDns {
+    shared: Arc<Shared>,
+    timeout: Duration,
+}
+
+impl ReverseDns {
+    /// `capacity` 为缓存的地址数（至少为 1），`timeout` 为每次 `annotate` 最多等待的时间
+    pub fn new(resolver: Arc<dyn HostResolver>, capacity: usize, timeout: Duration) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                resolver,
+                capacity: capacity.max(1),
+                state: Mutex::new(State::default()),
+                resolved: Condvar::new(),
+            }),
+            timeout,
+        }
+    }
+
+    /// 系统解析器，使用默认缓存大小和超时
+    pub fn system() -> Self {
+        Self::new(Arc::new(SystemResolver), DEFAULT_CACHE_CAPACITY, DEFAULT_TIMEOUT)
+    }
+
+    /// 填写连接的 `remote_host`。未连接和本地回环地址不查询；
+    /// 超时前未完成的地址本次保持 None，查询在后台继续
+    pub fn annotate(&self, connections: &mut [TcpConnection]) {
+        let wanted: HashSet<IpAddr> = connections.iter().filter_map(remote_ip).collect();
+        if wanted.is_empty() {
+            return;
+        }
+        let deadline = Instant::now() + self.timeout;
+
+        let mut state = self.shared.state.lock().unwrap();
+        for &addr in &wanted {
+            if !state.cache.contains_key(&addr) && state.pending.insert(addr) {
+                state.queue.push_back(addr);
+            }
+        }
+        while state.workers < MAX_WORKERS && state.workers < state.queue.len() {
+            state.workers += 1;
+            let shared = Arc::clone(&self.shared);
+            std::thread::spawn(move || shared.work());
+        }
+
+        while wanted.iter().any(|addr| state.pending.contains(addr)) {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = self.shared.resolved.wait_timeout(state, deadline - now).unwrap().0;
+        }
+
+        let hosts: HashMap<IpAddr, String> = wanted.into_iter()
+            .filter_map(|addr| state.get(&addr).flatten().map(|host| (addr, host)))
+            .collect();
+        drop(state);
+
+        for conn in connections {
+            conn.remote_host = remote_ip(conn).and_then(|addr| hosts.get(&addr).cloned());
+        }
+    }
+}
+
+impl std::fmt::Debug for ReverseDns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReverseDns")
+            .field("capacity", &self.shared.capacity)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+fn remote_ip(conn: &TcpConnection) -> Option<IpAddr> {
+    parse_ip(&conn.remote_addr).filter(|ip| !ip.is_unspecified() && !ip.is_loopback())
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod sys {
+    use std::ffi::CStr;
+    use std::net::IpAddr;
+
+    /// NI_MAXHOST
+    const HOST_LEN: usize = 1025;
+
+    pub fn reverse_lookup(addr: IpAddr) -> Option<String> {
+        match addr {
+            IpAddr::V4(v4) => {
+                let mut sin: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+                sin.sin_family = libc::AF_INET as libc::sa_family_t;
+                sin.sin_addr.s_addr = u32::from_ne_bytes(v4.octets());
+                #[cfg(target_os = "macos")]
+                {
+                    sin.sin_len = std::mem::size_of::<libc::sockaddr_in>() as u8;
+                }
+                getnameinfo(&sin)
+            }
+            IpAddr::V6(v6) => {
+                let mut sin6: libc::sockaddr_in6 = unsafe { std::mem::zeroed() };
+                sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+                sin6.sin6_addr.s6_addr = v6.octets();
+                #[cfg(target_os = "macos")]
+                {
+                    sin6.sin6_len = std::mem::size_of::<libc::sockaddr_in6>() as u8;
+                }
+                getnameinfo(&sin6)
+            }
+        }
+    }
+
+    /// `addr` 为 sockaddr_in 或 sockaddr_in6。NI_NAMEREQD：没有 PTR 记录时失败，而不是返回数字地址
+    fn getnameinfo<T>(addr: &T) -> Option<String> {
+        let mut host = [0 as libc::c_char; HOST_LEN];
+        let ret = unsafe {
+            libc::getnameinfo(
+                addr as *const T as *const libc::sockaddr,
+                std::mem::size_of::<T>() as libc::socklen_t,
+                host.as_mut_ptr(),
+                HOST_LEN as libc::socklen_t,
+                std::ptr::null_mut(),
+                0,
+                libc::NI_NAMEREQD,
+            )
+        };
+        if ret != 0 {
+            return None;
+        }
+        let name = unsafe { CStr::from_ptr(host.as_ptr()) }.to_string_lossy().into_owned();
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::net::IpAddr;
+    use std::sync::Once;
+    use windows::Win32::Networking::WinSock::{
+        GetNameInfoW, WSAStartup, AF_INET, AF_INET6, IN6_ADDR, IN6_ADDR_0, IN_ADDR, IN_ADDR_0, NI_NAMEREQD, SOCKADDR,
+        SOCKADDR_IN, SOCKADDR_IN6, WSADATA, socklen_t,
+    };
+
+    /// NI_MAXHOST
+    const HOST_LEN: usize = 1025;
+
+    pub fn reverse_lookup(addr: IpAddr) -> Option<String> {
+        // GetNameInfoW 需要先初始化 Winsock
+        static STARTUP: Once = Once::new();
+        STARTUP.call_once(|| {
+            let mut data = WSADATA::default();
+            unsafe { WSAStartup(0x0202, &mut data) };
+        });
+
+        match addr {
+            IpAddr::V4(v4) => getnameinfo(&SOCKADDR_IN {
+                sin_family: AF_INET,
+                sin_addr: IN_ADDR { S_un: IN_ADDR_0 { S_addr: u32::from_ne_bytes(v4.octets()) } },
+                ..Default::default()
+            }),
+            IpAddr::V6(v6) => getnameinfo(&SOCKADDR_IN6 {
+                sin6_family: AF_INET6,
+                sin6_addr: IN6_ADDR { u: IN6_ADDR_0 { Byte: v6.octets() } },
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// `addr` 为 SOCKADDR_IN 或 SOCKADDR_IN6。NI_NAMEREQD：没有 PTR 记录时失败，而不是返回数字地址
+    fn getnameinfo<T>(addr: &T) -> Option<String> {
+        let mut host = [0u16; HOST_LEN];
+        let ret = unsafe {
+            GetNameInfoW(
+                addr as *const T as *const SOCKADDR,
+                socklen_t(std::mem::size_of::<T>() as i32),
+                Some(&mut host),
+                None,
+                NI_NAMEREQD as i32,
+            )
+        };
+        if ret != 0 {
+            return None;
+        }
+        let len = host.iter().position(|&c| c == 0).unwrap_or(HOST_LEN);
+        let name = String::from_utf16_lossy(&host[..len]);
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod sys {
+    pub fn reverse_lookup(_addr: std::net::IpAddr) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TcpState;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 按固定表应答并记录查询次数；`delay` 模拟慢速 DNS
+    struct MockResolver {
+        hosts: HashMap<IpAddr, String>,
+        delay: Duration,
+        lookups: AtomicUsize,
+    }
+
+    impl MockResolver {
+        fn new(delay: Duration) -> Arc<Self> {
+            let hosts = [("93.184.216.34", "example.com"), ("140.82.112.3", "lb-140-82-112-3-iad.github.com")]
+                .into_iter()
+                .map(|(ip, host)| (ip.parse().unwrap(), host.to_string()))
+                .collect();
+            Arc::new(Self { hosts, delay, lookups: AtomicUsize::new(0) })
+        }
+    }
+
+    impl HostResolver for MockResolver {
+        fn reverse_lookup(&self, addr: IpAddr) -> Option<String> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(self.delay);
+            self.hosts.get(&addr).cloned()
+        }
+    }
+
+    fn conn(remote: &str, local_port: u16) -> TcpConnection {
+        TcpConnection {
+            local_addr: "10.0.0.2".into(),
+            local_port,
+            remote_addr: remote.into(),
+            remote_port: 443,
+            state: TcpState::Established,
+            pid: 1,
+            process_name: "proc1".into(),
+            raw_state: None,
+            remote_host: None,
+        }
+    }
+
+    #[test]
+    fn annotates_and_caches_hosts() {
+        let resolver = MockResolver::new(Duration::ZERO);
+        let dns = ReverseDns::new(resolver.clone(), 16, Duration::from_secs(5));
+        let mut connections = vec![
+            conn("93.184.216.34", 50001),
+            conn("::ffff:93.184.216.34", 50002),
+            conn("140.82.112.3", 50003),
+            conn("10.9.9.9", 50004),
+            conn("127.0.0.1", 50005),
+            conn("0.0.0.0", 8080),
+        ];
+
+        dns.annotate(&mut connections);
+        let hosts: Vec<_> = connections.iter().map(|c| c.remote_host.as_deref()).collect();
+        assert_eq!(hosts, [
+            Some("example.com"),
+            Some("example.com"),
+            Some("lb-140-82-112-3-iad.github.com"),
+            None,
+            None,
+            None,
+        ]);
+        // 回环和未连接地址不查询，IPv4 映射地址与 IPv4 共用一次查询
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 3);
+
+        // 没有 PTR 记录的结果同样缓存
+        dns.annotate(&mut connections);
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn slow_resolver_does_not_block() {
+        let resolver = MockResolver::new(Duration::from_millis(300));
+        let dns = ReverseDns::new(resolver.clone(), 16, Duration::from_millis(20));
+        let mut connections = vec![conn("93.184.216.34", 50001)];
+
+        let start = Instant::now();
+        dns.annotate(&mut connections);
+        assert!(start.elapsed() < Duration::from_millis(250));
+        assert_eq!(connections[0].remote_host, None);
+
+        // 后台查询完成后，下次标注直接命中缓存
+        std::thread::sleep(Duration::from_millis(400));
+        dns.annotate(&mut connections);
+        assert_eq!(connections[0].remote_host.as_deref(), Some("example.com"));
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let resolver = MockResolver::new(Duration::ZERO);
+        let dns = ReverseDns::new(resolver.clone(), 2, Duration::from_secs(5));
+
+        dns.annotate(&mut [conn("93.184.216.34", 50001)]);
+        dns.annotate(&mut [conn("140.82.112.3", 50002)]);
+        // 再次使用 93.184.216.34，10.9.9.9 挤出的是 140.82.112.3
+        dns.annotate(&mut [conn("93.184.216.34", 50001)]);
+        dns.annotate(&mut [conn("10.9.9.9", 50003)]);
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 3);
+
+        dns.annotate(&mut [conn("93.184.216.34", 50001)]);
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 3);
+        dns.annotate(&mut [conn("140.82.112.3", 50002)]);
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 4);
+    }
+}
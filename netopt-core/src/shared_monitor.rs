@@ -0,0 +1,175 @@
+//! 可跨线程共享的监控器
+//!
+//! 平台监控器内部缓存了 PID -> 进程名/路径，每次刷新都新建监控器会丢掉缓存，
+//! 导致 Windows 上反复调用 tasklist、macOS 上反复调用 ps。`SharedMonitor`
+//! 克隆后指向同一个监控器，并保存最近一次的系统统计
+
+use crate::clock::{system_clock, Clock};
+use crate::monitor::TcpMonitor;
+use crate::{Result, SystemTcpStats};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 共享的监控器，克隆开销只是增加引用计数
+#[derive(Clone)]
+pub struct SharedMonitor {
+    monitor: Arc<dyn TcpMonitor>,
+    last_stats: Arc<Mutex<Option<(Instant, SystemTcpStats)>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl SharedMonitor {
+    pub fn new(monitor: Arc<dyn TcpMonitor>) -> Self {
+        Self {
+            monitor,
+            last_stats: Arc::new(Mutex::new(None)),
+            clock: system_clock(),
+        }
+    }
+
+    /// 替换记录采集时间的时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 使用当前平台的监控器
+    pub fn platform() -> Self {
+        Self::new(Arc::from(crate::platform::create_monitor()))
+    }
+
+    /// 内部监控器，可交给 `AsyncTcpMonitor` 等需要 `Arc` 的接口
+    pub fn monitor(&self) -> Arc<dyn TcpMonitor> {
+        Arc::clone(&self.monitor)
+    }
+
+    /// 重新采集系统统计并记为最近结果
+    pub fn refresh(&self) -> Result<SystemTcpStats> {
+        self.refresh_at(self.clock.now())
+    }
+
+    /// 同 `refresh`，以指定时间记录
+    pub fn refresh_at(&self, now: Instant) -> Result<SystemTcpStats> {
+        let stats = self.monitor.get_system_stats()?;
+        *self.last_stats.lock().unwrap() = Some((now, stats.clone()));
+        Ok(stats)
+    }
+
+    /// 最近一次成功采集的统计
+    pub fn last_stats(&self) -> Option<SystemTcpStats> {
+        self.last_stats.lock().unwrap().as_ref().map(|(_, stats)| stats.clone())
+    }
+
+    /// 最近结果不超过 `max_age` 时直接返回，否则重新采集
+    pub fn stats_within(&self, max_age: Duration) -> Result<SystemTcpStats> {
+        self.stats_within_at(max_age, self.clock.now())
+    }
+
+    /// 同 `stats_within`，以指定时间判断是否过期
+    pub fn stats_within_at(&self, max_age: Duration, now: Instant) -> Result<SystemTcpStats> {
+        if let Some((at, stats)) = self.last_stats.lock().unwrap().as_ref() {
+            if now.saturating_duration_since(*at) <= max_age {
+                return Ok(stats.clone());
+            }
+        }
+        self.refresh_at(now)
+    }
+}
+
+impl Deref for SharedMonitor {
+    type Target = dyn TcpMonitor;
+
+    fn deref(&self) -> &Self::Target {
+        self.monitor.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::cache::PidCache;
+    use crate::{ProcessTcpStats, TcpConnection};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 像平台监控器一样缓存进程名，并统计实际查询次数
+    struct NameCachingMonitor {
+        names: Mutex<PidCache<String>>,
+        lookups: Arc<AtomicUsize>,
+    }
+
+    impl NameCachingMonitor {
+        fn new(lookups: &Arc<AtomicUsize>) -> Self {
+            Self {
+                names: Mutex::new(PidCache::default()),
+                lookups: Arc::clone(lookups),
+            }
+        }
+    }
+
+    impl TcpMonitor for NameCachingMonitor {
+        fn get_all_connections(&self) -> Result<Vec<TcpConnection>> {
+            Ok(Vec::new())
+        }
+
+        fn get_process_connections(&self, _pid: u32) -> Result<Vec<TcpConnection>> {
+            Ok(Vec::new())
+        }
+
+        fn get_process_stats(&self, pid: u32) -> Result<ProcessTcpStats> {
+            Ok(ProcessTcpStats { pid, ..Default::default() })
+        }
+
+        fn get_system_stats(&self) -> Result<SystemTcpStats> {
+            let mut names = self.names.lock().unwrap();
+            let by_process = (1..=10)
+                .map(|pid| ProcessTcpStats {
+                    pid,
+                    process_name: names.get_or_insert_with(pid, Instant::now(), || {
+                        self.lookups.fetch_add(1, Ordering::SeqCst);
+                        format!("proc{}", pid)
+                    }),
+                    ..Default::default()
+                })
+                .collect();
+            Ok(SystemTcpStats { by_process, ..Default::default() })
+        }
+    }
+
+    fn refresh_on_threads(refreshes: usize, make: impl Fn() -> SharedMonitor) {
+        for _ in 0..refreshes {
+            let shared = make();
+            std::thread::spawn(move || shared.refresh().unwrap()).join().unwrap();
+        }
+    }
+
+    #[test]
+    fn process_names_survive_between_refreshes() {
+        // 每次刷新新建监控器：10 个进程 × 5 次刷新
+        let lookups = Arc::new(AtomicUsize::new(0));
+        refresh_on_threads(5, || SharedMonitor::new(Arc::new(NameCachingMonitor::new(&lookups))));
+        assert_eq!(lookups.load(Ordering::SeqCst), 50);
+
+        // 共享监控器：只在第一次刷新时查询
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let shared = SharedMonitor::new(Arc::new(NameCachingMonitor::new(&lookups)));
+        refresh_on_threads(5, || shared.clone());
+        assert_eq!(lookups.load(Ordering::SeqCst), 10);
+        assert_eq!(shared.last_stats().unwrap().by_process.len(), 10);
+    }
+
+    #[test]
+    fn stats_within_reuses_recent_result() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let shared = SharedMonitor::new(Arc::new(NameCachingMonitor::new(&lookups)));
+        let start = Instant::now();
+        assert!(shared.last_stats().is_none());
+
+        shared.refresh_at(start).unwrap();
+        shared.stats_within_at(Duration::from_secs(2), start + Duration::from_secs(1)).unwrap();
+        assert_eq!(shared.last_stats.lock().unwrap().as_ref().unwrap().0, start);
+
+        shared.stats_within_at(Duration::from_secs(2), start + Duration::from_secs(3)).unwrap();
+        assert_eq!(shared.last_stats.lock().unwrap().as_ref().unwrap().0, start + Duration::from_secs(3));
+    }
+}
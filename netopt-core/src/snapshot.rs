@@ -0,0 +1,186 @@
+//! 网络状态快照
+//!
+//! 保存/加载完整的连接表和统计数据，便于离线分析和复现用户问题。
+//!
+//! 文件格式为 `{"schema": 1, "data": {...}}`。新增字段只能是可缺省的，
+//! 旧版本读取时忽略未知字段；不兼容的改动需要提升 `SNAPSHOT_SCHEMA`
+
+use crate::monitor::TcpMonitor;
+use crate::tcp_config::TcpConfigManager;
+use crate::{NetOptError, Result, SystemTcpStats, TcpConnection, TcpSystemConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 当前快照格式版本
+pub const SNAPSHOT_SCHEMA: u32 = 1;
+
+/// 带格式版本的快照外层
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    schema: u32,
+    data: &'a T,
+}
+
+/// 完整的网络状态快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkSnapshot {
+    /// 采集时间（Unix 时间戳，秒）
+    pub timestamp: u64,
+
+    /// 采集平台（`platform_name()`）
+    pub platform: String,
+
+    /// 全部TCP连接
+    pub connections: Vec<TcpConnection>,
+
+    /// 系统统计
+    pub stats: SystemTcpStats,
+
+    /// 采集时的TCP系统配置（用于复现端口范围）
+    pub tcp_config: Option<TcpSystemConfig>,
+}
+
+impl NetworkSnapshot {
+    /// 使用指定的监控器和配置管理器采集快照
+    pub fn capture_with(monitor: &dyn TcpMonitor, config_mgr: &dyn TcpConfigManager) -> Result<Self> {
+        let (connections, stats) = monitor.get_connections_and_stats()?;
+
+        Ok(Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            platform: crate::platform::platform_name().to_string(),
+            connections,
+            stats,
+            tcp_config: config_mgr.get_current_config().ok(),
+        })
+    }
+
+    /// 使用当前平台的监控器采集快照
+    pub fn capture() -> Result<Self> {
+        let monitor = crate::platform::create_monitor();
+        let config_mgr = crate::platform::create_config_manager();
+        Self::capture_with(monitor.as_ref(), config_mgr.as_ref())
+    }
+
+    /// 保存为格式化 JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        write_json(path, self)
+    }
+
+    /// 从 JSON 文件加载
+    pub fn load(path: &Path) -> Result<Self> {
+        read_json(path)
+    }
+}
+
+impl SystemTcpStats {
+    /// 保存统计快照为格式化 JSON
+    pub fn save_snapshot(&self, path: &Path) -> Result<()> {
+        write_json(path, self)
+    }
+
+    /// 从 JSON 文件加载统计快照
+    pub fn load_snapshot(path: &Path) -> Result<Self> {
+        read_json(path)
+    }
+}
+
+/// 序列化为带版本号的 JSON
+pub fn to_versioned_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string_pretty(&Envelope { schema: SNAPSHOT_SCHEMA, data: value })
+        .map_err(|e| NetOptError::SystemError(format!("快照序列化失败: {}", e)))
+}
+
+/// 解析带版本号的 JSON。没有外层的旧文件按原格式读取，
+/// 版本号高于当前支持的会被拒绝
+pub fn from_versioned_json<T: for<'de> Deserialize<'de>>(content: &str) -> Result<T> {
+    let mut raw: Value = serde_json::from_str(content)
+        .map_err(|e| NetOptError::ParseError(format!("快照解析失败: {}", e)))?;
+
+    let data = match raw.get("schema").and_then(Value::as_u64) {
+        Some(schema) if schema > SNAPSHOT_SCHEMA as u64 => {
+            return Err(NetOptError::InvalidParameter(format!(
+                "快照格式版本 {} 高于当前支持的 {}，请升级 NetOpt",
+                schema, SNAPSHOT_SCHEMA
+            )));
+        }
+        Some(_) => raw.get_mut("data").map(Value::take).unwrap_or(Value::Null),
+        None => raw,
+    };
+
+    serde_json::from_value(data)
+        .map_err(|e| NetOptError::ParseError(format!("快照解析失败: {}", e)))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    fs::write(path, to_versioned_json(value)?)?;
+    Ok(())
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
+    from_versioned_json(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TcpState;
+
+    fn stats() -> SystemTcpStats {
+        SystemTcpStats {
+            total_connections: 3,
+            by_state: [(TcpState::CloseWait, 3)].into(),
+            available_ports: 1000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_through_envelope() {
+        let json = to_versioned_json(&stats()).unwrap();
+        let raw: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(raw["schema"], SNAPSHOT_SCHEMA);
+
+        let loaded: SystemTcpStats = from_versioned_json(&json).unwrap();
+        assert_eq!(loaded.total_connections, 3);
+        assert_eq!(loaded.by_state.get(&TcpState::CloseWait), Some(&3));
+    }
+
+    #[test]
+    fn v1_snapshot_loads_with_missing_and_unknown_fields() {
+        // 早期 v1 快照没有后来加入的可选字段，也可能带有更新版本写入的未知字段
+        let json = r#"{
+            "schema": 1,
+            "data": {
+                "total_connections": 1,
+                "by_state": {"TimeWait": 1},
+                "by_process": [{"pid": 7, "process_name": "app", "total_connections": 1, "time_wait": 1}],
+                "available_ports": 500,
+                "port_usage_percent": 0.2,
+                "field_from_the_future": true
+            }
+        }"#;
+
+        let loaded: SystemTcpStats = from_versioned_json(json).unwrap();
+        assert_eq!(loaded.available_ports, 500);
+        assert_eq!(loaded.ephemeral_ports_in_use, 0);
+        assert_eq!(loaded.by_process[0].time_wait, 1);
+        assert_eq!(loaded.by_process[0].churn_rate, None);
+    }
+
+    #[test]
+    fn rejects_newer_schema_and_reads_legacy_files() {
+        let newer = r#"{"schema": 2, "data": {}}"#;
+        let err = from_versioned_json::<SystemTcpStats>(newer).unwrap_err();
+        assert!(err.to_string().contains("版本 2"));
+
+        let legacy = serde_json::to_string(&stats()).unwrap();
+        let loaded: SystemTcpStats = from_versioned_json(&legacy).unwrap();
+        assert_eq!(loaded.total_connections, 3);
+    }
+}
@@ -2,44 +2,116 @@
 
 use crate::{Result, NetOptError};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
 /// TCP系统参数配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TcpSystemConfig {
-    /// 最大用户端口号 (Windows: MaxUserPort, macOS: net.inet.ip.portrange.last)
+    /// 最大用户端口号 (Windows: netsh 动态端口范围上限, macOS: net.inet.ip.portrange.last, Linux: ip_local_port_range 上限)
     pub max_user_port: Option<u32>,
     
     /// TIME_WAIT状态等待时间（秒）
     /// Windows: TcpTimedWaitDelay, macOS: net.inet.tcp.msl * 2
     pub time_wait_delay: Option<u32>,
     
-    /// 动态端口起始 (Windows: 动态端口起始, macOS: net.inet.ip.portrange.first)
+    /// 动态端口起始 (Windows: netsh 动态端口起始, macOS: net.inet.ip.portrange.first)
     pub dynamic_port_start: Option<u32>,
     
     /// 最大SYN重传次数
     pub max_syn_retransmissions: Option<u32>,
     
     /// TCP KeepAlive 时间（秒）
+    /// Windows: KeepAliveTime (毫秒), macOS: net.inet.tcp.keepidle (毫秒)，由平台层换算
     pub keep_alive_time: Option<u32>,
     
     /// TCP KeepAlive 探测间隔（秒）
+    /// Windows: KeepAliveInterval (毫秒), macOS: net.inet.tcp.keepintvl (毫秒)，由平台层换算
     pub keep_alive_interval: Option<u32>,
+    
+    /// 允许复用 TIME_WAIT 端口发起新连接（仅 Linux: net.ipv4.tcp_tw_reuse）
+    #[serde(default, deserialize_with = "deserialize_tw_reuse")]
+    pub tw_reuse: Option<TwReuse>,
+    
+    /// TIME_WAIT 连接数上限（仅 Linux: net.ipv4.tcp_max_tw_buckets）
+    #[serde(default)]
+    pub max_tw_buckets: Option<u32>,
 }
 
-impl Default for TcpSystemConfig {
-    fn default() -> Self {
-        Self {
-            max_user_port: None,
-            time_wait_delay: None,
-            dynamic_port_start: None,
-            max_syn_retransmissions: None,
-            keep_alive_time: None,
-            keep_alive_interval: None,
+/// TIME_WAIT 端口复用模式，取值与 net.ipv4.tcp_tw_reuse 相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TwReuse {
+    /// 0：关闭
+    Off,
+    /// 1：开启
+    On,
+    /// 2：仅回环连接（内核默认）
+    LoopbackOnly,
+}
+
+impl TwReuse {
+    /// 对应的 sysctl 值
+    pub fn as_u32(self) -> u32 {
+        match self {
+            TwReuse::Off => 0,
+            TwReuse::On => 1,
+            TwReuse::LoopbackOnly => 2,
+        }
+    }
+
+    /// 由 sysctl 值转换，无法识别时返回 None
+    pub fn from_u32(v: u32) -> Option<Self> {
+        match v {
+            0 => Some(TwReuse::Off),
+            1 => Some(TwReuse::On),
+            2 => Some(TwReuse::LoopbackOnly),
+            _ => None,
         }
     }
 }
 
+impl From<bool> for TwReuse {
+    fn from(v: bool) -> Self {
+        if v { TwReuse::On } else { TwReuse::Off }
+    }
+}
+
+/// 兼容旧版本以布尔值保存的 tw_reuse
+fn deserialize_tw_reuse<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<TwReuse>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Bool(bool),
+        Mode(TwReuse),
+    }
+
+    Ok(Option::<Repr>::deserialize(deserializer)?.map(|r| match r {
+        Repr::Bool(v) => v.into(),
+        Repr::Mode(m) => m,
+    }))
+}
+
 impl TcpSystemConfig {
+    /// 只设置部分参数时使用，未设置的字段为 None（保持不变）
+    pub fn builder() -> TcpSystemConfigBuilder {
+        TcpSystemConfigBuilder::default()
+    }
+
+    /// 用 `other` 中已设置的字段覆盖当前配置，`other` 中为 None 的字段保留原值
+    pub fn merge(mut self, other: &TcpSystemConfig) -> Self {
+        self.max_user_port = other.max_user_port.or(self.max_user_port);
+        self.time_wait_delay = other.time_wait_delay.or(self.time_wait_delay);
+        self.dynamic_port_start = other.dynamic_port_start.or(self.dynamic_port_start);
+        self.max_syn_retransmissions = other.max_syn_retransmissions.or(self.max_syn_retransmissions);
+        self.keep_alive_time = other.keep_alive_time.or(self.keep_alive_time);
+        self.keep_alive_interval = other.keep_alive_interval.or(self.keep_alive_interval);
+        self.tw_reuse = other.tw_reuse.or(self.tw_reuse);
+        self.max_tw_buckets = other.max_tw_buckets.or(self.max_tw_buckets);
+        self
+    }
+
     /// 推荐的高性能配置
     pub fn high_performance() -> Self {
         Self {
@@ -49,6 +121,8 @@ impl TcpSystemConfig {
             max_syn_retransmissions: Some(2),
             keep_alive_time: Some(60),
             keep_alive_interval: Some(10),
+            tw_reuse: Some(TwReuse::On),
+            max_tw_buckets: Some(262144),
         }
     }
     
@@ -61,13 +135,62 @@ impl TcpSystemConfig {
             max_syn_retransmissions: Some(3),
             keep_alive_time: Some(7200),
             keep_alive_interval: Some(75),
+            tw_reuse: Some(TwReuse::Off),
+            max_tw_buckets: Some(65536),
+        }
+    }
+    
+    /// 按字段名列出所有参数值（tw_reuse 以 sysctl 值 0/1/2 表示）
+    pub fn fields(&self) -> [(&'static str, Option<u32>); 8] {
+        [
+            ("max_user_port", self.max_user_port),
+            ("time_wait_delay", self.time_wait_delay),
+            ("dynamic_port_start", self.dynamic_port_start),
+            ("max_syn_retransmissions", self.max_syn_retransmissions),
+            ("keep_alive_time", self.keep_alive_time),
+            ("keep_alive_interval", self.keep_alive_interval),
+            ("tw_reuse", self.tw_reuse.map(TwReuse::as_u32)),
+            ("max_tw_buckets", self.max_tw_buckets),
+        ]
+    }
+    
+    /// 只保留 `names` 中列出的字段（字段名同 `fields()`），其余为 None
+    pub fn only(&self, names: &[&str]) -> Result<TcpSystemConfig> {
+        let mut out = TcpSystemConfig::default();
+        for name in names {
+            match *name {
+                "max_user_port" => out.max_user_port = self.max_user_port,
+                "time_wait_delay" => out.time_wait_delay = self.time_wait_delay,
+                "dynamic_port_start" => out.dynamic_port_start = self.dynamic_port_start,
+                "max_syn_retransmissions" => out.max_syn_retransmissions = self.max_syn_retransmissions,
+                "keep_alive_time" => out.keep_alive_time = self.keep_alive_time,
+                "keep_alive_interval" => out.keep_alive_interval = self.keep_alive_interval,
+                "tw_reuse" => out.tw_reuse = self.tw_reuse,
+                "max_tw_buckets" => out.max_tw_buckets = self.max_tw_buckets,
+                other => return Err(NetOptError::InvalidParameter(format!("未知的配置字段: {}", other))),
+            }
         }
+        Ok(out)
+    }
+
+    /// 计算从当前配置变为 `new` 时实际发生变化的字段
+    ///
+    /// `new` 中为 `None` 的字段表示保持不变，不会出现在结果中
+    pub fn changes_to(&self, new: &TcpSystemConfig) -> Vec<ConfigChange> {
+        self.fields()
+            .into_iter()
+            .zip(new.fields())
+            .filter_map(|((field, old), (_, new))| match new {
+                Some(_) if new != old => Some(ConfigChange { field, old, new }),
+                _ => None,
+            })
+            .collect()
     }
     
     /// 验证配置合法性
     pub fn validate(&self) -> Result<()> {
         if let Some(port) = self.max_user_port {
-            if port < 1024 || port > 65535 {
+            if !(1024..=65535).contains(&port) {
                 return Err(NetOptError::InvalidParameter(
                     format!("max_user_port 必须在 1024-65535 之间，当前: {}", port)
                 ));
@@ -75,7 +198,7 @@ impl TcpSystemConfig {
         }
         
         if let Some(delay) = self.time_wait_delay {
-            if delay < 30 || delay > 300 {
+            if !(30..=300).contains(&delay) {
                 return Err(NetOptError::InvalidParameter(
                     format!("time_wait_delay 推荐在 30-300 秒之间，当前: {}", delay)
                 ));
@@ -92,17 +215,178 @@ impl TcpSystemConfig {
             }
         }
         
+        if let Some(retries) = self.max_syn_retransmissions {
+            if !(1..=10).contains(&retries) {
+                return Err(NetOptError::InvalidParameter(
+                    format!("max_syn_retransmissions 必须在 1-10 之间，当前: {}", retries)
+                ));
+            }
+        }
+        
+        if let Some(time) = self.keep_alive_time {
+            if time == 0 {
+                return Err(NetOptError::InvalidParameter(
+                    "keep_alive_time 必须大于 0".into()
+                ));
+            }
+        }
+        
+        if let Some(interval) = self.keep_alive_interval {
+            if interval == 0 {
+                return Err(NetOptError::InvalidParameter(
+                    "keep_alive_interval 必须大于 0".into()
+                ));
+            }
+            if let Some(time) = self.keep_alive_time {
+                if interval >= time {
+                    return Err(NetOptError::InvalidParameter(
+                        format!("keep_alive_interval ({}) 必须小于 keep_alive_time ({})", interval, time)
+                    ));
+                }
+            }
+        }
+        
         Ok(())
     }
 }
 
+/// `TcpSystemConfig` 的链式构造器，如 `TcpSystemConfig::builder().time_wait_delay(30).build()`
+#[derive(Debug, Clone, Default)]
+pub struct TcpSystemConfigBuilder {
+    config: TcpSystemConfig,
+}
+
+impl TcpSystemConfigBuilder {
+    pub fn max_user_port(mut self, v: u32) -> Self {
+        self.config.max_user_port = Some(v);
+        self
+    }
+
+    pub fn time_wait_delay(mut self, v: u32) -> Self {
+        self.config.time_wait_delay = Some(v);
+        self
+    }
+
+    pub fn dynamic_port_start(mut self, v: u32) -> Self {
+        self.config.dynamic_port_start = Some(v);
+        self
+    }
+
+    pub fn max_syn_retransmissions(mut self, v: u32) -> Self {
+        self.config.max_syn_retransmissions = Some(v);
+        self
+    }
+
+    pub fn keep_alive_time(mut self, v: u32) -> Self {
+        self.config.keep_alive_time = Some(v);
+        self
+    }
+
+    pub fn keep_alive_interval(mut self, v: u32) -> Self {
+        self.config.keep_alive_interval = Some(v);
+        self
+    }
+
+    pub fn tw_reuse(mut self, v: impl Into<TwReuse>) -> Self {
+        self.config.tw_reuse = Some(v.into());
+        self
+    }
+
+    pub fn max_tw_buckets(mut self, v: u32) -> Self {
+        self.config.max_tw_buckets = Some(v);
+        self
+    }
+
+    pub fn build(self) -> TcpSystemConfig {
+        self.config
+    }
+}
+
+/// 单个配置字段的变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigChange {
+    /// 字段名（与 `TcpSystemConfig` 字段一致）
+    pub field: &'static str,
+    /// 当前值（None 表示系统未设置/无法读取）
+    pub old: Option<u32>,
+    /// 将要写入的值
+    pub new: Option<u32>,
+}
+
+impl std::fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_else(|| "-".into());
+        write!(f, "{}: {} -> {}", self.field, show(self.old), show(self.new))
+    }
+}
+
+/// 写入后读回的值与期望不一致的字段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldMismatch {
+    /// 字段名（与 `TcpSystemConfig` 字段一致）
+    pub field: &'static str,
+    pub expected: u32,
+    /// 读回的值（None 表示读取失败）
+    pub actual: Option<u32>,
+}
+
+pub(crate) fn describe_mismatches(mismatches: &[FieldMismatch]) -> String {
+    mismatches.iter()
+        .map(|m| match m.actual {
+            Some(actual) => format!("{} 期望 {} 实际 {}", m.field, m.expected, actual),
+            None => format!("{} 期望 {} 无法读取", m.field, m.expected),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 在 apply_config 末尾调用：读回当前配置，检查 `expected` 中已设置的字段是否生效。
+/// `expected` 应只包含平台实际写入的字段，并按平台的存储精度换算
+pub(crate) fn verify_applied<M: TcpConfigManager + ?Sized>(mgr: &M, expected: &TcpSystemConfig) -> Result<()> {
+    let actual = mgr.get_current_config()?;
+    let mismatched: Vec<_> = expected.fields()
+        .into_iter()
+        .zip(actual.fields())
+        .filter_map(|((field, want), (_, got))| match want {
+            Some(want) if got != Some(want) => Some(FieldMismatch { field, expected: want, actual: got }),
+            _ => None,
+        })
+        .collect();
+
+    if mismatched.is_empty() {
+        Ok(())
+    } else {
+        Err(NetOptError::ConfigNotApplied(mismatched))
+    }
+}
+
+/// 秒转毫秒（饱和，避免溢出写入错误值）
+#[cfg(any(target_os = "windows", target_os = "macos", test))]
+pub(crate) fn secs_to_millis(secs: u32) -> u32 {
+    secs.saturating_mul(1000)
+}
+
+/// 毫秒转秒（向下取整）
+#[cfg(any(target_os = "windows", target_os = "macos", test))]
+pub(crate) fn millis_to_secs(millis: u32) -> u32 {
+    millis / 1000
+}
+
 /// TCP配置管理器 trait
 pub trait TcpConfigManager: Send + Sync {
     /// 获取当前系统配置
     fn get_current_config(&self) -> Result<TcpSystemConfig>;
     
-    /// 应用新配置（需要管理员权限）
+    /// 应用新配置（需要管理员权限）。
+    /// 只写入为 Some 的字段，None 表示保持系统当前值不变；要恢复默认值用 `reset_to_default`。
+    /// 写入后会读回校验，被其他进程或策略改回的字段以 `ConfigNotApplied` 返回，重复调用是安全的
     fn apply_config(&self, config: &TcpSystemConfig) -> Result<()>;
+
+    /// 将指定字段（字段名同 `TcpSystemConfig::fields()`）恢复为系统默认值。
+    /// 默认写入 `get_default_config()` 中的值，平台有更准确的方式时可覆盖
+    fn reset_to_default(&self, fields: &[&str]) -> Result<()> {
+        self.apply_config(&self.get_default_config().only(fields)?)
+    }
     
     /// 获取系统默认配置
     fn get_default_config(&self) -> TcpSystemConfig;
@@ -110,7 +394,301 @@ pub trait TcpConfigManager: Send + Sync {
     /// 检查是否有管理员权限
     fn has_admin_privileges(&self) -> bool;
     
-    /// 配置是否需要重启生效
+    /// 通过此管理器应用的修改中是否有需要重启才能生效的
     fn requires_reboot(&self) -> bool;
+    
+    /// 预览应用 `new` 前后的差异（基于当前系统配置）
+    fn diff(&self, new: &TcpSystemConfig) -> Result<Vec<ConfigChange>> {
+        Ok(self.get_current_config()?.changes_to(new))
+    }
+    
+    /// 将当前系统配置备份到 JSON 文件
+    fn backup_config(&self, path: &std::path::Path) -> Result<()> {
+        crate::backup::write_config(path, &self.get_current_config()?)
+    }
+    
+    /// 从备份文件恢复配置（需要管理员权限）
+    fn restore_config(&self, path: &std::path::Path) -> Result<()> {
+        let config = crate::backup::read_config(path)?;
+        self.apply_config(&config)
+    }
 }
 
+/// 记录是否应用过需要重启才能生效的修改，供 `requires_reboot` 使用
+#[derive(Debug, Default)]
+pub struct RebootTracker(AtomicBool);
+
+impl RebootTracker {
+    /// 对比写入前的配置和写入的配置，有 `needs_reboot` 的字段被修改时标记为待重启
+    pub fn record(&self, before: &TcpSystemConfig, applied: &TcpSystemConfig, needs_reboot: impl Fn(&str) -> bool) {
+        if before.changes_to(applied).iter().any(|c| needs_reboot(c.field)) {
+            self.mark();
+        }
+    }
+
+    /// 直接标记为待重启
+    pub fn mark(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 平台配置管理器读写系统参数的键值存储（sysctl 名、/proc/sys 文件名、注册表值名）。
+/// 默认使用真实系统，测试中换成 `MemoryBackend`，无需 root 也不会修改系统
+pub trait ConfigBackend: Send + Sync {
+    /// 读取参数原文，不存在或无法读取时为 None
+    fn read(&self, key: &str) -> Option<String>;
+
+    fn write(&self, key: &str, value: &str) -> Result<()>;
+
+    /// 删除参数，使系统回到内置默认值
+    fn remove(&self, key: &str) -> Result<()> {
+        Err(NetOptError::UnsupportedPlatform(format!("无法删除 {}", key)))
+    }
+
+    /// 是否为真实系统参数。只有真实系统在修改前自动备份
+    fn is_system(&self) -> bool {
+        true
+    }
+
+    /// 读取并解析为整数
+    fn read_u32(&self, key: &str) -> Option<u32> {
+        self.read(key)?.trim().parse().ok()
+    }
+}
+
+/// 内存中的参数存储，用于测试平台配置管理器
+#[derive(Debug, Default)]
+pub struct MemoryBackend(Mutex<HashMap<String, String>>);
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 预置参数
+    pub fn with(self, key: &str, value: impl ToString) -> Self {
+        self.0.lock().unwrap().insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+impl ConfigBackend for MemoryBackend {
+    fn read(&self, key: &str) -> Option<String> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<()> {
+        self.0.lock().unwrap().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn is_system(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_only_overwrites_set_fields() {
+        let base = TcpSystemConfig::conservative();
+        let overlay = TcpSystemConfig::builder().time_wait_delay(30).tw_reuse(true).build();
+
+        let merged = base.clone().merge(&overlay);
+
+        assert_eq!(merged.time_wait_delay, Some(30));
+        assert_eq!(merged.tw_reuse, Some(TwReuse::On));
+        // overlay 中为 None 的字段不覆盖
+        assert_eq!(merged.max_user_port, base.max_user_port);
+        assert_eq!(merged.keep_alive_time, base.keep_alive_time);
+        assert_eq!(base.changes_to(&overlay).len(), 2);
+    }
+
+    /// 记录 apply_config 收到的配置
+    #[derive(Default)]
+    struct RecordingManager(std::sync::Mutex<Vec<TcpSystemConfig>>);
+
+    impl TcpConfigManager for RecordingManager {
+        fn get_current_config(&self) -> Result<TcpSystemConfig> {
+            Ok(TcpSystemConfig::high_performance())
+        }
+
+        fn apply_config(&self, config: &TcpSystemConfig) -> Result<()> {
+            self.0.lock().unwrap().push(config.clone());
+            Ok(())
+        }
+
+        fn get_default_config(&self) -> TcpSystemConfig {
+            TcpSystemConfig::conservative()
+        }
+
+        fn has_admin_privileges(&self) -> bool {
+            true
+        }
+
+        fn requires_reboot(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn reset_writes_defaults_for_listed_fields_only() {
+        let mgr = RecordingManager::default();
+        mgr.reset_to_default(&["time_wait_delay", "tw_reuse"]).unwrap();
+
+        let applied = mgr.0.lock().unwrap().pop().unwrap();
+        let defaults = TcpSystemConfig::conservative();
+        assert_eq!(applied.time_wait_delay, defaults.time_wait_delay);
+        assert_eq!(applied.tw_reuse, defaults.tw_reuse);
+        // 未列出的字段为 None，apply_config 不会改动
+        assert_eq!(applied.fields().iter().filter(|(_, v)| v.is_some()).count(), 2);
+    }
+
+    #[test]
+    fn reset_rejects_unknown_fields() {
+        let mgr = RecordingManager::default();
+        assert!(mgr.reset_to_default(&["tcp_magic"]).is_err());
+        assert!(mgr.0.lock().unwrap().is_empty());
+    }
+
+    /// 写入后 time_wait_delay 被“其他进程”改回 60 的管理器
+    struct RevertingManager(std::sync::Mutex<TcpSystemConfig>);
+
+    impl TcpConfigManager for RevertingManager {
+        fn get_current_config(&self) -> Result<TcpSystemConfig> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn apply_config(&self, config: &TcpSystemConfig) -> Result<()> {
+            let written = self.0.lock().unwrap().clone().merge(config);
+            *self.0.lock().unwrap() = TcpSystemConfig { time_wait_delay: Some(60), ..written };
+            verify_applied(self, config)
+        }
+
+        fn get_default_config(&self) -> TcpSystemConfig {
+            TcpSystemConfig::default()
+        }
+
+        fn has_admin_privileges(&self) -> bool {
+            true
+        }
+
+        fn requires_reboot(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn read_back_reports_fields_that_did_not_stick() {
+        let mgr = RevertingManager(std::sync::Mutex::new(TcpSystemConfig::conservative()));
+        let config = TcpSystemConfig::builder().time_wait_delay(30).keep_alive_time(600).build();
+
+        let err = mgr.apply_config(&config).unwrap_err();
+
+        match &err {
+            NetOptError::ConfigNotApplied(mismatched) => {
+                assert_eq!(mismatched, &vec![FieldMismatch { field: "time_wait_delay", expected: 30, actual: Some(60) }]);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err.to_string().contains("time_wait_delay 期望 30 实际 60"));
+        // 没被改回的字段校验通过
+        assert!(mgr.apply_config(&TcpSystemConfig::builder().keep_alive_time(900).build()).is_ok());
+    }
+
+    #[test]
+    fn validate_accepts_bounds_and_rejects_just_outside() {
+        let valid = |config: TcpSystemConfig| config.validate().is_ok();
+
+        assert!(!valid(TcpSystemConfig { max_user_port: Some(1023), ..Default::default() }));
+        assert!(valid(TcpSystemConfig { max_user_port: Some(1024), ..Default::default() }));
+        assert!(valid(TcpSystemConfig { max_user_port: Some(65535), ..Default::default() }));
+        assert!(!valid(TcpSystemConfig { max_user_port: Some(65536), ..Default::default() }));
+
+        assert!(!valid(TcpSystemConfig { time_wait_delay: Some(29), ..Default::default() }));
+        assert!(valid(TcpSystemConfig { time_wait_delay: Some(30), ..Default::default() }));
+        assert!(valid(TcpSystemConfig { time_wait_delay: Some(300), ..Default::default() }));
+        assert!(!valid(TcpSystemConfig { time_wait_delay: Some(301), ..Default::default() }));
+
+        let ports = |start| TcpSystemConfig { dynamic_port_start: Some(start), max_user_port: Some(5000), ..Default::default() };
+        assert!(valid(ports(4999)));
+        assert!(!valid(ports(5000)));
+
+        assert!(!valid(TcpSystemConfig { max_syn_retransmissions: Some(0), ..Default::default() }));
+        assert!(valid(TcpSystemConfig { max_syn_retransmissions: Some(1), ..Default::default() }));
+        assert!(valid(TcpSystemConfig { max_syn_retransmissions: Some(10), ..Default::default() }));
+        assert!(!valid(TcpSystemConfig { max_syn_retransmissions: Some(11), ..Default::default() }));
+
+        assert!(!valid(TcpSystemConfig { keep_alive_time: Some(0), ..Default::default() }));
+        assert!(valid(TcpSystemConfig { keep_alive_time: Some(1), ..Default::default() }));
+
+        assert!(!valid(TcpSystemConfig { keep_alive_interval: Some(0), ..Default::default() }));
+        assert!(valid(TcpSystemConfig { keep_alive_interval: Some(1), ..Default::default() }));
+        let keepalive = |interval| TcpSystemConfig { keep_alive_time: Some(60), keep_alive_interval: Some(interval), ..Default::default() };
+        assert!(valid(keepalive(59)));
+        assert!(!valid(keepalive(60)));
+    }
+
+    #[test]
+    fn millisecond_conversion_truncates_and_saturates() {
+        for secs in [0, 1, 30, 7200, u32::MAX / 1000] {
+            assert_eq!(millis_to_secs(secs_to_millis(secs)), secs);
+        }
+        // 不足一秒的部分舍去
+        assert_eq!(millis_to_secs(999), 0);
+        assert_eq!(millis_to_secs(1999), 1);
+        // 溢出时饱和为 u32::MAX，而不是回绕成较小的值
+        assert_eq!(secs_to_millis(u32::MAX / 1000), 4_294_967_000);
+        assert_eq!(secs_to_millis(u32::MAX / 1000 + 1), u32::MAX);
+        assert_eq!(secs_to_millis(u32::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn tw_reuse_reads_legacy_booleans() {
+        let legacy: TcpSystemConfig = serde_json::from_str(r#"{"tw_reuse": true}"#).unwrap();
+        assert_eq!(legacy.tw_reuse, Some(TwReuse::On));
+
+        let config = TcpSystemConfig::builder().tw_reuse(TwReuse::LoopbackOnly).build();
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""tw_reuse":"loopback_only""#), "{}", json);
+        assert_eq!(serde_json::from_str::<TcpSystemConfig>(&json).unwrap().tw_reuse, Some(TwReuse::LoopbackOnly));
+        assert_eq!(serde_json::from_str::<TcpSystemConfig>("{}").unwrap().tw_reuse, None);
+    }
+
+    #[test]
+    fn merge_onto_empty_keeps_overlay() {
+        let merged = TcpSystemConfig::default().merge(&TcpSystemConfig::high_performance());
+        assert_eq!(merged.fields(), TcpSystemConfig::high_performance().fields());
+    }
+
+    #[test]
+    fn reboot_is_pending_only_after_a_reboot_field_changes() {
+        let tracker = RebootTracker::default();
+        let current = TcpSystemConfig::conservative();
+        let needs_reboot = |field: &str| field == "time_wait_delay";
+
+        // 没有修改，或写入的值与当前相同
+        assert!(!tracker.is_pending());
+        tracker.record(&current, &TcpSystemConfig::default(), needs_reboot);
+        tracker.record(&current, &TcpSystemConfig { time_wait_delay: current.time_wait_delay, ..Default::default() }, needs_reboot);
+        assert!(!tracker.is_pending());
+
+        // 立即生效的字段
+        tracker.record(&current, &TcpSystemConfig::builder().keep_alive_time(600).build(), needs_reboot);
+        assert!(!tracker.is_pending());
+
+        tracker.record(&current, &TcpSystemConfig::builder().time_wait_delay(30).build(), needs_reboot);
+        assert!(tracker.is_pending());
+    }
+}
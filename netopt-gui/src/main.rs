@@ -4,28 +4,153 @@
 //! 同时集成后台优化服务
 
 use eframe::egui;
-use netopt_core::platform::{create_monitor, create_config_manager, create_optimizer, has_admin_privileges, platform_name};
-use netopt_core::{SystemTcpStats, TcpState, TcpSystemConfig};
+use netopt_core::platform::{create_config_manager, create_monitor, create_optimizer, has_admin_privileges, platform_capabilities, platform_name};
+use netopt_core::{
+    caching_monitor, ActionType, AddressFamily, calculate_health_report, ConnectionKey, system_health, CachingMonitor, ExecutableTcpStats, HealthGrade, LeakReport, LeakVerdict, OptimizationEngine, PolicyStats, ProcessTcpStats, ReverseDns, SharedMonitor, SortKey, StatsDelta, StatsHistory, SystemTcpStats,
+    TcpConfigManager, TcpConnection, TcpState, TcpSystemConfig,
+};
 use netopt_core::{I18n, Language, TextKey, AppConfig};
-use netopt_core::policy::{AppPolicy, ThresholdAction};
+use netopt_core::config::{Theme, View};
+use netopt_core::i18n::{format_count, format_count_compact, format_percent};
+use netopt_core::policy::{AppPolicy, PolicyIssue, PolicyManager, ThresholdAction};
 
+use std::collections::HashMap;
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// 后台线程消息
 enum BgMessage {
     StatsResult(Result<SystemTcpStats, String>),
-    /// 优化执行结果 (进程名, 动作类型, 关闭连接数, 错误信息)
-    OptimizeResult(String, String, usize, Option<String>),
+    /// 优化执行结果 (进程名, 动作类型, 关闭连接数, 错误信息, 触发的策略名)。
+    /// 手动清理没有对应策略；默认策略的名称为空字符串
+    OptimizeResult(String, String, usize, Option<String>, Option<String>),
+    /// 进程连接列表 (PID, 结果)
+    ProcessConnections(u32, Result<Vec<TcpConnection>, String>),
+}
+
+fn theme_preference(theme: Theme) -> egui::ThemePreference {
+    match theme {
+        Theme::System => egui::ThemePreference::System,
+        Theme::Light => egui::ThemePreference::Light,
+        Theme::Dark => egui::ThemePreference::Dark,
+    }
+}
+
+fn theme_text_key(theme: Theme) -> TextKey {
+    match theme {
+        Theme::System => TextKey::ThemeSystem,
+        Theme::Light => TextKey::ThemeLight,
+        Theme::Dark => TextKey::ThemeDark,
+    }
+}
+
+fn sort_key_text_key(key: SortKey) -> TextKey {
+    match key {
+        SortKey::Total => TextKey::SortTotal,
+        SortKey::CloseWait => TextKey::SortCloseWait,
+        SortKey::TimeWait => TextKey::SortTimeWait,
+        SortKey::HealthAsc => TextKey::SortHealthAsc,
+    }
+}
+
+/// 状态色按深色背景选取，浅色主题下调暗以保证可读
+fn readable(ui: &egui::Ui, color: egui::Color32) -> egui::Color32 {
+    if ui.visuals().dark_mode {
+        color
+    } else {
+        let darken = |c: u8| (c as f32 * 0.6) as u8;
+        egui::Color32::from_rgb(darken(color.r()), darken(color.g()), darken(color.b()))
+    }
+}
+
+/// 进程视图中选中进程的连接详情
+struct ConnectionDetail {
+    pid: u32,
+    process_name: String,
+    connections: Vec<TcpConnection>,
+    /// 四元组首次出现的时间，用于估算连接存在时长
+    first_seen: HashMap<ConnectionKey, Instant>,
+    /// None 表示显示全部状态
+    state_filter: Option<TcpState>,
+    error: Option<String>,
+}
+
+impl ConnectionDetail {
+    fn new(pid: u32, process_name: String) -> Self {
+        Self {
+            pid,
+            process_name,
+            connections: Vec::new(),
+            first_seen: HashMap::new(),
+            state_filter: None,
+            error: None,
+        }
+    }
+
+    /// 更新连接列表，已消失的四元组不再保留首次出现时间
+    fn update(&mut self, connections: Vec<TcpConnection>, now: Instant) {
+        let mut first_seen = HashMap::with_capacity(connections.len());
+        for conn in &connections {
+            let key = conn.key();
+            let seen = self.first_seen.get(&key).copied().unwrap_or(now);
+            first_seen.insert(key, seen);
+        }
+        self.first_seen = first_seen;
+        self.connections = connections;
+        self.error = None;
+    }
+
+    fn age(&self, conn: &TcpConnection, now: Instant) -> Duration {
+        self.first_seen.get(&conn.key())
+            .map(|seen| now.duration_since(*seen))
+            .unwrap_or_default()
+    }
+
+    fn visible(&self) -> impl Iterator<Item = &TcpConnection> {
+        self.connections.iter().filter(|c| self.state_filter.is_none_or(|s| c.state == s))
+    }
+}
+
+/// 时长显示为 "45s" / "3m12s" / "2h05m"
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// 进程视图中选中进程的远端地址分布
+struct SelectedPeers {
+    pid: u32,
+    process_name: String,
+    peers: Vec<(std::net::IpAddr, usize)>,
 }
 
 fn main() -> eframe::Result<()> {
     tracing_subscriber::fmt::init();
 
+    // --config <目录>：使用指定配置目录（便携模式），在创建任何线程前设置
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pos) = args.iter().position(|a| a == "--config") {
+        match args.get(pos + 1) {
+            Some(dir) => std::env::set_var(netopt_core::config::CONFIG_DIR_ENV, dir),
+            None => tracing::warn!("--config 需要指定目录，使用默认配置目录"),
+        }
+    }
+
+    // 恢复上次的窗口尺寸和位置
+    let app_config = AppConfig::load().unwrap_or_default();
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size([app_config.window_width, app_config.window_height])
+        .with_min_inner_size([800.0, 500.0]);
+    if let Some(position) = app_config.window_position {
+        viewport = viewport.with_position(position);
+    }
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([900.0, 650.0])
-            .with_min_inner_size([800.0, 500.0]),
+        viewport,
         ..Default::default()
     };
 
@@ -35,7 +160,7 @@ fn main() -> eframe::Result<()> {
         Box::new(|cc| {
             // 加载中文字体
             setup_fonts(&cc.egui_ctx);
-            Ok(Box::new(NetOptApp::new(cc)))
+            Ok(Box::new(NetOptApp::new(cc, app_config)))
         }),
     )
 }
@@ -90,15 +215,133 @@ fn setup_fonts(ctx: &egui::Context) {
     ctx.set_fonts(fonts);
 }
 
-/// 应用主视图
-#[derive(Default, PartialEq)]
-enum View {
+/// 趋势图保留的采样数，按最短 1 秒刷新间隔覆盖 15 分钟
+const TREND_CAPACITY: usize = 900;
+
+/// 泄漏报告中确认泄漏所需的 CLOSE_WAIT 增长量
+const LEAK_MIN_GROWTH: usize = 20;
+
+/// 趋势图时间窗口
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum TrendWindow {
+    OneMinute,
+    #[default]
+    FiveMinutes,
+    FifteenMinutes,
+}
+
+impl TrendWindow {
+    fn duration(self) -> Duration {
+        match self {
+            TrendWindow::OneMinute => Duration::from_secs(60),
+            TrendWindow::FiveMinutes => Duration::from_secs(300),
+            TrendWindow::FifteenMinutes => Duration::from_secs(900),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TrendWindow::OneMinute => "1m",
+            TrendWindow::FiveMinutes => "5m",
+            TrendWindow::FifteenMinutes => "15m",
+        }
+    }
+}
+
+/// 进程列表的附加筛选条件
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum ProcessFilter {
     #[default]
-    Dashboard,
-    Processes,
-    Policies,
-    Settings,
-    Help,
+    All,
+    HasCloseWait,
+    HasPolicy,
+    Unhealthy,
+}
+
+impl ProcessFilter {
+    const ALL: [ProcessFilter; 4] = [
+        ProcessFilter::All,
+        ProcessFilter::HasCloseWait,
+        ProcessFilter::HasPolicy,
+        ProcessFilter::Unhealthy,
+    ];
+
+    fn text_key(self) -> TextKey {
+        match self {
+            ProcessFilter::All => TextKey::FilterAll,
+            ProcessFilter::HasCloseWait => TextKey::FilterHasCloseWait,
+            ProcessFilter::HasPolicy => TextKey::FilterHasPolicy,
+            ProcessFilter::Unhealthy => TextKey::FilterUnhealthy,
+        }
+    }
+}
+
+/// 进程名包含 `query`（已转为小写）且满足筛选条件
+fn process_matches(proc: &ProcessTcpStats, query: &str, filter: ProcessFilter, has_policy: bool) -> bool {
+    let name_matches = query.is_empty() || proc.process_name.to_lowercase().contains(query);
+    name_matches && match filter {
+        ProcessFilter::All => true,
+        ProcessFilter::HasCloseWait => proc.close_wait > 0,
+        ProcessFilter::HasPolicy => has_policy,
+        ProcessFilter::Unhealthy => proc.health_score < 50,
+    }
+}
+
+/// 生成进程列表行时的 (搜索词, 筛选条件, 策略数)，变化时整体重建
+type ProcessRowsKey = (String, ProcessFilter, usize);
+
+/// 进程列表当前显示的行。统计刷新时按差异更新，避免每帧复制整个进程列表
+#[derive(Default)]
+struct ProcessRows {
+    rows: Vec<ProcessTcpStats>,
+    key: Option<ProcessRowsKey>,
+}
+
+impl ProcessRows {
+    /// 进程集合不变时只替换数据变化的行，否则（或 `delta` 为 None 时）整体重建
+    fn update(
+        &mut self,
+        stats: &SystemTcpStats,
+        delta: Option<&StatsDelta>,
+        key: ProcessRowsKey,
+        matches: impl Fn(&ProcessTcpStats) -> bool,
+    ) {
+        let same_key = self.key.as_ref() == Some(&key);
+        self.key = Some(key);
+        match delta {
+            Some(delta) if same_key && !delta.membership_changed() => {
+                if !self.patch(stats, delta, &matches) {
+                    self.rebuild(stats, &matches);
+                }
+            }
+            _ => self.rebuild(stats, &matches),
+        }
+    }
+
+    fn rebuild(&mut self, stats: &SystemTcpStats, matches: &impl Fn(&ProcessTcpStats) -> bool) {
+        self.rows = stats.by_process.iter().filter(|p| matches(p)).cloned().collect();
+    }
+
+    /// 原地替换变化的行；原本被筛掉的进程变得满足条件时需要重建，返回 false
+    fn patch(&mut self, stats: &SystemTcpStats, delta: &StatsDelta, matches: &impl Fn(&ProcessTcpStats) -> bool) -> bool {
+        if delta.changed.is_empty() {
+            return true;
+        }
+        let mut changed: HashMap<u32, &ProcessTcpStats> = stats.by_process.iter()
+            .filter(|p| delta.changed.binary_search(&p.pid).is_ok())
+            .map(|p| (p.pid, p))
+            .collect();
+        for row in &mut self.rows {
+            if let Some(new) = changed.remove(&row.pid) {
+                *row = new.clone();
+            }
+        }
+        if changed.values().any(|p| matches(p)) {
+            return false;
+        }
+        self.rows.retain(|row| delta.changed.binary_search(&row.pid).is_err() || matches(row));
+        true
+    }
 }
 
 /// 主应用状态
@@ -107,6 +350,15 @@ struct NetOptApp {
     stats: Option<SystemTcpStats>,
     last_refresh: Instant,
     is_admin: bool,
+    /// 当前平台是否支持直接关闭其他进程的连接
+    connection_control: bool,
+    /// 各后台刷新线程共享，保留进程名缓存
+    monitor: SharedMonitor,
+    /// 连接详情的主机名解析，只在 `resolve_hostnames` 开启时查询
+    reverse_dns: ReverseDns,
+    process_rows: ProcessRows,
+    /// 整个会话共用，记录已应用的修改是否需要重启
+    config_mgr: Box<dyn TcpConfigManager>,
     tcp_config: TcpSystemConfig,
     status_message: String,
 
@@ -125,26 +377,60 @@ struct NetOptApp {
     // 后台优化
     last_optimize: Instant,
     optimize_log: Vec<String>, // 优化日志（最近的优化操作记录）
+
+    // 进程视图中选中的进程及其连接最多的远端地址
+    selected_peers: Option<SelectedPeers>,
+    // 选中进程的连接详情面板
+    connection_detail: Option<ConnectionDetail>,
+
+    // 进程视图筛选，切换视图后保留
+    process_query: String,
+    process_filter: ProcessFilter,
+    /// 同一程序的多个进程合并为一行
+    group_by_app: bool,
+
+    // 策略运行统计（策略名 -> 统计，不持久化）
+    policy_stats: HashMap<String, PolicyStats>,
+
+    // 仪表盘趋势图
+    history: StatsHistory,
+    trend_window: TrendWindow,
+    /// 仪表盘 Top 5 的排序依据
+    top_sort: SortKey,
 }
 
 impl NetOptApp {
-    fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(cc: &eframe::CreationContext<'_>, app_config: AppConfig) -> Self {
+        cc.egui_ctx.set_theme(theme_preference(app_config.theme));
+
         let config_mgr = create_config_manager();
         let tcp_config = config_mgr.get_current_config().unwrap_or_default();
 
-        // 加载持久化配置
-        let app_config = AppConfig::load().unwrap_or_default();
-
         let mut i18n = I18n::new();
         i18n.set_language(app_config.language);
 
+        // 配置目录下的 translation.json 覆盖内置翻译
+        if let Some(path) = AppConfig::config_path().ok().and_then(|p| p.parent().map(|d| d.join("translation.json"))) {
+            if path.exists() {
+                if let Err(e) = i18n.load_overrides(&path) {
+                    tracing::warn!("加载翻译文件失败: {}", e);
+                }
+            }
+        }
+
         let (bg_sender, bg_receiver) = channel();
 
         Self {
-            current_view: View::Dashboard,
+            current_view: app_config.last_view,
             stats: None,
             last_refresh: Instant::now() - Duration::from_secs(100),
             is_admin: has_admin_privileges(),
+            connection_control: platform_capabilities().can_close_connections,
+            // 自动刷新和手动刷新可能接连触发，短时间内复用同一次扫描
+            monitor: SharedMonitor::new(Arc::new(CachingMonitor::new(create_monitor(), caching_monitor::DEFAULT_TTL))),
+            reverse_dns: ReverseDns::system(),
+            process_rows: ProcessRows::default(),
+            config_mgr,
             tcp_config,
             status_message: String::new(),
             i18n,
@@ -155,6 +441,15 @@ impl NetOptApp {
             is_refreshing: false,
             last_optimize: Instant::now(),
             optimize_log: Vec::new(),
+            selected_peers: None,
+            connection_detail: None,
+            process_query: String::new(),
+            process_filter: ProcessFilter::default(),
+            group_by_app: false,
+            policy_stats: HashMap::new(),
+            history: StatsHistory::new(TREND_CAPACITY),
+            trend_window: TrendWindow::default(),
+            top_sort: SortKey::default(),
         }
     }
 
@@ -167,96 +462,73 @@ impl NetOptApp {
         self.status_message = self.i18n.t(TextKey::Refreshing).to_string();
 
         let sender = self.bg_sender.clone();
+        let monitor = self.monitor.clone();
         std::thread::spawn(move || {
-            let monitor = create_monitor();
-            let result = monitor.get_system_stats()
+            let result = monitor.refresh()
                 .map_err(|e| e.to_string());
             let _ = sender.send(BgMessage::StatsResult(result));
         });
     }
 
+    /// 在后台线程查询进程的连接列表
+    fn fetch_connections_async(&self, pid: u32) {
+        let sender = self.bg_sender.clone();
+        let monitor = self.monitor.clone();
+        let reverse_dns = self.app_config.resolve_hostnames.then(|| self.reverse_dns.clone());
+        std::thread::spawn(move || {
+            let result = monitor.get_process_connections(pid)
+                .map(|mut connections| {
+                    if let Some(dns) = &reverse_dns {
+                        dns.annotate(&mut connections);
+                    }
+                    connections
+                })
+                .map_err(|e| e.to_string());
+            let _ = sender.send(BgMessage::ProcessConnections(pid, result));
+        });
+    }
+
     /// 在后台执行优化（根据策略自动清理连接）
     fn run_optimize_async(&mut self, stats: &SystemTcpStats) {
+        if !self.connection_control {
+            return;
+        }
         let policy_manager = self.app_config.policy_manager.clone();
         let sender = self.bg_sender.clone();
 
         // 检查每个进程是否需要优化
         for proc_stats in &stats.by_process {
-            let policy = policy_manager.get_policy(&proc_stats.process_name).clone();
+            // 与服务、命令行一致使用有效策略，全局白名单端口和策略保护端口上的连接不会被关闭
+            let policy = policy_manager.effective_policy(&proc_stats.process_name);
 
             // 跳过白名单、未启用自动优化、或动作不是"自动优化"的进程
-            if !policy.auto_optimize || policy.threshold_action != ThresholdAction::Optimize {
+            if policy_manager.is_whitelisted(&proc_stats.process_name) || !policy.auto_optimize || policy.threshold_action != ThresholdAction::Optimize {
                 continue;
             }
 
-            let mut need_optimize_cw = false;
-            let mut need_optimize_tw = false;
-
-            // 检查 CLOSE_WAIT 阈值
-            if let Some(threshold) = policy.close_wait_threshold {
-                if proc_stats.close_wait > threshold {
-                    need_optimize_cw = true;
-                }
-            }
-
-            // 检查 TIME_WAIT 阈值
-            if let Some(threshold) = policy.time_wait_threshold {
-                if proc_stats.time_wait > threshold {
-                    need_optimize_tw = true;
-                }
-            }
+            let need_optimize_cw = policy.close_wait_threshold.is_some_and(|t| proc_stats.close_wait > t);
+            let need_optimize_tw = policy.time_wait_threshold.is_some_and(|t| proc_stats.time_wait > t);
 
             if need_optimize_cw || need_optimize_tw {
                 let pid = proc_stats.pid;
                 let process_name = proc_stats.process_name.clone();
                 let sender = sender.clone();
-                let cw = need_optimize_cw;
-                let tw = need_optimize_tw;
 
                 std::thread::spawn(move || {
-                    let optimizer = create_optimizer();
-                    let mut total_closed = 0;
-                    let mut action_desc = String::new();
-                    let mut error_msg = None;
-
-                    // 清理 CLOSE_WAIT
-                    if cw {
-                        match optimizer.close_connections_by_state(pid, TcpState::CloseWait) {
-                            Ok(n) => {
-                                total_closed += n;
-                                action_desc.push_str(&format!("CLOSE_WAIT:{}", n));
-                            }
-                            Err(e) => {
-                                error_msg = Some(format!("CLOSE_WAIT清理失败: {}", e));
-                            }
-                        }
-                    }
-
-                    // 清理 TIME_WAIT
-                    if tw {
-                        match optimizer.close_connections_by_state(pid, TcpState::TimeWait) {
-                            Ok(n) => {
-                                total_closed += n;
-                                if !action_desc.is_empty() { action_desc.push_str(", "); }
-                                action_desc.push_str(&format!("TIME_WAIT:{}", n));
-                            }
-                            Err(e) => {
-                                let msg = format!("TIME_WAIT清理失败: {}", e);
-                                if let Some(ref mut err) = error_msg {
-                                    err.push_str("; ");
-                                    err.push_str(&msg);
-                                } else {
-                                    error_msg = Some(msg);
-                                }
-                            }
-                        }
-                    }
-
+                    let (desc, closed, error) = match create_optimizer().optimize_process(pid, &policy) {
+                        Ok(action) => (
+                            format!("{:?}:{}", action.action_type, action.connections_affected),
+                            action.connections_affected,
+                            action.error_message,
+                        ),
+                        Err(e) => (String::new(), 0, Some(e.to_string())),
+                    };
                     let _ = sender.send(BgMessage::OptimizeResult(
                         process_name,
-                        action_desc,
-                        total_closed,
-                        error_msg,
+                        desc,
+                        closed,
+                        error,
+                        Some(policy.process_name),
                     ));
                 });
             }
@@ -265,6 +537,30 @@ impl NetOptApp {
         self.last_optimize = Instant::now();
     }
 
+    /// 在后台立即清理指定进程的 CLOSE_WAIT / TIME_WAIT，不检查策略阈值，受保护端口上的连接保留
+    fn force_cleanup_async(&self, pid: u32, process_name: String) {
+        let sender = self.bg_sender.clone();
+        let policy = self.app_config.policy_manager.effective_policy(&process_name);
+        std::thread::spawn(move || {
+            let (desc, closed, error) = match create_optimizer().force_cleanup(pid, true, &policy) {
+                Ok(actions) => (
+                    actions.iter()
+                        .map(|a| match a.action_type {
+                            ActionType::CloseCloseWait => format!("CLOSE_WAIT:{}", a.connections_affected),
+                            ActionType::CloseTimeWait => format!("TIME_WAIT:{}", a.connections_affected),
+                            other => format!("{:?}:{}", other, a.connections_affected),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    actions.iter().map(|a| a.connections_affected).sum(),
+                    None,
+                ),
+                Err(e) => (String::new(), 0, Some(e.to_string())),
+            };
+            let _ = sender.send(BgMessage::OptimizeResult(process_name, desc, closed, error, None));
+        });
+    }
+
     /// 处理后台消息
     fn process_bg_messages(&mut self) {
         while let Ok(msg) = self.bg_receiver.try_recv() {
@@ -278,15 +574,28 @@ impl NetOptApp {
                             if self.last_optimize.elapsed() > Duration::from_secs(30) {
                                 self.run_optimize_async(&stats);
                             }
+                            // 趋势图只用到汇总数据，进程列表只保留泄漏报告需要的有 CLOSE_WAIT 的进程以控制内存
+                            let leak_candidates = stats.by_process.iter().filter(|p| p.close_wait > 0).cloned().collect();
+                            self.history.push(SystemTcpStats { by_process: leak_candidates, ..stats.clone() });
+                            let delta = self.stats.as_ref().map(|old| stats.diff(old));
                             self.stats = Some(stats);
+                            self.sync_process_rows(delta.as_ref());
                             self.status_message = format!("{} - {}", self.i18n.t(TextKey::RefreshSuccess), platform_name());
+                            // 详情面板随统计一起刷新
+                            if let Some(pid) = self.connection_detail.as_ref().map(|d| d.pid) {
+                                self.fetch_connections_async(pid);
+                            }
                         }
                         Err(e) => {
                             self.status_message = format!("{}: {}", self.i18n.t(TextKey::RefreshFailed), e);
                         }
                     }
                 }
-                BgMessage::OptimizeResult(process_name, action, closed, error) => {
+                BgMessage::OptimizeResult(process_name, action, closed, error, policy_name) => {
+                    if let Some(name) = policy_name {
+                        self.policy_stats.entry(name).or_default().record(1, closed, std::time::SystemTime::now());
+                    }
+
                     let now = chrono::Local::now().format("%H:%M:%S").to_string();
                     let log_entry = if let Some(err) = error {
                         format!("[{}] {} {} (错误: {})", now, process_name, action, err)
@@ -307,6 +616,16 @@ impl NetOptApp {
                         self.status_message = format!("✨ {} 已清理 {} 连接", process_name, closed);
                     }
                 }
+                BgMessage::ProcessConnections(pid, result) => {
+                    // 期间可能已切换到其他进程
+                    let Some(detail) = self.connection_detail.as_mut().filter(|d| d.pid == pid) else {
+                        continue;
+                    };
+                    match result {
+                        Ok(connections) => detail.update(connections, Instant::now()),
+                        Err(e) => detail.error = Some(e),
+                    }
+                }
             }
         }
     }
@@ -319,7 +638,7 @@ impl NetOptApp {
         }
     }
 
-    fn t(&self, key: TextKey) -> &'static str {
+    fn t(&self, key: TextKey) -> &str {
         self.i18n.t(key)
     }
 }
@@ -329,6 +648,22 @@ impl eframe::App for NetOptApp {
         // 处理后台消息
         self.process_bg_messages();
 
+        // 跟踪窗口尺寸和位置，关闭时写入配置
+        let (inner, outer, closing) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (viewport.inner_rect, viewport.outer_rect, viewport.close_requested())
+        });
+        if let Some(rect) = inner {
+            self.app_config.window_width = rect.width();
+            self.app_config.window_height = rect.height();
+        }
+        if let Some(rect) = outer {
+            self.app_config.window_position = Some([rect.min.x, rect.min.y]);
+        }
+        if closing {
+            self.save_config();
+        }
+
         // 自动刷新（非阻塞）
         if self.app_config.auto_refresh && !self.is_refreshing && self.last_refresh.elapsed() > Duration::from_secs(self.app_config.refresh_interval) {
             self.refresh_stats_async();
@@ -340,20 +675,19 @@ impl eframe::App for NetOptApp {
                 ui.heading("🌐 Smart TCP Manager");
                 ui.separator();
 
-                if ui.selectable_label(self.current_view == View::Dashboard, self.t(TextKey::Dashboard)).clicked() {
-                    self.current_view = View::Dashboard;
-                }
-                if ui.selectable_label(self.current_view == View::Processes, self.t(TextKey::Processes)).clicked() {
-                    self.current_view = View::Processes;
-                }
-                if ui.selectable_label(self.current_view == View::Policies, self.t(TextKey::Policies)).clicked() {
-                    self.current_view = View::Policies;
-                }
-                if ui.selectable_label(self.current_view == View::Settings, self.t(TextKey::Settings)).clicked() {
-                    self.current_view = View::Settings;
-                }
-                if ui.selectable_label(self.current_view == View::Help, self.t(TextKey::Help)).clicked() {
-                    self.current_view = View::Help;
+                let views = [
+                    (View::Dashboard, TextKey::Dashboard),
+                    (View::Processes, TextKey::Processes),
+                    (View::Policies, TextKey::Policies),
+                    (View::Settings, TextKey::Settings),
+                    (View::Help, TextKey::Help),
+                ];
+                for (view, key) in views {
+                    if ui.selectable_label(self.current_view == view, self.t(key)).clicked() && self.current_view != view {
+                        self.current_view = view;
+                        self.app_config.last_view = view;
+                        self.config_dirty = true;
+                    }
                 }
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -362,15 +696,26 @@ impl eframe::App for NetOptApp {
                     egui::ComboBox::from_id_salt("lang_selector")
                         .selected_text(current_lang.display_name())
                         .show_ui(ui, |ui| {
-                            if ui.selectable_label(current_lang == Language::Chinese, "中文").clicked() {
-                                self.i18n.set_language(Language::Chinese);
-                                self.app_config.language = Language::Chinese;
-                                self.config_dirty = true;
+                            for lang in Language::ALL {
+                                if ui.selectable_label(current_lang == lang, lang.display_name()).clicked() {
+                                    self.i18n.set_language(lang);
+                                    self.app_config.language = lang;
+                                    self.config_dirty = true;
+                                }
                             }
-                            if ui.selectable_label(current_lang == Language::English, "English").clicked() {
-                                self.i18n.set_language(Language::English);
-                                self.app_config.language = Language::English;
-                                self.config_dirty = true;
+                        });
+
+                    // 主题切换
+                    let current_theme = self.app_config.theme;
+                    egui::ComboBox::from_id_salt("theme_selector")
+                        .selected_text(self.t(theme_text_key(current_theme)))
+                        .show_ui(ui, |ui| {
+                            for theme in Theme::ALL {
+                                if ui.selectable_label(current_theme == theme, self.i18n.t(theme_text_key(theme))).clicked() {
+                                    ctx.set_theme(theme_preference(theme));
+                                    self.app_config.theme = theme;
+                                    self.config_dirty = true;
+                                }
                             }
                         });
 
@@ -379,7 +724,7 @@ impl eframe::App for NetOptApp {
                     if !self.is_admin {
                         ui.label(egui::RichText::new(self.t(TextKey::AdminRequired)).color(egui::Color32::from_rgb(255, 100, 100)));
                     } else {
-                        ui.label(egui::RichText::new(self.t(TextKey::AdminGranted)).color(egui::Color32::GREEN));
+                        ui.label(egui::RichText::new(self.t(TextKey::AdminGranted)).color(readable(ui, egui::Color32::GREEN)));
                     }
                 });
             });
@@ -413,6 +758,13 @@ impl eframe::App for NetOptApp {
             });
         });
 
+        // 连接详情面板需在主内容区之前添加
+        if self.current_view == View::Processes && self.connection_detail.is_some() {
+            egui::SidePanel::right("connection_detail")
+                .default_width(480.0)
+                .show(ctx, |ui| self.show_connection_detail(ui));
+        }
+
         // 主内容区
         egui::CentralPanel::default().show(ctx, |ui| {
             match self.current_view {
@@ -446,23 +798,43 @@ impl NetOptApp {
         }
 
         let Some(stats) = &self.stats else {
-            ui.centered_and_justified(|ui| {
-                ui.spinner();
-            });
+            self.show_empty_state(ui, TextKey::LoadingData);
             return;
         };
 
-        ui.heading(self.t(TextKey::SystemOverview));
+        // 标题旁显示系统健康等级，悬停查看主要扣分项
+        let health = system_health(stats);
+        let t_health = self.t(TextKey::HealthScore);
+        ui.horizontal(|ui| {
+            ui.heading(self.t(TextKey::SystemOverview));
+            let color = match health.grade {
+                HealthGrade::A | HealthGrade::B => egui::Color32::GREEN,
+                HealthGrade::C | HealthGrade::D => egui::Color32::from_rgb(255, 150, 50),
+                HealthGrade::F => egui::Color32::RED,
+            };
+            let badge = ui.label(
+                egui::RichText::new(format!(" {} ", health.grade))
+                    .strong()
+                    .color(egui::Color32::BLACK)
+                    .background_color(color),
+            );
+            let mut tooltip = format!("{}: {}%", t_health, health.score);
+            for (issue, penalty) in &health.issues {
+                tooltip.push_str(&format!("\n{}: {}", issue.description(), penalty));
+            }
+            badge.on_hover_text(tooltip);
+        });
         ui.add_space(10.0);
 
         // 概览卡片
         let t_total = self.t(TextKey::TotalConnections);
         let t_avail = self.t(TextKey::AvailablePorts);
         let t_usage = self.t(TextKey::PortUsage);
+        let lang = self.i18n.current_language();
 
         ui.horizontal(|ui| {
-            Self::stat_card(ui, t_total, &stats.total_connections.to_string(), egui::Color32::LIGHT_BLUE);
-            Self::stat_card(ui, t_avail, &stats.available_ports.to_string(), egui::Color32::LIGHT_GREEN);
+            Self::stat_card(ui, t_total, &format_count_compact(stats.total_connections, lang), egui::Color32::LIGHT_BLUE);
+            Self::stat_card(ui, t_avail, &format_count_compact(stats.available_ports, lang), egui::Color32::LIGHT_GREEN);
             let color = if stats.port_usage_percent > 80.0 {
                 egui::Color32::RED
             } else if stats.port_usage_percent > 50.0 {
@@ -470,11 +842,23 @@ impl NetOptApp {
             } else {
                 egui::Color32::LIGHT_GREEN
             };
-            Self::stat_card(ui, t_usage, &format!("{:.1}%", stats.port_usage_percent), color);
+            Self::stat_card(ui, t_usage, &format_percent(stats.port_usage_percent), color);
         });
 
+        // 地址族分布，无法判断的不显示
+        let families: Vec<String> = [AddressFamily::Ipv4, AddressFamily::Ipv6]
+            .into_iter()
+            .map(|family| format!("{} {}", family, format_count(stats.by_family.get(&family).copied().unwrap_or(0))))
+            .collect();
+        ui.label(egui::RichText::new(families.join(" · ")).weak());
+
         ui.add_space(20.0);
 
+        if let Some(key) = self.empty_state() {
+            self.show_empty_state(ui, key);
+            return;
+        }
+
         // 连接状态分布
         ui.heading(self.t(TextKey::ConnectionStateDistribution));
 
@@ -483,25 +867,25 @@ impl NetOptApp {
         let t_attention = self.t(TextKey::NeedsAttention);
         let t_listen = self.t(TextKey::ListeningPorts);
 
-        let states = [
-            (TcpState::Established, "🟢", t_active),
-            (TcpState::TimeWait, "🟡", t_waiting),
-            (TcpState::CloseWait, "🔴", t_attention),
-            (TcpState::Listen, "🔵", t_listen),
+        let buckets = [
+            (TcpState::is_active as fn(TcpState) -> bool, "🟢", "ESTABLISHED", t_active),
+            (TcpState::is_waiting, "🟡", "TIME_WAIT", t_waiting),
+            (TcpState::is_half_closed, "🔴", "CLOSE_WAIT / LAST_ACK", t_attention),
+            (TcpState::is_listening, "🔵", "LISTEN", t_listen),
         ];
 
         egui::Grid::new("state_grid").striped(true).show(ui, |ui| {
-            for (state, icon, desc) in states {
-                let count = stats.by_state.get(&state).unwrap_or(&0);
+            for (in_bucket, icon, name, desc) in buckets {
+                let count = stats.count_where(in_bucket);
                 let percent = if stats.total_connections > 0 {
-                    (*count as f32 / stats.total_connections as f32) * 100.0
+                    (count as f32 / stats.total_connections as f32) * 100.0
                 } else {
                     0.0
                 };
 
-                ui.label(format!("{} {}", icon, state));
-                ui.label(count.to_string());
-                ui.label(format!("{:.1}%", percent));
+                ui.label(format!("{} {}", icon, name));
+                ui.label(format_count(count));
+                ui.label(format_percent(percent));
                 ui.label(desc);
                 ui.end_row();
             }
@@ -510,12 +894,25 @@ impl NetOptApp {
         ui.add_space(20.0);
 
         // Top 5 进程
+        ui.horizontal(|ui| {
+            ui.heading(self.i18n.t(TextKey::Top5Processes));
+            ui.label(self.i18n.t(TextKey::SortBy));
+            egui::ComboBox::from_id_salt("top_sort")
+                .selected_text(self.i18n.t(sort_key_text_key(self.top_sort)))
+                .show_ui(ui, |ui| {
+                    for key in SortKey::ALL {
+                        ui.selectable_value(&mut self.top_sort, key, self.i18n.t(sort_key_text_key(key)));
+                    }
+                });
+        });
+        let mut top = stats.by_process.clone();
+        self.top_sort.sort(&mut top);
+
         let t_proc = self.t(TextKey::ProcessName);
         let t_pid = self.t(TextKey::Pid);
         let t_conn = self.t(TextKey::Connections);
         let t_health = self.t(TextKey::HealthScore);
 
-        ui.heading(self.t(TextKey::Top5Processes));
         egui::Grid::new("top_procs").striped(true).show(ui, |ui| {
             ui.label(t_proc);
             ui.label(t_pid);
@@ -525,16 +922,17 @@ impl NetOptApp {
             ui.label(t_health);
             ui.end_row();
 
-            for proc in stats.by_process.iter().take(5) {
+            for proc in top.iter().take(5) {
                 ui.label(&proc.process_name);
                 ui.label(proc.pid.to_string());
-                ui.label(proc.total_connections.to_string());
+                ui.label(format_count(proc.total_connections));
 
-                let tw_color = if proc.time_wait > 100 { egui::Color32::from_rgb(255, 150, 50) } else { egui::Color32::WHITE };
-                ui.colored_label(tw_color, proc.time_wait.to_string());
+                let text_color = ui.visuals().text_color();
+                let tw_color = if proc.time_wait > 100 { readable(ui, egui::Color32::from_rgb(255, 150, 50)) } else { text_color };
+                ui.colored_label(tw_color, format_count(proc.time_wait));
 
-                let cw_color = if proc.close_wait > 50 { egui::Color32::RED } else { egui::Color32::WHITE };
-                ui.colored_label(cw_color, proc.close_wait.to_string());
+                let cw_color = if proc.close_wait > 50 { egui::Color32::RED } else { text_color };
+                ui.colored_label(cw_color, format_count(proc.close_wait));
 
                 let health_color = if proc.health_score >= 80 {
                     egui::Color32::GREEN
@@ -543,44 +941,261 @@ impl NetOptApp {
                 } else {
                     egui::Color32::RED
                 };
-                ui.colored_label(health_color, format!("{}%", proc.health_score));
+                let health_label = ui.colored_label(readable(ui, health_color), format!("{}%", proc.health_score));
+                if let Some(tooltip) = health_tooltip(proc) {
+                    health_label.on_hover_text(tooltip);
+                }
+                ui.end_row();
+            }
+        });
+
+        ui.add_space(20.0);
+        self.show_trend(ui);
+
+        ui.add_space(20.0);
+        self.show_leaks(ui);
+    }
+
+    /// 趋势窗口内 CLOSE_WAIT 持续增长的进程
+    fn show_leaks(&self, ui: &mut egui::Ui) {
+        ui.heading(self.t(TextKey::LeakReport));
+        let report = LeakReport::from_history(&self.history, self.trend_window.duration(), LEAK_MIN_GROWTH);
+        if report.processes.is_empty() {
+            ui.label(egui::RichText::new(self.t(TextKey::NoLeaks)).weak());
+            return;
+        }
+
+        egui::Grid::new("leak_grid").striped(true).num_columns(5).show(ui, |ui| {
+            ui.strong(self.t(TextKey::Pid));
+            ui.strong(self.t(TextKey::ProcessName));
+            ui.strong("CLOSE_WAIT");
+            ui.strong("Δ");
+            ui.label("");
+            ui.end_row();
+
+            for leak in &report.processes {
+                ui.label(leak.pid.to_string());
+                ui.label(&leak.process_name);
+                ui.label(format!("{} → {} (max {})", leak.first, leak.last, leak.peak));
+                ui.label(format!("{:+}", leak.growth));
+                let (key, color) = match leak.verdict {
+                    LeakVerdict::Confirmed => (TextKey::LeakConfirmed, egui::Color32::RED),
+                    LeakVerdict::Transient => (TextKey::LeakTransient, egui::Color32::from_rgb(255, 150, 50)),
+                    LeakVerdict::Watching => (TextKey::LeakWatching, egui::Color32::GRAY),
+                };
+                ui.horizontal(|ui| {
+                    ui.colored_label(readable(ui, color), self.t(key));
+                    if leak.restarted {
+                        ui.label(egui::RichText::new(self.t(TextKey::ProcessRestarted)).weak().small());
+                    }
+                });
                 ui.end_row();
             }
         });
     }
 
+    /// 总连接数、TIME_WAIT、CLOSE_WAIT 随时间的变化
+    fn show_trend(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading(self.t(TextKey::ConnectionTrend));
+            for window in [TrendWindow::OneMinute, TrendWindow::FiveMinutes, TrendWindow::FifteenMinutes] {
+                ui.selectable_value(&mut self.trend_window, window, window.label());
+            }
+        });
+
+        let span = self.trend_window.duration();
+        let Some(latest) = self.history.latest().map(|s| s.timestamp) else {
+            return;
+        };
+        let samples: Vec<_> = self.history.within(span).collect();
+        if samples.len() < 2 {
+            ui.label(egui::RichText::new(self.t(TextKey::CollectingHistory)).weak());
+            return;
+        }
+
+        let series = [
+            (self.t(TextKey::TotalConnections), egui::Color32::LIGHT_BLUE, None),
+            ("TIME_WAIT", egui::Color32::from_rgb(255, 150, 50), Some(TcpState::TimeWait)),
+            ("CLOSE_WAIT", egui::Color32::RED, Some(TcpState::CloseWait)),
+        ];
+        let value = |stats: &SystemTcpStats, state: Option<TcpState>| match state {
+            None => stats.total_connections,
+            Some(state) => stats.by_state.get(&state).copied().unwrap_or(0),
+        };
+        // 横轴为 Unix 时间（秒），按本地时间显示
+        let unix_secs = |t: std::time::SystemTime| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs_f64();
+        let clock = |secs: f64| {
+            chrono::DateTime::from_timestamp(secs as i64, 0)
+                .map(|t| t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+                .unwrap_or_default()
+        };
+        let end = unix_secs(latest);
+
+        let lines: Vec<egui_plot::Line> = series.iter()
+            .map(|(name, color, state)| {
+                let points: egui_plot::PlotPoints = samples.iter()
+                    .map(|s| [unix_secs(s.timestamp), value(&s.stats, *state) as f64])
+                    .collect();
+                egui_plot::Line::new(points).name(name).color(readable(ui, *color)).width(1.5)
+            })
+            .collect();
+
+        egui_plot::Plot::new("connection_trend")
+            .height(160.0)
+            .legend(egui_plot::Legend::default())
+            .x_axis_label(self.t(TextKey::TrendTime))
+            .y_axis_label(self.t(TextKey::Connections))
+            .x_axis_formatter(move |mark, _| clock(mark.value))
+            .y_axis_formatter(|mark, _| format_count(mark.value.max(0.0) as usize))
+            .label_formatter(move |name, point| {
+                let head = if name.is_empty() { String::new() } else { format!("{}\n", name) };
+                format!("{}{}\n{}", head, clock(point.x), format_count(point.y.max(0.0).round() as usize))
+            })
+            .include_x(end - span.as_secs_f64())
+            .include_x(end)
+            .include_y(0.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .show(ui, |plot_ui| {
+                for line in lines {
+                    plot_ui.line(line);
+                }
+            });
+
+        ui.horizontal(|ui| {
+            for (name, color, state) in &series {
+                let current = samples.last().map(|s| value(&s.stats, *state)).unwrap_or(0);
+                ui.colored_label(readable(ui, *color), format!("━ {} {}", name, format_count(current)));
+            }
+        });
+    }
+
+    /// 统计为空时的提示：尚未加载、系统空闲，或非管理员运行时可能因权限不足读不到连接
+    fn empty_state(&self) -> Option<TextKey> {
+        match &self.stats {
+            None => Some(TextKey::LoadingData),
+            Some(stats) if stats.total_connections == 0 && stats.by_process.is_empty() => Some(if self.is_admin {
+                TextKey::NoConnections
+            } else {
+                TextKey::NoConnectionsPermission
+            }),
+            Some(_) => None,
+        }
+    }
+
+    fn show_empty_state(&self, ui: &mut egui::Ui, key: TextKey) {
+        ui.add_space(40.0);
+        ui.vertical_centered(|ui| {
+            match key {
+                TextKey::LoadingData => {
+                    ui.spinner();
+                }
+                TextKey::NoConnectionsPermission => {
+                    ui.label(egui::RichText::new("🔒").size(32.0));
+                }
+                _ => {
+                    ui.label(egui::RichText::new("💤").size(32.0));
+                }
+            }
+            ui.label(self.t(key));
+        });
+    }
+
     fn stat_card(ui: &mut egui::Ui, title: &str, value: &str, color: egui::Color32) {
         egui::Frame::none()
-            .fill(egui::Color32::from_gray(40))
+            .fill(ui.visuals().widgets.inactive.weak_bg_fill)
             .rounding(8.0)
             .inner_margin(16.0)
             .show(ui, |ui| {
                 ui.vertical(|ui| {
                     ui.label(title);
-                    ui.colored_label(color, egui::RichText::new(value).size(28.0).strong());
+                    ui.colored_label(readable(ui, color), egui::RichText::new(value).size(28.0).strong());
                 });
             });
     }
 
     /// 进程列表视图
-    fn show_processes(&mut self, ui: &mut egui::Ui) {
-        // Clone data we need to avoid borrow conflicts
-        let processes = match &self.stats {
-            Some(stats) => stats.by_process.clone(),
-            None => {
-                ui.spinner();
-                return;
-            }
+    /// 生成进程列表行的条件
+    fn process_rows_key(&self) -> ProcessRowsKey {
+        (
+            self.process_query.trim().to_lowercase(),
+            self.process_filter,
+            self.app_config.policy_manager.all_policies().len(),
+        )
+    }
+
+    /// 按最新统计更新进程列表的行，`delta` 为 None 时整体重建
+    fn sync_process_rows(&mut self, delta: Option<&StatsDelta>) {
+        let Some(stats) = &self.stats else {
+            self.process_rows = ProcessRows::default();
+            return;
         };
+        let key = self.process_rows_key();
+        let query = key.0.clone();
+        let filter = self.process_filter;
+        let policy_manager = &self.app_config.policy_manager;
+        self.process_rows.update(stats, delta, key, |p| {
+            process_matches(p, &query, filter, policy_manager.find_policy(&p.process_name).is_some())
+        });
+    }
+
+    fn show_processes(&mut self, ui: &mut egui::Ui) {
+        if self.stats.is_none() {
+            self.show_empty_state(ui, TextKey::LoadingData);
+            return;
+        }
+        if let Some(key) = self.empty_state() {
+            ui.heading(self.t(TextKey::ProcessDetails));
+            self.show_empty_state(ui, key);
+            return;
+        }
 
         ui.heading(self.t(TextKey::ProcessDetails));
         ui.add_space(10.0);
 
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.process_query)
+                .hint_text(format!("🔍 {}", self.i18n.t(TextKey::SearchProcesses)))
+                .desired_width(200.0));
+            egui::ComboBox::from_id_salt("process_filter")
+                .selected_text(self.i18n.t(self.process_filter.text_key()))
+                .show_ui(ui, |ui| {
+                    for filter in ProcessFilter::ALL {
+                        ui.selectable_value(&mut self.process_filter, filter, self.i18n.t(filter.text_key()));
+                    }
+                });
+            ui.checkbox(&mut self.group_by_app, self.i18n.t(TextKey::GroupByApp));
+        });
+        ui.add_space(5.0);
+
+        // 搜索词、筛选条件或策略变化时重建行
+        if self.process_rows.key.as_ref() != Some(&self.process_rows_key()) {
+            self.sync_process_rows(None);
+        }
+
+        if self.group_by_app {
+            self.show_executable_groups(ui);
+            return;
+        }
+
         let t_proc = self.t(TextKey::ProcessName);
         let t_pid = self.t(TextKey::Pid);
         let t_health = self.t(TextKey::HealthScore);
         let t_add = self.t(TextKey::AddPolicy);
-        let t_added = self.t(TextKey::PolicyAdded);
+        let t_added = self.t(TextKey::PolicyAdded).to_string();
+        let t_peers = self.t(TextKey::TopRemotePeers).to_string();
+        let t_cleanup = self.t(TextKey::ForceCleanup);
+        let can_cleanup = self.connection_control && self.is_admin;
+        let t_cleanup_disabled = if !self.connection_control {
+            self.t(TextKey::ConnectionControlUnsupported)
+        } else {
+            self.t(TextKey::AdminRequired)
+        };
+        let selected_pid = self.connection_detail.as_ref().map(|d| d.pid);
+        let mut select_pid: Option<(u32, String)> = None;
+        let mut cleanup_pid: Option<(u32, String)> = None;
 
         // Collect existing policies for checking
         let existing_policies: std::collections::HashSet<String> =
@@ -590,6 +1205,19 @@ impl NetOptApp {
         // Collect actions to perform after iteration: (process_name, template_name)
         let mut add_policy_for: Option<(String, String)> = None;
 
+        // 选中进程的远端地址分布，显示在列表上方避免被滚动区域挤出
+        if let Some(selected) = &self.selected_peers {
+            ui.label(egui::RichText::new(format!("{} - {} ({})", t_peers, selected.process_name, selected.pid)).strong());
+            egui::Grid::new("peer_grid").striped(true).show(ui, |ui| {
+                for (addr, count) in &selected.peers {
+                    ui.label(addr.to_string());
+                    ui.label(format_count(*count));
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+        }
+
         egui::ScrollArea::vertical().show(ui, |ui| {
             egui::Grid::new("proc_grid").striped(true).show(ui, |ui| {
                 ui.label(egui::RichText::new(t_proc).strong());
@@ -601,21 +1229,32 @@ impl NetOptApp {
                 ui.label(egui::RichText::new("LISTEN").strong());
                 ui.label(egui::RichText::new(t_health).strong());
                 ui.label(egui::RichText::new("").strong()); // 策略列
+                ui.label(egui::RichText::new("").strong()); // 清理列
                 ui.end_row();
 
-                for proc in &processes {
-                    ui.label(&proc.process_name);
+                for proc in &self.process_rows.rows {
+                    let mut name_label = ui.selectable_label(selected_pid == Some(proc.pid), &proc.process_name);
+                    // 同名进程可通过完整路径区分
+                    if let Some(path) = &proc.exe_path {
+                        name_label = name_label.on_hover_text(path);
+                    }
+                    if name_label.clicked() {
+                        select_pid = Some((proc.pid, proc.process_name.clone()));
+                    }
                     ui.label(proc.pid.to_string());
-                    ui.label(proc.total_connections.to_string());
-                    ui.label(proc.established.to_string());
-                    ui.label(proc.time_wait.to_string());
-                    ui.label(proc.close_wait.to_string());
-                    ui.label(proc.listen.to_string());
-                    ui.label(format!("{}%", proc.health_score));
+                    ui.label(format_count(proc.total_connections));
+                    ui.label(format_count(proc.established));
+                    ui.label(format_count(proc.time_wait));
+                    ui.label(format_count(proc.close_wait));
+                    ui.label(format_count(proc.listen));
+                    let health_label = ui.label(format!("{}%", proc.health_score));
+                    if let Some(tooltip) = health_tooltip(proc) {
+                        health_label.on_hover_text(tooltip);
+                    }
 
                     // 如果已有策略显示"已配置"，否则显示策略模板下拉菜单
                     if existing_policies.contains(&proc.process_name) {
-                        ui.label(egui::RichText::new("✓ 已配置").color(egui::Color32::GREEN).small());
+                        ui.label(egui::RichText::new("✓ 已配置").color(readable(ui, egui::Color32::GREEN)).small());
                     } else {
                         let proc_name = proc.process_name.clone();
                         egui::ComboBox::from_id_salt(format!("add_policy_{}", proc.pid))
@@ -639,26 +1278,246 @@ impl NetOptApp {
                                 }
                             });
                     }
+
+                    let has_stale = proc.close_wait > 0 || proc.time_wait > 0;
+                    let mut cleanup_btn = ui.add_enabled(can_cleanup && has_stale, egui::Button::new(format!("🧹 {}", t_cleanup)));
+                    if !can_cleanup {
+                        cleanup_btn = cleanup_btn.on_disabled_hover_text(t_cleanup_disabled);
+                    }
+                    if cleanup_btn.clicked() {
+                        cleanup_pid = Some((proc.pid, proc.process_name.clone()));
+                    }
                     ui.end_row();
                 }
             });
         });
 
+        // 选中进程后查询一次远端地址分布（排除本地回环），连接列表在后台获取
+        if let Some((pid, name)) = select_pid {
+            self.connection_detail = Some(ConnectionDetail::new(pid, name.clone()));
+            self.fetch_connections_async(pid);
+            match self.monitor.top_remote_peers(pid, 5, false) {
+                Ok(peers) => self.selected_peers = Some(SelectedPeers { pid, process_name: name, peers }),
+                Err(e) => self.status_message = format!("{}: {}", t_peers, e),
+            }
+        }
+
+        if let Some((pid, name)) = cleanup_pid {
+            self.force_cleanup_async(pid, name);
+        }
+
         // Apply action outside of closure
         if let Some((process_name, template)) = add_policy_for {
-            let policy = match template.as_str() {
+            let mut policy = match template.as_str() {
                 "high_performance" => AppPolicy::high_performance(&process_name),
                 "crawler" => AppPolicy::crawler(&process_name),
                 "server" => AppPolicy::server(&process_name),
                 "restricted" => AppPolicy::restricted(&process_name),
                 _ => AppPolicy { process_name: process_name.clone(), ..AppPolicy::default() },
             };
+            // 不支持连接控制时自动优化必然失败
+            policy.auto_optimize &= self.connection_control;
             self.app_config.policy_manager.set_policy(policy);
             self.status_message = format!("{}: {} ({})", t_added, process_name, template);
             self.config_dirty = true;
         }
     }
 
+    /// 按程序合并的进程列表，搜索和筛选条件先作用于单个进程
+    fn show_executable_groups(&self, ui: &mut egui::Ui) {
+        let groups = ExecutableTcpStats::group(&self.process_rows.rows);
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("exe_grid").striped(true).show(ui, |ui| {
+                ui.label(egui::RichText::new(self.t(TextKey::ProcessName)).strong());
+                ui.label(egui::RichText::new(self.t(TextKey::ProcessCount)).strong());
+                ui.label(egui::RichText::new("Total").strong());
+                ui.label(egui::RichText::new("ESTABLISHED").strong());
+                ui.label(egui::RichText::new("TIME_WAIT").strong());
+                ui.label(egui::RichText::new("CLOSE_WAIT").strong());
+                ui.label(egui::RichText::new("LISTEN").strong());
+                ui.label(egui::RichText::new(self.t(TextKey::HealthScore)).strong());
+                ui.end_row();
+
+                for group in &groups {
+                    let name_label = ui.label(&group.process_name);
+                    if let Some(path) = &group.exe_path {
+                        name_label.on_hover_text(path);
+                    }
+                    let pids: Vec<String> = group.pids.iter().map(u32::to_string).collect();
+                    ui.label(group.pids.len().to_string())
+                        .on_hover_text(format!("PID: {}", pids.join(", ")));
+                    ui.label(format_count(group.total_connections));
+                    ui.label(format_count(group.established));
+                    ui.label(format_count(group.time_wait));
+                    ui.label(format_count(group.close_wait));
+                    ui.label(format_count(group.listen));
+                    ui.label(format!("{}%", group.health_score));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    /// 选中进程的连接列表，可按状态筛选并复制
+    fn show_connection_detail(&mut self, ui: &mut egui::Ui) {
+        let Some(detail) = &self.connection_detail else {
+            return;
+        };
+        let now = Instant::now();
+        let mut close = false;
+        let mut filter = detail.state_filter;
+        let mut copy = false;
+        let mut resolve = self.app_config.resolve_hostnames;
+
+        ui.horizontal(|ui| {
+            ui.heading(format!("{} - {} ({})", self.t(TextKey::ConnectionDetails), detail.process_name, detail.pid));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                close = ui.button("✖").clicked();
+            });
+        });
+
+        // 只列出当前存在的状态
+        let mut states: Vec<TcpState> = Vec::new();
+        for conn in &detail.connections {
+            if !states.contains(&conn.state) {
+                states.push(conn.state);
+            }
+        }
+        states.sort_by_key(|s| s.to_string());
+
+        ui.horizontal(|ui| {
+            let all = self.t(TextKey::AllStates);
+            egui::ComboBox::from_id_salt("connection_state_filter")
+                .selected_text(filter.map(|s| s.to_string()).unwrap_or_else(|| all.to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut filter, None, all);
+                    for state in &states {
+                        let count = detail.connections.iter().filter(|c| c.state == *state).count();
+                        ui.selectable_value(&mut filter, Some(*state), format!("{} ({})", state, count));
+                    }
+                });
+            copy = ui.button(format!("📋 {}", self.t(TextKey::CopyToClipboard))).clicked();
+            ui.checkbox(&mut resolve, self.t(TextKey::ResolveHostnames))
+                .on_hover_text(self.t(TextKey::ResolveHostnamesHint));
+        });
+
+        if let Some(err) = &detail.error {
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), err);
+        }
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("connection_grid").striped(true).show(ui, |ui| {
+                ui.label(egui::RichText::new(self.t(TextKey::LocalAddress)).strong());
+                ui.label(egui::RichText::new(self.t(TextKey::RemoteAddress)).strong());
+                if resolve {
+                    ui.label(egui::RichText::new(self.t(TextKey::RemoteHost)).strong());
+                }
+                ui.label(egui::RichText::new(self.t(TextKey::ConnectionState)).strong());
+                ui.label(egui::RichText::new(self.t(TextKey::ConnectionAge)).strong());
+                ui.end_row();
+
+                for conn in detail.visible() {
+                    ui.label(format!("{}:{}", conn.local_addr, conn.local_port));
+                    ui.label(format!("{}:{}", conn.remote_addr, conn.remote_port));
+                    if resolve {
+                        ui.label(conn.remote_host.as_deref().unwrap_or("-"));
+                    }
+                    ui.label(conn.state.to_string());
+                    ui.label(format_age(detail.age(conn, now)));
+                    ui.end_row();
+                }
+            });
+        });
+
+        if copy {
+            let text: Vec<String> = detail.visible()
+                .map(|c| {
+                    let host = match (&c.remote_host, resolve) {
+                        (Some(host), true) => format!("\t{}", host),
+                        (None, true) => "\t-".to_string(),
+                        (_, false) => String::new(),
+                    };
+                    format!(
+                        "{}:{}\t{}:{}{}\t{}\t{}",
+                        c.local_addr, c.local_port, c.remote_addr, c.remote_port, host, c.state,
+                        format_age(detail.age(c, now)),
+                    )
+                })
+                .collect();
+            ui.ctx().copy_text(text.join("\n"));
+            self.status_message = self.t(TextKey::CopiedToClipboard).to_string();
+        }
+
+        let resolve_changed = resolve != self.app_config.resolve_hostnames;
+        if resolve_changed {
+            self.app_config.resolve_hostnames = resolve;
+            self.config_dirty = true;
+        }
+
+        if close {
+            self.connection_detail = None;
+            self.selected_peers = None;
+        } else if let Some(detail) = &mut self.connection_detail {
+            detail.state_filter = filter;
+            // 开启解析后立即重新获取，不必等下次刷新
+            if resolve_changed && resolve {
+                let pid = detail.pid;
+                self.fetch_connections_async(pid);
+            }
+        }
+    }
+
+    /// "距上次触发: 2m05s · 动作次数: 12 · 涉及连接: 340"
+    fn policy_stats_text(&self, policy_name: &str) -> String {
+        let Some(stats) = self.policy_stats.get(policy_name) else {
+            return self.t(TextKey::NeverTriggered).to_string();
+        };
+        let since = stats.last_triggered
+            .and_then(|t| t.elapsed().ok())
+            .map(format_age)
+            .unwrap_or_default();
+        format!(
+            "{}: {} · {}: {} · {}: {}",
+            self.t(TextKey::LastTriggered), since,
+            self.t(TextKey::PolicyActions), stats.total_actions,
+            self.t(TextKey::ConnectionsAffected), stats.connections_affected,
+        )
+    }
+
+    /// 策略参数校验警告，以及按当前连接预览会触发的连接数
+    fn show_policy_check(&self, ui: &mut egui::Ui, policy: &AppPolicy) {
+        let running: Vec<&ProcessTcpStats> = self.stats.iter()
+            .flat_map(|s| s.by_process.iter())
+            .filter(|p| p.process_name == policy.process_name)
+            .collect();
+
+        // 同名多个进程时按连接数最多的检查
+        let busiest = running.iter().max_by_key(|p| p.total_connections).copied();
+        for issue in policy.check(busiest) {
+            let text = match issue {
+                PolicyIssue::CloseWaitAboveTimeWait => self.t(TextKey::WarnCloseWaitAboveTimeWait).to_string(),
+                PolicyIssue::MaxConnectionsBelowUsage { current } => {
+                    format!("{} ({})", self.t(TextKey::WarnMaxBelowUsage), format_count(current))
+                }
+            };
+            ui.colored_label(egui::Color32::YELLOW, text);
+        }
+
+        if running.is_empty() {
+            ui.label(egui::RichText::new(self.t(TextKey::ProcessNotRunning)).small().weak());
+            return;
+        }
+        let mut policies = PolicyManager::new();
+        policies.set_policy(policy.clone());
+        let mut engine = OptimizationEngine::new(policies);
+        let affected: usize = running.iter()
+            .flat_map(|p| engine.analyze_and_decide(p))
+            .map(|a| a.connections_affected)
+            .sum();
+        ui.label(egui::RichText::new(format!("{}: {}", self.t(TextKey::PolicyPreview), format_count(affected))).small());
+    }
+
     /// 策略管理视图
     fn show_policies(&mut self, ui: &mut egui::Ui) {
         ui.heading(self.t(TextKey::PolicyManagement));
@@ -703,6 +1562,7 @@ impl NetOptApp {
                         ThresholdAction::Optimize => self.t(TextKey::ActionOptimize),
                         ThresholdAction::RestartProcess => self.t(TextKey::ActionRestart),
                         ThresholdAction::Ignore => self.t(TextKey::ActionIgnore),
+                        _ => self.t(TextKey::ThresholdAction),
                     })
                     .show_ui(ui, |ui| {
                         for action in [ThresholdAction::Alert, ThresholdAction::Optimize, ThresholdAction::Ignore] {
@@ -712,7 +1572,10 @@ impl NetOptApp {
                                 ThresholdAction::Ignore => self.t(TextKey::ActionIgnore),
                                 _ => continue,
                             };
-                            if ui.selectable_label(current_action == action, label).clicked() && current_action != action {
+                            let enabled = self.connection_control || action != ThresholdAction::Optimize;
+                            let item = ui.add_enabled(enabled, egui::SelectableLabel::new(current_action == action, label))
+                                .on_disabled_hover_text(self.t(TextKey::ConnectionControlUnsupported));
+                            if item.clicked() && current_action != action {
                                 self.app_config.policy_manager.default_policy.threshold_action = action;
                                 self.config_dirty = true;
                             }
@@ -726,25 +1589,15 @@ impl NetOptApp {
             ui.horizontal(|ui| {
                 // 恢复默认值按钮
                 if ui.button(self.t(TextKey::ResetToDefault)).clicked() {
-                    self.app_config.policy_manager.default_policy = AppPolicy::default();
+                    self.app_config.policy_manager.reset_default_policy();
                     self.status_message = self.t(TextKey::DefaultsReset).to_string();
                     self.config_dirty = true;
                 }
 
                 // 应用到所有策略按钮
                 if ui.button(self.t(TextKey::ApplyToAll)).clicked() {
-                    let default = self.app_config.policy_manager.default_policy.clone();
-                    let policy_names: Vec<String> = self.app_config.policy_manager.all_policies()
-                        .iter().map(|p| p.process_name.clone()).collect();
-                    for name in policy_names {
-                        if let Some(mut policy) = self.app_config.policy_manager.remove_policy(&name) {
-                            policy.time_wait_threshold = default.time_wait_threshold;
-                            policy.close_wait_threshold = default.close_wait_threshold;
-                            policy.threshold_action = default.threshold_action;
-                            self.app_config.policy_manager.set_policy(policy);
-                        }
-                    }
-                    self.status_message = self.t(TextKey::AppliedToAll).to_string();
+                    let count = self.app_config.policy_manager.apply_defaults_to_all();
+                    self.status_message = format!("{} ({})", self.t(TextKey::AppliedToAll), count);
                     self.config_dirty = true;
                 }
             });
@@ -784,9 +1637,9 @@ impl NetOptApp {
                     ui.label(egui::RichText::new("⚠️ 需要管理员权限才能应用").color(egui::Color32::YELLOW));
                 } else {
                     if ui.button("📥 应用端口设置").clicked() {
-                        let config_mgr = create_config_manager();
-                        match config_mgr.apply_config(&self.tcp_config) {
-                            Ok(_) => self.status_message = "✅ 端口设置已应用，重启后生效".to_string(),
+                        match self.config_mgr.apply_config(&self.tcp_config) {
+                            Ok(_) if self.config_mgr.requires_reboot() => self.status_message = "✅ 端口设置已应用，重启后生效".to_string(),
+                            Ok(_) => self.status_message = "✅ 端口设置已应用".to_string(),
                             Err(e) => self.status_message = format!("❌ 应用失败: {}", e),
                         }
                     }
@@ -808,7 +1661,7 @@ impl NetOptApp {
         if policy_names.is_empty() {
             ui.label(self.t(TextKey::NoPolicies));
         } else {
-            let t_delete = self.t(TextKey::DeletePolicy);
+            let t_delete = self.t(TextKey::DeletePolicy).to_string();
             let mut policy_to_delete: Option<String> = None;
 
             egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
@@ -822,13 +1675,17 @@ impl NetOptApp {
                             ui.horizontal(|ui| {
                                 ui.label(self.t(TextKey::AutoOptimize));
                                 let mut auto_opt = policy.auto_optimize;
-                                if ui.checkbox(&mut auto_opt, "").changed() {
+                                let checkbox = ui.add_enabled(self.connection_control, egui::Checkbox::new(&mut auto_opt, ""))
+                                    .on_disabled_hover_text(self.t(TextKey::ConnectionControlUnsupported));
+                                if checkbox.changed() {
                                     let mut p = policy.clone();
                                     p.auto_optimize = auto_opt;
                                     self.app_config.policy_manager.set_policy(p);
                                     self.config_dirty = true;
                                 }
                             });
+                            ui.label(egui::RichText::new(self.policy_stats_text(name)).small().weak());
+                            self.show_policy_check(ui, &policy);
 
                             egui::Grid::new(format!("policy_{}", name)).num_columns(3).show(ui, |ui| {
                                 // TIME_WAIT阈值
@@ -876,6 +1733,7 @@ impl NetOptApp {
                                         ThresholdAction::Optimize => self.t(TextKey::ActionOptimize),
                                         ThresholdAction::RestartProcess => self.t(TextKey::ActionRestart),
                                         ThresholdAction::Ignore => self.t(TextKey::ActionIgnore),
+                                        _ => self.t(TextKey::ThresholdAction),
                                     })
                                     .show_ui(ui, |ui| {
                                         for action in [ThresholdAction::Alert, ThresholdAction::Optimize, ThresholdAction::Ignore] {
@@ -885,7 +1743,10 @@ impl NetOptApp {
                                                 ThresholdAction::Ignore => self.t(TextKey::ActionIgnore),
                                                 _ => continue,
                                             };
-                                            if ui.selectable_label(current_action == action, label).clicked() && current_action != action {
+                                            let enabled = self.connection_control || action != ThresholdAction::Optimize;
+                                            let item = ui.add_enabled(enabled, egui::SelectableLabel::new(current_action == action, label))
+                                                .on_disabled_hover_text(self.t(TextKey::ConnectionControlUnsupported));
+                                            if item.clicked() && current_action != action {
                                                 let mut p = policy.clone();
                                                 p.threshold_action = action;
                                                 self.app_config.policy_manager.set_policy(p);
@@ -898,7 +1759,7 @@ impl NetOptApp {
                             });
 
                             ui.horizontal(|ui| {
-                                if ui.button(t_delete).clicked() {
+                                if ui.button(&t_delete).clicked() {
                                     policy_to_delete = Some(name.clone());
                                 }
                             });
@@ -937,21 +1798,20 @@ impl NetOptApp {
 
         // 预设配置
         ui.heading(self.t(TextKey::QuickConfig));
-        let t_loaded = self.t(TextKey::ConfigLoaded);
+        let t_loaded = self.t(TextKey::ConfigLoaded).to_string();
         ui.horizontal(|ui| {
             if ui.button(self.t(TextKey::HighPerformance)).clicked() {
                 self.tcp_config = TcpSystemConfig::high_performance();
-                self.status_message = t_loaded.into();
+                self.status_message = t_loaded.clone();
             }
             if ui.button(self.t(TextKey::Conservative)).clicked() {
                 self.tcp_config = TcpSystemConfig::conservative();
-                self.status_message = t_loaded.into();
+                self.status_message = t_loaded.clone();
             }
             if ui.button(self.t(TextKey::ReadCurrent)).clicked() {
-                let mgr = create_config_manager();
-                if let Ok(c) = mgr.get_current_config() {
+                if let Ok(c) = self.config_mgr.get_current_config() {
                     self.tcp_config = c;
-                    self.status_message = t_loaded.into();
+                    self.status_message = t_loaded.clone();
                 }
             }
         });
@@ -960,7 +1820,7 @@ impl NetOptApp {
 
         // 详细配置
         ui.heading(self.t(TextKey::DetailedConfig));
-        let t_rec = self.t(TextKey::Recommended);
+        let t_rec = self.t(TextKey::Recommended).to_string();
         egui::Grid::new("config_grid").show(ui, |ui| {
             // MaxUserPort
             ui.label(self.t(TextKey::MaxUserPort));
@@ -993,15 +1853,14 @@ impl NetOptApp {
         ui.add_space(20.0);
 
         // 应用按钮
-        let t_applied = self.t(TextKey::ConfigApplied);
-        let t_failed = self.t(TextKey::ApplyFailed);
+        let t_applied = self.t(TextKey::ConfigApplied).to_string();
+        let t_failed = self.t(TextKey::ApplyFailed).to_string();
         ui.horizontal(|ui| {
             let apply_btn = ui.add_enabled(self.is_admin, egui::Button::new(self.t(TextKey::ApplyConfig)));
             if apply_btn.clicked() {
-                let mgr = create_config_manager();
-                match mgr.apply_config(&self.tcp_config) {
+                match self.config_mgr.apply_config(&self.tcp_config) {
                     Ok(_) => {
-                        self.status_message = t_applied.into();
+                        self.status_message = t_applied.clone();
                     }
                     Err(e) => {
                         self.status_message = format!("{}: {}", t_failed, e);
@@ -1009,7 +1868,7 @@ impl NetOptApp {
                 }
             }
 
-            if mgr_requires_reboot() {
+            if self.config_mgr.requires_reboot() {
                 ui.colored_label(egui::Color32::from_rgb(255, 150, 50), self.t(TextKey::RebootRequired));
             }
         });
@@ -1074,8 +1933,15 @@ impl NetOptApp {
     }
 }
 
-fn mgr_requires_reboot() -> bool {
-    let mgr = create_config_manager();
-    mgr.requires_reboot()
-}
 
+/// 健康评分的扣分明细，无扣分时返回 None
+fn health_tooltip(proc: &ProcessTcpStats) -> Option<String> {
+    let report = calculate_health_report(proc);
+    if report.factors.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = report.factors.iter()
+        .map(|(factor, penalty)| format!("{}: {}", factor.condition(), penalty))
+        .collect();
+    Some(lines.join("\n"))
+}
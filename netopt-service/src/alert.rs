@@ -0,0 +1,231 @@
+//! 异常告警推送
+//!
+//! 将达到指定级别的异常推送到外部系统（Webhook / Slack 等）。
+//! 发送在阻塞线程池中进行，慢的接收端不会拖住监控循环
+
+use netopt_core::{ConnectionAnomaly, Severity};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// 告警内容
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    /// 一行摘要，Slack incoming webhook 直接显示此字段
+    pub text: String,
+    pub host: String,
+    /// Unix 时间戳（秒）
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub anomaly: ConnectionAnomaly,
+}
+
+impl Alert {
+    pub fn new(anomaly: ConnectionAnomaly, host: &str) -> Self {
+        Self {
+            text: format!(
+                "[{}] {:?} {} ({}): {}",
+                host, anomaly.severity, anomaly.process_name, anomaly.pid, anomaly.message
+            ),
+            host: host.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            anomaly,
+        }
+    }
+}
+
+/// 告警接收端
+pub trait AlertSink: Send + Sync {
+    /// 用于日志的名称
+    fn name(&self) -> &str;
+
+    /// 发送一条告警（阻塞调用）
+    fn send(&self, alert: &Alert) -> Result<(), String>;
+}
+
+/// 配置文件中的 Webhook 项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+
+    /// 最低告警级别
+    #[serde(default = "default_min_severity")]
+    pub min_severity: Severity,
+
+    /// 请求超时（秒）
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_min_severity() -> Severity {
+    Severity::Critical
+}
+
+fn default_timeout_secs() -> u64 {
+    5
+}
+
+/// 以 JSON POST 告警到指定 URL
+pub struct WebhookSink {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl WebhookSink {
+    pub fn new(url: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+}
+
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.url
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        self.agent
+            .post(&self.url)
+            .send_json(alert)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// 按级别把告警分发到多个接收端
+pub struct AlertDispatcher {
+    sinks: Vec<(Severity, Arc<dyn AlertSink>)>,
+    host: String,
+}
+
+impl AlertDispatcher {
+    pub fn new() -> Self {
+        Self {
+            sinks: Vec::new(),
+            host: hostname(),
+        }
+    }
+
+    pub fn from_config(webhooks: &[WebhookConfig]) -> Self {
+        let mut dispatcher = Self::new();
+        for hook in webhooks {
+            let sink = WebhookSink::new(&hook.url, Duration::from_secs(hook.timeout_secs));
+            dispatcher.add_sink(hook.min_severity, Arc::new(sink));
+        }
+        dispatcher
+    }
+
+    pub fn add_sink(&mut self, min_severity: Severity, sink: Arc<dyn AlertSink>) {
+        self.sinks.push((min_severity, sink));
+    }
+
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// 异步分发，立即返回
+    pub fn dispatch(&self, anomaly: &ConnectionAnomaly) {
+        let alert = Alert::new(anomaly.clone(), &self.host);
+        for (min_severity, sink) in &self.sinks {
+            if anomaly.severity < *min_severity {
+                continue;
+            }
+            let sink = Arc::clone(sink);
+            let alert = alert.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = sink.send(&alert) {
+                    warn!("告警发送失败 ({}): {}", sink.name(), e);
+                }
+            });
+        }
+    }
+}
+
+impl Default for AlertDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 主机名，取不到时为 "unknown"
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netopt_core::AnomalyType;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSink(Mutex<Vec<Alert>>);
+
+    impl AlertSink for RecordingSink {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn send(&self, alert: &Alert) -> Result<(), String> {
+            self.0.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    fn anomaly(severity: Severity) -> ConnectionAnomaly {
+        ConnectionAnomaly {
+            pid: 42,
+            process_name: "crawler".into(),
+            anomaly_type: AnomalyType::TooManyCloseWait,
+            severity,
+            message: "CLOSE_WAIT连接数过多: 80".into(),
+            suggestion: String::new(),
+        }
+    }
+
+    #[test]
+    fn payload_contains_anomaly_fields() {
+        let alert = Alert::new(anomaly(Severity::Critical), "box");
+        let json = serde_json::to_value(&alert).unwrap();
+
+        assert_eq!(json["host"], "box");
+        assert_eq!(json["pid"], 42);
+        assert_eq!(json["process_name"], "crawler");
+        assert_eq!(json["anomaly_type"], "TooManyCloseWait");
+        assert!(json["text"].as_str().unwrap().contains("crawler"));
+    }
+
+    #[tokio::test]
+    async fn dispatch_respects_min_severity() {
+        let critical_only = Arc::new(RecordingSink::default());
+        let everything = Arc::new(RecordingSink::default());
+        let mut dispatcher = AlertDispatcher::new();
+        dispatcher.add_sink(Severity::Critical, critical_only.clone());
+        dispatcher.add_sink(Severity::Info, everything.clone());
+
+        dispatcher.dispatch(&anomaly(Severity::Warning));
+        dispatcher.dispatch(&anomaly(Severity::Critical));
+        // 等待阻塞线程池中的发送完成
+        for _ in 0..100 {
+            if everything.0.lock().unwrap().len() == 2 && critical_only.0.lock().unwrap().len() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(critical_only.0.lock().unwrap().len(), 1);
+        assert_eq!(everything.0.lock().unwrap().len(), 2);
+    }
+}
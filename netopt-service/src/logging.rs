@@ -0,0 +1,166 @@
+//! 日志输出配置
+//!
+//! 级别、输出目标（stdout 或按时间轮转的文件）和格式（文本 / JSON）来自服务配置的 `log` 字段，
+//! 环境变量优先。JSON 每行一条，便于日志收集系统解析。日志配置在启动时生效，SIGHUP 不会重新加载
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// 覆盖日志格式的环境变量（human / json）
+const LOG_FORMAT_ENV: &str = "NETOPT_LOG_FORMAT";
+
+/// 覆盖日志文件路径的环境变量
+const LOG_FILE_ENV: &str = "NETOPT_LOG_FILE";
+
+/// 日志格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// 便于阅读的单行文本
+    #[default]
+    Human,
+    /// 每行一个 JSON 对象
+    Json,
+}
+
+/// 日志文件轮转周期
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+/// 日志配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// 级别或过滤指令，如 "debug"、"netopt_core=debug,info"。设置了 RUST_LOG 时以其为准
+    pub level: String,
+    pub format: LogFormat,
+    /// 日志文件路径，轮转后的文件名追加日期后缀。None 表示输出到 stdout（可用 NETOPT_LOG_FILE 覆盖）
+    pub file: Option<PathBuf>,
+    pub rotation: LogRotation,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".into(),
+            format: LogFormat::Human,
+            file: None,
+            rotation: LogRotation::Daily,
+        }
+    }
+}
+
+impl std::fmt::Display for LogConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "级别 {}，格式 {:?}，输出 ", self.level, self.format)?;
+        match &self.file {
+            Some(path) => write!(f, "{}（轮转: {:?}）", path.display(), self.rotation),
+            None => write!(f, "stdout"),
+        }
+    }
+}
+
+impl LogConfig {
+    /// 应用环境变量覆盖：RUST_LOG、NETOPT_LOG_FORMAT、NETOPT_LOG_FILE
+    pub fn with_env(mut self) -> Self {
+        self.apply_env(|name| std::env::var(name).ok());
+        self
+    }
+
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) {
+        if let Some(level) = var(EnvFilter::DEFAULT_ENV).filter(|v| !v.is_empty()) {
+            self.level = level;
+        }
+        match var(LOG_FORMAT_ENV).as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("json") => self.format = LogFormat::Json,
+            Some("human") => self.format = LogFormat::Human,
+            _ => {}
+        }
+        if let Some(file) = var(LOG_FILE_ENV).filter(|v| !v.is_empty()) {
+            self.file = Some(PathBuf::from(file));
+        }
+    }
+
+    /// 安装全局日志订阅者。写文件时返回的 guard 需要保持到退出，否则缓冲中的日志会丢失
+    pub fn init(&self) -> anyhow::Result<Option<WorkerGuard>> {
+        let filter = EnvFilter::try_new(&self.level)
+            .map_err(|e| anyhow::anyhow!("无效的日志级别 {:?}: {}", self.level, e))?;
+
+        let (writer, guard) = match &self.file {
+            Some(path) => {
+                let file_name = path.file_name()
+                    .ok_or_else(|| anyhow::anyhow!("日志文件路径缺少文件名: {}", path.display()))?;
+                let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+                let rotation = match self.rotation {
+                    LogRotation::Hourly => Rotation::HOURLY,
+                    LogRotation::Daily => Rotation::DAILY,
+                    LogRotation::Never => Rotation::NEVER,
+                };
+                let appender = RollingFileAppender::new(rotation, dir, file_name);
+                let (writer, guard) = tracing_appender::non_blocking(appender);
+                (BoxMakeWriter::new(writer), Some(guard))
+            }
+            None => (BoxMakeWriter::new(std::io::stdout), None),
+        };
+
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(writer)
+            .with_ansi(self.file.is_none());
+        let layer = match self.format {
+            LogFormat::Human => layer.boxed(),
+            LogFormat::Json => layer.json().boxed(),
+        };
+        tracing_subscriber::registry().with(filter).with(layer).try_init()?;
+        Ok(guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_default_to_info_stdout() {
+        let config: LogConfig = serde_json::from_str(r#"{"format": "json"}"#).unwrap();
+        assert_eq!(config.level, "info");
+        assert_eq!(config.format, LogFormat::Json);
+        assert_eq!(config.file, None);
+        assert_eq!(config.rotation, LogRotation::Daily);
+        assert_eq!(LogConfig::default().to_string(), "级别 info，格式 Human，输出 stdout");
+    }
+
+    #[test]
+    fn env_overrides_config() {
+        let mut config = LogConfig { level: "warn".into(), ..Default::default() };
+        config.apply_env(|name| match name {
+            "RUST_LOG" => Some("debug".into()),
+            "NETOPT_LOG_FORMAT" => Some("JSON".into()),
+            "NETOPT_LOG_FILE" => Some("/var/log/netopt/service.log".into()),
+            _ => None,
+        });
+        assert_eq!(config.level, "debug");
+        assert_eq!(config.format, LogFormat::Json);
+        assert_eq!(config.file, Some(PathBuf::from("/var/log/netopt/service.log")));
+
+        // 空值和无法识别的格式不覆盖
+        let mut config = LogConfig::default();
+        config.apply_env(|name| match name {
+            "RUST_LOG" => Some(String::new()),
+            "NETOPT_LOG_FORMAT" => Some("xml".into()),
+            _ => None,
+        });
+        assert_eq!(config, LogConfig::default());
+    }
+}
@@ -0,0 +1,646 @@
+//! NetOpt Service - 后台TCP连接优化服务
+//!
+//! 定期监控系统TCP连接状态，检测异常并按策略执行优化
+
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "notify")]
+mod notify;
+#[cfg(feature = "webhook")]
+mod alert;
+mod logging;
+
+use netopt_core::platform::{create_monitor, create_config_manager, create_optimizer, has_admin_privileges};
+use netopt_core::{
+    detect_anomalies_at, duplicate_listener_anomalies, exceeds_thresholds, retry_with_backoff, ActionType, AppConfig,
+    AsyncTcpMonitor, AuditLog, Backoff, ConnectionOptimizer, NetOptError, flapping_anomalies, ConnectionTracker,
+    OptimizationEngine, OptimizationPool, OptimizationSummary, PolicyManager, RateTracker, Severity, StatsOptions, TcpMonitor,
+};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn, error};
+
+/// 指定服务配置文件路径的环境变量
+const SERVICE_CONFIG_ENV: &str = "NETOPT_SERVICE_CONFIG";
+
+/// 服务配置（JSON 文件，缺失的字段取默认值）
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct ServiceConfig {
+    /// 监控间隔（秒）
+    monitor_interval: u64,
+
+    /// TIME_WAIT 告警阈值
+    time_wait_threshold: usize,
+
+    /// CLOSE_WAIT 告警阈值
+    close_wait_threshold: usize,
+
+    /// 记录的最低异常级别
+    min_severity: Severity,
+
+    /// 是否执行自动优化
+    auto_optimize: bool,
+
+    /// 同时优化的进程数上限
+    optimize_parallelism: usize,
+
+    /// 审计日志轮转大小（字节）
+    audit_max_bytes: u64,
+
+    /// 获取系统状态失败时本轮的重试次数
+    monitor_retries: u32,
+
+    /// 连续失败超过此轮数时记录 Critical 并告警
+    monitor_failure_cycles: u32,
+
+    /// 计算端口使用率的动态端口范围 [起始, 结束]，不设置时读取系统配置。
+    /// 容器或 VPN 环境下检测到的范围不准确时使用
+    port_range: Option<(u32, u32)>,
+
+    /// Prometheus 指标端口（可通过 NETOPT_METRICS_PORT 覆盖）
+    #[cfg(feature = "metrics")]
+    metrics_port: u16,
+
+    /// Prometheus 指标监听地址，默认只监听本机（可通过 NETOPT_METRICS_BIND 覆盖）
+    #[cfg(feature = "metrics")]
+    metrics_bind: std::net::IpAddr,
+
+    /// Critical 异常时发送桌面通知
+    #[cfg(feature = "notify")]
+    notify: bool,
+
+    /// 同一异常持续存在时的重复通知间隔（分钟）
+    #[cfg(feature = "notify")]
+    notify_repeat_minutes: u64,
+
+    /// 告警推送地址，可配置多个
+    #[cfg(feature = "webhook")]
+    webhooks: Vec<alert::WebhookConfig>,
+
+    /// 日志级别、格式和输出目标，只在启动时生效
+    log: logging::LogConfig,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            monitor_interval: 30,
+            time_wait_threshold: 100,
+            close_wait_threshold: 25,
+            min_severity: Severity::Warning,
+            auto_optimize: false,
+            optimize_parallelism: 4,
+            audit_max_bytes: netopt_core::audit::DEFAULT_MAX_BYTES,
+            monitor_retries: 3,
+            monitor_failure_cycles: 3,
+            port_range: None,
+            #[cfg(feature = "metrics")]
+            metrics_port: std::env::var("NETOPT_METRICS_PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(9464),
+            #[cfg(feature = "metrics")]
+            metrics_bind: std::env::var("NETOPT_METRICS_BIND")
+                .ok()
+                .and_then(|a| a.parse().ok())
+                .unwrap_or(std::net::Ipv4Addr::LOCALHOST.into()),
+            #[cfg(feature = "notify")]
+            notify: false,
+            #[cfg(feature = "notify")]
+            notify_repeat_minutes: 30,
+            #[cfg(feature = "webhook")]
+            webhooks: Vec::new(),
+            log: logging::LogConfig::default(),
+        }
+    }
+}
+
+impl ServiceConfig {
+    /// 配置文件路径：`--config <路径>` > NETOPT_SERVICE_CONFIG > 配置目录下的 service.json
+    fn resolve_path() -> netopt_core::Result<PathBuf> {
+        let args: Vec<String> = std::env::args().collect();
+        if let Some(pos) = args.iter().position(|a| a == "--config") {
+            return args.get(pos + 1)
+                .map(PathBuf::from)
+                .ok_or_else(|| NetOptError::InvalidParameter("--config 需要指定文件路径".into()));
+        }
+        if let Some(path) = std::env::var_os(SERVICE_CONFIG_ENV) {
+            return Ok(PathBuf::from(path));
+        }
+        let app_config = AppConfig::config_path()?;
+        Ok(app_config.with_file_name("service.json"))
+    }
+
+    /// 从文件加载，文件不存在时使用默认配置
+    fn load(path: &Path) -> netopt_core::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| NetOptError::SystemError(format!("服务配置解析失败: {}", e)))
+    }
+}
+
+/// 本次运行的统计，退出时输出
+struct SessionSummary {
+    started: std::time::Instant,
+    cycles: u64,
+    anomalies: u64,
+    actions: u64,
+}
+
+impl Default for SessionSummary {
+    fn default() -> Self {
+        Self {
+            started: std::time::Instant::now(),
+            cycles: 0,
+            anomalies: 0,
+            actions: 0,
+        }
+    }
+}
+
+/// 等待退出信号：Ctrl-C，Unix 上还包括 SIGTERM（systemd/launchd 停止服务）
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+            }
+            Err(e) => {
+                warn!("无法监听 SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// 重新加载信号：Unix 上为 SIGHUP，其他平台永不触发
+struct ReloadSignal {
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+}
+
+impl ReloadSignal {
+    fn new() -> std::io::Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            hangup: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.hangup.recv().await;
+
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
+
+/// 从 GUI 共用的 AppConfig 加载策略，失败时使用默认策略
+fn load_policies() -> PolicyManager {
+    match AppConfig::load() {
+        Ok(app_config) => {
+            info!("已加载 {} 条应用策略", app_config.policy_manager.all_policies().len());
+            app_config.policy_manager
+        }
+        Err(e) => {
+            warn!("加载应用配置失败，使用默认策略: {}", e);
+            PolicyManager::new()
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// 服务读写的文件
+struct ServicePaths {
+    /// 服务配置，收到 SIGHUP 时重新读取
+    config: PathBuf,
+    /// 与 GUI 共用的应用配置，修改后重新加载策略
+    app_config: PathBuf,
+    /// 优化动作的审计日志
+    audit: PathBuf,
+}
+
+/// 监控循环：按间隔扫描连接、记录异常并按策略优化，`shutdown` 完成后返回本次运行的统计
+async fn run(
+    monitor: Arc<dyn TcpMonitor>,
+    optimizer: Arc<dyn ConnectionOptimizer>,
+    mut config: ServiceConfig,
+    mut engine: OptimizationEngine,
+    paths: ServicePaths,
+    shutdown: impl Future<Output = ()>,
+) -> std::io::Result<SessionSummary> {
+    let mut policy_mtime = modified_time(&paths.app_config);
+    let mut audit = AuditLog::new(&paths.audit, config.audit_max_bytes);
+    info!("审计日志: {}", audit.path().display());
+
+    #[cfg(feature = "metrics")]
+    let latest_stats = {
+        let latest = metrics::SharedStats::default();
+        tokio::spawn(metrics::serve((config.metrics_bind, config.metrics_port).into(), latest.clone()));
+        latest
+    };
+
+    #[cfg(feature = "notify")]
+    let mut debouncer = notify::Debouncer::new(Duration::from_secs(config.notify_repeat_minutes * 60));
+
+    #[cfg(feature = "webhook")]
+    let mut alerts = alert::AlertDispatcher::from_config(&config.webhooks);
+    #[cfg(feature = "webhook")]
+    info!("告警推送: {} 个 Webhook", alerts.len());
+
+    info!("开始监控，间隔: {}秒", config.monitor_interval);
+    let mut interval = tokio::time::interval(Duration::from_secs(config.monitor_interval));
+    let mut shutdown = std::pin::pin!(shutdown);
+    let mut reload = ReloadSignal::new()?;
+    let mut summary = SessionSummary::default();
+    let mut rate_tracker = RateTracker::new();
+    let mut conn_tracker = ConnectionTracker::default();
+    let mut monitor_failures = 0u32;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = &mut shutdown => {
+                info!("收到退出信号，正在停止服务...");
+                break;
+            }
+            _ = reload.recv() => {
+                info!("收到 SIGHUP，重新加载配置");
+                match ServiceConfig::load(&paths.config) {
+                    Ok(new_config) => {
+                        if new_config.monitor_interval != config.monitor_interval {
+                            interval = tokio::time::interval(Duration::from_secs(new_config.monitor_interval));
+                        }
+                        #[cfg(feature = "notify")]
+                        if new_config.notify_repeat_minutes != config.notify_repeat_minutes {
+                            debouncer = notify::Debouncer::new(Duration::from_secs(new_config.notify_repeat_minutes * 60));
+                        }
+                        if new_config.log != config.log {
+                            warn!("日志配置的修改需要重启服务后生效");
+                        }
+                        config = new_config;
+                        #[cfg(feature = "webhook")]
+                        {
+                            alerts = alert::AlertDispatcher::from_config(&config.webhooks);
+                        }
+                        audit = AuditLog::new(audit.path(), config.audit_max_bytes);
+                        info!("服务配置 ({}): {:?}", paths.config.display(), config);
+                    }
+                    Err(e) => error!("重新加载服务配置失败，保留当前配置: {}", e),
+                }
+                policy_mtime = modified_time(&paths.app_config);
+                *engine.policy_manager_mut() = load_policies();
+                continue;
+            }
+        }
+        summary.cycles += 1;
+
+        let mtime = modified_time(&paths.app_config);
+        if mtime != policy_mtime {
+            policy_mtime = mtime;
+            info!("应用配置已修改，重新加载策略");
+            *engine.policy_manager_mut() = load_policies();
+        }
+
+        // netstat 解析较慢，放到阻塞线程池执行以免阻塞运行时，连接列表和统计来自同一次扫描。
+        // 短暂失败时退避重试，重试等待总和不超过半个监控间隔
+        let backoff = Backoff::new(
+            config.monitor_retries,
+            Duration::from_millis(500),
+            Duration::from_secs(config.monitor_interval) / 2,
+        );
+        let options = StatsOptions { port_range: config.port_range, ..Default::default() };
+        let (connections, mut stats) = match retry_with_backoff(backoff, || monitor.get_connections_and_stats_async(options)).await {
+            Ok(scan) => {
+                if monitor_failures > config.monitor_failure_cycles {
+                    info!("监控已恢复（此前连续失败 {} 轮）", monitor_failures);
+                }
+                monitor_failures = 0;
+                scan
+            }
+            Err(e) => {
+                monitor_failures += 1;
+                if monitor_failures <= config.monitor_failure_cycles {
+                    error!("获取系统状态失败: {}", e);
+                    continue;
+                }
+                error!("监控已连续 {} 轮失败: {}", monitor_failures, e);
+                // 只在刚超过阈值时告警一次
+                #[cfg(any(feature = "webhook", feature = "notify"))]
+                if monitor_failures == config.monitor_failure_cycles + 1 {
+                    let anomaly = netopt_core::ConnectionAnomaly {
+                        pid: std::process::id(),
+                        process_name: "netopt-service".into(),
+                        anomaly_type: netopt_core::AnomalyType::MonitorUnavailable,
+                        severity: Severity::Critical,
+                        message: format!("监控已连续 {} 轮失败: {}", monitor_failures, e),
+                        suggestion: "检查 netstat 等系统工具是否可用，以及服务的运行权限".into(),
+                    };
+                    #[cfg(feature = "webhook")]
+                    alerts.dispatch(&anomaly);
+                    #[cfg(feature = "notify")]
+                    if config.notify {
+                        notify::send(&anomaly);
+                    }
+                }
+                continue;
+            }
+        };
+
+        info!(
+            "系统状态: 总连接={}, 端口使用={:.1}%, TIME_WAIT={}, CLOSE_WAIT={}",
+            stats.total_connections,
+            stats.port_usage_percent,
+            stats.by_state.get(&netopt_core::TcpState::TimeWait).unwrap_or(&0),
+            stats.by_state.get(&netopt_core::TcpState::CloseWait).unwrap_or(&0),
+        );
+
+        // 连接创建速率、状态抖动和重复监听
+        RateTracker::apply(&rate_tracker.update(&connections), &mut stats);
+        let flapping = conn_tracker.update(&connections);
+        let duplicates = duplicate_listener_anomalies(&connections);
+
+        let problematic = stats.by_process.iter()
+            .filter(|p| {
+                exceeds_thresholds(
+                    p,
+                    config.time_wait_threshold,
+                    config.close_wait_threshold,
+                    netopt_core::CONNECTIONS_WARNING_LIMIT,
+                ) || p.churn_rate.is_some_and(|r| r > netopt_core::CHURN_WARNING_RATE)
+                    // 策略的最大连接数可能低于全局告警线
+                    || engine.policy_manager().get_policy(&p.process_name)
+                        .max_connections
+                        .is_some_and(|max| p.total_connections > max)
+            })
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "notify")]
+        let mut critical = Vec::new();
+        let mut jobs = Vec::new();
+
+        for flap in &flapping {
+            info!(
+                "[{}] {}: 连接状态抖动 {}:{} -> {}:{} ({} 次)",
+                flap.process_name, flap.pid, flap.local_addr, flap.local_port,
+                flap.remote_addr, flap.remote_port, flap.transitions,
+            );
+        }
+        let other = flapping_anomalies(&flapping).into_iter()
+            .chain(duplicates)
+            .filter(|a| a.severity >= config.min_severity);
+
+        for anomaly in problematic.iter().flat_map(|p| detect_anomalies_at(p, config.min_severity)).chain(other) {
+            summary.anomalies += 1;
+            #[cfg(feature = "webhook")]
+            alerts.dispatch(&anomaly);
+            match anomaly.severity {
+                Severity::Critical => {
+                    error!("[{}] {}: {}", anomaly.process_name, anomaly.pid, anomaly.message);
+                    #[cfg(feature = "notify")]
+                    critical.push(anomaly);
+                }
+                Severity::Warning => warn!("[{}] {}: {}", anomaly.process_name, anomaly.pid, anomaly.message),
+                Severity::Info => info!("[{}] {}: {}", anomaly.process_name, anomaly.pid, anomaly.message),
+            }
+        }
+
+        // 本轮全部动作，结束时汇总为一行日志
+        let mut cycle_actions = Vec::new();
+        for proc_stats in problematic {
+
+            if config.auto_optimize {
+                for action in engine.analyze_and_decide(proc_stats) {
+                    if action.action_type == ActionType::None {
+                        if let Err(e) = audit.record(&action) {
+                            warn!("写入审计日志失败: {}", e);
+                        }
+                        cycle_actions.push(action);
+                    } else if !jobs.iter().any(|(pid, _)| *pid == proc_stats.pid) {
+                        let mut policy = engine.policy_manager().effective_policy(&proc_stats.process_name);
+                        policy.process_name = proc_stats.process_name.clone();
+                        jobs.push((proc_stats.pid, policy));
+                    }
+                }
+            }
+        }
+
+        // 并发执行清理，单个慢进程不会拖住整轮优化；复用本轮扫描的连接表
+        if !jobs.is_empty() {
+            let optimizer = Arc::clone(&optimizer);
+            let pool = OptimizationPool::new(config.optimize_parallelism);
+            match tokio::task::spawn_blocking(move || pool.run_from(optimizer.as_ref(), &jobs, &connections)).await {
+                Ok(results) => {
+                    for action in &results {
+                        if let Some(err) = &action.error_message {
+                            warn!("[{}] {}: 优化失败: {}", action.process_name, action.pid, err);
+                        }
+                        if let Err(e) = audit.record(action) {
+                            warn!("写入审计日志失败: {}", e);
+                        }
+                    }
+                    cycle_actions.extend(results);
+                }
+                Err(e) => error!("执行优化失败: {}", e),
+            }
+        }
+        // 只统计真正写入审计日志的动作：一个清理任务可能覆盖多个决策
+        summary.actions += cycle_actions.len() as u64;
+        let cycle_summary = OptimizationSummary::from_actions(&cycle_actions);
+        if !cycle_summary.is_empty() {
+            info!("本轮优化: {}", cycle_summary);
+        }
+
+        #[cfg(feature = "notify")]
+        if config.notify {
+            for anomaly in debouncer.filter(&critical, std::time::Instant::now()) {
+                notify::send(anomaly);
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        latest_stats.update(stats);
+    }
+
+
+    Ok(summary)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // 日志配置来自服务配置，需先加载配置再初始化日志
+    let config_path = ServiceConfig::resolve_path()?;
+    let config = ServiceConfig::load(&config_path)?;
+    let log_config = config.log.clone().with_env();
+    let _log_guard = log_config.init()?;
+
+    info!("NetOpt Service v{} 启动中...", env!("CARGO_PKG_VERSION"));
+    info!("日志: {}", log_config);
+
+    if !has_admin_privileges() {
+        warn!("未检测到管理员权限，某些功能可能受限");
+    }
+
+    info!("服务配置 ({}): {:?}", config_path.display(), config);
+
+    let app_config = AppConfig::config_path()?;
+    let paths = ServicePaths {
+        config: config_path,
+        audit: app_config.with_file_name("actions.log"),
+        app_config,
+    };
+    let monitor: Arc<dyn TcpMonitor> = Arc::from(create_monitor());
+    let optimizer: Arc<dyn ConnectionOptimizer> = Arc::from(create_optimizer());
+    let engine = OptimizationEngine::new(load_policies());
+
+    match create_config_manager().get_current_config() {
+        Ok(tcp_config) => {
+            info!("当前TCP配置:");
+            info!("  MaxUserPort: {:?}", tcp_config.max_user_port);
+            info!("  TcpTimedWaitDelay: {:?}", tcp_config.time_wait_delay);
+        }
+        Err(e) => error!("无法读取TCP配置: {}", e),
+    }
+
+    let summary = run(monitor, optimizer, config, engine, paths, shutdown_signal()).await?;
+    info!(
+        "服务已停止: 运行 {}秒, 监控 {} 轮, 异常 {} 次, 优化动作 {} 个",
+        summary.started.elapsed().as_secs(),
+        summary.cycles,
+        summary.anomalies,
+        summary.actions,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netopt_core::platform::mock::MockTcpMonitor;
+    use netopt_core::{AppPolicy, OptimizationAction, TcpConnection, TcpState};
+    use std::sync::Mutex;
+
+    /// 记录被优化的进程和传入的连接数，不关闭真实连接
+    #[derive(Default)]
+    struct RecordingOptimizer {
+        optimized: Mutex<Vec<(u32, usize)>>,
+    }
+
+    impl ConnectionOptimizer for RecordingOptimizer {
+        fn close_connection(&self, _conn: &TcpConnection) -> netopt_core::Result<()> {
+            Ok(())
+        }
+
+        fn close_connections_by_state(&self, _pid: u32, _state: TcpState, _policy: &AppPolicy) -> netopt_core::Result<usize> {
+            Ok(0)
+        }
+
+        fn optimize_process(&self, _pid: u32, _policy: &AppPolicy) -> netopt_core::Result<OptimizationAction> {
+            unreachable!("服务应复用本轮扫描的连接表")
+        }
+
+        fn optimize_process_from(&self, pid: u32, policy: &AppPolicy, connections: &[TcpConnection]) -> netopt_core::Result<OptimizationAction> {
+            self.optimized.lock().unwrap().push((pid, connections.iter().filter(|c| c.pid == pid).count()));
+            Ok(OptimizationAction {
+                pid,
+                process_name: policy.process_name.clone(),
+                action_type: ActionType::CloseCloseWait,
+                reason: "测试".into(),
+                connections_affected: 60,
+                success: true,
+                error_message: None,
+            })
+        }
+
+        fn supports_connection_control(&self) -> bool {
+            true
+        }
+    }
+
+    fn conn(pid: u32, name: &str, local_port: u16, state: TcpState) -> TcpConnection {
+        TcpConnection {
+            local_addr: "10.0.0.2".into(),
+            local_port,
+            remote_addr: "93.184.216.34".into(),
+            remote_port: 443,
+            state,
+            pid,
+            process_name: name.into(),
+            raw_state: None,
+            remote_host: None,
+        }
+    }
+
+    /// leaky (PID 42) 有 60 个 CLOSE_WAIT，healthy (PID 7) 只有正常连接
+    fn leaky_monitor() -> MockTcpMonitor {
+        let leaky = (0..60).map(|i| conn(42, "leaky", 50000 + i, TcpState::CloseWait));
+        let healthy = (0..10).map(|i| conn(7, "healthy", 51000 + i, TcpState::Established));
+        MockTcpMonitor::new(leaky.chain(healthy).collect())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_reports_and_optimizes_leaky_process() {
+        let dir = std::env::temp_dir().join(format!("netopt-service-run-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = ServicePaths {
+            config: dir.join("service.json"),
+            app_config: dir.join("config.json"),
+            audit: dir.join("actions.log"),
+        };
+        let config = ServiceConfig {
+            auto_optimize: true,
+            #[cfg(feature = "metrics")]
+            metrics_port: 0,
+            ..Default::default()
+        };
+        let optimizer = Arc::new(RecordingOptimizer::default());
+
+        // 间隔 30 秒：第 0、30、60 秒各监控一轮，第 75 秒退出
+        let summary = run(
+            Arc::new(leaky_monitor()),
+            optimizer.clone(),
+            config,
+            OptimizationEngine::new(PolicyManager::new()),
+            paths,
+            tokio::time::sleep(Duration::from_secs(75)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.cycles, 3);
+        assert_eq!(summary.anomalies, 3);
+        assert_eq!(summary.actions, 3);
+        assert_eq!(*optimizer.optimized.lock().unwrap(), [(42, 60), (42, 60), (42, 60)]);
+
+        let entries = AuditLog::new(dir.join("actions.log"), u64::MAX).read_since(SystemTime::UNIX_EPOCH).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries.len() as u64, summary.actions);
+        assert!(entries.iter().all(|e| e.action.pid == 42
+            && e.action.process_name == "leaky"
+            && e.action.action_type == ActionType::CloseCloseWait));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+//! Prometheus 指标端点
+//!
+//! 在 `/metrics` 上以文本格式暴露最近一次采集的统计数据
+
+use netopt_core::export::render_prometheus;
+use netopt_core::SystemTcpStats;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn, error};
+
+/// 监控循环与 HTTP 端点共享的最新统计
+#[derive(Clone, Default)]
+pub struct SharedStats(Arc<RwLock<Option<SystemTcpStats>>>);
+
+impl SharedStats {
+    pub fn update(&self, stats: SystemTcpStats) {
+        if let Ok(mut guard) = self.0.write() {
+            *guard = Some(stats);
+        }
+    }
+
+    fn render(&self) -> String {
+        self.0.read()
+            .ok()
+            .and_then(|guard| guard.as_ref().map(render_prometheus))
+            .unwrap_or_default()
+    }
+}
+
+/// 启动 HTTP 服务，监听 `addr`
+pub async fn serve(addr: SocketAddr, stats: SharedStats) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(l) => l,
+        Err(e) => {
+            error!("指标地址 {} 绑定失败: {}", addr, e);
+            return;
+        }
+    };
+    info!("Prometheus 指标已启用: http://{}/metrics", addr);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let stats = stats.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle(stream, stats).await {
+                        warn!("指标请求处理失败: {}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("接受指标连接失败: {}", e),
+        }
+    }
+}
+
+async fn handle(mut stream: TcpStream, stats: SharedStats) -> std::io::Result<()> {
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let (status, body) = if path == "/metrics" {
+        ("200 OK", stats.render())
+    } else {
+        ("404 Not Found", "Not Found\n".to_string())
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,136 @@
+//! 桌面通知
+//!
+//! 检测到 Critical 异常时弹出系统通知。同一进程的同类异常只在首次出现、
+//! 消失后再次出现，或距上次通知超过 `repeat` 时才会再次通知
+
+use netopt_core::{AnomalyType, ConnectionAnomaly};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tracing::warn;
+
+/// 通知去重
+pub struct Debouncer {
+    /// (pid, 异常类型) -> 上次通知时间
+    active: HashMap<(u32, AnomalyType), Instant>,
+    repeat: Duration,
+}
+
+impl Debouncer {
+    pub fn new(repeat: Duration) -> Self {
+        Self {
+            active: HashMap::new(),
+            repeat,
+        }
+    }
+
+    /// 传入本轮检测到的全部 Critical 异常，返回需要通知的部分。
+    /// 本轮未出现的异常视为已恢复，下次出现时会重新通知
+    pub fn filter<'a>(&mut self, anomalies: &'a [ConnectionAnomaly], now: Instant) -> Vec<&'a ConnectionAnomaly> {
+        let mut current = HashMap::new();
+        let mut due = Vec::new();
+
+        for anomaly in anomalies {
+            let key = (anomaly.pid, anomaly.anomaly_type);
+            let last = match self.active.get(&key) {
+                Some(&last) if now.duration_since(last) < self.repeat => last,
+                _ => {
+                    due.push(anomaly);
+                    now
+                }
+            };
+            current.insert(key, last);
+        }
+
+        self.active = current;
+        due
+    }
+}
+
+/// 在后台发送桌面通知，失败只记录警告
+pub fn send(anomaly: &ConnectionAnomaly) {
+    let title = format!("NetOpt: {} ({})", anomaly.process_name, anomaly.pid);
+    let body = format!("{}\n{}", anomaly.message, anomaly.suggestion);
+
+    tokio::spawn(async move {
+        match notify_command(&title, &body).status().await {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("桌面通知发送失败: 退出码 {:?}", status.code()),
+            Err(e) => warn!("桌面通知发送失败: {}", e),
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn notify_command(title: &str, body: &str) -> Command {
+    let mut cmd = Command::new("notify-send");
+    cmd.args(["-u", "critical", "-a", "NetOpt", title, body]);
+    cmd
+}
+
+#[cfg(target_os = "macos")]
+fn notify_command(title: &str, body: &str) -> Command {
+    // 通过环境变量传递文本，避免 AppleScript 转义问题
+    let mut cmd = Command::new("osascript");
+    cmd.args([
+        "-e",
+        r#"display notification (system attribute "NETOPT_BODY") with title (system attribute "NETOPT_TITLE")"#,
+    ]);
+    cmd.env("NETOPT_TITLE", title).env("NETOPT_BODY", body);
+    cmd
+}
+
+#[cfg(target_os = "windows")]
+fn notify_command(title: &str, body: &str) -> Command {
+    const SCRIPT: &str = r#"
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null
+$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $xml.GetElementsByTagName('text')
+$text.Item(0).AppendChild($xml.CreateTextNode($env:NETOPT_TITLE)) > $null
+$text.Item(1).AppendChild($xml.CreateTextNode($env:NETOPT_BODY)) > $null
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('NetOpt').Show([Windows.UI.Notifications.ToastNotification]::new($xml))
+"#;
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT]);
+    cmd.env("NETOPT_TITLE", title).env("NETOPT_BODY", body);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netopt_core::Severity;
+
+    fn anomaly(pid: u32) -> ConnectionAnomaly {
+        ConnectionAnomaly {
+            pid,
+            process_name: format!("proc{}", pid),
+            anomaly_type: AnomalyType::TooManyCloseWait,
+            severity: Severity::Critical,
+            message: String::new(),
+            suggestion: String::new(),
+        }
+    }
+
+    #[test]
+    fn suppresses_repeats_until_interval() {
+        let mut debouncer = Debouncer::new(Duration::from_secs(600));
+        let start = Instant::now();
+        let current = [anomaly(1)];
+
+        assert_eq!(debouncer.filter(&current, start).len(), 1);
+        assert!(debouncer.filter(&current, start + Duration::from_secs(30)).is_empty());
+        assert_eq!(debouncer.filter(&current, start + Duration::from_secs(600)).len(), 1);
+    }
+
+    #[test]
+    fn renotifies_after_condition_clears() {
+        let mut debouncer = Debouncer::new(Duration::from_secs(600));
+        let start = Instant::now();
+        let current = [anomaly(1)];
+
+        assert_eq!(debouncer.filter(&current, start).len(), 1);
+        assert!(debouncer.filter(&[], start + Duration::from_secs(30)).is_empty());
+        assert_eq!(debouncer.filter(&current, start + Duration::from_secs(60)).len(), 1);
+    }
+}
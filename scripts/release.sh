@@ -71,6 +71,7 @@ package_release() {
     
     local gui_bin="target/$target/release/netopt-gui$ext"
     local service_bin="target/$target/release/netopt-service$ext"
+    local cli_bin="target/$target/release/netopt-cli$ext"
     
     if [[ ! -f "$gui_bin" ]]; then
         echo "⚠️  未找到编译产物: $gui_bin"
@@ -84,6 +85,7 @@ package_release() {
     # 复制文件
     cp "$gui_bin" "$pkg_dir/"
     cp "$service_bin" "$pkg_dir/" 2>/dev/null || true
+    cp "$cli_bin" "$pkg_dir/" 2>/dev/null || true
     cp README.md "$pkg_dir/" 2>/dev/null || true
     cp LICENSE "$pkg_dir/" 2>/dev/null || true
     